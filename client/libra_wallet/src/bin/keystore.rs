@@ -0,0 +1,91 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encrypt wallet recovery files and key files into passphrase protected keystores, e.g. the
+//! faucet key file passed to the client with `-m`. The passphrase is asked for on the terminal or
+//! read from `LIBRA_WALLET_PASSPHRASE`.
+
+use failure::prelude::*;
+use libra_wallet::{
+    io_utils,
+    keystore::{read_new_passphrase, read_passphrase, Keystore, KIND_KEYPAIR},
+};
+use std::{fs, path::PathBuf, process};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "keystore",
+    about = "Encrypt wallet recovery files and key files with a passphrase"
+)]
+enum Args {
+    /// Encrypt a plain text wallet recovery file in place.
+    #[structopt(name = "encrypt-mnemonic")]
+    EncryptMnemonic {
+        #[structopt(short = "n", long = "mnemonic_file", parse(from_os_str))]
+        mnemonic_file: PathBuf,
+    },
+    /// Encrypt a keypair file as written by generate_keypair.
+    #[structopt(name = "encrypt-key")]
+    EncryptKey {
+        #[structopt(short = "i", long = "input", parse(from_os_str))]
+        input: PathBuf,
+        /// Where to write the keystore. Defaults to the input file.
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Check that a keystore opens with the passphrase.
+    #[structopt(name = "check")]
+    Check {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
+fn new_passphrase() -> Result<String> {
+    read_new_passphrase("New passphrase: ")?
+        .ok_or_else(|| format_err!("An empty passphrase does not protect anything"))
+}
+
+fn run(args: Args) -> Result<()> {
+    match args {
+        Args::EncryptMnemonic { mnemonic_file } => {
+            ensure!(
+                !io_utils::is_encrypted(&mnemonic_file),
+                "{:?} is encrypted already",
+                mnemonic_file
+            );
+            io_utils::encrypt_recovery(&mnemonic_file, &new_passphrase()?)?;
+            println!("Encrypted {:?}", mnemonic_file);
+        }
+        Args::EncryptKey { input, output } => {
+            let key = fs::read(&input)?;
+            ensure!(
+                !Keystore::is_keystore(&key),
+                "{:?} is encrypted already",
+                input
+            );
+            let keystore = Keystore::encrypt(KIND_KEYPAIR, &key, &new_passphrase()?)?;
+            let output = output.unwrap_or_else(|| input.clone());
+            if output == input {
+                keystore.save_over_plaintext(&output)?;
+            } else {
+                keystore.save(&output)?;
+            }
+            println!("Encrypted key written to {:?}", output);
+        }
+        Args::Check { file } => {
+            let keystore = Keystore::load(&file)?;
+            keystore.decrypt(&keystore.kind, &read_passphrase("Passphrase: ")?)?;
+            println!("The passphrase opens the {} keystore {:?}", keystore.kind, file);
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Args::from_args()) {
+        println!("[ERROR] {}", e);
+        process::exit(1);
+    }
+}
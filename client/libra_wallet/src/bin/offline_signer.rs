@@ -0,0 +1,143 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sign a transaction file prepared by the client, e.g. with `usds mint ... --prepare <file>`,
+//! on a machine that holds the wallet mnemonic and has no network access. The signed file is
+//! handed back to the client and sent with `submit <file>`.
+//!
+//! For a sender controlled by a multi-ed25519 policy, every key holder signs the file with
+//! `--signer <address of the wallet account>` in turn, the first one also passing the policy
+//! with `--multi_public_key`. The file is ready to be submitted once the threshold is met.
+
+use failure::prelude::*;
+use libra_wallet::{io_utils, offline::OfflineTransaction};
+use std::{
+    convert::TryFrom,
+    io::{stdin, stdout, Write},
+    path::PathBuf,
+    process,
+};
+use structopt::StructOpt;
+use types::{account_address::AccountAddress, transaction::MultiEd25519PublicKey};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "offline_signer",
+    about = "Review and sign a prepared transaction file with a wallet mnemonic"
+)]
+struct Args {
+    /// Wallet recovery file as written by the client, `<mnemonic>;<number of accounts>` or an
+    /// encrypted keystore. The passphrase is asked for or read from `LIBRA_WALLET_PASSPHRASE`.
+    #[structopt(short = "n", long = "mnemonic_file", parse(from_os_str))]
+    pub mnemonic_file: PathBuf,
+    /// Transaction file prepared by the client.
+    #[structopt(short = "i", long = "input", parse(from_os_str))]
+    pub input: PathBuf,
+    /// Where to write the signed transaction file. Defaults to the input file.
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output: Option<PathBuf>,
+    /// Number of wallet accounts to derive keys for, if the sender was created after the
+    /// recovery file was written.
+    #[structopt(short = "d", long = "depth")]
+    pub depth: Option<u64>,
+    /// Sign without asking for confirmation.
+    #[structopt(short = "y", long = "yes")]
+    pub yes: bool,
+    /// Hex encoded address of the wallet account whose key signs for a multi-ed25519 sender.
+    #[structopt(short = "s", long = "signer")]
+    pub signer: Option<String>,
+    /// Hex encoded multi-ed25519 policy of the sender, as printed by `usds multisig`.
+    #[structopt(short = "m", long = "multi_public_key")]
+    pub multi_public_key: Option<String>,
+}
+
+fn print_transaction(txn: &OfflineTransaction) -> Result<()> {
+    println!("Description (not verified): {}", txn.description);
+    println!("Sender:          {}", txn.sender);
+    println!("Sequence number: {}", txn.sequence_number);
+    println!("Max gas amount:  {}", txn.max_gas_amount);
+    println!("Gas unit price:  {}", txn.gas_unit_price);
+    println!("Expiration time: {}", txn.expiration_time);
+    println!("Script hash:     {}", txn.script_hash);
+    for module_hash in &txn.module_hashes {
+        println!("Module hash:     {}", module_hash);
+    }
+    println!("Arguments:");
+    for arg in &txn.args {
+        println!("\t{}", arg);
+    }
+    match &txn.script_source {
+        Some(source) => println!("Script source (not verified):\n{}", source),
+        None => println!("Script source:   not included"),
+    }
+    // The source and description come from whoever prepared the file, this is what gets signed.
+    println!("Code to sign:\n{}", txn.code_disassembly()?);
+    if let Some(multi_public_key) = &txn.multi_public_key {
+        println!("Multi-ed25519 policy: {}", multi_public_key);
+        for partial in &txn.partial_signatures {
+            println!("\tSigned by key {}: {}", partial.index, partial.public_key);
+        }
+    }
+    Ok(())
+}
+
+fn confirm() -> Result<bool> {
+    print!("Sign this transaction? [y/N] ");
+    stdout().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut txn = OfflineTransaction::load(&args.input)?;
+    if let Some(multi_public_key) = &args.multi_public_key {
+        txn.set_multi_public_key(&MultiEd25519PublicKey::from_bytes(&hex::decode(
+            multi_public_key,
+        )?)?)?;
+    }
+    print_transaction(&txn)?;
+    if txn.signed_txn.is_some() && args.signer.is_none() {
+        println!("The transaction is already signed, its signature will be replaced");
+    }
+    if !args.yes && !confirm()? {
+        bail!("Signing declined");
+    }
+
+    let mut wallet = io_utils::recover_with_passphrase_prompt(&args.mnemonic_file)?;
+    if let Some(depth) = args.depth {
+        if depth > wallet.key_leaf() {
+            wallet.generate_addresses(depth)?;
+        }
+    }
+    match &args.signer {
+        Some(signer) => {
+            let signer = AccountAddress::try_from(signer.clone())?;
+            match txn.add_partial_signature(&wallet, &signer)? {
+                0 => {
+                    // Make sure the file we hand out is accepted by the client.
+                    txn.signed_transaction()?;
+                    println!("The signature threshold is met");
+                }
+                missing => println!("{} more signatures are required", missing),
+            }
+        }
+        None => {
+            txn.sign(&wallet)?;
+            // Make sure the file we hand out is accepted by the client.
+            txn.signed_transaction()?;
+        }
+    }
+
+    let output = args.output.unwrap_or(args.input);
+    txn.save(&output)?;
+    println!("Signed transaction written to {:?}", output);
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Args::from_args()) {
+        println!("[ERROR] {}", e);
+        process::exit(1);
+    }
+}
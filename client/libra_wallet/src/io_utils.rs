@@ -4,10 +4,13 @@
 //! A module to generate, store and load known users accounts.
 //! The concept of known users can be helpful for testing to provide reproducible results.
 
-use crate::*;
+use crate::{
+    keystore::{read_passphrase, Keystore, KIND_MNEMONIC},
+    *,
+};
 use failure::prelude::*;
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{BufRead, BufReader, Write},
     path::Path,
 };
@@ -15,13 +18,43 @@ use std::{
 /// Delimiter used to ser/deserialize account data.
 pub const DELIMITER: &str = ";";
 
-/// Recover wallet from the path specified.
+/// Recover wallet from the path specified. The file has to be in plain text, see
+/// `recover_encrypted` for keystores.
 pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
+    ensure!(
+        !is_encrypted(path),
+        "The recovery file is encrypted, a passphrase is required"
+    );
     let input = File::open(path)?;
     let mut buffered = BufReader::new(input);
 
     let mut line = String::new();
     let _ = buffered.read_line(&mut line)?;
+    wallet_from_recovery_line(&line)
+}
+
+/// Recover wallet from the path specified, decrypting the keystore with `passphrase`.
+pub fn recover_encrypted<P: AsRef<Path>>(path: &P, passphrase: &str) -> Result<WalletLibrary> {
+    let line = Keystore::load(path.as_ref())?.decrypt(KIND_MNEMONIC, passphrase)?;
+    wallet_from_recovery_line(&String::from_utf8(line)?)
+}
+
+/// Recover wallet from the path specified, asking for the passphrase if the file is encrypted.
+pub fn recover_with_passphrase_prompt<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
+    if is_encrypted(path) {
+        let prompt = format!("Passphrase of {}: ", path.as_ref().display());
+        recover_encrypted(path, &read_passphrase(&prompt)?)
+    } else {
+        recover(path)
+    }
+}
+
+/// Whether the file at `path` is a keystore rather than a plain text recovery file.
+pub fn is_encrypted<P: AsRef<Path>>(path: &P) -> bool {
+    fs::read(path).map_or(false, |bytes| Keystore::is_keystore(&bytes))
+}
+
+fn wallet_from_recovery_line(line: &str) -> Result<WalletLibrary> {
     let parts: Vec<&str> = line.split(DELIMITER).collect();
     ensure!(parts.len() == 2, format!("Invalid entry '{}'", line));
 
@@ -32,16 +65,39 @@ pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
     Ok(wallet)
 }
 
-/// Write wallet seed to file.
-pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
-    let mut output = File::create(path)?;
-    writeln!(
-        output,
+fn recovery_line(wallet: &WalletLibrary) -> String {
+    format!(
         "{}{}{}",
         wallet.mnemonic().to_string(),
         DELIMITER,
         wallet.key_leaf()
-    )?;
+    )
+}
+
+/// Write wallet seed to file.
+/// NOTE: The mnemonic is written in plain text, prefer `write_encrypted_recovery`.
+pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
+    let mut output = File::create(path)?;
+    writeln!(output, "{}", recovery_line(wallet))?;
 
     Ok(())
 }
+
+/// Write wallet seed to a keystore encrypted with `passphrase`.
+pub fn write_encrypted_recovery<P: AsRef<Path>>(
+    wallet: &WalletLibrary,
+    path: &P,
+    passphrase: &str,
+) -> Result<()> {
+    Keystore::encrypt(KIND_MNEMONIC, recovery_line(wallet).as_bytes(), passphrase)?
+        .save(path.as_ref())
+}
+
+/// Replace a plain text recovery file by a keystore encrypted with `passphrase`. The plain text
+/// file is overwritten before it is replaced, see `Keystore::save_over_plaintext`.
+pub fn encrypt_recovery<P: AsRef<Path>>(path: &P, passphrase: &str) -> Result<WalletLibrary> {
+    let wallet = recover(path)?;
+    Keystore::encrypt(KIND_MNEMONIC, recovery_line(&wallet).as_bytes(), passphrase)?
+        .save_over_plaintext(path.as_ref())?;
+    Ok(wallet)
+}
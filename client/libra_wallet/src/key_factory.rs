@@ -116,6 +116,15 @@ impl ExtendedPrivKey {
             ed25519_dalek::ExpandedSecretKey::from(&self.private_key);
         expanded_secret_key.sign(msg.as_ref(), &public_key)
     }
+
+    /// Sign the raw bytes of an arbitrary message, so that it can be checked on chain with
+    /// Signature.ed25519_verify.
+    pub fn sign_arbitrary_message(&self, msg: &[u8]) -> ed25519_dalek::Signature {
+        let public_key: ed25519_dalek::PublicKey = (&self.private_key).into();
+        let expanded_secret_key: ed25519_dalek::ExpandedSecretKey =
+            ed25519_dalek::ExpandedSecretKey::from(&self.private_key);
+        expanded_secret_key.sign(msg, &public_key)
+    }
 }
 
 /// Wrapper struct from which we derive child keys
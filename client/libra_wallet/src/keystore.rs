@@ -0,0 +1,309 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Passphrase encrypted files for the wallet mnemonic and imported keys.
+//!
+//! A keystore is a JSON file holding one secret, e.g. the recovery line of a wallet or a
+//! serialized keypair. The encryption key is derived from the passphrase with scrypt and a random
+//! salt, and the secret is sealed with ChaCha20-Poly1305. The kind of the secret is authenticated
+//! along with it, so a keystore can not be passed off as one of another kind. The KDF parameters
+//! are stored in the file, older files stay readable when the defaults are raised.
+
+use crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    chacha20poly1305::ChaCha20Poly1305,
+};
+use failure::prelude::*;
+pub use libra_crypto::passphrase::ScryptKdf;
+use libra_crypto::passphrase::{sibling_path, write_atomically};
+use rand::{rngs::EntropyRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// Version of the keystore format written by this module.
+pub const KEYSTORE_VERSION: u32 = 1;
+/// Environment variable the passphrase is read from when set, for non-interactive use.
+pub const PASSPHRASE_ENV_VAR: &str = "LIBRA_WALLET_PASSPHRASE";
+/// Kind of the keystore holding a wallet recovery line, `<mnemonic>;<number of accounts>`.
+pub const KIND_MNEMONIC: &str = "mnemonic";
+/// Kind of the keystore holding a serialized ed25519 keypair, e.g. the faucet key file.
+pub const KIND_KEYPAIR: &str = "ed25519_keypair";
+
+const CIPHER: &str = "chacha20poly1305";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 8;
+const TAG_LENGTH: usize = 16;
+
+/// An encrypted secret as stored on disk.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    /// What the secret is, `mnemonic` or `ed25519_keypair`.
+    pub kind: String,
+    pub kdf: ScryptKdf,
+    pub cipher: String,
+    /// Hex encoded nonce of the cipher.
+    pub nonce: String,
+    /// Hex encoded encrypted secret.
+    pub ciphertext: String,
+    /// Hex encoded authentication tag.
+    pub tag: String,
+}
+
+impl Keystore {
+    /// Encrypt `secret` with the default KDF parameters.
+    pub fn encrypt(kind: &str, secret: &[u8], passphrase: &str) -> Result<Self> {
+        Self::encrypt_with_kdf(kind, secret, passphrase, ScryptKdf::new())
+    }
+
+    /// Encrypt `secret` with a key derived by `kdf`.
+    pub fn encrypt_with_kdf(
+        kind: &str,
+        secret: &[u8],
+        passphrase: &str,
+        kdf: ScryptKdf,
+    ) -> Result<Self> {
+        let key = derive_key(&kdf, passphrase)?;
+        let mut nonce = [0u8; NONCE_LENGTH];
+        EntropyRng::new().fill_bytes(&mut nonce);
+        let mut ciphertext = vec![0u8; secret.len()];
+        let mut tag = [0u8; TAG_LENGTH];
+        ChaCha20Poly1305::new(&key, &nonce, kind.as_bytes()).encrypt(
+            secret,
+            &mut ciphertext,
+            &mut tag,
+        );
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            kind: kind.to_string(),
+            kdf,
+            cipher: CIPHER.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            tag: hex::encode(tag),
+        })
+    }
+
+    /// Decrypt the secret, which has to be of the expected kind.
+    pub fn decrypt(&self, kind: &str, passphrase: &str) -> Result<Vec<u8>> {
+        ensure!(
+            self.version == KEYSTORE_VERSION,
+            "Unsupported keystore version {}",
+            self.version
+        );
+        ensure!(
+            self.cipher == CIPHER,
+            "Unsupported keystore cipher {}",
+            self.cipher
+        );
+        ensure!(
+            self.kind == kind,
+            "The keystore holds a {} instead of a {}",
+            self.kind,
+            kind
+        );
+        let nonce = hex::decode(&self.nonce)?;
+        let tag = hex::decode(&self.tag)?;
+        ensure!(
+            nonce.len() == NONCE_LENGTH && tag.len() == TAG_LENGTH,
+            "Invalid keystore nonce or tag"
+        );
+        let ciphertext = hex::decode(&self.ciphertext)?;
+        let key = derive_key(&self.kdf, passphrase)?;
+        let mut secret = vec![0u8; ciphertext.len()];
+        if !ChaCha20Poly1305::new(&key, &nonce, self.kind.as_bytes()).decrypt(
+            &ciphertext,
+            &mut secret,
+            &tag,
+        ) {
+            bail!("Wrong passphrase or corrupted keystore");
+        }
+        Ok(secret)
+    }
+
+    /// Write the keystore, readable by the owner only. An existing file is replaced once the new
+    /// one is completely written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_atomically(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    /// Write the keystore in place of the plain secret it was made from. Once the keystore is
+    /// completely written next to it, the plain file is overwritten with zeros before being
+    /// replaced, so its content is not left behind in the freed blocks. Copies of it elsewhere,
+    /// e.g. in backups, snapshots or on copy-on-write and journaling file systems, are not reached.
+    pub fn save_over_plaintext(&self, path: &Path) -> Result<()> {
+        let staged_path = sibling_path(path, ".keystore")?;
+        write_atomically(&staged_path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        if let Ok(metadata) = fs::metadata(path) {
+            let mut file = OpenOptions::new().write(true).open(path)?;
+            file.write_all(&vec![0u8; metadata.len() as usize])?;
+            file.sync_all()?;
+        }
+        fs::rename(&staged_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Parse the content of a keystore file.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Whether the file content is a keystore rather than a plain secret.
+    pub fn is_keystore(bytes: &[u8]) -> bool {
+        Self::parse(bytes).is_ok()
+    }
+}
+
+fn derive_key(kdf: &ScryptKdf, passphrase: &str) -> Result<[u8; KEY_LENGTH]> {
+    let mut key = [0u8; KEY_LENGTH];
+    kdf.derive_key(passphrase.as_bytes(), &mut key)?;
+    Ok(key)
+}
+
+/// The passphrase from `LIBRA_WALLET_PASSPHRASE` or else asked for on the terminal. Fails when
+/// neither is available, e.g. when the client runs in a script without the variable.
+pub fn read_passphrase(prompt: &str) -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+    rpassword::read_password_from_tty(Some(prompt)).map_err(|e| {
+        format_err!(
+            "Unable to read the passphrase, set {} when not running in a terminal: {}",
+            PASSPHRASE_ENV_VAR,
+            e
+        )
+    })
+}
+
+/// A passphrase to encrypt a new keystore with, asked for twice on the terminal. `None` only when
+/// the user explicitly leaves it empty, in which case the secret stays in plain text and a warning
+/// is printed. Fails when no passphrase can be read.
+pub fn read_new_passphrase(prompt: &str) -> Result<Option<String>> {
+    let passphrase = match env::var(PASSPHRASE_ENV_VAR) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = rpassword::read_password_from_tty(Some(prompt)).map_err(|e| {
+                format_err!(
+                    "Unable to read the passphrase, set {} when not running in a terminal: {}",
+                    PASSPHRASE_ENV_VAR,
+                    e
+                )
+            })?;
+            if !passphrase.is_empty() {
+                let confirmation =
+                    rpassword::read_password_from_tty(Some("Repeat the passphrase: "))?;
+                ensure!(passphrase == confirmation, "The passphrases do not match");
+            }
+            passphrase
+        }
+    };
+    if passphrase.is_empty() {
+        eprintln!("Warning: the passphrase is empty, the secret is kept in plain text");
+        return Ok(None);
+    }
+    Ok(Some(passphrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keystore::{sibling_path, Keystore, ScryptKdf, KIND_KEYPAIR, KIND_MNEMONIC};
+    use tempfile::NamedTempFile;
+
+    fn light_kdf() -> ScryptKdf {
+        ScryptKdf::with_cost(4, 8, 1)
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let secret = b"abandon ability able;3".to_vec();
+        let keystore =
+            Keystore::encrypt_with_kdf(KIND_MNEMONIC, &secret, "correct horse", light_kdf())
+                .unwrap();
+        assert_ne!(hex::decode(&keystore.ciphertext).unwrap(), secret);
+
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        keystore.save(&path).unwrap();
+        let loaded = Keystore::load(&path).unwrap();
+        assert_eq!(loaded, keystore);
+        assert_eq!(
+            loaded.decrypt(KIND_MNEMONIC, "correct horse").unwrap(),
+            secret
+        );
+
+        assert!(loaded.decrypt(KIND_MNEMONIC, "wrong horse").is_err());
+        assert!(loaded.decrypt(KIND_KEYPAIR, "correct horse").is_err());
+        // Relabeling the secret breaks the authentication.
+        let mut relabeled = loaded.clone();
+        relabeled.kind = KIND_KEYPAIR.to_string();
+        assert!(relabeled.decrypt(KIND_KEYPAIR, "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_save_over_plaintext() {
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        std::fs::write(&path, b"abandon ability able;3").unwrap();
+        let keystore =
+            Keystore::encrypt_with_kdf(KIND_MNEMONIC, b"abandon ability able;3", "", light_kdf())
+                .unwrap();
+        keystore.save_over_plaintext(&path).unwrap();
+        assert_eq!(Keystore::load(&path).unwrap(), keystore);
+        assert!(!sibling_path(&path, ".keystore").unwrap().exists());
+    }
+
+    #[test]
+    fn test_save_over_plaintext_keystore_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.keystore");
+        std::fs::write(&path, b"abandon ability able;3").unwrap();
+        let keystore =
+            Keystore::encrypt_with_kdf(KIND_MNEMONIC, b"abandon ability able;3", "", light_kdf())
+                .unwrap();
+        keystore.save_over_plaintext(&path).unwrap();
+        assert_eq!(Keystore::load(&path).unwrap(), keystore);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_save_replaces_leftover_temp_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet");
+        let tmp_path = dir.path().join("wallet.tmp");
+        std::fs::write(&tmp_path, b"leftover").unwrap();
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        // A file only differing in its extension is left alone.
+        let other_path = dir.path().join("wallet.json");
+        std::fs::write(&other_path, b"other").unwrap();
+
+        let keystore =
+            Keystore::encrypt_with_kdf(KIND_KEYPAIR, &[1, 2, 3], "", light_kdf()).unwrap();
+        keystore.save(&path).unwrap();
+        assert_eq!(Keystore::load(&path).unwrap(), keystore);
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert!(!tmp_path.exists());
+        assert_eq!(std::fs::read(&other_path).unwrap(), b"other");
+    }
+
+    #[test]
+    fn test_is_keystore() {
+        let keystore =
+            Keystore::encrypt_with_kdf(KIND_KEYPAIR, &[1, 2, 3], "", light_kdf()).unwrap();
+        assert!(Keystore::is_keystore(
+            serde_json::to_string(&keystore).unwrap().as_bytes()
+        ));
+        assert!(!Keystore::is_keystore(b"abandon ability able;3\n"));
+    }
+}
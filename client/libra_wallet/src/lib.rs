@@ -14,9 +14,15 @@ pub mod io_utils;
 /// Utils for key derivation
 pub mod key_factory;
 
+/// Passphrase encrypted storage of the mnemonic and imported keys
+pub mod keystore;
+
 /// Utils for mnemonic seed
 pub mod mnemonic;
 
+/// Transaction files of the offline signing workflow
+pub mod offline;
+
 /// Utils for wallet library
 pub mod wallet_library;
 
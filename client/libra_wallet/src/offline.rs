@@ -0,0 +1,390 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transaction files of the offline signing workflow.
+//!
+//! The online client prepares an unsigned transaction and writes it to a JSON file, an offline
+//! machine holding the mnemonic signs it, and the online client submits the signed file. Next to
+//! the proto encoded transaction, the file spells out its fields, the script source and the
+//! arguments so that reviewers can inspect it before signing. The spelled out fields are checked
+//! against the encoded transaction whenever the file is loaded. The script source and the
+//! description are written by whoever prepared the file and can't be checked offline, so the
+//! signer reviews the disassembly of the encoded code instead, see `code_disassembly`.
+//!
+//! A sender controlled by a multi-ed25519 policy is signed for by several offline machines in
+//! turn. Each adds the signature of its key to the file, and once the threshold of the policy is
+//! met the signed transaction is assembled from the collected signatures.
+
+use crate::wallet_library::WalletLibrary;
+use failure::prelude::*;
+use libra_crypto::{HashValue, Signature};
+use proto_conv::{FromProtoBytes, IntoProto, IntoProtoBytes};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, fs, path::Path};
+use types::{
+    account_address::AccountAddress,
+    transaction::{
+        MultiEd25519PublicKey, MultiEd25519Signature, RawTransaction, SignedTransaction,
+        TransactionArgument, TransactionAuthenticator, TransactionPayload,
+    },
+};
+use vm::file_format::{CompiledModule, CompiledScript};
+
+/// A transaction on its way through the offline signing workflow.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OfflineTransaction {
+    /// What the transaction does, e.g. the client command that prepared it. Not verified.
+    pub description: String,
+    /// Hex encoded sender address.
+    pub sender: String,
+    /// Sequence number of the sender the transaction is valid for.
+    pub sequence_number: u64,
+    /// Maximal gas the transaction may use.
+    pub max_gas_amount: u64,
+    /// Price per gas unit.
+    pub gas_unit_price: u64,
+    /// Seconds since the unix epoch after which the transaction is discarded.
+    pub expiration_time: u64,
+    /// Source of the script with the module addresses filled in. Not verified, the signer
+    /// shows the disassembly of the script code next to it.
+    pub script_source: Option<String>,
+    /// Hex encoded sha3 of the script code.
+    pub script_hash: String,
+    /// Hex encoded sha3 of every module the transaction publishes.
+    pub module_hashes: Vec<String>,
+    /// The script arguments.
+    pub args: Vec<String>,
+    /// Hex encoded proto bytes of the `RawTransaction`, this is what gets signed.
+    pub raw_txn: String,
+    /// Hex encoded proto bytes of the `SignedTransaction`, set once the transaction is signed.
+    pub signed_txn: Option<String>,
+    /// Hex encoded multi-ed25519 policy of the sender, if it is controlled by several keys.
+    #[serde(default)]
+    pub multi_public_key: Option<String>,
+    /// Signatures collected so far from the keys of the policy, ordered by key index.
+    #[serde(default)]
+    pub partial_signatures: Vec<PartialSignature>,
+}
+
+/// The signature of one key of a multi-ed25519 policy.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartialSignature {
+    /// Index of the key in the policy.
+    pub index: u8,
+    /// Hex encoded public key, for reviewers.
+    pub public_key: String,
+    /// Hex encoded ed25519 signature of the transaction.
+    pub signature: String,
+}
+
+fn hash_hex(bytes: &[u8]) -> String {
+    format!("{:x}", HashValue::from_iter_sha3(vec![bytes]))
+}
+
+fn format_argument(arg: &TransactionArgument) -> String {
+    match arg {
+        TransactionArgument::U64(value) => format!("u64: {}", value),
+        TransactionArgument::Address(address) => format!("address: {}", hex::encode(address)),
+        TransactionArgument::String(string) => format!("string: {}", string),
+        TransactionArgument::ByteArray(bytes) => format!("bytearray: {}", bytes),
+    }
+}
+
+impl OfflineTransaction {
+    /// Describe an unsigned transaction, it must carry a program.
+    pub fn new(
+        raw_txn: RawTransaction,
+        description: String,
+        script_source: Option<String>,
+    ) -> Result<Self> {
+        let raw_txn_bytes = raw_txn.clone().into_proto_bytes()?;
+        let mut txn = Self::describe(raw_txn)?;
+        txn.description = description;
+        txn.script_source = script_source;
+        txn.raw_txn = hex::encode(raw_txn_bytes);
+        Ok(txn)
+    }
+
+    /// The fields of the file derived from the transaction, without description and source.
+    fn describe(raw_txn: RawTransaction) -> Result<Self> {
+        let proto = raw_txn.clone().into_proto();
+        let program = match raw_txn.into_payload() {
+            TransactionPayload::Program(program) => program,
+            TransactionPayload::WriteSet(_) => bail!("Write set transactions can not be prepared"),
+        };
+        Ok(OfflineTransaction {
+            description: String::new(),
+            sender: hex::encode(proto.get_sender_account()),
+            sequence_number: proto.get_sequence_number(),
+            max_gas_amount: proto.get_max_gas_amount(),
+            gas_unit_price: proto.get_gas_unit_price(),
+            expiration_time: proto.get_expiration_time(),
+            script_source: None,
+            script_hash: hash_hex(program.code()),
+            module_hashes: program.modules().iter().map(|m| hash_hex(m)).collect(),
+            args: program.args().iter().map(format_argument).collect(),
+            raw_txn: String::new(),
+            signed_txn: None,
+            multi_public_key: None,
+            partial_signatures: vec![],
+        })
+    }
+
+    /// Decode the unsigned transaction and check that the spelled out fields match it.
+    pub fn raw_transaction(&self) -> Result<RawTransaction> {
+        let raw_txn = RawTransaction::from_proto_bytes(&hex::decode(&self.raw_txn)?)?;
+        let described = Self::describe(raw_txn.clone())?;
+        ensure!(
+            self.sender == described.sender
+                && self.sequence_number == described.sequence_number
+                && self.max_gas_amount == described.max_gas_amount
+                && self.gas_unit_price == described.gas_unit_price
+                && self.expiration_time == described.expiration_time
+                && self.script_hash == described.script_hash
+                && self.module_hashes == described.module_hashes
+                && self.args == described.args,
+            "The fields of the file do not match the encoded transaction"
+        );
+        Ok(raw_txn)
+    }
+
+    /// Disassemble the script code and the modules of the transaction, so that the signer
+    /// reviews what is actually signed rather than the unverified source.
+    pub fn code_disassembly(&self) -> Result<String> {
+        let program = match self.raw_transaction()?.into_payload() {
+            TransactionPayload::Program(program) => program,
+            TransactionPayload::WriteSet(_) => bail!("Write set transactions can not be prepared"),
+        };
+        let mut disassembly = format!("{}", CompiledScript::deserialize(program.code())?);
+        for module in program.modules() {
+            write!(disassembly, "{}", CompiledModule::deserialize(module)?)?;
+        }
+        Ok(disassembly)
+    }
+
+    /// Sign the transaction with the key of the sender held by the wallet.
+    pub fn sign(&mut self, wallet: &WalletLibrary) -> Result<()> {
+        let signed_txn = wallet.sign_txn(self.raw_transaction()?)?;
+        self.signed_txn = Some(hex::encode(signed_txn.into_proto_bytes()?));
+        Ok(())
+    }
+
+    /// The multi-ed25519 policy of the sender, if set.
+    pub fn multi_public_key(&self) -> Result<Option<MultiEd25519PublicKey>> {
+        self.multi_public_key
+            .as_ref()
+            .map(|key| MultiEd25519PublicKey::from_bytes(&hex::decode(key)?))
+            .transpose()
+    }
+
+    /// Record the multi-ed25519 policy of the sender. It can not be changed once set, as the
+    /// collected signatures refer to its keys by index.
+    pub fn set_multi_public_key(&mut self, public_key: &MultiEd25519PublicKey) -> Result<()> {
+        let encoded = hex::encode(public_key.to_bytes());
+        match &self.multi_public_key {
+            Some(current) => ensure!(
+                *current == encoded,
+                "The transaction is prepared for another multi-ed25519 policy"
+            ),
+            None => self.multi_public_key = Some(encoded),
+        }
+        Ok(())
+    }
+
+    /// Add the signature of the key of `signer` held by the wallet, which has to be one of the
+    /// keys of the policy. Once the threshold is met, the signed transaction is assembled.
+    /// Returns the number of signatures still missing.
+    pub fn add_partial_signature(
+        &mut self,
+        wallet: &WalletLibrary,
+        signer: &AccountAddress,
+    ) -> Result<usize> {
+        let policy = self
+            .multi_public_key()?
+            .ok_or_else(|| format_err!("The transaction has no multi-ed25519 policy"))?;
+        let raw_txn = self.raw_transaction()?;
+        let (public_key, signature) = wallet.sign_txn_as(signer, raw_txn.clone())?;
+        let index = policy
+            .index_of(&public_key)
+            .ok_or_else(|| format_err!("The key of {} is not part of the policy", signer))?;
+        self.partial_signatures.retain(|partial| partial.index != index);
+        self.partial_signatures.push(PartialSignature {
+            index,
+            public_key: hex::encode(public_key.to_slice()),
+            signature: hex::encode(&signature.to_compact()[..]),
+        });
+        self.partial_signatures.sort_by_key(|partial| partial.index);
+
+        let missing =
+            (policy.threshold() as usize).saturating_sub(self.partial_signatures.len());
+        if missing == 0 {
+            // Only exactly `threshold` signatures are accepted, so any collected beyond that stay
+            // in the file but are left out of the transaction.
+            let signatures = self
+                .partial_signatures
+                .iter()
+                .take(policy.threshold() as usize)
+                .map(|partial| {
+                    Ok((
+                        partial.index,
+                        Signature::from_compact(&hex::decode(&partial.signature)?)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let signed_txn = SignedTransaction::craft_authenticated_transaction_for_client(
+                raw_txn,
+                TransactionAuthenticator::multi_ed25519(
+                    policy,
+                    MultiEd25519Signature::new(signatures)?,
+                ),
+            );
+            self.signed_txn = Some(hex::encode(signed_txn.into_proto_bytes()?));
+        }
+        Ok(missing)
+    }
+
+    /// Decode the signed transaction and check that it signs the unsigned one with a valid
+    /// signature.
+    pub fn signed_transaction(&self) -> Result<SignedTransaction> {
+        let raw_txn = self.raw_transaction()?;
+        let signed_txn = match &self.signed_txn {
+            Some(signed_txn) => SignedTransaction::from_proto_bytes(&hex::decode(signed_txn)?)?,
+            None => bail!("The transaction is not signed yet"),
+        };
+        ensure!(
+            signed_txn.clone().into_raw_transaction() == raw_txn,
+            "The signed transaction differs from the prepared one"
+        );
+        signed_txn.clone().check_signature()?;
+        Ok(signed_txn)
+    }
+
+    /// Load a transaction file, checking its fields.
+    pub fn load(path: &Path) -> Result<Self> {
+        let txn: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        txn.raw_transaction()?;
+        Ok(txn)
+    }
+
+    /// Save the transaction file, replacing any previous content.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{offline::OfflineTransaction, wallet_library::WalletLibrary};
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+    use libra_crypto::PublicKey;
+    use types::{
+        account_address::AccountAddress,
+        transaction::{MultiEd25519PublicKey, Program, RawTransaction, TransactionArgument},
+    };
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        let program = Program::new(
+            vec![1, 2, 3],
+            vec![],
+            vec![
+                TransactionArgument::U64(100),
+                TransactionArgument::Address(AccountAddress::default()),
+            ],
+        );
+        RawTransaction::new(sender, 7, program, 10_000, 0, Duration::from_secs(1_000))
+    }
+
+    #[test]
+    fn test_sign_offline_transaction() {
+        let mut wallet = WalletLibrary::new();
+        let (sender, _) = wallet.new_address().unwrap();
+        let mut txn =
+            OfflineTransaction::new(raw_txn(sender), "usds mint".to_string(), None).unwrap();
+        assert_eq!(txn.sequence_number, 7);
+        assert_eq!(txn.args[0], "u64: 100");
+        assert!(txn.signed_transaction().is_err());
+
+        let file = NamedTempFile::new().unwrap();
+        txn.save(file.path()).unwrap();
+        let mut loaded = OfflineTransaction::load(file.path()).unwrap();
+        assert_eq!(loaded, txn);
+
+        loaded.sign(&wallet).unwrap();
+        let signed_txn = loaded.signed_transaction().unwrap();
+        assert_eq!(signed_txn.sender(), sender);
+        assert_eq!(signed_txn.sequence_number(), 7);
+
+        // The wallet does not hold the key of another sender.
+        let mut other =
+            OfflineTransaction::new(raw_txn(AccountAddress::random()), String::new(), None)
+                .unwrap();
+        assert!(other.sign(&wallet).is_err());
+    }
+
+    #[test]
+    fn test_multi_signed_offline_transaction() {
+        let mut wallet = WalletLibrary::new();
+        let signers: Vec<_> = (0..3).map(|_| wallet.new_address().unwrap().0).collect();
+        let public_keys = signers
+            .iter()
+            .map(|signer| {
+                let mut txn =
+                    OfflineTransaction::new(raw_txn(*signer), String::new(), None).unwrap();
+                txn.sign(&wallet).unwrap();
+                txn.signed_transaction().unwrap().public_key_bytes()
+            })
+            .map(|bytes| PublicKey::from_slice(&bytes).unwrap())
+            .collect();
+        let policy = MultiEd25519PublicKey::new(public_keys, 2).unwrap();
+
+        let mut txn =
+            OfflineTransaction::new(raw_txn(AccountAddress::random()), String::new(), None)
+                .unwrap();
+        assert!(txn.add_partial_signature(&wallet, &signers[0]).is_err());
+        txn.set_multi_public_key(&policy).unwrap();
+        assert_eq!(txn.add_partial_signature(&wallet, &signers[2]).unwrap(), 1);
+        // Signing twice with the same key does not count twice.
+        assert_eq!(txn.add_partial_signature(&wallet, &signers[2]).unwrap(), 1);
+        assert!(txn.signed_transaction().is_err());
+        assert_eq!(txn.add_partial_signature(&wallet, &signers[0]).unwrap(), 0);
+        assert_eq!(txn.partial_signatures[0].index, 0);
+        let signed_txn = txn.signed_transaction().unwrap();
+        assert_eq!(signed_txn.public_key_bytes(), policy.to_bytes());
+
+        let other = MultiEd25519PublicKey::new(policy.public_keys()[..2].to_vec(), 1).unwrap();
+        assert!(txn.set_multi_public_key(&other).is_err());
+    }
+
+    #[test]
+    fn test_code_disassembly() {
+        let program = vm_genesis::encode_transfer_program(&AccountAddress::random(), 100);
+        let transfer_txn = RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            program,
+            10_000,
+            0,
+            Duration::from_secs(1_000),
+        );
+        let txn = OfflineTransaction::new(transfer_txn, String::new(), None).unwrap();
+        let disassembly = txn.code_disassembly().unwrap();
+        assert!(disassembly.contains("CompiledScript"));
+        assert!(disassembly.contains("pay_from_sender"));
+
+        // Code that isn't a script can't be reviewed.
+        let txn = OfflineTransaction::new(raw_txn(AccountAddress::random()), String::new(), None)
+            .unwrap();
+        assert!(txn.code_disassembly().is_err());
+    }
+
+    #[test]
+    fn test_tampered_offline_transaction() {
+        let mut txn =
+            OfflineTransaction::new(raw_txn(AccountAddress::random()), String::new(), None)
+                .unwrap();
+        txn.args[0] = "u64: 1".to_string();
+        assert!(txn.raw_transaction().is_err());
+    }
+}
@@ -17,7 +17,7 @@ use crate::{
     key_factory::{ChildNumber, KeyFactory, Seed},
     mnemonic::Mnemonic,
 };
-use libra_crypto::hash::CryptoHash;
+use libra_crypto::{hash::CryptoHash, PublicKey, Signature};
 use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
 use rand::{rngs::EntropyRng, Rng};
@@ -79,6 +79,18 @@ impl WalletLibrary {
         Ok(wallet)
     }
 
+    /// Function that writes the wallet Mnemonic to a keystore encrypted with the passphrase
+    pub fn write_encrypted_recovery(&self, output_file_path: &Path, passphrase: &str) -> Result<()> {
+        io_utils::write_encrypted_recovery(&self, &output_file_path, passphrase)?;
+        Ok(())
+    }
+
+    /// Recover wallet from the keystore at input_file_path
+    pub fn recover_encrypted(input_file_path: &Path, passphrase: &str) -> Result<WalletLibrary> {
+        let wallet = io_utils::recover_encrypted(&input_file_path, passphrase)?;
+        Ok(wallet)
+    }
+
     /// Get the current ChildNumber in u64 format
     pub fn key_leaf(&self) -> u64 {
         self.key_leaf.0
@@ -172,6 +184,51 @@ impl WalletLibrary {
             ))
         }
     }
+
+    /// Sign a Libra RawTransaction with the PrivateKey associated to `signer`, which does not
+    /// have to be the sender, e.g. one of the keys of the multi-ed25519 policy controlling the
+    /// sender. Returns the public key and the signature.
+    pub fn sign_txn_as(
+        &self,
+        signer: &AccountAddress,
+        txn: RawTransaction,
+    ) -> Result<(PublicKey, Signature)> {
+        if let Some(child) = self.addr_map.get(signer) {
+            let raw_bytes = txn.into_proto().write_to_bytes()?;
+            let txn_hashvalue = RawTransactionBytes(&raw_bytes).hash();
+
+            let child_key = self.key_factory.private_child(child.clone())?;
+            let signature = child_key.sign(txn_hashvalue);
+            Ok((
+                PublicKey::from_slice(&child_key.get_public().to_bytes())?,
+                Signature::from_compact(&signature.to_bytes())?,
+            ))
+        } else {
+            Err(WalletError::LibraWalletGeneric(
+                "Well, that address is nowhere to be found... This is awkward".to_string(),
+            ))
+        }
+    }
+
+    /// Sign an arbitrary message with the PrivateKey associated to a particular AccountAddress.
+    /// Returns the signature and the public key to check it with.
+    pub fn sign_message(
+        &self,
+        address: &AccountAddress,
+        message: &[u8],
+    ) -> Result<(ed25519_dalek::Signature, ed25519_dalek::PublicKey)> {
+        if let Some(child) = self.addr_map.get(address) {
+            let child_key = self.key_factory.private_child(child.clone())?;
+            Ok((
+                child_key.sign_arbitrary_message(message),
+                child_key.get_public(),
+            ))
+        } else {
+            Err(WalletError::LibraWalletGeneric(
+                "Well, that address is nowhere to be found... This is awkward".to_string(),
+            ))
+        }
+    }
 }
 
 /// WalletLibrary naturally support TransactionSigner trait.
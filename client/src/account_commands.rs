@@ -13,7 +13,7 @@ impl Command for AccountCommand {
     fn get_description(&self) -> &'static str {
         "Account operations"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
         let commands: Vec<Box<dyn Command>> = vec![
             Box::new(AccountCommandCreate {}),
             Box::new(AccountCommandListAccounts {}),
@@ -22,7 +22,7 @@ impl Command for AccountCommand {
             Box::new(AccountCommandMint {}),
         ];
 
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        subcommand_execute(&params[0], commands, client, &params[1..])
     }
 }
 
@@ -36,16 +36,22 @@ impl Command for AccountCommandCreate {
     fn get_description(&self) -> &'static str {
         "Create an account. Returns reference ID to use in other operations"
     }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Creating/retrieving next account from wallet");
         match client.create_next_account(true) {
-            Ok(account_data) => println!(
-                "Created/retrieved account #{} address {}",
-                account_data.index,
-                hex::encode(account_data.address)
-            ),
-            Err(e) => report_error("Error creating account", e),
+            Ok(account_data) => {
+                println!(
+                    "Created/retrieved account #{} address {}",
+                    account_data.index,
+                    hex::encode(account_data.address)
+                );
+                output.record_value("index", account_data.index as u64);
+                output.record_value("address", hex::encode(account_data.address));
+            }
+            Err(e) => output.report_error("Error creating account", e),
         }
+        output
     }
 }
 
@@ -62,7 +68,8 @@ impl Command for AccountCommandRecoverWallet {
     fn get_description(&self) -> &'static str {
         "Recover Libra wallet from the file path"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Recovering Wallet");
         match client.recover_wallet_accounts(&params) {
             Ok(account_data) => {
@@ -74,8 +81,9 @@ impl Command for AccountCommandRecoverWallet {
                     println!("#{} address {}", data.index, hex::encode(data.address));
                 }
             }
-            Err(e) => report_error("Error recovering Libra wallet", e),
+            Err(e) => output.report_error("Error recovering Libra wallet", e),
         }
+        output
     }
 }
 
@@ -92,12 +100,14 @@ impl Command for AccountCommandWriteRecovery {
     fn get_description(&self) -> &'static str {
         "Save Libra wallet mnemonic recovery seed to disk"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Saving Libra wallet mnemonic recovery seed to disk");
         match client.write_recovery(&params) {
             Ok(_) => println!("Saved mnemonic seed to disk"),
-            Err(e) => report_error("Error writing mnemonic recovery seed to file", e),
+            Err(e) => output.report_error("Error writing mnemonic recovery seed to file", e),
         }
+        output
     }
 }
 
@@ -111,8 +121,10 @@ impl Command for AccountCommandListAccounts {
     fn get_description(&self) -> &'static str {
         "Print all accounts that were created or loaded"
     }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> CommandOutput {
+        let output = CommandOutput::default();
         client.print_all_accounts();
+        output
     }
 }
 
@@ -129,9 +141,10 @@ impl Command for AccountCommandMint {
     fn get_description(&self) -> &'static str {
         "Mint coins to the account. Suffix 'b' is for blocking"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 3 {
-            println!("Invalid number of arguments for mint");
+            output.report_usage_error("Invalid number of arguments for mint");
         }
         println!(">> Minting coins");
         let is_blocking = blocking_cmd(params[0]);
@@ -145,7 +158,8 @@ impl Command for AccountCommandMint {
                     println!("Mint request submitted");
                 }
             }
-            Err(e) => report_error("Error minting coins", e),
+            Err(e) => output.report_error("Error minting coins", e),
         }
+        output
     }
 }
@@ -1,12 +1,15 @@
-use std::collections::{BTreeMap, HashMap};
-use std::convert::{TryFrom, TryInto};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::{TryFrom, TryInto},
+};
 
 use failure::prelude::*;
-use types::account_config::AccountResource;
-use types::account_state_blob::AccountStateBlob;
+use types::{account_config::AccountResource, account_state_blob::AccountStateBlob};
 
-use crate::client_proxy::ModuleRegistryEntry;
-use crate::resource::{ChannelResource, ETokenResource, Resource};
+use crate::{
+    client_proxy::ModuleRegistryEntry,
+    resource::{ChannelResource, ETokenResource, Resource},
+};
 use itertools::Itertools;
 
 #[derive(Debug)]
@@ -16,10 +19,14 @@ pub struct AccountState {
 }
 
 impl AccountState {
-    pub fn from_blob(blob: &AccountStateBlob, module_registry: &Vec<ModuleRegistryEntry>) -> Result<Self> {
+    pub fn from_blob(
+        blob: &AccountStateBlob,
+        module_registry: &Vec<ModuleRegistryEntry>,
+    ) -> Result<Self> {
         let mut resources = HashMap::new();
         let map: BTreeMap<Vec<u8>, Vec<u8>> = blob.try_into()?;
-        let account_resource = AccountResource::make_from(&map).unwrap_or(AccountResource::default());
+        let account_resource =
+            AccountResource::make_from(&map).unwrap_or(AccountResource::default());
         for module in module_registry {
             resources.insert(module.name.clone(), module.get_resource(&map));
         }
@@ -29,8 +36,16 @@ impl AccountState {
         })
     }
 
-    pub fn find_resource(&self,filter:impl FnMut(&&Resource)->bool) -> Option<Resource>{
-        self.resources.iter().map(|(_k,v)|v.as_slice()).collect_vec().as_slice().concat().iter().find(filter).cloned()
+    pub fn find_resource(&self, filter: impl FnMut(&&Resource) -> bool) -> Option<Resource> {
+        self.resources
+            .iter()
+            .map(|(_k, v)| v.as_slice())
+            .collect_vec()
+            .as_slice()
+            .concat()
+            .iter()
+            .find(filter)
+            .cloned()
     }
 }
 
@@ -49,4 +64,4 @@ impl TryFrom<&AccountStateBlob> for AccountState {
         let map: BTreeMap<Vec<u8>, Vec<u8>> = value.try_into()?;
         Self::try_from(&map)
     }
-}
\ No newline at end of file
+}
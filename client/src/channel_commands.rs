@@ -2,38 +2,54 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use core::borrow::Borrow;
-use std::collections::BTreeMap;
-use std::convert::{TryFrom, TryInto};
-use std::fs;
-use std::path::Path;
-
-use bytecode_verifier::VerifiedModule;
-use bytecode_verifier::verifier::VerifiedProgram;
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+use bytecode_verifier::{verifier::VerifiedProgram, VerifiedModule};
 use canonical_serialization::SimpleSerializer;
 use compiler::Compiler;
 use failure::prelude::*;
 use lazy_static::lazy_static;
-use types::access_path::AccessPath;
-use types::account_address::AccountAddress;
-use types::account_config::AccountResource;
-use types::byte_array::ByteArray;
-use types::transaction::{Program, RawTransaction, TransactionArgument};
-use types::write_set::{WriteOp, WriteSetMut};
-use vm::access::ScriptAccess;
-use vm::file_format::{CompiledProgram, FunctionSignature, SignatureToken};
-
-use crate::{client_proxy::*, commands::*, resource::*, usds_commands::*, TransferRequest, TransferConform};
+use parity_multiaddr::Multiaddr;
+use types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    byte_array::ByteArray,
+    transaction::{Program, RawTransaction, TransactionArgument},
+    write_set::{WriteOp, WriteSetMut},
+};
+use vm::{
+    access::ScriptAccess,
+    file_format::{CompiledProgram, FunctionSignature, SignatureToken},
+};
+
+use crate::{
+    channel_daemon::{self, ChannelMessage},
+    client_proxy::*,
+    commands::*,
+    resource::*,
+    usds_commands::*,
+    ChannelLocalData, OffchainChannel, TransferConform, TransferRequest,
+};
 
 lazy_static! {
-
-    pub static ref CHANNEL_TEMPLATE: String = {include_str!("../move/channel.mvir").to_string()};
-    pub static ref CHANNEL_OPEN_TEMPLATE: String = {include_str!("../move/channel_open.mvir").to_string()};
-    pub static ref CHANNEL_CLOSE_TEMPLATE: String = {include_str!("../move/channel_close.mvir").to_string()};
-    pub static ref CHANNEL_CLOSE_WITH_PROOF_TEMPLATE: String = {include_str!("../move/channel_close_with_proof.mvir").to_string()};
-    pub static ref CHANNEL_SETTLE_TEMPLATE: String = {include_str!("../move/channel_settle.mvir").to_string()};
+    pub static ref CHANNEL_TEMPLATE: String = { include_str!("../move/channel.mvir").to_string() };
+    pub static ref CHANNEL_OPEN_TEMPLATE: String =
+        { include_str!("../move/channel_open.mvir").to_string() };
+    pub static ref CHANNEL_CLOSE_TEMPLATE: String =
+        { include_str!("../move/channel_close.mvir").to_string() };
+    pub static ref CHANNEL_CLOSE_WITH_PROOF_TEMPLATE: String =
+        { include_str!("../move/channel_close_with_proof.mvir").to_string() };
+    pub static ref CHANNEL_SETTLE_TEMPLATE: String =
+        { include_str!("../move/channel_settle.mvir").to_string() };
 }
 
-
 /// Major command for channel operations.
 pub struct ChannelCommand {}
 
@@ -44,20 +60,27 @@ impl Command for ChannelCommand {
     fn get_description(&self) -> &'static str {
         "Channel operations"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
-            Box::new(ChannelCommandDeploy {}),
-            Box::new(ChannelCommandOpen {}),
-            Box::new(ChannelCommandClose {}),
-            Box::new(ChannelCommandShow {}),
-            Box::new(ChannelCommandSettle{}),
-            Box::new(ChannelCommandOffchainTransfer {}),
-            Box::new(ChannelCommandOffchainConform {}),
-            Box::new(ChannelCommandOffchainProcessConform {}),
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        subcommand_execute(&params[0], channel_subcommands(), client, &params[1..])
     }
+    fn execute_shared(&self, client: &SharedClientProxy, params: &[&str]) -> CommandOutput {
+        subcommand_execute_shared(&params[0], channel_subcommands(), client, &params[1..])
+    }
+}
+
+fn channel_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(ChannelCommandDeploy {}),
+        Box::new(ChannelCommandOpen {}),
+        Box::new(ChannelCommandClose {}),
+        Box::new(ChannelCommandShow {}),
+        Box::new(ChannelCommandSettle {}),
+        Box::new(ChannelCommandOffchainTransfer {}),
+        Box::new(ChannelCommandOffchainConform {}),
+        Box::new(ChannelCommandOffchainProcessConform {}),
+        Box::new(ChannelCommandListen {}),
+        Box::new(ChannelCommandPay {}),
+    ]
 }
 
 pub struct ChannelCommandDeploy {}
@@ -72,28 +95,41 @@ impl Command for ChannelCommandDeploy {
     fn get_description(&self) -> &'static str {
         "Deploy channel Module to an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 2 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
 
-        execute_script(client, &address, &CHANNEL_TEMPLATE, vec![]).map(|(compiled_program, deps, seq)| {
-            let verified_program = VerifiedProgram::new(compiled_program.clone(), &deps).unwrap();
-            client.registry_module("channel".to_string(), address.clone(), verified_program.modules().to_vec());
-            (compiled_program, deps, seq)
-        }).map(handler_result).map_err(handler_err).ok();
+        match execute_script(client, &options, &address, &CHANNEL_TEMPLATE, vec![]) {
+            Ok((compiled_program, deps, seq)) => {
+                let verified_program =
+                    VerifiedProgram::new(compiled_program.clone(), &deps).unwrap();
+                client.registry_module(
+                    "channel".to_string(),
+                    address.clone(),
+                    verified_program.modules().to_vec(),
+                );
+                handler_result(&options, (compiled_program, deps, seq));
+            }
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
-
 /// Open channel
 pub struct ChannelCommandOpen {}
 
@@ -107,42 +143,59 @@ impl Command for ChannelCommandOpen {
     fn get_description(&self) -> &'static str {
         "Open channel with an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 4 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let other_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[3]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
-            }
-        };
-        execute_script(client, &address, &CHANNEL_OPEN_TEMPLATE, vec![TransactionArgument::Address(other_address.clone()), TransactionArgument::U64(amount)]).map(handler_result).map_err(handler_err).ok();
+                output.report_error("invalid amount", e.into());
+                return output;
+            }
+        };
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &CHANNEL_OPEN_TEMPLATE,
+            vec![
+                TransactionArgument::Address(other_address.clone()),
+                TransactionArgument::U64(amount),
+            ],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
         client.sync_channel_status(address, other_address);
+        output
     }
 }
 
-
 /// Close channel
 pub struct ChannelCommandClose {}
 
@@ -156,60 +209,116 @@ impl Command for ChannelCommandClose {
     fn get_description(&self) -> &'static str {
         "Close a channel."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let other_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
+        if let Err(e) = client.sync_channel_status(address, other_address) {
+            output.report_error("sync_channel_status error", e);
+            return output;
+        }
         let account_data = match client.get_account_data(address) {
             Some(account_data) => account_data,
             None => {
                 println!("get account data fail.");
-                return;
+                return output;
             }
         };
-        let channel = match account_data.get_channel(&other_address){
+        let channel = match account_data.get_channel(&other_address) {
             Some(channel) => channel,
             None => {
                 println!("get channel with address {} fail.", other_address);
-                return;
+                return output;
             }
         };
-        match  &channel.data{
+        // Submit the local proof when it is newer than any proof on chain, this also challenges a
+        // close of the other party.
+        let newer_proof = newer_local_proof(channel);
+        let self_open = channel.self_status.is_open();
+        match newer_proof {
             Some(offchain_data) => {
-                let args = vec![TransactionArgument::Address(other_address), TransactionArgument::U64(offchain_data.version),
-                                TransactionArgument::U64(offchain_data.self_balance), TransactionArgument::U64(offchain_data.other_balance),
-                                TransactionArgument::ByteArray(ByteArray::new(offchain_data.self_signature.clone())), TransactionArgument::ByteArray(ByteArray::new(offchain_data.other_signature.clone()))
-                ];
-                execute_script(client, &address, &CHANNEL_CLOSE_WITH_PROOF_TEMPLATE, args).map(handler_result).map_err(handler_err).ok();
+                match execute_script(
+                    client,
+                    &options,
+                    &address,
+                    &CHANNEL_CLOSE_WITH_PROOF_TEMPLATE,
+                    close_with_proof_args(other_address, &offchain_data),
+                ) {
+                    Ok(result) => handler_result(&options, result),
+                    Err(e) => output.report_error("execute command fail:", e),
+                }
+            }
+            None if self_open => {
+                match execute_script(
+                    client,
+                    &options,
+                    &address,
+                    &CHANNEL_CLOSE_TEMPLATE,
+                    vec![TransactionArgument::Address(other_address.clone())],
+                ) {
+                    Ok(result) => handler_result(&options, result),
+                    Err(e) => output.report_error("execute command fail:", e),
+                }
             }
             None => {
-                execute_script(client, &address, &CHANNEL_CLOSE_TEMPLATE, vec![TransactionArgument::Address(other_address.clone())]).map(handler_result).map_err(handler_err).ok();
+                println!("channel is already closed and there is no newer proof to submit.");
+                return output;
             }
         };
-        client.sync_channel_status(address, other_address);
+        if let Err(e) = client.sync_channel_status(address, other_address) {
+            output.report_error("execute command fail:", e);
+        }
+        output
     }
 }
 
+/// The local proof of the channel, if it is newer than the proofs submitted on chain by both sides.
+fn newer_local_proof(channel: &OffchainChannel) -> Option<ChannelLocalData> {
+    let submitted_version = channel.submitted_proof_version();
+    channel
+        .data
+        .as_ref()
+        .filter(|data| data.version > submitted_version)
+        .cloned()
+}
 
+fn close_with_proof_args(
+    other_address: AccountAddress,
+    offchain_data: &ChannelLocalData,
+) -> Vec<TransactionArgument> {
+    vec![
+        TransactionArgument::Address(other_address),
+        TransactionArgument::U64(offchain_data.version),
+        TransactionArgument::U64(offchain_data.self_balance),
+        TransactionArgument::U64(offchain_data.other_balance),
+        TransactionArgument::ByteArray(ByteArray::new(offchain_data.self_signature.clone())),
+        TransactionArgument::ByteArray(ByteArray::new(offchain_data.other_signature.clone())),
+    ]
+}
 
 /// Close channel
 pub struct ChannelCommandShow {}
@@ -224,20 +333,21 @@ impl Command for ChannelCommandShow {
     fn get_description(&self) -> &'static str {
         "Show channels."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 2 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
 
@@ -245,18 +355,17 @@ impl Command for ChannelCommandShow {
             Some(account_data) => account_data,
             None => {
                 println!("get account data fail.");
-                return;
+                return output;
             }
         };
 
-        for channel in &account_data.channels{
+        for channel in &account_data.channels {
             println!("channel:{:#?}", channel);
         }
+        output
     }
 }
 
-
-
 /// Settle channel
 pub struct ChannelCommandSettle {}
 
@@ -270,34 +379,148 @@ impl Command for ChannelCommandSettle {
     fn get_description(&self) -> &'static str {
         "Settle an channel"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let other_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        execute_script(client, &address, &CHANNEL_SETTLE_TEMPLATE, vec![TransactionArgument::Address(other_address.clone())]).map(handler_result).map_err(handler_err).ok();
-        client.sync_channel_status(address, other_address);
+        if let Err(e) = client.sync_channel_status(address, other_address) {
+            output.report_error("sync_channel_status error", e);
+            return output;
+        }
+        let (newer_proof, other_open) = match client
+            .get_account_data(address)
+            .and_then(|account_data| account_data.get_channel(&other_address))
+        {
+            Some(channel) => (newer_local_proof(channel), channel.other_status.is_open()),
+            None => {
+                println!("get channel with address {} fail.", other_address);
+                return output;
+            }
+        };
+        if let Some(offchain_data) = newer_proof {
+            println!(
+                "submit newer local proof version {} before settle.",
+                offchain_data.version
+            );
+            if let Err(e) = execute_script(
+                client,
+                &options,
+                &address,
+                &CHANNEL_CLOSE_WITH_PROOF_TEMPLATE,
+                close_with_proof_args(other_address, &offchain_data),
+            ) {
+                output.report_error("submit proof fail", e);
+                return output;
+            }
+        }
+        if other_open {
+            println!("other side is not closed, settle only succeeds after the challenge period of the close.");
+        }
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &CHANNEL_SETTLE_TEMPLATE,
+            vec![TransactionArgument::Address(other_address.clone())],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        if let Err(e) = client.sync_channel_status(address, other_address) {
+            output.report_error("execute command fail:", e);
+        }
+        output
     }
 }
 
+/// Build the next transfer request of the channel and sign it.
+fn create_transfer_request(
+    client: &mut ClientProxy,
+    address: AccountAddress,
+    other_address: AccountAddress,
+    amount: u64,
+) -> Result<TransferRequest> {
+    client.sync_channel_status(address, other_address)?;
+    let channel = client
+        .get_account_data(address)
+        .and_then(|account_data| account_data.get_channel(&other_address))
+        .ok_or_else(|| format_err!("get channel with address {} fail.", other_address))?;
+    let mut request = channel.transfer(amount)?;
+    let message =
+        channel.self_proof_message(request.version, request.self_balance, request.other_balance)?;
+    request.signature = client.sign_channel_message(address, &message)?;
+    // Only a conform of this request is accepted afterwards.
+    client
+        .get_account_data(address)
+        .and_then(|account_data| account_data.get_channel(&other_address))
+        .ok_or_else(|| format_err!("get channel with address {} fail.", other_address))?
+        .pending_request = Some(request.clone());
+    Ok(request)
+}
+
+/// Countersign a transfer request of the other party and save the new channel state.
+fn conform_transfer_request(
+    client: &mut ClientProxy,
+    address: AccountAddress,
+    request: TransferRequest,
+) -> Result<TransferConform> {
+    let other_address = request.sender;
+    client.sync_channel_status(address, other_address)?;
+    let message = client
+        .get_account_data(address)
+        .and_then(|account_data| account_data.get_channel(&other_address))
+        .ok_or_else(|| format_err!("get channel with address {} fail.", other_address))?
+        .self_proof_message(request.version, request.other_balance, request.self_balance)?;
+    let signature = client.sign_channel_message(address, &message)?;
+    let channel = client
+        .get_account_data(address)
+        .and_then(|account_data| account_data.get_channel(&other_address))
+        .ok_or_else(|| format_err!("get channel with address {} fail.", other_address))?;
+    let conform = channel.conform(request, signature)?;
+    client.save_channel_state(address, other_address)?;
+    Ok(conform)
+}
+
+/// Apply the countersignature of the other party to a sent request and save the new channel state.
+fn process_transfer_conform(
+    client: &mut ClientProxy,
+    address: AccountAddress,
+    conform: TransferConform,
+) -> Result<()> {
+    let other_address = conform.sender;
+    client.sync_channel_status(address, other_address)?;
+    let channel = client
+        .get_account_data(address)
+        .and_then(|account_data| account_data.get_channel(&other_address))
+        .ok_or_else(|| format_err!("get channel with address {} fail.", other_address))?;
+    channel.process_transfer_conform(conform)?;
+    println!("channel: {:#?}", channel);
+    client.save_channel_state(address, other_address)
+}
 
 /// Offchain transfer
 pub struct ChannelCommandOffchainTransfer {}
@@ -312,81 +535,59 @@ impl Command for ChannelCommandOffchainTransfer {
     fn get_description(&self) -> &'static str {
         "Transfer offchain LibraCoin to other."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 4 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let other_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[3]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
+                output.report_error("invalid amount", e.into());
+                return output;
             }
         };
 
-        match client.sync_channel_status(address, other_address){
-            Err(e) => {
-                report_error("sync_channel_status error", e.into());
-                return;
-            }
-            Ok(_) =>{
-                //ignore
-            }
-        };
-
-        let account_data = match client.get_account_data(address) {
-            Some(account_data) => account_data,
-            None => {
-                print!("get account data fail.");
-                return;
-            }
-        };
-        let channel = match account_data.get_channel(&other_address) {
-            Some(channel) => channel,
-            None => {
-                println!("get channel data fail.");
-                return;
-            }
-        };
-        let request = match channel.transfer(amount){
+        let request = match create_transfer_request(client, address, other_address, amount) {
             Ok(request) => request,
             Err(e) => {
-                report_error("transfer fail: {:?}", e.into());
-                return;
+                output.report_error("transfer fail", e);
+                return output;
             }
         };
 
-        match bincode::serialize(&request){
+        match bincode::serialize(&request) {
             Ok(bytes) => {
                 let hex = hex::encode(bytes);
                 println!("please send transfer request to other:");
-                println!("{}",hex);
+                println!("{}", hex);
             }
             Err(e) => {
-                report_error("transfer fail: {:?}", e.into());
-                return;
+                output.report_error("transfer fail: {:?}", e.into());
+                return output;
             }
         }
-        return;
+        return output;
+        output
     }
 }
 
@@ -403,87 +604,63 @@ impl Command for ChannelCommandOffchainConform {
     fn get_description(&self) -> &'static str {
         "Conform offchain transfer request from other."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let hex = params[2];
-        let bytes = match hex::decode(hex){
+        let bytes = match hex::decode(hex) {
             Ok(bytes) => bytes,
             Err(e) => {
-                report_error("parse hex error.", e.into());
-                return;
+                output.report_error("parse hex error.", e.into());
+                return output;
             }
         };
 
-        let request = match bincode::deserialize::<TransferRequest>(bytes.as_slice()){
+        let request = match bincode::deserialize::<TransferRequest>(bytes.as_slice()) {
             Ok(request) => request,
             Err(e) => {
-                report_error("parse request error.", e.into());
-                return;
+                output.report_error("parse request error.", e.into());
+                return output;
             }
         };
-        let other_address = request.sender.clone();
-        match client.sync_channel_status(address, other_address){
-            Err(e) => {
-                report_error("sync_channel_status error", e.into());
-                return;
-            }
-            Ok(_) =>{
-                //ignore
-            }
-        };
-
-        let account_data = match client.get_account_data(address) {
-            Some(account_data) => account_data,
-            None => {
-                print!("get account data fail.");
-                return;
-            }
-        };
-        let mut channel = match account_data.get_channel(&other_address) {
-            Some(channel) => channel,
-            None => {
-                println!("get channel offchain data fail.");
-                return;
-            }
-        };
-        let conform = match channel.conform(request) {
+        let conform = match conform_transfer_request(client, address, request) {
             Ok(conform) => conform,
             Err(e) => {
-                report_error("conform error", e.into());
-                return;
+                output.report_error("conform error", e);
+                return output;
             }
         };
 
-        match bincode::serialize(&conform){
+        match bincode::serialize(&conform) {
             Ok(bytes) => {
                 let hex = hex::encode(bytes);
                 println!("please send transfer conform to other:");
-                println!("{}",hex);
+                println!("{}", hex);
             }
             Err(e) => {
-                report_error("transfer conform fail: {:?}", e.into());
-                return;
+                output.report_error("transfer conform fail: {:?}", e.into());
+                return output;
             }
         }
-        return;
+        return output;
+        output
     }
 }
 
-
 /// Offchain transfer conform
 pub struct ChannelCommandOffchainProcessConform {}
 
@@ -497,74 +674,237 @@ impl Command for ChannelCommandOffchainProcessConform {
     fn get_description(&self) -> &'static str {
         "Process transfer conform."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         if !client.exist_module("channel") {
             println!("Please deploy channel first.");
-            return;
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let hex = params[2];
-        let bytes = match hex::decode(hex){
+        let bytes = match hex::decode(hex) {
             Ok(bytes) => bytes,
             Err(e) => {
-                report_error("parse hex error.", e.into());
-                return;
+                output.report_error("parse hex error.", e.into());
+                return output;
             }
         };
 
-        let conform = match bincode::deserialize::<TransferConform>(bytes.as_slice()){
+        let conform = match bincode::deserialize::<TransferConform>(bytes.as_slice()) {
             Ok(conform) => conform,
             Err(e) => {
-                report_error("parse conform error.", e.into());
-                return;
+                output.report_error("parse conform error.", e.into());
+                return output;
             }
         };
 
-        let other_address = conform.sender;
+        if let Err(e) = process_transfer_conform(client, address, conform) {
+            output.report_error("process conform error", e);
+        }
+        output
+    }
+}
 
-        match client.sync_channel_status(address, other_address){
+/// Serve transfer requests of the other parties over the network.
+pub struct ChannelCommandListen {}
+
+impl ChannelCommandListen {
+    /// Parse the parameters of the command, reports the error and returns None if they are invalid.
+    fn parse_params(
+        client: &mut ClientProxy,
+        output: &mut CommandOutput,
+        params: &[&str],
+    ) -> Option<(AccountAddress, Multiaddr, Option<usize>)> {
+        if params.len() != 3 && params.len() != 4 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return None;
+        }
+        if !client.exist_module("channel") {
+            println!("Please deploy channel first.");
+            return None;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return None;
+            }
+        };
+        let listen_addr = match params[2].parse::<Multiaddr>() {
+            Ok(addr) => addr,
             Err(e) => {
-                report_error("sync_channel_status error", e.into());
-                return;
+                output.report_error("invalid multiaddr", e.into());
+                return None;
             }
-            Ok(_) =>{
-                //ignore
+        };
+        let max_requests = match params.get(3).map(|s| s.parse::<usize>()).transpose() {
+            Ok(max_requests) => max_requests,
+            Err(e) => {
+                output.report_error("invalid max_requests", e.into());
+                return None;
             }
         };
+        Some((address, listen_addr, max_requests))
+    }
+}
 
-        let account_data = match client.get_account_data(address) {
-            Some(account_data) => account_data,
-            None => {
-                print!("get account data fail.");
-                return;
+impl Command for ChannelCommandListen {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["listen", "l"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <multiaddr> [max_requests]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Run the channel daemon, countersign transfer requests received at a multiaddr, e.g. /ip4/127.0.0.1/tcp/9000. \
+        Serves until max_requests requests are served, or until the client is stopped. \
+        In interactive mode the daemon runs in the background."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (address, listen_addr, max_requests) =
+            match Self::parse_params(client, &mut output, params) {
+                Some(params) => params,
+                None => return output,
+            };
+        let result = channel_daemon::serve(listen_addr, max_requests, |request| {
+            answer_transfer_request(client, address, request)
+        });
+        if let Err(e) = result {
+            output.report_error("channel daemon fail", e);
+        }
+        output
+    }
+    fn execute_shared(&self, client: &SharedClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (address, listen_addr, max_requests) =
+            match Self::parse_params(&mut client.lock().unwrap(), &mut output, params) {
+                Some(params) => params,
+                None => return output,
+            };
+        // The client is only locked while a request is answered, so other commands keep running.
+        let daemon_client = Arc::clone(client);
+        let result = channel_daemon::spawn_serve(listen_addr, max_requests, move |request| {
+            answer_transfer_request(&mut daemon_client.lock().unwrap(), address, request)
+        });
+        match result {
+            Ok((listen_addr, _handle)) => {
+                println!(
+                    "Channel daemon listening on {} in the background",
+                    listen_addr
+                );
+                output.record_value("listen_addr", listen_addr.to_string());
+            }
+            Err(e) => output.report_error("channel daemon fail", e),
+        }
+        output
+    }
+}
+
+/// Countersign a transfer request received by the daemon and build the answer to send back.
+fn answer_transfer_request(
+    client: &mut ClientProxy,
+    address: AccountAddress,
+    request: TransferRequest,
+) -> ChannelMessage {
+    let (other_address, amount) = (request.sender, request.amount);
+    match conform_transfer_request(client, address, request) {
+        Ok(conform) => {
+            println!(
+                "Received {} from {}, channel version {}.",
+                amount, other_address, conform.request.version
+            );
+            ChannelMessage::Conform(conform)
+        }
+        Err(e) => {
+            // Not reported as an error of the command, the daemon may run in the background.
+            println!("[ERROR] conform error: {}", e);
+            ChannelMessage::Reject(e.to_string())
+        }
+    }
+}
+
+/// Transfer offchain to the daemon of the other party.
+pub struct ChannelCommandPay {}
+
+impl Command for ChannelCommandPay {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["pay", "p"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <account_ref_id>|<account_address> <multiaddr> <amount>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Transfer offchain LibraCoin to other through its channel daemon at multiaddr."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() != 5 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if !client.exist_module("channel") {
+            println!("Please deploy channel first.");
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        let mut channel = match account_data.get_channel(&other_address) {
-            Some(channel) => channel,
-            None => {
-                println!("get channel offchain data fail.");
-                return;
+        let other_address = match client.get_account_address_from_parameter(params[2]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        match channel.process_transfer_conform(conform){
-            Ok(()) => {
-                println!("channel: {:#?}", channel);
-            },
+        let peer_addr = match params[3].parse::<Multiaddr>() {
+            Ok(addr) => addr,
             Err(e) => {
-                report_error("process conform error", e.into());
-                return;
+                output.report_error("invalid multiaddr", e.into());
+                return output;
             }
+        };
+        let amount = match ClientProxy::convert_to_micro_libras(params[4]) {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid amount", e.into());
+                return output;
+            }
+        };
+
+        let request = match create_transfer_request(client, address, other_address, amount) {
+            Ok(request) => request,
+            Err(e) => {
+                output.report_error("transfer fail", e);
+                return output;
+            }
+        };
+        match channel_daemon::send_request(peer_addr, request) {
+            Ok(ChannelMessage::Conform(conform)) => {
+                if let Err(e) = process_transfer_conform(client, address, conform) {
+                    output.report_error("process conform error", e);
+                }
+            }
+            Ok(ChannelMessage::Reject(reason)) => {
+                println!("Transfer rejected by other: {}", reason)
+            }
+            Ok(message) => println!("Unexpected answer from other: {:?}", message),
+            Err(e) => output.report_error("send transfer request fail", e),
         }
-        return;
+        output
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,344 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peer to peer exchange of offchain channel updates.
+//!
+//! The payer dials the payee over TCP, upgraded with a noise handshake, and sends a signed
+//! `TransferRequest`. The payee answers with its countersignature in a `TransferConform`, or with
+//! a rejection. Noise only encrypts the connection, the parties are authenticated by the channel
+//! signatures which are checked against the public keys registered on chain.
+//!
+//! The daemon serves connections concurrently. Every connection has to finish its handshake
+//! within `HANDSHAKE_TIMEOUT` and send its request within `READ_TIMEOUT`, so a peer that stalls
+//! can't hold the daemon.
+
+use crate::{TransferConform, TransferRequest};
+use failure::prelude::*;
+use futures_03::{
+    compat::Compat01As03,
+    executor::block_on,
+    future::{poll_fn, TryFutureExt},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    stream::{FuturesUnordered, Stream},
+    Future,
+};
+use logger::prelude::*;
+use netcore::transport::{
+    tcp::{TcpSocket, TcpTransport},
+    Transport, TransportExt,
+};
+use noise::{NoiseConfig, NoiseSocket};
+use parity_multiaddr::Multiaddr;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    sync::{mpsc, Arc},
+    task::Poll,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use tokio::{runtime::current_thread::Runtime, timer::Timeout};
+
+/// Largest message accepted from a peer.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Time an inbound connection has to complete the noise handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a peer has to send its request once connected.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Message exchanged between the two parties of a channel.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ChannelMessage {
+    /// Transfer request signed by the payer.
+    Request(TransferRequest),
+    /// Countersignature of the payee.
+    Conform(TransferConform),
+    /// The payee refused the request, with the reason.
+    Reject(String),
+}
+
+/// Build a Tcp + Noise transport.
+///
+/// The noise keys are random for every connection, so the handshake doesn't tell who the peer is
+/// and anyone can connect. A request only changes the channel once its signature is checked
+/// against the key of its sender registered on chain, see `OffchainChannel::conform`, and the
+/// payer likewise checks the countersignature of the answer.
+pub fn build_channel_transport() -> impl Transport<Output = NoiseSocket<TcpSocket>> {
+    TcpTransport::default().and_then(move |socket, origin| {
+        async move {
+            let noise_config = Arc::new(NoiseConfig::new_random());
+            let (_remote_static_key, socket) =
+                noise_config.upgrade_connection(socket, origin).await?;
+            Ok(socket)
+        }
+    })
+}
+
+/// Read a length prefixed message.
+pub async fn read_message<S: AsyncRead + Unpin>(socket: &mut S) -> Result<ChannelMessage> {
+    let mut len_bytes = [0u8; 4];
+    socket.read_exact(&mut len_bytes).await?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    ensure!(
+        len <= MAX_MESSAGE_SIZE,
+        "message of {} bytes is too large",
+        len
+    );
+    let mut bytes = vec![0u8; len];
+    socket.read_exact(&mut bytes).await?;
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+/// Write a length prefixed message.
+pub async fn write_message<S: AsyncWrite + Unpin>(
+    socket: &mut S,
+    message: &ChannelMessage,
+) -> Result<()> {
+    let bytes = bincode::serialize(message)?;
+    ensure!(
+        bytes.len() <= MAX_MESSAGE_SIZE,
+        "message of {} bytes is too large",
+        bytes.len()
+    );
+    socket
+        .write_all(&(bytes.len() as u32).to_be_bytes())
+        .await?;
+    socket.write_all(&bytes).await?;
+    socket.flush().await?;
+    Ok(())
+}
+
+/// Send a signed transfer request to the peer listening at `addr` and wait for its answer.
+pub fn send_request(addr: Multiaddr, request: TransferRequest) -> Result<ChannelMessage> {
+    block_on(async move {
+        let transport = build_channel_transport();
+        let mut socket = transport.dial(addr)?.await?;
+        write_message(&mut socket, &ChannelMessage::Request(request)).await?;
+        let response = read_message(&mut socket).await?;
+        socket.close().await?;
+        Ok(response)
+    })
+}
+
+/// Serve transfer requests of peers at `addr`. Every request is passed to `handler` and its answer
+/// is sent back. Returns after `max_requests` connections if given, otherwise serves until the
+/// process is stopped.
+pub fn serve<F>(addr: Multiaddr, max_requests: Option<usize>, handler: F) -> Result<()>
+where
+    F: FnMut(TransferRequest) -> ChannelMessage,
+{
+    serve_with(addr, max_requests, handler, |listen_addr| {
+        println!("Channel daemon listening on {}", listen_addr)
+    })
+}
+
+/// Serve transfer requests like `serve`, on a background thread. Returns once the daemon listens,
+/// with the address it listens on, e.g. the port picked for `/tcp/0`, and the handle of the thread.
+pub fn spawn_serve<F>(
+    addr: Multiaddr,
+    max_requests: Option<usize>,
+    handler: F,
+) -> Result<(Multiaddr, JoinHandle<Result<()>>)>
+where
+    F: FnMut(TransferRequest) -> ChannelMessage + Send + 'static,
+{
+    let (listening_sender, listening_receiver) = mpsc::channel();
+    let handle = thread::Builder::new()
+        .name("channel-daemon".to_string())
+        .spawn(move || {
+            serve_with(addr, max_requests, handler, move |listen_addr| {
+                // The caller only waits for the first address.
+                let _ = listening_sender.send(listen_addr.clone());
+            })
+        })?;
+    match listening_receiver.recv() {
+        Ok(listen_addr) => Ok((listen_addr, handle)),
+        // The daemon stopped before listening, report why.
+        Err(_) => match handle.join() {
+            Ok(Err(e)) => Err(e),
+            Ok(Ok(())) => bail!("channel daemon stopped before listening"),
+            Err(_) => bail!("channel daemon panicked"),
+        },
+    }
+}
+
+/// Outcome of waiting for the listener and the connections being served.
+enum ServeEvent<T> {
+    Inbound(Option<T>),
+    Served,
+}
+
+fn serve_with<F, L>(
+    addr: Multiaddr,
+    max_requests: Option<usize>,
+    handler: F,
+    on_listening: L,
+) -> Result<()>
+where
+    F: FnMut(TransferRequest) -> ChannelMessage,
+    L: FnOnce(&Multiaddr),
+{
+    // The timeouts need a tokio timer. The handler borrows the client, so everything runs on the
+    // current thread: connections are served concurrently, but answered one at a time.
+    let mut runtime = Runtime::new()?;
+    let daemon = async move {
+        let transport = build_channel_transport().with_timeout(HANDSHAKE_TIMEOUT);
+        let (mut listener, listen_addr) = transport.listen_on(addr)?;
+        on_listening(&listen_addr);
+
+        let handler = RefCell::new(handler);
+        let mut connections = FuturesUnordered::new();
+        let mut accepted = 0;
+        let mut listening = true;
+        loop {
+            let event = poll_fn(|context| {
+                if listening {
+                    if let Poll::Ready(next) = Pin::new(&mut listener).poll_next(context) {
+                        return Poll::Ready(ServeEvent::Inbound(next));
+                    }
+                }
+                match Pin::new(&mut connections).poll_next(context) {
+                    Poll::Ready(Some(())) => Poll::Ready(ServeEvent::Served),
+                    _ => Poll::Pending,
+                }
+            });
+            match event.await {
+                ServeEvent::Inbound(Some(next)) => {
+                    let (inbound, peer_addr) = next?;
+                    connections.push(serve_connection(inbound, peer_addr, &handler));
+                    accepted += 1;
+                    listening = max_requests.map_or(true, |max| accepted < max);
+                }
+                ServeEvent::Inbound(None) => listening = false,
+                ServeEvent::Served => {}
+            }
+            if !listening && connections.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    };
+    runtime.block_on(Box::pin(daemon).compat())
+}
+
+/// Complete the handshake of an inbound connection, read its request and answer it.
+async fn serve_connection<U, E, F>(inbound: U, peer_addr: Multiaddr, handler: &RefCell<F>)
+where
+    U: Future<Output = std::result::Result<NoiseSocket<TcpSocket>, E>>,
+    E: std::fmt::Display,
+    F: FnMut(TransferRequest) -> ChannelMessage,
+{
+    let mut socket = match inbound.await {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("Channel handshake with {} failed: {}", peer_addr, e);
+            return;
+        }
+    };
+    let read = Timeout::new(Box::pin(read_message(&mut socket)).compat(), READ_TIMEOUT);
+    match Compat01As03::new(read).await {
+        Ok(ChannelMessage::Request(request)) => {
+            // Not held across an await, so connections never contend for it.
+            let response = (&mut *handler.borrow_mut())(request);
+            if let Err(e) = write_message(&mut socket, &response).await {
+                warn!("Unable to answer {}: {}", peer_addr, e);
+            }
+        }
+        Ok(message) => warn!("Unexpected message from {}: {:?}", peer_addr, message),
+        Err(ref e) if e.is_elapsed() => {
+            warn!("No message from {} within {:?}", peer_addr, READ_TIMEOUT)
+        }
+        Err(e) => warn!("Unable to read message from {}: {}", peer_addr, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        channel_daemon::{read_message, send_request, spawn_serve, write_message, ChannelMessage},
+        TransferRequest,
+    };
+    use futures_03::{executor::block_on, io::AsyncWriteExt};
+    use memsocket::MemorySocket;
+    use parity_multiaddr::Protocol;
+    use std::net::TcpStream;
+    use types::account_address::AccountAddress;
+
+    fn transfer_request() -> TransferRequest {
+        TransferRequest {
+            sender: AccountAddress::random(),
+            version: 1,
+            amount: 10,
+            self_balance: 90,
+            other_balance: 110,
+            signature: vec![1; 64],
+        }
+    }
+
+    #[test]
+    fn test_message_framing() {
+        let (mut a, mut b) = MemorySocket::new_pair();
+        let message = ChannelMessage::Request(transfer_request());
+        block_on(write_message(&mut a, &message)).unwrap();
+        assert_eq!(block_on(read_message(&mut b)).unwrap(), message);
+
+        // A length prefix beyond the limit is refused before reading the payload.
+        block_on(a.write_all(&u32::max_value().to_be_bytes())).unwrap();
+        assert!(block_on(read_message(&mut b)).is_err());
+    }
+
+    #[test]
+    fn test_spawn_serve() {
+        let request = transfer_request();
+        let expected = request.clone();
+        let (listen_addr, handle) = spawn_serve(
+            "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
+            Some(1),
+            move |request| {
+                assert_eq!(request, expected);
+                ChannelMessage::Reject("no channel".to_string())
+            },
+        )
+        .unwrap();
+
+        // The daemon answers while the caller keeps running, and stops after max_requests.
+        assert_eq!(
+            send_request(listen_addr, request).unwrap(),
+            ChannelMessage::Reject("no channel".to_string())
+        );
+        handle.join().unwrap().unwrap();
+
+        // An address the daemon can't listen on is reported to the caller.
+        assert!(spawn_serve("/memory/0".parse().unwrap(), None, |_| unreachable!()).is_err());
+    }
+
+    #[test]
+    fn test_stalled_peer_does_not_block_daemon() {
+        let (listen_addr, handle) =
+            spawn_serve("/ip4/127.0.0.1/tcp/0".parse().unwrap(), Some(2), |_| {
+                ChannelMessage::Reject("no channel".to_string())
+            })
+            .unwrap();
+        let port = listen_addr
+            .iter()
+            .find_map(|protocol| match protocol {
+                Protocol::Tcp(port) => Some(port),
+                _ => None,
+            })
+            .unwrap();
+
+        // A peer that connects but never starts the handshake doesn't hold up the next one.
+        let stalled = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        assert_eq!(
+            send_request(listen_addr, transfer_request()).unwrap(),
+            ChannelMessage::Reject("no channel".to_string())
+        );
+
+        // The daemon stops once the stalled connection is gone too.
+        drop(stalled);
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -2,18 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    collections::HashMap,
-    convert::TryFrom,
+    collections::{BTreeMap, HashMap},
+    convert::{TryFrom, TryInto},
     fmt,
     fs::{self, File},
-    io::{Read, stdout, Write},
-    path::Path,
+    io::{stdout, Read, Write},
+    path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
     thread, time,
 };
-use std::collections::BTreeMap;
 
+use chrono::Utc;
 use futures::{future::Future, stream::Stream};
 use hyper;
 use num_traits::{
@@ -21,36 +21,68 @@ use num_traits::{
     identities::Zero,
 };
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use tokio::{self, runtime::Runtime};
 
 use admission_control_proto::proto::admission_control::SubmitTransactionRequest;
 use bytecode_verifier::VerifiedModule;
 use config::trusted_peers::TrustedPeersConfig;
-use crypto::signing::KeyPair;
+use crypto::{hash::CryptoHash, signing::KeyPair};
 use failure::prelude::*;
-use libra_wallet::{io_utils, wallet_library::WalletLibrary};
+use libra_wallet::{
+    io_utils,
+    keystore::{read_new_passphrase, Keystore, KIND_KEYPAIR},
+    offline::OfflineTransaction,
+    wallet_library::WalletLibrary,
+};
 use logger::prelude::*;
-use proto_conv::{FromProtoBytes, IntoProto};
+use proto_conv::{FromProto, FromProtoBytes, IntoProto};
 use types::{
     access_path::AccessPath,
     account_address::{AccountAddress, ADDRESS_LENGTH},
     account_config::{
-        account_received_event_path, account_sent_event_path, AccountResource,
-        association_address, get_account_resource_or_default,
+        account_received_event_path, account_sent_event_path, association_address,
+        get_account_resource_or_default, AccountResource,
     },
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
-    transaction::{Program, RawTransaction, SignedTransaction, Version},
+    language_storage::ModuleId,
+    transaction::{
+        Program, RawTransaction, SignedTransaction, TransactionStatus as VMTransactionStatus,
+        Version,
+    },
     transaction_helpers::{create_signed_txn, TransactionSigner},
     validator_verifier::ValidatorVerifier,
+    vm_error::{ExecutionStatus, VMStatus},
+};
+use vm::{
+    file_format::CompiledModule,
+    gas_schedule::{MAXIMUM_NUMBER_OF_GAS_UNITS, MIN_TRANSACTION_GAS_UNITS},
 };
 
-use crate::{account_state::AccountState, AccountData, AccountStatus, commands::*, grpc_client::GRPCClient, OffchainChannel, resource::{ETokenResource, ChannelResource, ProofResource, Resource}};
+use crate::{
+    account_state::AccountState,
+    commands::*,
+    dry_run::{self, Simulation},
+    grpc_client::GRPCClient,
+    resource::{
+        etoken_event_path, CapabilityResource, ChannelResource, ETokenResource, OrderBookResource,
+        PausedResource, ProofResource, ReserveAttestationResource, Resource, SupplyResource,
+    },
+    resource_decoder::ResourceDecoder,
+    AccountData, AccountStatus, ChannelLocalData, OffchainChannel,
+};
 
-const CLIENT_WALLET_MNEMONIC_FILE: &str = "client.mnemonic";
+pub const CLIENT_WALLET_MNEMONIC_FILE: &str = "client.mnemonic";
+const CLIENT_MODULE_REGISTRY_FILE: &str = "client.modules.toml";
+const CLIENT_CHANNEL_STORE_FILE: &str = "client.channels.toml";
 const GAS_UNIT_PRICE: u64 = 0;
-const MAX_GAS_AMOUNT: u64 = 10_000;
+/// Margin added to the gas used by a local execution, the state may change until the transaction
+/// is executed on chain.
+const GAS_ESTIMATE_MARGIN_PERCENT: u64 = 20;
 const TX_EXPIRATION: i64 = 100;
+/// Prepared transactions have to be carried to the offline signer and back.
+const OFFLINE_TX_EXPIRATION: i64 = 24 * 3600;
 
 /// Enum used for error formatting.
 #[derive(Debug)]
@@ -96,24 +128,179 @@ pub struct ModuleRegistryEntry {
     pub modules: Vec<VerifiedModule>,
 }
 
+/// Module registry as saved next to the wallet recovery file. Only the module names and accounts
+/// are kept, the code is re-fetched from the account state when the registry is loaded.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModuleRegistryFile {
+    /// registered modules
+    pub modules: Vec<ModuleRegistryRecord>,
+}
+
+/// A module registry entry in the registry file.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ModuleRegistryRecord {
+    /// module name
+    pub name: String,
+    /// hex encoded account address the module is published under
+    pub account: String,
+}
+
+impl ModuleRegistryFile {
+    /// Load the registry file, a missing file is an empty registry.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// Save the registry file, replacing any previous content. The registry is written to a
+    /// temporary file renamed over the previous one, so that the file is never left truncated.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_vec(self)?;
+        let tmp_path = path.with_extension("toml.tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Add the records to the registry, replacing the records with the same name.
+    pub fn merge(&mut self, records: Vec<ModuleRegistryRecord>) {
+        for record in records {
+            self.modules.retain(|r| r.name != record.name);
+            self.modules.push(record);
+        }
+        self.modules.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Latest mutually signed offchain state of every channel, saved next to the wallet recovery file
+/// so that it can still be submitted with `close_with_proof` after a restart.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ChannelStoreFile {
+    /// stored channel states
+    pub channels: Vec<ChannelStateRecord>,
+}
+
+/// A channel state in the channel store file, addresses and signatures are hex encoded.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChannelStateRecord {
+    pub self_address: String,
+    pub other_address: String,
+    pub version: u64,
+    pub self_balance: u64,
+    pub other_balance: u64,
+    pub self_signature: String,
+    pub other_signature: String,
+}
+
+impl ChannelStoreFile {
+    /// Load the store file, a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// Save the store file, replacing any previous content.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_vec(self)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Find the state of the channel between the two accounts.
+    pub fn get(
+        &self,
+        self_address: &AccountAddress,
+        other_address: &AccountAddress,
+    ) -> Option<&ChannelStateRecord> {
+        let (self_address, other_address) = (self_address.to_string(), other_address.to_string());
+        self.channels
+            .iter()
+            .find(|r| r.self_address == self_address && r.other_address == other_address)
+    }
+
+    /// Replace the state of the channel between the two accounts, `None` removes it.
+    pub fn put(
+        &mut self,
+        self_address: &AccountAddress,
+        other_address: &AccountAddress,
+        data: Option<&ChannelLocalData>,
+    ) {
+        let (self_hex, other_hex) = (self_address.to_string(), other_address.to_string());
+        self.channels
+            .retain(|r| !(r.self_address == self_hex && r.other_address == other_hex));
+        if let Some(data) = data {
+            self.channels.push(ChannelStateRecord {
+                self_address: self_hex,
+                other_address: other_hex,
+                version: data.version,
+                self_balance: data.self_balance,
+                other_balance: data.other_balance,
+                self_signature: hex::encode(&data.self_signature),
+                other_signature: hex::encode(&data.other_signature),
+            });
+        }
+    }
+}
+
+impl ChannelStateRecord {
+    pub fn to_local_data(&self) -> Result<ChannelLocalData> {
+        Ok(ChannelLocalData {
+            version: self.version,
+            self_balance: self.self_balance,
+            other_balance: self.other_balance,
+            self_signature: hex::decode(&self.self_signature)?,
+            other_signature: hex::decode(&self.other_signature)?,
+        })
+    }
+}
+
 impl ModuleRegistryEntry {
     pub fn get_resource(&self, data: &BTreeMap<Vec<u8>, Vec<u8>>) -> Vec<Resource> {
         let mut resources = vec![];
         match self.name.as_str() {
             "etoken" => {
-                resources.push(Resource::EToken(ETokenResource::make_from(self.account.clone(), data).ok()));
+                resources.push(Resource::EToken(
+                    ETokenResource::make_from(self.account.clone(), data).ok(),
+                ));
+                resources.push(Resource::Capability(
+                    CapabilityResource::make_from(self.account.clone(), data).ok(),
+                ));
+                resources.push(Resource::Supply(
+                    SupplyResource::make_from(self.account.clone(), data).ok(),
+                ));
+                resources.push(Resource::Paused(
+                    PausedResource::make_from(self.account.clone(), data).ok(),
+                ));
+                resources.push(Resource::ReserveAttestation(
+                    ReserveAttestationResource::make_from(self.account.clone(), data).ok(),
+                ));
+                resources.push(Resource::OrderBook(
+                    OrderBookResource::make_from(self.account.clone(), data).ok(),
+                ));
             }
             "channel" => {
-                resources.push(Resource::Channel(ChannelResource::make_from(self.account.clone(), data).map_err(|e| {
-                    //println!("get channel resource error:{:?}",e)
-                }).ok()));
-                resources.push(Resource::Proof(ProofResource::make_from(self.account.clone(), data).map_err(|e| {
-                    //println!("get channel resource error:{:?}",e)
-                }).ok()));
-            }
-            _ => {
-                panic!("unsupported resource:{}", self.name.clone())
+                resources.push(Resource::Channel(
+                    ChannelResource::make_from(self.account.clone(), data)
+                        .map_err(|e| {
+                            //println!("get channel resource error:{:?}",e)
+                        })
+                        .ok(),
+                ));
+                resources.push(Resource::Proof(
+                    ProofResource::make_from(self.account.clone(), data)
+                        .map_err(|e| {
+                            //println!("get channel resource error:{:?}",e)
+                        })
+                        .ok(),
+                ));
             }
+            _ => panic!("unsupported resource:{}", self.name.clone()),
         };
         return resources;
     }
@@ -138,6 +325,14 @@ pub struct ClientProxy {
 
     /// module registry
     pub module_registry: HashMap<String, ModuleRegistryEntry>,
+    /// Account the USDS modules were published under at genesis, if configured.
+    usds_issuer: Option<AccountAddress>,
+    /// File the module registry is saved to, next to the wallet recovery file.
+    module_registry_file: PathBuf,
+    /// File the offchain channel states are saved to, next to the wallet recovery file.
+    channel_store_file: PathBuf,
+    /// Transactions submitted and values recorded for the running command.
+    command_output: CommandOutput,
 }
 
 impl ClientProxy {
@@ -150,6 +345,7 @@ impl ClientProxy {
         sync_on_wallet_recovery: bool,
         faucet_server: Option<String>,
         mnemonic_file: Option<String>,
+        keystore_passphrase: Option<String>,
     ) -> Result<Self> {
         let validators_config = TrustedPeersConfig::load_config(Path::new(validator_set_file));
         let validators = validators_config.get_trusted_consensus_peers();
@@ -157,9 +353,16 @@ impl ClientProxy {
             !validators.is_empty(),
             "Not able to load validators from trusted peers config!"
         );
+        let bls_validators = validators_config.get_trusted_consensus_bls_peers();
         // Total 3f + 1 validators, 2f + 1 correct signatures are required.
         // If < 4 validators, all validators have to agree.
-        let validator_verifier = Arc::new(ValidatorVerifier::new(validators));
+        let mut validator_verifier = ValidatorVerifier::new(validators);
+        // Ledger infos certified by an aggregated BLS12-381 signature are only verifiable with the
+        // BLS keys of all the validators.
+        if bls_validators.len() == validator_verifier.len() {
+            validator_verifier = validator_verifier.with_bls_public_keys(bls_validators)?;
+        }
+        let validator_verifier = Arc::new(validator_verifier);
         let client = GRPCClient::new(host, ac_port, validator_verifier)?;
 
         let accounts = vec![];
@@ -169,7 +372,7 @@ impl ClientProxy {
             None
         } else {
             let faucet_account_keypair: KeyPair =
-                ClientProxy::load_faucet_account_file(faucet_account_file);
+                ClientProxy::load_faucet_account_file(faucet_account_file, &keystore_passphrase)?;
             let faucet_account_data = Self::get_account_data_from_address(
                 &client,
                 association_address(),
@@ -197,15 +400,184 @@ impl ClientProxy {
             address_to_ref_id,
             faucet_server,
             faucet_account,
-            wallet: Self::get_libra_wallet(mnemonic_file)?,
+            module_registry_file: Self::get_wallet_sibling_file_path(
+                &mnemonic_file,
+                CLIENT_MODULE_REGISTRY_FILE,
+            )?,
+            channel_store_file: Self::get_wallet_sibling_file_path(
+                &mnemonic_file,
+                CLIENT_CHANNEL_STORE_FILE,
+            )?,
+            wallet: Self::get_libra_wallet(mnemonic_file, &keystore_passphrase)?,
             sync_on_wallet_recovery,
             module_registry: HashMap::new(),
+            usds_issuer: None,
+            command_output: CommandOutput::default(),
         })
     }
 
     /// registry a new module
-    pub fn registry_module(&mut self, name: String, account: AccountAddress, modules: Vec<VerifiedModule>) {
-        self.module_registry.insert(name.clone(), ModuleRegistryEntry { name, account, modules });
+    pub fn registry_module(
+        &mut self,
+        name: String,
+        account: AccountAddress,
+        modules: Vec<VerifiedModule>,
+    ) {
+        self.module_registry.insert(
+            name.clone(),
+            ModuleRegistryEntry {
+                name,
+                account,
+                modules,
+            },
+        );
+        if let Err(e) = self.save_module_registry() {
+            warn!(
+                "Unable to save module registry to {:?}: {}",
+                self.module_registry_file, e
+            );
+        }
+    }
+
+    /// Save the names and accounts of the registered modules to the registry file. The modules
+    /// already in the file and not registered in this session are kept.
+    pub fn save_module_registry(&self) -> Result<()> {
+        let mut registry_file = ModuleRegistryFile::load(&self.module_registry_file)?;
+        registry_file.merge(
+            self.module_registry
+                .values()
+                .map(|entry| ModuleRegistryRecord {
+                    name: entry.name.clone(),
+                    account: entry.account.to_string(),
+                })
+                .collect(),
+        );
+        registry_file.save(&self.module_registry_file)
+    }
+
+    /// Load the registry file and re-fetch the code of every module in it from chain.
+    /// Returns the names of the loaded modules, the modules that can't be fetched are skipped but
+    /// stay in the file.
+    pub fn load_module_registry(&mut self) -> Result<Vec<String>> {
+        let registry_file = ModuleRegistryFile::load(&self.module_registry_file)?;
+        let mut names = vec![];
+        for record in registry_file.modules {
+            match self.fetch_registry_record(&record) {
+                Ok(entry) => {
+                    self.module_registry.insert(record.name.clone(), entry);
+                    names.push(record.name);
+                }
+                Err(e) => warn!("Unable to load module {} from chain: {}", record.name, e),
+            }
+        }
+        Ok(names)
+    }
+
+    fn fetch_registry_record(
+        &mut self,
+        record: &ModuleRegistryRecord,
+    ) -> Result<ModuleRegistryEntry> {
+        let account = AccountAddress::from_str(&record.account)?;
+        let modules = self
+            .fetch_module_from_chain(&record.name, account)?
+            .ok_or_else(|| {
+                format_err!("Module {} is not published under {}", record.name, account)
+            })?;
+        Ok(ModuleRegistryEntry {
+            name: record.name.clone(),
+            account,
+            modules,
+        })
+    }
+
+    /// Set the account the USDS modules were published under at genesis.
+    pub fn set_usds_issuer(&mut self, issuer: AccountAddress) {
+        self.usds_issuer = Some(issuer);
+    }
+
+    /// Fetch the code of a module published on chain under `account` and registry it.
+    pub fn registry_module_from_chain(
+        &mut self,
+        name: String,
+        account: AccountAddress,
+    ) -> Result<()> {
+        if !self.try_registry_module_from_chain(name.clone(), account)? {
+            bail!("Module {} is not published under {}", name, account);
+        }
+        Ok(())
+    }
+
+    /// Same as `registry_module_from_chain`, but returns false instead of failing when the module
+    /// is not published under `account`. Other errors, e.g. of the network, are returned.
+    pub fn try_registry_module_from_chain(
+        &mut self,
+        name: String,
+        account: AccountAddress,
+    ) -> Result<bool> {
+        match self.fetch_module_from_chain(&name, account)? {
+            Some(modules) => {
+                self.registry_module(name, account, modules);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Fetch and verify the code of a module published on chain under `account`, `None` if it is
+    /// not published there.
+    fn fetch_module_from_chain(
+        &mut self,
+        name: &str,
+        account: AccountAddress,
+    ) -> Result<Option<Vec<VerifiedModule>>> {
+        let module_names: &[&str] = match name {
+            "etoken" => &["ETokenCapability", "EToken"],
+            "channel" => &["Channel"],
+            _ => bail!("unsupported module:{}", name),
+        };
+        let blob = match self.get_account_state_and_update(account)?.0 {
+            Some(blob) => blob,
+            None => return Ok(None),
+        };
+        let account_btree: BTreeMap<Vec<u8>, Vec<u8>> = (&blob).try_into()?;
+        let codes = module_names
+            .iter()
+            .map(|module_name| {
+                let module_id = ModuleId::new(account, module_name.to_string());
+                account_btree.get(&AccessPath::code_access_path(&module_id).path)
+            })
+            .collect::<Vec<_>>();
+        if codes.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+        codes
+            .into_iter()
+            .zip(module_names)
+            .map(|(code, module_name)| {
+                let code = code.ok_or_else(|| {
+                    format_err!("Module {} is not published under {}", module_name, account)
+                })?;
+                let compiled_module = CompiledModule::deserialize(code).map_err(|e| {
+                    format_err!("Unable to deserialize module {}: {:?}", module_name, e)
+                })?;
+                VerifiedModule::new(compiled_module).map_err(|(_, errors)| {
+                    format_err!("Module {} failed to verify: {:?}", module_name, errors)
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Make sure the etoken module is registered, looking it up under the configured USDS issuer
+    /// when this session did not issue it.
+    pub fn resolve_etoken_module(&mut self) -> Result<()> {
+        if self.exist_module("etoken") {
+            return Ok(());
+        }
+        let issuer = self
+            .usds_issuer
+            .ok_or_else(|| format_err!("Please issue etoken first."))?;
+        self.registry_module_from_chain("etoken".to_string(), issuer)
     }
 
     /// check module is exist
@@ -215,67 +587,127 @@ impl ClientProxy {
 
     /// get all module.
     pub fn get_module_registry(&self) -> Vec<ModuleRegistryEntry> {
-        return self.module_registry.iter().map(|(_k, v)| v.clone()).collect::<Vec<_>>();
+        return self
+            .module_registry
+            .iter()
+            .map(|(_k, v)| v.clone())
+            .collect::<Vec<_>>();
     }
 
-    pub fn sync_channel_status(&mut self, self_address: AccountAddress, other_address: AccountAddress) -> Result<()> {
-        let self_blob = self.client.get_account_blob(self_address.clone())?.0.ok_or(format_err!("Unable to get account state by address {}", self_address))?;
-        let other_blob = self.client.get_account_blob(other_address.clone())?.0.ok_or(format_err!("Unable to get account state by address {}", other_address))?;
+    /// Sign an offchain channel proof message with the wallet key of the account.
+    pub fn sign_channel_message(
+        &mut self,
+        address: AccountAddress,
+        message: &[u8],
+    ) -> Result<Vec<u8>> {
+        let account_data = self
+            .get_account_data(address)
+            .ok_or_else(|| format_err!("Unable to get account data {}", address))?;
+        ensure!(
+            account_data.key_pair.is_none(),
+            "Only wallet accounts can sign channel proofs."
+        );
+        let (signature, _) = self.wallet.sign_message(&address, message)?;
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    pub fn sync_channel_status(
+        &mut self,
+        self_address: AccountAddress,
+        other_address: AccountAddress,
+    ) -> Result<()> {
+        let self_blob = self
+            .client
+            .get_account_blob(self_address.clone())?
+            .0
+            .ok_or(format_err!(
+                "Unable to get account state by address {}",
+                self_address
+            ))?;
+        let other_blob = self
+            .client
+            .get_account_blob(other_address.clone())?
+            .0
+            .ok_or(format_err!(
+                "Unable to get account state by address {}",
+                other_address
+            ))?;
 
         let module_registry = self.get_module_registry();
         let self_state = AccountState::from_blob(&self_blob, &module_registry)?;
         let other_state = AccountState::from_blob(&other_blob, &module_registry)?;
 
-        let mut self_account_data = self.get_account_data(self_address.clone()).ok_or(format_err!("Unable to get account data {}", self_address))?;
+        let channel_store_file = self.channel_store_file.clone();
+        let mut self_account_data = self
+            .get_account_data(self_address.clone())
+            .ok_or(format_err!("Unable to get account data {}", self_address))?;
 
-        let self_channel_resource = match self_state.find_resource(|r| -> bool {
-            match r {
-                Resource::Channel(_) => true,
-                _ => false
-            }
-        }).unwrap() {
+        let self_channel_resource = match self_state
+            .find_resource(|r| -> bool {
+                match r {
+                    Resource::Channel(_) => true,
+                    _ => false,
+                }
+            })
+            .unwrap()
+        {
             Resource::Channel(resource) => resource,
             _ => None,
         };
         if self_channel_resource.is_none() {
             self_account_data.delete_channel(&other_address);
-            return Ok(())
+            // The channel is settled, its offchain state is of no use anymore.
+            let mut store = ChannelStoreFile::load(&channel_store_file)?;
+            if store.get(&self_address, &other_address).is_some() {
+                store.put(&self_address, &other_address, None);
+                store.save(&channel_store_file)?;
+            }
+            return Ok(());
         }
 
         let self_channel_resource = self_channel_resource.unwrap();
-        //.ok_or(format_err!("Unable to get account channel resource by address {}", self_address))?;
-
-        let other_channel_resource = match other_state.find_resource(|r| -> bool {
-            match r {
-                Resource::Channel(_) => true,
-                _ => false
-            }
-        }).unwrap() {
+        //.ok_or(format_err!("Unable to get account channel resource by address {}",
+        //.ok_or(format_err!("Unable self_address))?;
+
+        let other_channel_resource = match other_state
+            .find_resource(|r| -> bool {
+                match r {
+                    Resource::Channel(_) => true,
+                    _ => false,
+                }
+            })
+            .unwrap()
+        {
             Resource::Channel(resource) => resource,
             _ => None,
         };
 
-        let self_proof_resource = match self_state.find_resource(|r| -> bool {
-            match r {
-                Resource::Proof(_) => true,
-                _ => false
-            }
-        }).unwrap() {
+        let self_proof_resource = match self_state
+            .find_resource(|r| -> bool {
+                match r {
+                    Resource::Proof(_) => true,
+                    _ => false,
+                }
+            })
+            .unwrap()
+        {
             Resource::Proof(resource) => resource,
             _ => None,
         };
 
-        let other_proof_resource = match other_state.find_resource(|r| -> bool {
-            match r {
-                Resource::Proof(_) => true,
-                _ => false
-            }
-        }).unwrap() {
+        let other_proof_resource = match other_state
+            .find_resource(|r| -> bool {
+                match r {
+                    Resource::Proof(_) => true,
+                    _ => false,
+                }
+            })
+            .unwrap()
+        {
             Resource::Proof(resource) => resource,
             _ => None,
         };
 
-
         match self_account_data.get_channel(&other_address) {
             Some(channel) => {
                 channel.update_with_resource(self_channel_resource, self_proof_resource);
@@ -284,13 +716,41 @@ impl ClientProxy {
                 }
             }
             None => {
-                let channel = OffchainChannel::new(self_address, other_address, self_channel_resource, other_channel_resource, self_proof_resource, other_proof_resource);
+                let mut channel = OffchainChannel::new(
+                    self_address,
+                    other_address,
+                    self_channel_resource,
+                    other_channel_resource,
+                    self_proof_resource,
+                    other_proof_resource,
+                );
+                // Restore the latest mutually signed state saved before a restart.
+                if let Some(record) =
+                    ChannelStoreFile::load(&channel_store_file)?.get(&self_address, &other_address)
+                {
+                    channel.data = Some(record.to_local_data()?);
+                }
                 self_account_data.append_channel(channel);
             }
         }
         Ok(())
     }
 
+    /// Save the offchain state of the channel between the two accounts to the channel store file.
+    pub fn save_channel_state(
+        &mut self,
+        self_address: AccountAddress,
+        other_address: AccountAddress,
+    ) -> Result<()> {
+        let data = self
+            .get_account_data(self_address)
+            .and_then(|account_data| account_data.get_channel(&other_address))
+            .and_then(|channel| channel.data.clone());
+        let mut store = ChannelStoreFile::load(&self.channel_store_file)?;
+        store.put(&self_address, &other_address, data.as_ref());
+        store.save(&self.channel_store_file)
+    }
+
     fn get_account_ref_id(&self, sender_account_address: &AccountAddress) -> Result<usize> {
         Ok(*self
             .address_to_ref_id
@@ -431,6 +891,7 @@ impl ClientProxy {
                             "Transaction completed, found sequence number {}]",
                             chain_seq_number
                         );
+                        self.resolve_committed_transactions(account, chain_seq_number);
                         break;
                     }
                     if max_iterations % 100 == 0 {
@@ -481,9 +942,11 @@ impl ClientProxy {
                 .ok_or_else(|| {
                     format_err!("Unable to find sender account: {}", sender_account_ref_id)
                 })?;
-            self.client.submit_transaction(Some(sender_mut), &req)?;
+            let resp = self.client.submit_transaction(Some(sender_mut), &req);
             sender_address = sender_mut.address;
             sender_sequence = sender_mut.sequence_number;
+            self.record_submitted_transaction(&req, &resp);
+            resp?;
         }
 
         if is_blocking {
@@ -496,27 +959,41 @@ impl ClientProxy {
         })
     }
 
-    /// Send transaction with program
+    /// Send transaction with program, or only execute it locally for a dry run.
     pub fn send_transaction(
         &mut self,
         sender_address: &AccountAddress,
         program: Program,
-        gas_unit_price: Option<u64>,
-        max_gas_amount: Option<u64>,
+        options: &TransactionOptions,
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
         let sender_sequence;
         let resp;
-        let sender_account_ref_id = *self
-            .address_to_ref_id
-            .get(sender_address)
-            .ok_or_else(|| {
+        let sender_account_ref_id =
+            *self.address_to_ref_id.get(sender_address).ok_or_else(|| {
                 format_err!(
                     "Unable to find existing managing account by address: {}, to see all existing \
                      accounts, run: 'account list'",
                     sender_address
                 )
             })?;
+        if options.dry_run {
+            // Without a limit the simulation shows the gas the transaction needs.
+            let raw_txn = self.unchecked_transaction(
+                *sender_address,
+                program,
+                options
+                    .max_gas_amount
+                    .unwrap_or(MAXIMUM_NUMBER_OF_GAS_UNITS),
+                options.gas_unit_price.unwrap_or(GAS_UNIT_PRICE),
+            )?;
+            let sequence_number = raw_txn.sequence_number();
+            self.simulate_transaction(raw_txn)?;
+            return Ok(IndexAndSequence {
+                account_index: AccountEntry::Index(sender_account_ref_id),
+                sequence_number,
+            });
+        }
         {
             let sender = self.accounts.get(sender_account_ref_id).ok_or_else(|| {
                 format_err!("Unable to find sender account: {}", sender_account_ref_id)
@@ -525,8 +1002,8 @@ impl ClientProxy {
             let req = self.create_submit_transaction_req(
                 program,
                 sender,
-                gas_unit_price, /* gas_unit_price */
-                max_gas_amount, /* max_gas_amount */
+                options.max_gas_amount,
+                options.gas_unit_price,
             )?;
             let sender_mut = self
                 .accounts
@@ -536,6 +1013,7 @@ impl ClientProxy {
                 })?;
             resp = self.client.submit_transaction(Some(sender_mut), &req);
             sender_sequence = sender_mut.sequence_number;
+            self.record_submitted_transaction(&req, &resp);
         }
 
         if is_blocking {
@@ -603,12 +1081,18 @@ impl ClientProxy {
         )
     }
 
-    /// Submit a transaction to the network.
+    /// Submit a transaction to the network. With a signer, the file holds a proto encoded
+    /// `RawTransaction` that is signed by the signer's key. Without one, the file is a transaction
+    /// file of the offline signing workflow, signed by `offline_signer`.
     pub fn submit_transaction_from_disk(
         &mut self,
         space_delim_strings: &[&str],
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
+        if space_delim_strings.len() == 2 {
+            let txn = OfflineTransaction::load(Path::new(space_delim_strings[1]))?;
+            return self.submit_signed_transaction(txn.signed_transaction()?, is_blocking);
+        }
         let signer_account_address =
             self.get_account_address_from_parameter(space_delim_strings[1])?;
 
@@ -637,9 +1121,7 @@ impl ClientProxy {
         txn: RawTransaction,
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
-        let sender_address;
-        let sender_sequence;
-        {
+        let signed_txn = {
             let signer_account_ref_id = self.get_account_ref_id(&signer_address)?;
             let signer_account = self.accounts.get(signer_account_ref_id).ok_or_else(|| {
                 format_err!("Unable to find sender account: {}", signer_account_ref_id)
@@ -648,19 +1130,30 @@ impl ClientProxy {
                 Some(key_pair) => Box::new(key_pair),
                 None => Box::new(&self.wallet),
             };
-            let mut req = SubmitTransactionRequest::new();
-            let txn = signer.sign_txn(txn).map_err(|_| {
+            signer.sign_txn(txn).map_err(|_| {
                 format_err!(
                     "Account #{} failed to sign transaction",
                     signer_account_ref_id
                 )
-            })?;
-            sender_address = txn.sender();
-            sender_sequence = txn.sequence_number();
+            })?
+        };
+        self.submit_signed_transaction(signed_txn, is_blocking)
+    }
 
-            req.set_signed_txn(txn.into_proto());
-            self.client.submit_transaction(None, &req)?;
-        }
+    /// Submit a transaction signed elsewhere, e.g. by `offline_signer`.
+    pub fn submit_signed_transaction(
+        &mut self,
+        txn: SignedTransaction,
+        is_blocking: bool,
+    ) -> Result<IndexAndSequence> {
+        let sender_address = txn.sender();
+        let sender_sequence = txn.sequence_number();
+
+        let mut req = SubmitTransactionRequest::new();
+        req.set_signed_txn(txn.into_proto());
+        let resp = self.client.submit_transaction(None, &req);
+        self.record_submitted_transaction(&req, &resp);
+        resp?;
 
         if is_blocking {
             self.wait_for_transaction(sender_address, sender_sequence);
@@ -674,6 +1167,110 @@ impl ClientProxy {
         })
     }
 
+    /// Build an unsigned transaction for the offline signing workflow, valid for the next
+    /// sequence number of the sender on chain. The sender does not have to be a local account.
+    pub fn prepare_transaction(
+        &self,
+        sender_address: AccountAddress,
+        program: Program,
+        gas_unit_price: Option<u64>,
+        max_gas_amount: Option<u64>,
+    ) -> Result<RawTransaction> {
+        let (max_gas_amount, gas_unit_price) =
+            self.gas_parameters(sender_address, &program, max_gas_amount, gas_unit_price)?;
+        self.unchecked_transaction(sender_address, program, max_gas_amount, gas_unit_price)
+    }
+
+    /// Build an unsigned transaction for the next sequence number of the sender on chain with the
+    /// given gas parameters as they are.
+    fn unchecked_transaction(
+        &self,
+        sender_address: AccountAddress,
+        program: Program,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+    ) -> Result<RawTransaction> {
+        let sequence_number = self.client.get_sequence_number(sender_address)?;
+        Ok(RawTransaction::new(
+            sender_address,
+            sequence_number,
+            program,
+            max_gas_amount,
+            gas_unit_price,
+            time::Duration::new((Utc::now().timestamp() + OFFLINE_TX_EXPIRATION) as u64, 0),
+        ))
+    }
+
+    /// Gas parameters of a transaction, `(max_gas_amount, gas_unit_price)`. Without values given
+    /// by the caller, e.g. with the `--max-gas` and `--gas-price` options of a command, the max
+    /// gas amount is estimated by executing the program locally and the price is the default one.
+    /// Fails if the sender can not pay for the max gas amount, the way mempool would reject the
+    /// transaction.
+    pub fn gas_parameters(
+        &self,
+        sender_address: AccountAddress,
+        program: &Program,
+        max_gas_amount: Option<u64>,
+        gas_unit_price: Option<u64>,
+    ) -> Result<(u64, u64)> {
+        let gas_unit_price = gas_unit_price.unwrap_or(GAS_UNIT_PRICE);
+        let max_gas_amount = match max_gas_amount {
+            Some(max_gas_amount) => max_gas_amount,
+            None => self.estimate_gas(sender_address, program.clone())?,
+        };
+        let balance =
+            get_account_resource_or_default(&self.client.get_account_blob(sender_address)?.0)?
+                .balance();
+        check_gas_balance(balance, max_gas_amount, gas_unit_price)?;
+        Ok((max_gas_amount, gas_unit_price))
+    }
+
+    /// Estimate the max gas amount of a program by executing it locally against the latest ledger
+    /// state, with a margin for state changes until it is executed on chain.
+    pub fn estimate_gas(&self, sender_address: AccountAddress, program: Program) -> Result<u64> {
+        let (status, gas_used) = self.simulate_gas(sender_address, program)?;
+        match status {
+            VMTransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)) => {}
+            status => bail!(
+                "Unable to estimate the gas, the transaction fails with {:?}. Give --max-gas to \
+                 submit it anyway",
+                status
+            ),
+        }
+        let estimate = gas_used * (100 + GAS_ESTIMATE_MARGIN_PERCENT) / 100;
+        Ok(estimate
+            .max(MIN_TRANSACTION_GAS_UNITS)
+            .min(MAXIMUM_NUMBER_OF_GAS_UNITS))
+    }
+
+    /// Execute a program locally with the largest max gas amount, returning the status and the
+    /// gas used.
+    pub fn simulate_gas(
+        &self,
+        sender_address: AccountAddress,
+        program: Program,
+    ) -> Result<(VMTransactionStatus, u64)> {
+        // The gas used does not depend on the price, a zero price keeps the balance out of it.
+        let raw_txn =
+            self.unchecked_transaction(sender_address, program, MAXIMUM_NUMBER_OF_GAS_UNITS, 0)?;
+        let simulation = dry_run::simulate(&self.client, raw_txn, &self.get_module_registry())?;
+        Ok((
+            simulation.output.status().clone(),
+            simulation.output.gas_used(),
+        ))
+    }
+
+    /// Execute a transaction locally against the latest ledger state and print its output,
+    /// without signing it with the keys of the sender or submitting it.
+    pub fn simulate_transaction(&mut self, raw_txn: RawTransaction) -> Result<Simulation> {
+        let module_registry = self.get_module_registry();
+        let simulation = dry_run::simulate(&self.client, raw_txn, &module_registry)?;
+        let decoder = ResourceDecoder::from_registry(&module_registry);
+        simulation.print(&decoder)?;
+        self.record_value("simulation", simulation.to_json(&decoder)?);
+        Ok(simulation)
+    }
+
     /// Get the latest account state from validator.
     pub fn get_latest_account_state(
         &mut self,
@@ -796,8 +1393,16 @@ impl ClientProxy {
         let path = match space_delim_strings[2] {
             "sent" => account_sent_event_path(),
             "received" => account_received_event_path(),
+            "etoken_sent" | "etoken_received" | "etoken_mint" | "etoken_burn" => {
+                self.resolve_etoken_module()?;
+                etoken_event_path(
+                    self.module_registry["etoken"].account,
+                    &space_delim_strings[2]["etoken_".len()..],
+                )?
+            }
             _ => bail!(
-                "Unknown event type: {:?}, only sent and received are supported",
+                "Unknown event type: {:?}, only sent, received, etoken_sent, etoken_received, \
+                 etoken_mint and etoken_burn are supported",
                 space_delim_strings[2]
             ),
         };
@@ -832,8 +1437,13 @@ impl ClientProxy {
             "Invalid number of arguments for writing recovery"
         );
 
-        self.wallet
-            .write_recovery(&Path::new(space_delim_strings[1]))?;
+        let path = Path::new(space_delim_strings[1]);
+        match read_new_passphrase(
+            "Passphrase to encrypt the recovery file with (empty to write it in plain text): ",
+        )? {
+            Some(passphrase) => self.wallet.write_encrypted_recovery(&path, &passphrase)?,
+            None => self.wallet.write_recovery(&path)?,
+        }
         Ok(())
     }
 
@@ -847,7 +1457,7 @@ impl ClientProxy {
             "Invalid number of arguments for recovering wallets"
         );
 
-        let wallet = WalletLibrary::recover(&Path::new(space_delim_strings[1]))?;
+        let wallet = io_utils::recover_with_passphrase_prompt(&Path::new(space_delim_strings[1]))?;
         let wallet_addresses = wallet.get_addresses()?;
         let mut account_data = Vec::new();
         for address in wallet_addresses {
@@ -943,14 +1553,26 @@ impl ClientProxy {
             },
             false => (0, AccountStatus::Local),
         };
-        Ok(AccountData::new(
-            address,
-            key_pair,
-            sequence_number,
-            status))
+        Ok(AccountData::new(address, key_pair, sequence_number, status))
     }
 
-    fn get_libra_wallet(mnemonic_file: Option<String>) -> Result<WalletLibrary> {
+    fn get_wallet_sibling_file_path(
+        mnemonic_file: &Option<String>,
+        file_name: &str,
+    ) -> Result<PathBuf> {
+        let wallet_recovery_file_path = match mnemonic_file {
+            Some(input_mnemonic_word) => Path::new(input_mnemonic_word).to_path_buf(),
+            None => std::env::current_dir()?.join(CLIENT_WALLET_MNEMONIC_FILE),
+        };
+        Ok(wallet_recovery_file_path.with_file_name(file_name))
+    }
+
+    /// Load the wallet from the recovery file, or create one if there is none. With a passphrase,
+    /// a new or plain text recovery file is written as an encrypted keystore, which migrates it.
+    fn get_libra_wallet(
+        mnemonic_file: Option<String>,
+        keystore_passphrase: &Option<String>,
+    ) -> Result<WalletLibrary> {
         let wallet_recovery_file_path = if let Some(input_mnemonic_word) = mnemonic_file {
             Path::new(&input_mnemonic_word).to_path_buf()
         } else {
@@ -959,11 +1581,33 @@ impl ClientProxy {
             file_path
         };
 
+        if io_utils::is_encrypted(&wallet_recovery_file_path) {
+            let passphrase = keystore_passphrase.as_ref().ok_or_else(|| {
+                format_err!(
+                    "The wallet file {:?} is encrypted, a passphrase is required",
+                    wallet_recovery_file_path
+                )
+            })?;
+            return io_utils::recover_encrypted(&wallet_recovery_file_path, passphrase);
+        }
         let wallet = if let Ok(recovered_wallet) = io_utils::recover(&wallet_recovery_file_path) {
-            recovered_wallet
+            match keystore_passphrase {
+                Some(passphrase) => {
+                    let wallet =
+                        io_utils::encrypt_recovery(&wallet_recovery_file_path, passphrase)?;
+                    println!("Encrypted the wallet file {:?}", wallet_recovery_file_path);
+                    wallet
+                }
+                None => recovered_wallet,
+            }
         } else {
             let new_wallet = WalletLibrary::new();
-            new_wallet.write_recovery(&wallet_recovery_file_path)?;
+            match keystore_passphrase {
+                Some(passphrase) => {
+                    new_wallet.write_encrypted_recovery(&wallet_recovery_file_path, passphrase)?
+                }
+                None => new_wallet.write_recovery(&wallet_recovery_file_path)?,
+            }
             new_wallet
         };
         Ok(wallet)
@@ -974,10 +1618,24 @@ impl ClientProxy {
         self.wallet = wallet;
     }
 
-    fn load_faucet_account_file(faucet_account_file: &str) -> KeyPair {
+    fn load_faucet_account_file(
+        faucet_account_file: &str,
+        keystore_passphrase: &Option<String>,
+    ) -> Result<KeyPair> {
         match fs::read(faucet_account_file) {
+            Ok(data) if Keystore::is_keystore(&data) => {
+                let passphrase = keystore_passphrase.as_ref().ok_or_else(|| {
+                    format_err!(
+                        "The faucet account file {} is encrypted, a passphrase is required",
+                        faucet_account_file
+                    )
+                })?;
+                let data = Keystore::parse(&data)?.decrypt(KIND_KEYPAIR, passphrase)?;
+                Ok(bincode::deserialize(&data[..])?)
+            }
             Ok(data) => {
-                bincode::deserialize(&data[..]).expect("Unable to deserialize faucet account file")
+                Ok(bincode::deserialize(&data[..])
+                    .expect("Unable to deserialize faucet account file"))
             }
             Err(e) => {
                 panic!(
@@ -1021,6 +1679,7 @@ impl ClientProxy {
         )?;
         let mut sender_mut = self.faucet_account.as_mut().unwrap();
         let resp = self.client.submit_transaction(Some(&mut sender_mut), &req);
+        self.record_submitted_transaction(&req, &resp);
         if is_blocking {
             self.wait_for_transaction(
                 sender_address,
@@ -1043,7 +1702,7 @@ impl ClientProxy {
             "http://{}?amount={}&address={:?}",
             self.faucet_server, num_coins, receiver
         )
-            .parse::<hyper::Uri>()?;
+        .parse::<hyper::Uri>()?;
 
         let response = runtime.block_on(client.get(url))?;
         let status_code = response.status();
@@ -1100,21 +1759,78 @@ impl ClientProxy {
             Some(key_pair) => Box::new(key_pair),
             None => Box::new(&self.wallet),
         };
+        let (max_gas_amount, gas_unit_price) = self.gas_parameters(
+            sender_account.address,
+            &program,
+            max_gas_amount,
+            gas_unit_price,
+        )?;
         let signed_txn = create_signed_txn(
             *signer,
             program,
             sender_account.address,
             sender_account.sequence_number,
-            max_gas_amount.unwrap_or(MAX_GAS_AMOUNT),
-            gas_unit_price.unwrap_or(GAS_UNIT_PRICE),
+            max_gas_amount,
+            gas_unit_price,
             TX_EXPIRATION,
         )
-            .unwrap();
+        .unwrap();
         let mut req = SubmitTransactionRequest::new();
         req.set_signed_txn(signed_txn.into_proto());
         Ok(req)
     }
 
+    /// Record a submitted transaction in the output of the running command.
+    fn record_submitted_transaction(&mut self, req: &SubmitTransactionRequest, resp: &Result<()>) {
+        let signed_txn = match SignedTransaction::from_proto(req.get_signed_txn().clone()) {
+            Ok(signed_txn) => signed_txn,
+            Err(_) => return,
+        };
+        let status = match resp {
+            Err(e) => TransactionStatus::Rejected(e.to_string()),
+            Ok(_) => TransactionStatus::Submitted,
+        };
+        self.command_output.transactions.push(TransactionOutput {
+            sender: hex::encode(signed_txn.sender()),
+            sequence_number: signed_txn.sequence_number(),
+            hash: format!("{:x}", signed_txn.hash()),
+            status,
+        });
+    }
+
+    /// Set the status of the recorded transactions of `account` below `chain_seq_number` from the
+    /// VM status the ledger committed them with.
+    fn resolve_committed_transactions(&mut self, account: AccountAddress, chain_seq_number: u64) {
+        let sender = hex::encode(account);
+        for txn in self.command_output.transactions.iter_mut() {
+            if txn.status != TransactionStatus::Submitted
+                || txn.sender != sender
+                || txn.sequence_number >= chain_seq_number
+            {
+                continue;
+            }
+            match self.client.get_txn_vm_status(account, txn.sequence_number) {
+                Ok(Some(vm_status)) => txn.status = TransactionStatus::from_vm_status(vm_status),
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Unable to get the status of transaction {} of {}: {}",
+                    txn.sequence_number, account, e
+                ),
+            }
+        }
+    }
+
+    /// Take the transactions and values recorded for the running command.
+    pub fn take_command_output(&mut self) -> CommandOutput {
+        std::mem::replace(&mut self.command_output, CommandOutput::default())
+    }
+
+    /// Record a value for the running command, for values produced below the command, e.g. by a
+    /// simulation.
+    fn record_value<V: Into<serde_json::Value>>(&mut self, name: &str, value: V) {
+        self.command_output.record_value(name, value);
+    }
+
     fn mut_account_from_parameter(&mut self, para: &str) -> Result<&mut AccountData> {
         let account_ref_id = match is_address(para) {
             true => {
@@ -1159,6 +1875,27 @@ fn parse_bool(para: &str) -> Result<bool> {
     Ok(para.to_lowercase().parse::<bool>()?)
 }
 
+/// Mempool rejects a transaction unless the sender's balance covers `max_gas_amount *
+/// gas_unit_price`, together with the transactions of the sender it already holds.
+fn check_gas_balance(balance: u64, max_gas_amount: u64, gas_unit_price: u64) -> Result<()> {
+    let required_balance = max_gas_amount.checked_mul(gas_unit_price).ok_or_else(|| {
+        format_err!(
+            "Max gas amount {} times gas price {} overflows",
+            max_gas_amount,
+            gas_unit_price
+        )
+    })?;
+    ensure!(
+        balance >= required_balance,
+        "Insufficient balance for gas: balance {}, required {} ({} gas units at {})",
+        balance,
+        required_balance,
+        max_gas_amount,
+        gas_unit_price
+    );
+    Ok(())
+}
+
 impl fmt::Display for AccountEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -1176,7 +1913,14 @@ mod tests {
     use libra_wallet::io_utils;
     use proptest::prelude::*;
 
-    use crate::client_proxy::{AddressAndIndex, ClientProxy, parse_bool};
+    use crate::{
+        client_proxy::{
+            check_gas_balance, parse_bool, AddressAndIndex, ChannelStoreFile, ClientProxy,
+            ModuleRegistryFile, ModuleRegistryRecord,
+        },
+        ChannelLocalData,
+    };
+    use types::account_address::AccountAddress;
 
     fn generate_accounts_from_wallet(count: usize) -> (ClientProxy, Vec<AddressAndIndex>) {
         let mut accounts = Vec::new();
@@ -1200,8 +1944,9 @@ mod tests {
             false,
             None,
             Some(mnemonic_path),
+            None,
         )
-            .unwrap();
+        .unwrap();
         for _ in 0..count {
             accounts.push(client_proxy.create_next_account(false).unwrap());
         }
@@ -1265,6 +2010,80 @@ mod tests {
         assert_eq!(client.wallet.mnemonic(), wallet.mnemonic());
     }
 
+    #[test]
+    fn test_module_registry_file() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.into_temp_path();
+        let registry_file = ModuleRegistryFile {
+            modules: vec![ModuleRegistryRecord {
+                name: "etoken".to_string(),
+                account: AccountAddress::random().to_string(),
+            }],
+        };
+        registry_file.save(&path).expect("failed to write to file");
+        assert_eq!(
+            ModuleRegistryFile::load(&path).expect("failed to load from file"),
+            registry_file
+        );
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_module_registry_file_merge() {
+        let record = |name: &str, account: AccountAddress| ModuleRegistryRecord {
+            name: name.to_string(),
+            account: account.to_string(),
+        };
+        let (etoken, channel, new_channel) = (
+            AccountAddress::random(),
+            AccountAddress::random(),
+            AccountAddress::random(),
+        );
+        let mut registry_file = ModuleRegistryFile {
+            modules: vec![record("etoken", etoken), record("channel", channel)],
+        };
+        registry_file.merge(vec![record("channel", new_channel)]);
+        assert_eq!(
+            registry_file.modules,
+            vec![record("channel", new_channel), record("etoken", etoken)]
+        );
+    }
+
+    #[test]
+    fn test_channel_store_file() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.into_temp_path();
+        let (self_address, other_address) = (AccountAddress::random(), AccountAddress::random());
+        let data = ChannelLocalData {
+            version: 2,
+            self_balance: 90,
+            other_balance: 110,
+            self_signature: vec![1; 64],
+            other_signature: vec![2; 64],
+        };
+        let mut store = ChannelStoreFile::default();
+        store.put(&self_address, &other_address, Some(&data));
+        store.save(&path).expect("failed to write to file");
+
+        let mut store = ChannelStoreFile::load(&path).expect("failed to load from file");
+        let record = store
+            .get(&self_address, &other_address)
+            .expect("channel state not found");
+        assert_eq!(record.to_local_data().unwrap(), data);
+        assert!(store.get(&other_address, &self_address).is_none());
+
+        store.put(&self_address, &other_address, None);
+        assert!(store.channels.is_empty());
+    }
+
+    #[test]
+    fn test_check_gas_balance() {
+        assert!(check_gas_balance(0, 10_000, 0).is_ok());
+        assert!(check_gas_balance(20_000, 10_000, 2).is_ok());
+        assert!(check_gas_balance(19_999, 10_000, 2).is_err());
+        assert!(check_gas_balance(u64::max_value(), u64::max_value(), 2).is_err());
+    }
+
     proptest! {
         // Proptest is used to verify that the conversion will not panic with random input.
         #[test]
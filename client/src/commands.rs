@@ -2,22 +2,197 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_commands::AccountCommand, client_proxy::ClientProxy, query_commands::QueryCommand,
-    submit_transaction_command::SubmitTransactionFromDiskCommand,
-    transfer_commands::TransferCommand,
-    usds_commands::USDSCommand,
-    channel_commands::ChannelCommand,
+    account_commands::AccountCommand, channel_commands::ChannelCommand, client_proxy::ClientProxy,
+    query_commands::QueryCommand, submit_transaction_command::SubmitTransactionFromDiskCommand,
+    transfer_commands::TransferCommand, usds_commands::USDSCommand,
 };
 
 use failure::prelude::*;
+use lazy_static::lazy_static;
 use metrics::counters::*;
-use std::{collections::HashMap, sync::Arc};
-use types::account_address::ADDRESS_LENGTH;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+use types::{
+    account_address::ADDRESS_LENGTH,
+    vm_error::{ExecutionStatus, VMStatus},
+};
+
+lazy_static! {
+    static ref STREAM_OUTPUT: Mutex<Option<File>> = Mutex::new(None);
+}
+
+/// Client shared between the interactive mode and the commands running in the background.
+pub type SharedClientProxy = Arc<Mutex<ClientProxy>>;
+
+/// Structured result of a command line, returned by `Command::execute`. The scripted mode of the
+/// client prints it as JSON and derives the exit code from it.
+#[derive(Debug, Default, Serialize)]
+pub struct CommandOutput {
+    /// The command line that was run.
+    pub command: String,
+    /// Errors reported by the command.
+    pub errors: Vec<String>,
+    /// Transactions submitted by the command.
+    pub transactions: Vec<TransactionOutput>,
+    /// Values the command queried, e.g. a balance or the decoded resources of an account.
+    pub values: Map<String, Value>,
+}
+
+/// A transaction submitted by a command.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionOutput {
+    /// Hex encoded sender of the transaction.
+    pub sender: String,
+    /// Sequence number of the transaction.
+    pub sequence_number: u64,
+    /// Hex encoded hash of the signed transaction.
+    pub hash: String,
+    /// What is known about the transaction when the command returns.
+    pub status: TransactionStatus,
+}
+
+/// Status of a submitted transaction.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Accepted by admission control, the command did not wait for it.
+    Submitted,
+    /// Committed and executed successfully.
+    Committed,
+    /// Committed, but the execution failed with the VM status, e.g. the script aborted. The gas
+    /// was charged and the writes of the script were dropped.
+    Failed(VMStatus),
+    /// Refused by admission control or the VM validation, with the reason.
+    Rejected(String),
+}
+
+impl TransactionStatus {
+    /// The status of a committed transaction executed with `vm_status`.
+    pub fn from_vm_status(vm_status: VMStatus) -> Self {
+        match vm_status {
+            VMStatus::Execution(ExecutionStatus::Executed) => TransactionStatus::Committed,
+            vm_status => TransactionStatus::Failed(vm_status),
+        }
+    }
+}
+
+impl CommandOutput {
+    /// Whether the command reported no error and no transaction was rejected or failed.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+            && self.transactions.iter().all(|txn| match txn.status {
+                TransactionStatus::Submitted | TransactionStatus::Committed => true,
+                TransactionStatus::Failed(_) | TransactionStatus::Rejected(_) => false,
+            })
+    }
+
+    /// Record a value queried by the command.
+    pub fn record_value<V: Into<Value>>(&mut self, name: &str, value: V) {
+        self.values.insert(name.to_string(), value.into());
+    }
+
+    /// Print the error and bump up error counter.
+    pub fn report_error(&mut self, msg: &str, e: Error) {
+        let error = format!("{}: {}", msg, pretty_format_error(e));
+        println!("[ERROR] {}", error);
+        COUNTER_CLIENT_ERRORS.inc();
+        self.errors.push(error);
+    }
+
+    /// Print a usage error of a command, e.g. a wrong number of arguments.
+    pub fn report_usage_error(&mut self, msg: &str) {
+        println!("{}", msg);
+        self.errors.push(msg.to_string());
+    }
+
+    /// Add the errors, transactions and values of a nested command, e.g. a sub command.
+    pub fn extend(&mut self, other: CommandOutput) {
+        self.errors.extend(other.errors);
+        self.transactions.extend(other.transactions);
+        self.values.extend(other.values);
+    }
+}
+
+/// How a command submits its transactions, given with `--max-gas <units>`, `--gas-price <price>`
+/// and `--dry-run`. Transactions without gas parameters get the estimated max gas amount and the
+/// default gas unit price. Dry runs only execute the transactions locally, see `usds simulate`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TransactionOptions {
+    /// Maximum number of gas units the transactions may use.
+    pub max_gas_amount: Option<u64>,
+    /// Price of a gas unit in micro LibraCoin.
+    pub gas_unit_price: Option<u64>,
+    /// Execute the transactions against the latest ledger state instead of submitting them.
+    pub dry_run: bool,
+}
+
+impl TransactionOptions {
+    /// Split the transaction options off the params of a command.
+    pub fn split<'a>(params: &[&'a str]) -> Result<(Vec<&'a str>, Self)> {
+        let mut options = TransactionOptions::default();
+        let mut rest = vec![];
+        let mut iter = params.iter();
+        while let Some(param) = iter.next() {
+            let option = match *param {
+                "--max-gas" => &mut options.max_gas_amount,
+                "--gas-price" => &mut options.gas_unit_price,
+                "--dry-run" => {
+                    options.dry_run = true;
+                    continue;
+                }
+                _ => {
+                    rest.push(*param);
+                    continue;
+                }
+            };
+            let value = iter
+                .next()
+                .ok_or_else(|| format_err!("Missing value of {}", param))?;
+            *option = Some(
+                value
+                    .parse()
+                    .map_err(|e| format_err!("Invalid value of {}: {}", param, e))?,
+            );
+        }
+        Ok((rest, options))
+    }
+}
+
+/// Split the transaction options off the params of a command submitting transactions, reporting
+/// invalid ones in `output`.
+pub fn split_transaction_options<'a>(
+    output: &mut CommandOutput,
+    params: &[&'a str],
+) -> Option<(Vec<&'a str>, TransactionOptions)> {
+    match TransactionOptions::split(params) {
+        Ok(split) => Some(split),
+        Err(e) => {
+            output.report_usage_error(&format!("Invalid transaction option: {}", e));
+            None
+        }
+    }
+}
 
-/// Print the error and bump up error counter.
-pub fn report_error(msg: &str, e: Error) {
-    println!("[ERROR] {}: {}", msg, pretty_format_error(e));
-    COUNTER_CLIENT_ERRORS.inc();
+/// Send the JSON lines of streaming commands, e.g. `query watch`, to `out`.
+pub fn set_stream_output(out: File) {
+    *STREAM_OUTPUT.lock().unwrap() = Some(out);
+}
+
+/// Emit a JSON line of a streaming command right away, if the scripted mode prints JSON. Commands
+/// that never return can not wait for their `CommandOutput` to be printed.
+pub fn stream_value(value: &Value) {
+    if let Some(out) = STREAM_OUTPUT.lock().unwrap().as_mut() {
+        let written = writeln!(out, "{}", value).and_then(|_| out.flush());
+        if let Err(e) = written {
+            eprintln!("Unable to write the JSON output: {}", e);
+        }
+    }
 }
 
 fn pretty_format_error(e: Error) -> String {
@@ -67,7 +242,7 @@ pub fn get_commands() -> (
         Arc::new(TransferCommand {}),
         Arc::new(SubmitTransactionFromDiskCommand {}),
         Arc::new(USDSCommand {}),
-        Arc::new(ChannelCommand{}),
+        Arc::new(ChannelCommand {}),
     ];
     let mut alias_to_cmd = HashMap::new();
     for command in &commands {
@@ -108,7 +283,31 @@ pub fn subcommand_execute(
     commands: Vec<Box<dyn Command>>,
     client: &mut ClientProxy,
     params: &[&str],
-) {
+) -> CommandOutput {
+    match find_subcommand(parent_command_name, &commands, params) {
+        Ok(command) => command.execute(client, params),
+        Err(output) => output,
+    }
+}
+
+/// Execute sub command of a client shared with background tasks.
+pub fn subcommand_execute_shared(
+    parent_command_name: &str,
+    commands: Vec<Box<dyn Command>>,
+    client: &SharedClientProxy,
+    params: &[&str],
+) -> CommandOutput {
+    match find_subcommand(parent_command_name, &commands, params) {
+        Ok(command) => command.execute_shared(client, params),
+        Err(output) => output,
+    }
+}
+
+fn find_subcommand<'a>(
+    parent_command_name: &str,
+    commands: &'a [Box<dyn Command>],
+    params: &[&str],
+) -> std::result::Result<&'a dyn Command, CommandOutput> {
     let mut commands_map = HashMap::new();
     for (i, cmd) in commands.iter().enumerate() {
         for alias in cmd.get_aliases() {
@@ -118,17 +317,75 @@ pub fn subcommand_execute(
         }
     }
 
+    let mut output = CommandOutput::default();
     if params.is_empty() {
-        print_subcommand_help(parent_command_name, &commands);
-        return;
+        print_subcommand_help(parent_command_name, commands);
+        output.report_usage_error(&format!("Missing sub command of {}", parent_command_name));
+        return Err(output);
     }
 
     match commands_map.get(&params[0]) {
-        Some(&idx) => commands[idx].execute(client, &params),
-        _ => print_subcommand_help(parent_command_name, &commands),
+        Some(&idx) => Ok(commands[idx].as_ref()),
+        _ => {
+            print_subcommand_help(parent_command_name, commands);
+            output.report_usage_error(&format!("Unknown sub command: {:?}", params[0]));
+            Err(output)
+        }
     }
 }
 
+/// Run one command line against the client and return its output. Returns `None` for a blank
+/// line.
+pub fn run_command_line(
+    alias_to_cmd: &HashMap<&'static str, Arc<dyn Command>>,
+    client: &mut ClientProxy,
+    line: &str,
+) -> Option<CommandOutput> {
+    let mut output = run_command_line_with(alias_to_cmd, line, |cmd, params| {
+        cmd.execute(client, params)
+    })?;
+    output.extend(client.take_command_output());
+    Some(output)
+}
+
+/// Run one command line against a client shared with background tasks, see
+/// `Command::execute_shared`.
+pub fn run_shared_command_line(
+    alias_to_cmd: &HashMap<&'static str, Arc<dyn Command>>,
+    client: &SharedClientProxy,
+    line: &str,
+) -> Option<CommandOutput> {
+    let mut output = run_command_line_with(alias_to_cmd, line, |cmd, params| {
+        cmd.execute_shared(client, params)
+    })?;
+    output.extend(client.lock().unwrap().take_command_output());
+    Some(output)
+}
+
+fn run_command_line_with<F>(
+    alias_to_cmd: &HashMap<&'static str, Arc<dyn Command>>,
+    line: &str,
+    execute: F,
+) -> Option<CommandOutput>
+where
+    F: FnOnce(&dyn Command, &[&str]) -> CommandOutput,
+{
+    let params = parse_cmd(line);
+    if params[0].is_empty() {
+        return None;
+    }
+    let mut output = match alias_to_cmd.get(params[0]) {
+        Some(cmd) => execute(cmd.as_ref(), &params),
+        None => {
+            let mut output = CommandOutput::default();
+            output.report_usage_error(&format!("Unknown command: {:?}", params[0]));
+            output
+        }
+    };
+    output.command = line.trim().to_string();
+    Some(output)
+}
+
 /// Trait to perform client operations.
 pub trait Command {
     /// all commands and aliases this command support.
@@ -139,6 +396,124 @@ pub trait Command {
     }
     /// string that describes what the command does.
     fn get_description(&self) -> &'static str;
-    /// code to execute.
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]);
+    /// code to execute, returns the errors and the values of the command. Transactions submitted
+    /// through the client are added to the output by the caller, see
+    /// `ClientProxy::take_command_output`.
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput;
+    /// code to execute when the client is shared with background tasks, as in the interactive
+    /// mode. Commands that keep running after they return, e.g. a daemon, override it to only lock
+    /// the client while they use it.
+    fn execute_shared(&self, client: &SharedClientProxy, params: &[&str]) -> CommandOutput {
+        self.execute(&mut client.lock().unwrap(), params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::*;
+
+    fn transaction(status: TransactionStatus) -> TransactionOutput {
+        TransactionOutput {
+            sender: "00".repeat(32),
+            sequence_number: 0,
+            hash: "11".repeat(32),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_command_output_success() {
+        let mut output = CommandOutput::default();
+        assert!(output.is_success());
+        output
+            .transactions
+            .push(transaction(TransactionStatus::Committed));
+        output
+            .transactions
+            .push(transaction(TransactionStatus::Submitted));
+        assert!(output.is_success());
+        output
+            .transactions
+            .push(transaction(TransactionStatus::Rejected(
+                "invalid sequence number".to_string(),
+            )));
+        assert!(!output.is_success());
+
+        let mut output = CommandOutput::default();
+        output
+            .errors
+            .push("Invalid number of arguments for command".to_string());
+        assert!(!output.is_success());
+
+        let mut output = CommandOutput::default();
+        output
+            .transactions
+            .push(transaction(TransactionStatus::from_vm_status(
+                VMStatus::Execution(ExecutionStatus::AssertionFailure(3)),
+            )));
+        assert!(!output.is_success());
+    }
+
+    #[test]
+    fn test_transaction_status_from_vm_status() {
+        assert_eq!(
+            TransactionStatus::from_vm_status(VMStatus::Execution(ExecutionStatus::Executed)),
+            TransactionStatus::Committed
+        );
+        assert_eq!(
+            TransactionStatus::from_vm_status(VMStatus::Execution(ExecutionStatus::OutOfGas)),
+            TransactionStatus::Failed(VMStatus::Execution(ExecutionStatus::OutOfGas))
+        );
+    }
+
+    #[test]
+    fn test_split_transaction_options() {
+        let (params, options) = TransactionOptions::split(&[
+            "mint",
+            "0",
+            "100",
+            "--max-gas",
+            "5000",
+            "--gas-price",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(params, vec!["mint", "0", "100"]);
+        assert_eq!(options.max_gas_amount, Some(5000));
+        assert_eq!(options.gas_unit_price, Some(2));
+        assert!(!options.dry_run);
+
+        let (params, options) =
+            TransactionOptions::split(&["mint", "--dry-run", "0", "100"]).unwrap();
+        assert_eq!(params, vec!["mint", "0", "100"]);
+        assert_eq!(
+            options,
+            TransactionOptions {
+                dry_run: true,
+                ..TransactionOptions::default()
+            }
+        );
+
+        let (params, options) = TransactionOptions::split(&["supply"]).unwrap();
+        assert_eq!(params, vec!["supply"]);
+        assert_eq!(options, TransactionOptions::default());
+
+        assert!(TransactionOptions::split(&["mint", "--max-gas"]).is_err());
+        assert!(TransactionOptions::split(&["mint", "--gas-price", "cheap"]).is_err());
+    }
+
+    #[test]
+    fn test_command_output_json() {
+        let mut output = CommandOutput::default();
+        output.command = "query balance 0".to_string();
+        output
+            .values
+            .insert("balance".to_string(), "1.000000".into());
+        output
+            .transactions
+            .push(transaction(TransactionStatus::Committed));
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["values"]["balance"], "1.000000");
+        assert_eq!(json["transactions"][0]["status"], "committed");
+    }
 }
@@ -0,0 +1,390 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Execute transactions locally against a snapshot of the ledger, for `usds simulate`.
+//!
+//! The accounts a transaction is expected to touch, i.e. the sender, the stdlib and registered
+//! module accounts and the address arguments, are read with proofs in one request, so they describe
+//! the same ledger version. Accounts the VM reads beyond them are fetched when first read. The
+//! transaction is signed with a throwaway key whose authentication key replaces the sender's in the
+//! snapshot, so neither the keys of the sender nor the validator are involved.
+
+use crate::{
+    client_proxy::ModuleRegistryEntry,
+    grpc_client::GRPCClient,
+    resource_decoder::{resources_to_json, ResourceDecoder},
+};
+use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
+use config::config::{VMConfig, VMPublishingOption};
+use crypto::signing;
+use failure::prelude::*;
+use serde_json::{json, Value};
+use state_view::StateView;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    convert::TryInto,
+};
+use types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{
+        account_resource_path, association_address, core_code_address, AccountResource,
+    },
+    account_state_blob::AccountStateBlob,
+    byte_array::ByteArray,
+    get_with_proof::RequestItem,
+    transaction::{
+        RawTransaction, TransactionArgument, TransactionOutput, TransactionPayload, Version,
+    },
+    write_set::{WriteOp, WriteSetMut},
+};
+use vm_runtime::{MoveVM, VMExecutor};
+
+type AccountMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Account states read from a validator, as seen by the VM.
+pub struct StateSnapshot<'a> {
+    /// Where accounts missing from the snapshot are fetched, if anywhere.
+    client: Option<&'a GRPCClient>,
+    version: Version,
+    accounts: RefCell<HashMap<AccountAddress, AccountMap>>,
+}
+
+impl<'a> StateSnapshot<'a> {
+    /// Read the states of `addresses` at the latest ledger version.
+    pub fn fetch(client: &'a GRPCClient, addresses: &[AccountAddress]) -> Result<Self> {
+        let mut addresses = addresses.to_vec();
+        addresses.sort();
+        addresses.dedup();
+        let response = client.get_with_proof_sync(
+            addresses
+                .iter()
+                .map(|address| RequestItem::GetAccountState { address: *address })
+                .collect(),
+        )?;
+        let mut accounts = HashMap::new();
+        for (address, item) in addresses.into_iter().zip(response.response_items) {
+            let blob = item.into_get_account_state_response()?.blob;
+            accounts.insert(address, account_map(&blob)?);
+        }
+        Ok(StateSnapshot {
+            client: Some(client),
+            version: response.ledger_info_with_sigs.ledger_info().version(),
+            accounts: RefCell::new(accounts),
+        })
+    }
+
+    /// A snapshot of the given accounts only, every other account does not exist.
+    pub fn from_accounts(version: Version, accounts: HashMap<AccountAddress, AccountMap>) -> Self {
+        StateSnapshot {
+            client: None,
+            version,
+            accounts: RefCell::new(accounts),
+        }
+    }
+
+    /// Ledger version the snapshot was read at.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The state of an account, fetched first if it is not in the snapshot yet.
+    pub fn account(&self, address: AccountAddress) -> Result<AccountMap> {
+        if let Some(account) = self.accounts.borrow().get(&address) {
+            return Ok(account.clone());
+        }
+        let account = match self.client {
+            Some(client) => account_map(&client.get_account_blob(address)?.0)?,
+            None => AccountMap::new(),
+        };
+        self.accounts.borrow_mut().insert(address, account.clone());
+        Ok(account)
+    }
+
+    /// Replace the authentication key of an account. Returns false if the account does not exist.
+    pub fn set_authentication_key(
+        &self,
+        address: AccountAddress,
+        authentication_key: ByteArray,
+    ) -> Result<bool> {
+        let mut account = self.account(address)?;
+        let bytes = match account.get(&account_resource_path()) {
+            Some(bytes) => with_authentication_key(bytes, authentication_key)?,
+            None => return Ok(false),
+        };
+        account.insert(account_resource_path(), bytes);
+        self.accounts.borrow_mut().insert(address, account);
+        Ok(true)
+    }
+
+    /// The states of the accounts written by `output`, after the write set is applied.
+    pub fn apply(
+        &self,
+        output: &TransactionOutput,
+    ) -> Result<BTreeMap<AccountAddress, AccountMap>> {
+        let mut accounts = BTreeMap::new();
+        for (access_path, write_op) in output.write_set() {
+            if !accounts.contains_key(&access_path.address) {
+                accounts.insert(access_path.address, self.account(access_path.address)?);
+            }
+            let account = accounts.get_mut(&access_path.address).unwrap();
+            match write_op {
+                WriteOp::Value(value) => account.insert(access_path.path.clone(), value.clone()),
+                WriteOp::Deletion => account.remove(&access_path.path),
+            };
+        }
+        Ok(accounts)
+    }
+}
+
+impl<'a> StateView for StateSnapshot<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .account(access_path.address)?
+            .get(&access_path.path)
+            .cloned())
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+/// The result of executing a transaction locally.
+pub struct Simulation {
+    /// Ledger version of the snapshot the transaction was executed against.
+    pub version: Version,
+    /// What the VM returned, with the authentication key of the sender restored.
+    pub output: TransactionOutput,
+    /// States of the accounts written by the transaction, after it.
+    pub accounts: BTreeMap<AccountAddress, AccountMap>,
+}
+
+impl Simulation {
+    /// JSON of the output, write set values and event data are hex encoded and the written
+    /// accounts are decoded.
+    pub fn to_json(&self, decoder: &ResourceDecoder) -> Result<Value> {
+        let write_set = self
+            .output
+            .write_set()
+            .iter()
+            .map(|(access_path, write_op)| match write_op {
+                WriteOp::Value(value) => json!({
+                    "access_path": access_path.to_string(),
+                    "value": hex::encode(value),
+                }),
+                WriteOp::Deletion => json!({
+                    "access_path": access_path.to_string(),
+                    "deletion": true,
+                }),
+            })
+            .collect::<Vec<_>>();
+        let events = self
+            .output
+            .events()
+            .iter()
+            .map(|event| {
+                json!({
+                    "access_path": event.access_path().to_string(),
+                    "sequence_number": event.sequence_number(),
+                    "event_data": hex::encode(event.event_data()),
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut accounts = serde_json::Map::new();
+        for (address, account) in &self.accounts {
+            accounts.insert(
+                hex::encode(address),
+                resources_to_json(&decoder.decode_account(account)?),
+            );
+        }
+        Ok(json!({
+            "version": self.version,
+            "status": format!("{:?}", self.output.status()),
+            "gas_used": self.output.gas_used(),
+            "write_set": write_set,
+            "events": events,
+            "accounts": accounts,
+        }))
+    }
+
+    /// Print the output for the REPL.
+    pub fn print(&self, decoder: &ResourceDecoder) -> Result<()> {
+        println!("Simulated against ledger version {}", self.version);
+        println!("Status: {:?}", self.output.status());
+        println!("Gas used: {}", self.output.gas_used());
+        println!("Write set:");
+        for (access_path, write_op) in self.output.write_set() {
+            println!("\t{} => {:?}", access_path, write_op);
+        }
+        println!("Events:");
+        for event in self.output.events() {
+            println!("\t{}", event);
+        }
+        for (address, account) in &self.accounts {
+            println!(
+                "Account {} after the transaction: {:#}",
+                address,
+                resources_to_json(&decoder.decode_account(account)?)
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Execute `raw_txn` against the latest ledger state without submitting it.
+pub fn simulate(
+    client: &GRPCClient,
+    raw_txn: RawTransaction,
+    module_registry: &[ModuleRegistryEntry],
+) -> Result<Simulation> {
+    let sender = raw_txn.sender();
+    let mut addresses = vec![sender, core_code_address(), association_address()];
+    addresses.extend(module_registry.iter().map(|entry| entry.account));
+    if let TransactionPayload::Program(program) = raw_txn.clone().into_payload() {
+        addresses.extend(program.args().iter().filter_map(|arg| match arg {
+            TransactionArgument::Address(address) => Some(*address),
+            _ => None,
+        }));
+    }
+    let snapshot = StateSnapshot::fetch(client, &addresses)?;
+
+    let original_key = AccountResource::make_from(&snapshot.account(sender)?)
+        .map(|account| account.authentication_key().clone())
+        .ok();
+    let (private_key, public_key) = signing::generate_keypair();
+    snapshot.set_authentication_key(
+        sender,
+        ByteArray::new(AccountAddress::from(public_key).to_vec()),
+    )?;
+    let signed_txn = raw_txn.sign(&private_key, public_key)?.into_inner();
+
+    // Module publishing is allowed, the validator enforces its own policy on submission.
+    let config = VMConfig {
+        publishing_options: VMPublishingOption::Open,
+    };
+    let output = MoveVM::execute_block(vec![signed_txn], &config, &snapshot)
+        .pop()
+        .ok_or_else(|| format_err!("The VM returned no output"))?;
+    let output = match original_key {
+        Some(key) => restore_authentication_key(output, sender, key)?,
+        None => output,
+    };
+    Ok(Simulation {
+        version: snapshot.version(),
+        accounts: snapshot.apply(&output)?,
+        output,
+    })
+}
+
+/// Put the authentication key of the sender back into the account resource written by the epilogue.
+fn restore_authentication_key(
+    output: TransactionOutput,
+    sender: AccountAddress,
+    authentication_key: ByteArray,
+) -> Result<TransactionOutput> {
+    let sender_path = AccessPath::new(sender, account_resource_path());
+    let write_set = output
+        .write_set()
+        .iter()
+        .map(|(access_path, write_op)| match write_op {
+            WriteOp::Value(value) if *access_path == sender_path => Ok((
+                access_path.clone(),
+                WriteOp::Value(with_authentication_key(value, authentication_key.clone())?),
+            )),
+            _ => Ok((access_path.clone(), write_op.clone())),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TransactionOutput::new(
+        WriteSetMut::new(write_set).freeze()?,
+        output.events().to_vec(),
+        output.gas_used(),
+        output.status().clone(),
+    ))
+}
+
+/// The serialized account resource with another authentication key.
+fn with_authentication_key(bytes: &[u8], authentication_key: ByteArray) -> Result<Vec<u8>> {
+    let account: AccountResource = SimpleDeserializer::deserialize(bytes)?;
+    SimpleSerializer::serialize(&AccountResource::new(
+        account.balance(),
+        account.sequence_number(),
+        authentication_key,
+        account.sent_events_count(),
+        account.received_events_count(),
+    ))
+}
+
+fn account_map(blob: &Option<AccountStateBlob>) -> Result<AccountMap> {
+    match blob {
+        Some(blob) => blob.try_into(),
+        None => Ok(AccountMap::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dry_run::{with_authentication_key, StateSnapshot};
+    use canonical_serialization::SimpleSerializer;
+    use state_view::StateView;
+    use std::collections::{BTreeMap, HashMap};
+    use types::{
+        access_path::AccessPath,
+        account_address::AccountAddress,
+        account_config::{account_resource_path, AccountResource},
+        byte_array::ByteArray,
+    };
+
+    #[test]
+    fn test_snapshot_authentication_key() {
+        let address = AccountAddress::random();
+        let account = AccountResource::new(100, 3, ByteArray::new(vec![1; 32]), 2, 1);
+        let bytes: Vec<u8> = SimpleSerializer::serialize(&account).unwrap();
+        let mut account_map = BTreeMap::new();
+        account_map.insert(account_resource_path(), bytes.clone());
+        let mut accounts = HashMap::new();
+        accounts.insert(address, account_map);
+        let snapshot = StateSnapshot::from_accounts(7, accounts);
+
+        assert!(snapshot
+            .set_authentication_key(address, ByteArray::new(vec![2; 32]))
+            .unwrap());
+        let patched = snapshot
+            .get(&AccessPath::new(address, account_resource_path()))
+            .unwrap()
+            .unwrap();
+        let expected: Vec<u8> = SimpleSerializer::serialize(&AccountResource::new(
+            100,
+            3,
+            ByteArray::new(vec![2; 32]),
+            2,
+            1,
+        ))
+        .unwrap();
+        assert_eq!(patched, expected);
+        assert_eq!(
+            with_authentication_key(&patched, ByteArray::new(vec![1; 32])).unwrap(),
+            bytes
+        );
+
+        // Accounts outside of the snapshot do not exist.
+        let other = AccountAddress::random();
+        assert!(!snapshot
+            .set_authentication_key(other, ByteArray::new(vec![2; 32]))
+            .unwrap());
+        assert_eq!(
+            snapshot
+                .get(&AccessPath::new(other, account_resource_path()))
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,334 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Follow the events and balances of an account as they are committed, for `query watch`.
+//!
+//! Every event stream is tracked by the sequence number of its next event and polled with
+//! `UpdateToLatestLedger` from there. The gRPC client verifies each response against the
+//! validator set like any other read. A cursor only moves past events of a poll that succeeded as
+//! a whole, so after a lost connection the watcher resumes where it left off, without skipping or
+//! repeating events.
+
+use crate::{
+    client_proxy::ClientProxy,
+    grpc_client::GRPCClient,
+    resource::{etoken_event_path, ETokenResource},
+    resource_decoder::ResourceDecoder,
+};
+use failure::prelude::*;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, convert::TryInto, fmt, thread, time::Duration};
+use types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{
+        account_received_event_path, account_sent_event_path, get_account_resource_or_default,
+    },
+    account_state_blob::AccountStateBlob,
+    contract_event::EventWithProof,
+    transaction::Version,
+};
+
+/// Largest number of events fetched by one request.
+const WATCH_BATCH_SIZE: u64 = 100;
+/// Time between two polls.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest time between two attempts while the validator is not reachable.
+const WATCH_MAX_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An event stream of the watched account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventCursor {
+    /// Name of the stream, e.g. `sent` or `etoken_mint`.
+    pub name: String,
+    /// Access path of the event counter.
+    pub access_path: AccessPath,
+    /// Sequence number of the next event of the stream.
+    pub next_sequence_number: u64,
+}
+
+/// Something the watcher saw committed.
+#[derive(Debug)]
+pub enum WatchUpdate {
+    /// A new event of a stream.
+    Event {
+        stream: String,
+        event: EventWithProof,
+    },
+    /// A changed balance, `balance` for LibraCoin or `etoken_balance`.
+    Balance {
+        name: String,
+        value: u64,
+        version: Version,
+    },
+}
+
+impl WatchUpdate {
+    /// JSON line of the update, event data is hex encoded.
+    pub fn to_json(&self) -> Value {
+        match self {
+            WatchUpdate::Event { stream, event } => json!({
+                "type": "event",
+                "stream": stream,
+                "sequence_number": event.event.sequence_number(),
+                "transaction_version": event.transaction_version,
+                "event_index": event.event_index,
+                "event_data": hex::encode(event.event.event_data()),
+            }),
+            WatchUpdate::Balance {
+                name,
+                value,
+                version,
+            } => json!({
+                "type": "balance",
+                "name": name,
+                "value": value,
+                "version": version,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for WatchUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WatchUpdate::Event { stream, event } => write!(
+                f,
+                "[{} #{}] version {}: {}",
+                stream,
+                event.event.sequence_number(),
+                event.transaction_version,
+                event.event
+            ),
+            WatchUpdate::Balance {
+                name,
+                value,
+                version,
+            } => write!(f, "[{}] {} at version {}", name, value, version),
+        }
+    }
+}
+
+/// Follows the event streams and balances of one account.
+pub struct EventWatcher {
+    account: AccountAddress,
+    cursors: Vec<EventCursor>,
+    /// Address of the etoken module, if etoken events are watched.
+    etoken: Option<AccountAddress>,
+    /// Last balances seen.
+    balances: BTreeMap<String, u64>,
+}
+
+impl EventWatcher {
+    /// Watch the `sent`, `received`, `etoken` or `all` (sent and received) events of `account`.
+    /// Without `start_sequence_number` only events committed from now on are reported.
+    pub fn new(
+        client: &mut ClientProxy,
+        account: AccountAddress,
+        kind: &str,
+        start_sequence_number: Option<u64>,
+    ) -> Result<Self> {
+        let mut streams = vec![];
+        if kind == "sent" || kind == "all" {
+            streams.push(("sent".to_string(), account_sent_event_path()));
+        }
+        if kind == "received" || kind == "all" {
+            streams.push(("received".to_string(), account_received_event_path()));
+        }
+        let etoken = if kind == "etoken" {
+            client.resolve_etoken_module()?;
+            let module_address = client.module_registry["etoken"].account;
+            for event_type in &["sent", "received", "mint", "burn"] {
+                streams.push((
+                    format!("etoken_{}", event_type),
+                    etoken_event_path(module_address, event_type)?,
+                ));
+            }
+            Some(module_address)
+        } else {
+            None
+        };
+        ensure!(
+            !streams.is_empty(),
+            "Unknown event type: {:?}, only sent, received, etoken and all are supported",
+            kind
+        );
+
+        let (blob, _) = client.client.get_account_blob(account)?;
+        let account_map = account_map(&blob)?;
+        let module_registry = client.get_module_registry();
+        let decoder = ResourceDecoder::from_registry(&module_registry);
+        let cursors = streams
+            .into_iter()
+            .map(|(name, path)| {
+                let next_sequence_number = match start_sequence_number {
+                    Some(start) => start,
+                    None => decoder.event_count(&account_map, &path)?,
+                };
+                Ok(EventCursor {
+                    name,
+                    access_path: AccessPath::new(account, path),
+                    next_sequence_number,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut watcher = EventWatcher {
+            account,
+            cursors,
+            etoken,
+            balances: BTreeMap::new(),
+        };
+        watcher.balances = watcher.read_balances(&blob)?;
+        Ok(watcher)
+    }
+
+    pub fn cursors(&self) -> &[EventCursor] {
+        &self.cursors
+    }
+
+    fn read_balances(&self, blob: &Option<AccountStateBlob>) -> Result<BTreeMap<String, u64>> {
+        let mut balances = BTreeMap::new();
+        balances.insert(
+            "balance".to_string(),
+            get_account_resource_or_default(blob)?.balance(),
+        );
+        if let Some(module_address) = self.etoken {
+            let value = ETokenResource::make_from(module_address, &account_map(blob)?)
+                .map(|etoken| etoken.value)
+                .unwrap_or(0);
+            balances.insert("etoken_balance".to_string(), value);
+        }
+        Ok(balances)
+    }
+
+    /// Fetch the events committed since the last poll and, if there are any, the balances they
+    /// changed. On error no cursor moves, so the next poll fetches the same events again.
+    pub fn poll(&mut self, client: &GRPCClient) -> Result<Vec<WatchUpdate>> {
+        let mut updates = vec![];
+        let mut cursors = self.cursors.clone();
+        for cursor in cursors.iter_mut() {
+            loop {
+                let (events, _) = client.get_events_by_access_path(
+                    cursor.access_path.clone(),
+                    cursor.next_sequence_number,
+                    true,
+                    WATCH_BATCH_SIZE,
+                )?;
+                let fetched = events.len() as u64;
+                let events = take_new_events(&mut cursor.next_sequence_number, events, |event| {
+                    event.event.sequence_number()
+                })?;
+                updates.extend(events.into_iter().map(|event| WatchUpdate::Event {
+                    stream: cursor.name.clone(),
+                    event,
+                }));
+                if fetched < WATCH_BATCH_SIZE {
+                    break;
+                }
+            }
+        }
+
+        let mut balances = self.balances.clone();
+        if !updates.is_empty() {
+            let (blob, version) = client.get_account_blob(self.account)?;
+            balances = self.read_balances(&blob)?;
+            for (name, value) in &balances {
+                if self.balances.get(name) != Some(value) {
+                    updates.push(WatchUpdate::Balance {
+                        name: name.clone(),
+                        value: *value,
+                        version,
+                    });
+                }
+            }
+        }
+        self.cursors = cursors;
+        self.balances = balances;
+        Ok(updates)
+    }
+
+    /// Poll until the process is stopped, passing every update to `on_update`. While the
+    /// validator can not be reached the watcher backs off and retries.
+    pub fn run(&mut self, client: &GRPCClient, mut on_update: impl FnMut(&WatchUpdate)) {
+        let mut interval = WATCH_POLL_INTERVAL;
+        loop {
+            match self.poll(client) {
+                Ok(updates) => {
+                    updates.iter().for_each(&mut on_update);
+                    interval = WATCH_POLL_INTERVAL;
+                }
+                Err(e) => {
+                    println!(
+                        "[WARN] Unable to fetch new events, retrying in {}s: {}",
+                        interval.as_secs(),
+                        e
+                    );
+                    interval = std::cmp::min(interval * 2, WATCH_MAX_RETRY_INTERVAL);
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+fn account_map(blob: &Option<AccountStateBlob>) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    match blob {
+        Some(blob) => blob.try_into(),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Keep the events from `next` on, checking that they continue the stream without a gap, and
+/// move `next` past them. Events before `next` were reported already and are dropped.
+fn take_new_events<T>(
+    next: &mut u64,
+    events: Vec<T>,
+    sequence_number: impl Fn(&T) -> u64,
+) -> Result<Vec<T>> {
+    let mut expected = *next;
+    let mut new_events = vec![];
+    for event in events {
+        let number = sequence_number(&event);
+        if number < expected {
+            continue;
+        }
+        ensure!(
+            number == expected,
+            "Expected event {} but got {}, events are missing",
+            expected,
+            number
+        );
+        expected += 1;
+        new_events.push(event);
+    }
+    *next = expected;
+    Ok(new_events)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event_watcher::take_new_events;
+
+    #[test]
+    fn test_take_new_events() {
+        let mut next = 3;
+        let events = take_new_events(&mut next, vec![3, 4, 5], |n| *n).unwrap();
+        assert_eq!(events, vec![3, 4, 5]);
+        assert_eq!(next, 6);
+
+        // Events seen before a reconnect are not reported twice.
+        let events = take_new_events(&mut next, vec![4, 5, 6, 7], |n| *n).unwrap();
+        assert_eq!(events, vec![6, 7]);
+        assert_eq!(next, 8);
+
+        // A gap is an error and the cursor does not move.
+        assert!(take_new_events(&mut next, vec![9], |n| *n).is_err());
+        assert_eq!(next, 8);
+
+        assert!(take_new_events(&mut next, Vec::<u64>::new(), |n| *n)
+            .unwrap()
+            .is_empty());
+        assert_eq!(next, 8);
+    }
+}
@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AccountData;
+use crate::{
+    light_client::{need_to_retry, LightClient, TrustedState},
+    AccountData,
+};
 use admission_control_proto::{
     proto::{
         admission_control::{
@@ -14,29 +17,23 @@ use admission_control_proto::{
 use failure::prelude::*;
 use futures::Future;
 use grpcio::{CallOption, ChannelBuilder, EnvBuilder};
-use logger::prelude::*;
-use proto_conv::{FromProto, IntoProto};
+use proto_conv::FromProto;
 use std::sync::Arc;
 use types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::get_account_resource_or_default,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
-    get_with_proof::{
-        RequestItem, ResponseItem, UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
-    },
+    get_with_proof::{RequestItem, UpdateToLatestLedgerResponse},
     transaction::{SignedTransaction, Version},
     validator_verifier::ValidatorVerifier,
     vm_error::{VMStatus, VMValidationStatus},
 };
 
-const MAX_GRPC_RETRY_COUNT: u64 = 1;
-
 /// Struct holding dependencies of client.
 pub struct GRPCClient {
     client: AdmissionControlClient,
-    validator_verifier: Arc<ValidatorVerifier>,
+    light_client: LightClient,
 }
 
 impl GRPCClient {
@@ -48,10 +45,12 @@ impl GRPCClient {
         let env = Arc::new(EnvBuilder::new().name_prefix("grpc-client-").build());
         let ch = ChannelBuilder::new(env).connect(&conn_addr);
         let client = AdmissionControlClient::new(ch);
+        let light_client =
+            LightClient::from_client(client.clone(), TrustedState::new(validator_verifier));
 
         Ok(GRPCClient {
             client,
-            validator_verifier,
+            light_client,
         })
     }
 
@@ -65,7 +64,7 @@ impl GRPCClient {
         let mut resp = self.submit_transaction_opt(req);
 
         let mut try_cnt = 0_u64;
-        while Self::need_to_retry(&mut try_cnt, &resp) {
+        while need_to_retry(&mut try_cnt, &resp) {
             resp = self.submit_transaction_opt(&req);
         }
 
@@ -130,62 +129,25 @@ impl GRPCClient {
             .submit_transaction_opt(resp, Self::get_default_grpc_call_option())?)
     }
 
-    fn get_with_proof_async(
-        &self,
-        requested_items: Vec<RequestItem>,
-    ) -> Result<impl Future<Item = UpdateToLatestLedgerResponse, Error = failure::Error>> {
-        let req = UpdateToLatestLedgerRequest::new(0, requested_items.clone());
-        debug!("get_with_proof with request: {:?}", req);
-        let proto_req = req.clone().into_proto();
-        let validator_verifier = Arc::clone(&self.validator_verifier);
-        let ret = self
-            .client
-            .update_to_latest_ledger_async_opt(&proto_req, Self::get_default_grpc_call_option())?
-            .then(move |get_with_proof_resp| {
-                // TODO: Cache/persist client_known_version to work with validator set change when
-                // the feature is available.
-
-                let resp = UpdateToLatestLedgerResponse::from_proto(get_with_proof_resp?)?;
-                resp.verify(validator_verifier, &req)?;
-                Ok(resp)
-            });
-        Ok(ret)
-    }
-
-    fn need_to_retry<T>(try_cnt: &mut u64, ret: &Result<T>) -> bool {
-        if *try_cnt <= MAX_GRPC_RETRY_COUNT {
-            *try_cnt += 1;
-            if let Err(error) = ret {
-                if let Some(grpc_error) = error.downcast_ref::<grpcio::Error>() {
-                    if let grpcio::Error::RpcFailure(grpc_rpc_failure) = grpc_error {
-                        // Only retry when the connection is down to make sure we won't
-                        // send one txn twice.
-                        return grpc_rpc_failure.status == grpcio::RpcStatusCode::Unavailable;
-                    }
-                }
-            }
-        }
-        false
-    }
     /// Sync version of get_with_proof
     pub(crate) fn get_with_proof_sync(
         &self,
         requested_items: Vec<RequestItem>,
     ) -> Result<UpdateToLatestLedgerResponse> {
-        let mut resp: Result<UpdateToLatestLedgerResponse> =
-            self.get_with_proof_async(requested_items.clone())?.wait();
-        let mut try_cnt = 0_u64;
-
-        while Self::need_to_retry(&mut try_cnt, &resp) {
-            resp = self.get_with_proof_async(requested_items.clone())?.wait();
-        }
+        self.light_client.get_with_proof(requested_items)
+    }
 
-        Ok(resp?)
+    /// The light client verifying the reads of this client.
+    pub fn light_client(&self) -> &LightClient {
+        &self.light_client
     }
 
     /// Get the latest account sequence number for the account specified.
     pub fn get_sequence_number(&self, address: AccountAddress) -> Result<u64> {
-        Ok(get_account_resource_or_default(&self.get_account_blob(address)?.0)?.sequence_number())
+        Ok(self
+            .light_client
+            .get_account_resource(address)?
+            .sequence_number())
     }
 
     /// Get the latest account state blob from validator.
@@ -193,18 +155,7 @@ impl GRPCClient {
         &self,
         address: AccountAddress,
     ) -> Result<(Option<AccountStateBlob>, Version)> {
-        let req_item = RequestItem::GetAccountState { address };
-
-        let mut response = self.get_with_proof_sync(vec![req_item])?;
-        let account_state_with_proof = response
-            .response_items
-            .remove(0)
-            .into_get_account_state_response()?;
-
-        Ok((
-            account_state_with_proof.blob,
-            response.ledger_info_with_sigs.ledger_info().version(),
-        ))
+        self.light_client.get_account_state(address)
     }
 
     /// Get transaction from validator by account and sequence number.
@@ -214,21 +165,28 @@ impl GRPCClient {
         sequence_number: u64,
         fetch_events: bool,
     ) -> Result<Option<(SignedTransaction, Option<Vec<ContractEvent>>)>> {
-        let req_item = RequestItem::GetAccountTransactionBySequenceNumber {
+        let signed_txn_with_proof = self.light_client.get_transaction_by_sequence_number(
             account,
             sequence_number,
             fetch_events,
-        };
-
-        let mut response = self.get_with_proof_sync(vec![req_item])?;
-        let (signed_txn_with_proof, _) = response
-            .response_items
-            .remove(0)
-            .into_get_account_txn_by_seq_num_response()?;
-
+        )?;
         Ok(signed_txn_with_proof.map(|t| (t.signed_transaction, t.events)))
     }
 
+    /// Get the VM status a transaction was committed with, by account and sequence number.
+    pub fn get_txn_vm_status(
+        &self,
+        account: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<Option<VMStatus>> {
+        let signed_txn_with_proof = self.light_client.get_transaction_by_sequence_number(
+            account,
+            sequence_number,
+            false,
+        )?;
+        Ok(signed_txn_with_proof.map(|t| t.proof.transaction_info().vm_status().clone()))
+    }
+
     /// Get transactions in range (start_version..start_version + limit - 1) from validator.
     pub fn get_txn_by_range(
         &self,
@@ -236,17 +194,9 @@ impl GRPCClient {
         limit: u64,
         fetch_events: bool,
     ) -> Result<Vec<(SignedTransaction, Option<Vec<ContractEvent>>)>> {
-        // Make the request.
-        let req_item = RequestItem::GetTransactions {
-            start_version,
-            limit,
-            fetch_events,
-        };
-        let mut response = self.get_with_proof_sync(vec![req_item])?;
-        let txn_list_with_proof = response
-            .response_items
-            .remove(0)
-            .into_get_transactions_response()?;
+        let txn_list_with_proof =
+            self.light_client
+                .get_transactions(start_version, limit, fetch_events)?;
 
         // Transform the response.
         let num_txns = txn_list_with_proof.transaction_and_infos.len();
@@ -271,25 +221,8 @@ impl GRPCClient {
         ascending: bool,
         limit: u64,
     ) -> Result<(Vec<EventWithProof>, Option<AccountStateWithProof>)> {
-        let req_item = RequestItem::GetEventsByEventAccessPath {
-            access_path,
-            start_event_seq_num,
-            ascending,
-            limit,
-        };
-
-        let mut response = self.get_with_proof_sync(vec![req_item])?;
-        let value_with_proof = response.response_items.remove(0);
-        match value_with_proof {
-            ResponseItem::GetEventsByEventAccessPath {
-                events_with_proof,
-                proof_of_latest_event,
-            } => Ok((events_with_proof, proof_of_latest_event)),
-            _ => bail!(
-                "Incorrect type of response returned: {:?}",
-                value_with_proof
-            ),
-        }
+        self.light_client
+            .get_events(access_path, start_event_seq_num, ascending, limit)
     }
 
     fn get_default_grpc_call_option() -> CallOption {
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #![feature(duration_float)]
+#![feature(async_await)]
 //#![deny(missing_docs)]
 //! Libra Client
 //!
@@ -9,26 +10,36 @@
 //! It supposes all public APIs.
 use crypto::signing::KeyPair;
 use failure::prelude::*;
-use types::account_address::AccountAddress;
 use serde::{Deserialize, Serialize};
+use types::account_address::AccountAddress;
 
 use crate::resource::*;
 use std::collections::HashMap;
 
 pub(crate) mod account_commands;
+pub(crate) mod account_state;
+pub(crate) mod channel_commands;
+/// Peer to peer exchange of offchain channel updates.
+pub mod channel_daemon;
 /// Main instance of client holding corresponding information, e.g. account address.
 pub mod client_proxy;
 /// Command struct to interact with client.
 pub mod commands;
+/// Execute transactions locally against a snapshot of the ledger.
+pub mod dry_run;
+/// Follow the events and balances of an account as they are committed.
+pub(crate) mod event_watcher;
 /// gRPC client wrapper to connect to validator.
 pub(crate) mod grpc_client;
+/// Proof verifying reads that track a trusted ledger info and validator set changes.
+pub mod light_client;
 pub(crate) mod query_commands;
+pub(crate) mod resource;
+/// Decode account resources with the struct layouts of the published Move modules.
+pub mod resource_decoder;
 pub(crate) mod submit_transaction_command;
 pub(crate) mod transfer_commands;
-pub(crate) mod resource;
-pub(crate) mod account_state;
 pub(crate) mod usds_commands;
-pub(crate) mod channel_commands;
 
 /// Offchain transfer request
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -53,6 +64,40 @@ impl TransferRequest {
     }
 }
 
+/// The message a channel party signs for a balance proof, from its own point of view:
+/// signer ++ other ++ signer_open_height ++ other_open_height ++ version ++ signer_balance ++
+/// other_balance, u64 in little endian. The open heights keep proofs of an earlier channel from
+/// being replayed after a reopen. It must match Channel.proof_message in move/channel.mvir.
+pub fn channel_proof_message(
+    signer: &AccountAddress,
+    other: &AccountAddress,
+    signer_open_height: u64,
+    other_open_height: u64,
+    version: u64,
+    signer_balance: u64,
+    other_balance: u64,
+) -> Vec<u8> {
+    let mut message = signer.to_vec();
+    message.extend_from_slice(&other.to_vec());
+    message.extend_from_slice(&signer_open_height.to_le_bytes());
+    message.extend_from_slice(&other_open_height.to_le_bytes());
+    message.extend_from_slice(&version.to_le_bytes());
+    message.extend_from_slice(&signer_balance.to_le_bytes());
+    message.extend_from_slice(&other_balance.to_le_bytes());
+    message
+}
+
+/// Check a channel proof signature against a public key registered on chain.
+pub fn verify_channel_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+    let public_key = ed25519_dalek::PublicKey::from_bytes(public_key)
+        .map_err(|e| format_err!("invalid public key: {}", e))?;
+    let signature = ed25519_dalek::Signature::from_bytes(signature)
+        .map_err(|e| format_err!("invalid signature: {}", e))?;
+    public_key
+        .verify(message, &signature)
+        .map_err(|e| format_err!("check signature fail: {}", e))
+}
+
 /// Offchain transfer conform
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct TransferConform {
@@ -109,41 +154,51 @@ pub struct OffchainChannel {
     pub self_status: ChannelStatus,
     pub other_status: ChannelStatus,
     pub data: Option<ChannelLocalData>,
+    /// The last transfer request sent to the other party and not conformed yet.
+    #[serde(default)]
+    pub pending_request: Option<TransferRequest>,
 }
 
 impl OffchainChannel {
-    pub fn new(self_address: AccountAddress, other_address: AccountAddress, self_channel: ChannelResource, other_channel: Option<ChannelResource>, self_proof: Option<ProofResource>, other_proof: Option<ProofResource>) -> Self {
+    pub fn new(
+        self_address: AccountAddress,
+        other_address: AccountAddress,
+        self_channel: ChannelResource,
+        other_channel: Option<ChannelResource>,
+        self_proof: Option<ProofResource>,
+        other_proof: Option<ProofResource>,
+    ) -> Self {
         let data = match &self_proof {
-            Some(proof) => {
-                Some(ChannelLocalData {
-                    version: proof.version,
-                    self_balance: proof.self_balance,
-                    self_signature: proof.self_signature.as_bytes().to_vec(),
-                    other_balance: proof.other_balance,
-                    other_signature: proof.other_signature.as_bytes().to_vec(),
-                })
-            }
+            Some(proof) => Some(ChannelLocalData {
+                version: proof.version,
+                self_balance: proof.self_balance,
+                self_signature: proof.self_signature.as_bytes().to_vec(),
+                other_balance: proof.other_balance,
+                other_signature: proof.other_signature.as_bytes().to_vec(),
+            }),
             None => None,
         };
 
         OffchainChannel {
             self_address,
             other_address,
-            self_status:
-            if self_channel.closed {
+            self_status: if self_channel.closed {
                 ChannelStatus::Closed(self_channel, self_proof)
             } else {
                 ChannelStatus::Open(self_channel)
             },
             other_status: match other_channel {
-                Some(other_channel) => if other_channel.closed {
-                    ChannelStatus::Closed(other_channel, other_proof)
-                } else {
-                    ChannelStatus::Open(other_channel)
-                },
-                None => ChannelStatus::None()
+                Some(other_channel) => {
+                    if other_channel.closed {
+                        ChannelStatus::Closed(other_channel, other_proof)
+                    } else {
+                        ChannelStatus::Open(other_channel)
+                    }
+                }
+                None => ChannelStatus::None(),
             },
             data,
+            pending_request: None,
         }
     }
 
@@ -151,6 +206,84 @@ impl OffchainChannel {
         return self.self_status.is_open() && self.other_status.is_open();
     }
 
+    /// The public key the other party registered when opening its side of the channel.
+    pub fn other_public_key(&self) -> Result<Vec<u8>> {
+        match &self.other_status {
+            ChannelStatus::Open(resource) | ChannelStatus::Closed(resource, _) => {
+                Ok(resource.public_key.as_bytes().to_vec())
+            }
+            ChannelStatus::None() => bail!("other channel is not opened."),
+        }
+    }
+
+    /// The heights self and the other party opened their sides of the channel at.
+    fn open_heights(&self) -> Result<(u64, u64)> {
+        let open_height = |status: &ChannelStatus| match status {
+            ChannelStatus::Open(resource) | ChannelStatus::Closed(resource, _) => {
+                Some(resource.open_height)
+            }
+            ChannelStatus::None() => None,
+        };
+        match (
+            open_height(&self.self_status),
+            open_height(&self.other_status),
+        ) {
+            (Some(self_open_height), Some(other_open_height)) => {
+                Ok((self_open_height, other_open_height))
+            }
+            (None, _) => bail!("self channel is not opened."),
+            (_, None) => bail!("other channel is not opened."),
+        }
+    }
+
+    /// The message self signs for a proof with the given version and balances.
+    pub fn self_proof_message(
+        &self,
+        version: u64,
+        self_balance: u64,
+        other_balance: u64,
+    ) -> Result<Vec<u8>> {
+        let (self_open_height, other_open_height) = self.open_heights()?;
+        Ok(channel_proof_message(
+            &self.self_address,
+            &self.other_address,
+            self_open_height,
+            other_open_height,
+            version,
+            self_balance,
+            other_balance,
+        ))
+    }
+
+    /// The message the other party signs for a proof with the given version and balances, from
+    /// self's point of view.
+    pub fn other_proof_message(
+        &self,
+        version: u64,
+        self_balance: u64,
+        other_balance: u64,
+    ) -> Result<Vec<u8>> {
+        let (self_open_height, other_open_height) = self.open_heights()?;
+        Ok(channel_proof_message(
+            &self.other_address,
+            &self.self_address,
+            other_open_height,
+            self_open_height,
+            version,
+            other_balance,
+            self_balance,
+        ))
+    }
+
+    /// The newest proof version submitted on chain by either side, 0 if there is none.
+    pub fn submitted_proof_version(&self) -> u64 {
+        let version = |status: &ChannelStatus| match status {
+            ChannelStatus::Closed(_, Some(proof)) => proof.version,
+            _ => 0,
+        };
+        std::cmp::max(version(&self.self_status), version(&self.other_status))
+    }
+
     pub fn transfer(&self, amount: u64) -> Result<TransferRequest> {
         ensure!(self.is_ready(), "channel is not ready");
         if let Some(data) = &self.data {
@@ -160,7 +293,7 @@ impl OffchainChannel {
                 amount,
                 version: data.version + 1,
                 self_balance: data.self_balance - amount,
-                other_balance: data.other_balance - amount,
+                other_balance: data.other_balance + amount,
                 signature: vec![],
             });
         }
@@ -173,7 +306,6 @@ impl OffchainChannel {
                     version: 1,
                     self_balance: resource.coin - amount,
                     other_balance: other_resource.coin + amount,
-                    //TODO
                     signature: vec![],
                 });
             }
@@ -181,14 +313,33 @@ impl OffchainChannel {
         bail!("unexpect channel status.")
     }
 
-    pub fn conform(&mut self, request: TransferRequest) -> Result<TransferConform> {
-        let signature = vec![];
+    /// Accept a transfer request of the other party, signature is self's signature over
+    /// `self_proof_message(request.version, request.other_balance, request.self_balance)`.
+    pub fn conform(
+        &mut self,
+        request: TransferRequest,
+        signature: Vec<u8>,
+    ) -> Result<TransferConform> {
         ensure!(self.is_ready(), "channel is not ready");
+        verify_channel_signature(
+            &self.other_public_key()?,
+            &self.other_proof_message(
+                request.version,
+                request.other_balance,
+                request.self_balance,
+            )?,
+            &request.signature,
+        )?;
         if let Some(data) = self.data.as_mut() {
             ensure!(data.version + 1 == request.version, "check version fail");
-            ensure!(data.self_balance + request.amount == request.other_balance, "balance check fail.");
-            ensure!(data.total_balance() == request.total_balance(), "balance check fail.");
-            //TODO check signature
+            ensure!(
+                data.self_balance + request.amount == request.other_balance,
+                "balance check fail."
+            );
+            ensure!(
+                data.total_balance() == request.total_balance(),
+                "balance check fail."
+            );
             data.version = request.version;
             data.self_balance = request.other_balance;
             data.other_balance = request.self_balance;
@@ -200,8 +351,14 @@ impl OffchainChannel {
             if let ChannelStatus::Open(resource) = &self.self_status {
                 if let ChannelStatus::Open(other_resource) = &self.other_status {
                     ensure!(other_resource.coin >= request.amount, "balance not enough.");
-                    ensure!(resource.coin + other_resource.coin == request.total_balance(), "balance check fail.");
-                    ensure!(request.other_balance == resource.coin + request.amount, "balance check fail.");
+                    ensure!(
+                        resource.coin + other_resource.coin == request.total_balance(),
+                        "balance check fail."
+                    );
+                    ensure!(
+                        request.other_balance == resource.coin + request.amount,
+                        "balance check fail."
+                    );
                 } else {
                     bail!("unexpect channel status.")
                 }
@@ -218,19 +375,40 @@ impl OffchainChannel {
             };
             self.data = Some(data);
         }
-        Ok(
-            TransferConform {
-                sender: self.self_address.clone(),
-                //TODO
-                signature,
-                request,
-            }
-        )
+        Ok(TransferConform {
+            sender: self.self_address.clone(),
+            signature,
+            request,
+        })
     }
 
+    /// Apply the countersignature of the other party to the pending request, the conform must be
+    /// for exactly that request and the request must still follow the current state.
     pub fn process_transfer_conform(&mut self, conform: TransferConform) -> Result<()> {
-        //TODO check request
         ensure!(self.is_ready(), "channel is not ready");
+        ensure!(
+            conform.request.sender == self.self_address,
+            "conform is not for a request of this account."
+        );
+        ensure!(
+            self.pending_request.as_ref() == Some(&conform.request),
+            "conform does not match the pending request."
+        );
+        let expected_version = self.data.as_ref().map_or(1, |data| data.version + 1);
+        ensure!(
+            conform.request.version == expected_version,
+            "check version fail"
+        );
+        verify_channel_signature(
+            &self.other_public_key()?,
+            &self.other_proof_message(
+                conform.request.version,
+                conform.request.self_balance,
+                conform.request.other_balance,
+            )?,
+            &conform.signature,
+        )?;
+        self.pending_request = None;
         if let Some(data) = self.data.as_mut() {
             data.version = conform.request.version;
             data.self_balance = conform.request.self_balance;
@@ -250,7 +428,11 @@ impl OffchainChannel {
         Ok(())
     }
 
-    pub fn update_with_resource(&mut self, channel_resource: ChannelResource, proof_resource: Option<ProofResource>) {
+    pub fn update_with_resource(
+        &mut self,
+        channel_resource: ChannelResource,
+        proof_resource: Option<ProofResource>,
+    ) {
         if channel_resource.other == self.other_address {
             if channel_resource.closed {
                 self.self_status = ChannelStatus::Closed(channel_resource, proof_resource)
@@ -300,10 +482,12 @@ pub enum AccountStatus {
 }
 
 impl AccountData {
-    pub fn new(address: AccountAddress,
-               key_pair: Option<KeyPair>,
-               sequence_number: u64,
-               status: AccountStatus) -> Self {
+    pub fn new(
+        address: AccountAddress,
+        key_pair: Option<KeyPair>,
+        sequence_number: u64,
+        status: AccountStatus,
+    ) -> Self {
         AccountData {
             address,
             key_pair,
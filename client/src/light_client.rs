@@ -0,0 +1,462 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proof verifying reads from a validator, without the account bookkeeping of the CLI.
+//!
+//! A `LightClient` trusts a validator set and the latest `LedgerInfoWithSignatures` it verified.
+//! Every request asks for a ledger at least as new as the trusted one, every response is checked
+//! against the trusted validators, and validator set changes carried by a response are verified
+//! one after the other before the response itself. Services embed it to read account states,
+//! transactions and events they can trust.
+
+use admission_control_proto::proto::admission_control_grpc::AdmissionControlClient;
+use failure::prelude::*;
+use grpcio::{CallOption, ChannelBuilder, EnvBuilder};
+use logger::prelude::*;
+use proto_conv::{FromProto, IntoProto};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{get_account_resource_or_default, AccountResource},
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    contract_event::EventWithProof,
+    get_with_proof::{RequestItem, UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{SignedTransactionWithProof, TransactionListWithProof, Version},
+    validator_verifier::ValidatorVerifier,
+};
+
+const MAX_GRPC_RETRY_COUNT: u64 = 1;
+const GRPC_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// What a `LightClient` trusts.
+#[derive(Clone)]
+pub struct TrustedState {
+    /// The latest verified ledger info, `None` until the first response.
+    ledger_info_with_sigs: Option<LedgerInfoWithSignatures>,
+    /// The validators signing the ledger infos of the current epoch.
+    validator_verifier: Arc<ValidatorVerifier>,
+}
+
+impl TrustedState {
+    /// Trust the validators of a configuration, e.g. the trusted peers file, from genesis on.
+    pub fn new(validator_verifier: Arc<ValidatorVerifier>) -> Self {
+        TrustedState {
+            ledger_info_with_sigs: None,
+            validator_verifier,
+        }
+    }
+
+    /// Trust a ledger info, which has to be signed by a quorum of the validators.
+    pub fn with_ledger_info(
+        validator_verifier: Arc<ValidatorVerifier>,
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<Self> {
+        ledger_info_with_sigs.verify(&validator_verifier)?;
+        Ok(TrustedState {
+            ledger_info_with_sigs: Some(ledger_info_with_sigs),
+            validator_verifier,
+        })
+    }
+
+    pub fn ledger_info_with_sigs(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.ledger_info_with_sigs.as_ref()
+    }
+
+    pub fn validator_verifier(&self) -> Arc<ValidatorVerifier> {
+        Arc::clone(&self.validator_verifier)
+    }
+
+    /// Version of the trusted ledger, responses describing an older one are refused.
+    pub fn version(&self) -> Version {
+        self.ledger_info_with_sigs
+            .as_ref()
+            .map_or(0, |ledger_info| ledger_info.ledger_info().version())
+    }
+
+    fn epoch_num(&self) -> u64 {
+        self.ledger_info_with_sigs
+            .as_ref()
+            .map_or(0, |ledger_info| ledger_info.ledger_info().epoch_num())
+    }
+
+    /// Verify a response to `request` and return the state trusting its ledger info.
+    pub fn verify_response(
+        &self,
+        request: &UpdateToLatestLedgerRequest,
+        response: &UpdateToLatestLedgerResponse,
+    ) -> Result<TrustedState> {
+        ensure!(
+            request.client_known_version == self.version(),
+            "The request was not made for the trusted version {}",
+            self.version()
+        );
+        let mut validator_verifier = Arc::clone(&self.validator_verifier);
+        let mut epoch_num = self.epoch_num();
+        for change in &response.validator_change_events {
+            let change_epoch_num = change.ledger_info_with_sigs().ledger_info().epoch_num();
+            ensure!(
+                change_epoch_num > epoch_num,
+                "Validator change of epoch {} is not newer than epoch {}",
+                change_epoch_num,
+                epoch_num
+            );
+            let validator_set = change.verify(&validator_verifier)?;
+            validator_verifier = Arc::new(ValidatorVerifier::from(&validator_set));
+            epoch_num = change_epoch_num;
+        }
+        response.verify(Arc::clone(&validator_verifier), request)?;
+        Ok(TrustedState {
+            ledger_info_with_sigs: Some(response.ledger_info_with_sigs.clone()),
+            validator_verifier,
+        })
+    }
+}
+
+/// Verified reads from a validator.
+pub struct LightClient {
+    client: AdmissionControlClient,
+    trusted_state: Mutex<TrustedState>,
+}
+
+impl LightClient {
+    /// Connect to the admission control service at `host:port`.
+    pub fn new(host: &str, port: &str, trusted_state: TrustedState) -> Self {
+        let conn_addr = format!("{}:{}", host, port);
+        let env = Arc::new(EnvBuilder::new().name_prefix("light-client-").build());
+        let ch = ChannelBuilder::new(env).connect(&conn_addr);
+        Self::from_client(AdmissionControlClient::new(ch), trusted_state)
+    }
+
+    /// Read through an existing admission control client.
+    pub fn from_client(client: AdmissionControlClient, trusted_state: TrustedState) -> Self {
+        LightClient {
+            client,
+            trusted_state: Mutex::new(trusted_state),
+        }
+    }
+
+    /// The current trusted state, e.g. to persist and restore it with
+    /// `TrustedState::with_ledger_info`.
+    pub fn trusted_state(&self) -> TrustedState {
+        self.trusted_state.lock().unwrap().clone()
+    }
+
+    /// Send the request items and verify the response. The trusted state moves to the ledger of
+    /// the response, unless another read already moved it further.
+    pub fn get_with_proof(
+        &self,
+        requested_items: Vec<RequestItem>,
+    ) -> Result<UpdateToLatestLedgerResponse> {
+        let trusted_state = self.trusted_state();
+        let request = UpdateToLatestLedgerRequest::new(trusted_state.version(), requested_items);
+        debug!("get_with_proof with request: {:?}", request);
+
+        let mut response = self.update_to_latest_ledger(&request);
+        let mut try_cnt = 0_u64;
+        while need_to_retry(&mut try_cnt, &response) {
+            response = self.update_to_latest_ledger(&request);
+        }
+        let response = response?;
+
+        let new_state = trusted_state.verify_response(&request, &response)?;
+        let mut current = self.trusted_state.lock().unwrap();
+        if new_state.version() >= current.version() {
+            *current = new_state;
+        }
+        Ok(response)
+    }
+
+    fn update_to_latest_ledger(
+        &self,
+        request: &UpdateToLatestLedgerRequest,
+    ) -> Result<UpdateToLatestLedgerResponse> {
+        let proto_response = self.client.update_to_latest_ledger_opt(
+            &request.clone().into_proto(),
+            CallOption::default()
+                .wait_for_ready(true)
+                .timeout(GRPC_TIMEOUT),
+        )?;
+        UpdateToLatestLedgerResponse::from_proto(proto_response)
+    }
+
+    /// The state of an account and the version of the ledger it was read from.
+    pub fn get_account_state(
+        &self,
+        address: AccountAddress,
+    ) -> Result<(Option<AccountStateBlob>, Version)> {
+        let mut response = self.get_with_proof(vec![RequestItem::GetAccountState { address }])?;
+        let account_state_with_proof = response
+            .response_items
+            .remove(0)
+            .into_get_account_state_response()?;
+        Ok((
+            account_state_with_proof.blob,
+            response.ledger_info_with_sigs.ledger_info().version(),
+        ))
+    }
+
+    /// The LibraAccount resource of an account, the default one if the account does not exist.
+    pub fn get_account_resource(&self, address: AccountAddress) -> Result<AccountResource> {
+        get_account_resource_or_default(&self.get_account_state(address)?.0)
+    }
+
+    /// The transaction of an account with the given sequence number, if committed.
+    pub fn get_transaction_by_sequence_number(
+        &self,
+        account: AccountAddress,
+        sequence_number: u64,
+        fetch_events: bool,
+    ) -> Result<Option<SignedTransactionWithProof>> {
+        let mut response =
+            self.get_with_proof(vec![RequestItem::GetAccountTransactionBySequenceNumber {
+                account,
+                sequence_number,
+                fetch_events,
+            }])?;
+        let (signed_txn_with_proof, _) = response
+            .response_items
+            .remove(0)
+            .into_get_account_txn_by_seq_num_response()?;
+        Ok(signed_txn_with_proof)
+    }
+
+    /// Up to `limit` transactions from `start_version` on.
+    pub fn get_transactions(
+        &self,
+        start_version: Version,
+        limit: u64,
+        fetch_events: bool,
+    ) -> Result<TransactionListWithProof> {
+        let mut response = self.get_with_proof(vec![RequestItem::GetTransactions {
+            start_version,
+            limit,
+            fetch_events,
+        }])?;
+        response
+            .response_items
+            .remove(0)
+            .into_get_transactions_response()
+    }
+
+    /// Up to `limit` events of an event access path from `start_event_seq_num` on. The account
+    /// state proving the latest event sequence number is returned when fewer events exist.
+    pub fn get_events(
+        &self,
+        access_path: AccessPath,
+        start_event_seq_num: u64,
+        ascending: bool,
+        limit: u64,
+    ) -> Result<(Vec<EventWithProof>, Option<AccountStateWithProof>)> {
+        let mut response = self.get_with_proof(vec![RequestItem::GetEventsByEventAccessPath {
+            access_path,
+            start_event_seq_num,
+            ascending,
+            limit,
+        }])?;
+        response
+            .response_items
+            .remove(0)
+            .into_get_events_by_access_path_response()
+    }
+}
+
+/// Retry once when the connection is down.
+pub(crate) fn need_to_retry<T>(try_cnt: &mut u64, ret: &Result<T>) -> bool {
+    if *try_cnt <= MAX_GRPC_RETRY_COUNT {
+        *try_cnt += 1;
+        if let Err(error) = ret {
+            if let Some(grpc_error) = error.downcast_ref::<grpcio::Error>() {
+                if let grpcio::Error::RpcFailure(grpc_rpc_failure) = grpc_error {
+                    // Only retry when the connection is down to make sure we won't
+                    // send one txn twice.
+                    return grpc_rpc_failure.status == grpcio::RpcStatusCode::Unavailable;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::light_client::TrustedState;
+    use canonical_serialization::SimpleSerializer;
+    use crypto::{hash::CryptoHash, x25519, HashValue};
+    use std::{collections::HashMap, sync::Arc};
+    use types::{
+        access_path::{AccessPath, VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH},
+        account_address::AccountAddress,
+        block_metadata::BlockMetadata,
+        contract_event::{ContractEvent, EventWithProof},
+        get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        proof::{AccumulatorProof, EventProof},
+        transaction::TransactionInfo,
+        validator_change::ValidatorChangeEventWithProof,
+        validator_public_keys::ValidatorPublicKeys,
+        validator_set::ValidatorSet,
+        validator_signer::ValidatorSigner,
+        validator_verifier::ValidatorVerifier,
+        vm_error::{ExecutionStatus, VMStatus},
+    };
+
+    fn signed_ledger_info(signer: &ValidatorSigner, version: u64) -> LedgerInfoWithSignatures {
+        sign_ledger_info(
+            signer,
+            LedgerInfo::new(
+                version,
+                HashValue::random(),
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                0,
+            ),
+        )
+    }
+
+    fn sign_ledger_info(
+        signer: &ValidatorSigner,
+        ledger_info: LedgerInfo,
+    ) -> LedgerInfoWithSignatures {
+        let mut signatures = HashMap::new();
+        signatures.insert(
+            signer.author(),
+            signer.sign_message(ledger_info.hash()).unwrap(),
+        );
+        LedgerInfoWithSignatures::new(ledger_info, signatures)
+    }
+
+    #[test]
+    fn test_trusted_state_moves_forward() {
+        let signer = ValidatorSigner::random();
+        let verifier = Arc::new(ValidatorVerifier::new_single(
+            signer.author(),
+            signer.public_key(),
+        ));
+        let state = TrustedState::new(verifier);
+        assert_eq!(state.version(), 0);
+
+        let request = UpdateToLatestLedgerRequest::new(0, vec![]);
+        let response =
+            UpdateToLatestLedgerResponse::new(vec![], signed_ledger_info(&signer, 5), vec![]);
+        let state = state.verify_response(&request, &response).unwrap();
+        assert_eq!(state.version(), 5);
+
+        // A ledger older than the trusted one is refused.
+        let request = UpdateToLatestLedgerRequest::new(5, vec![]);
+        let response =
+            UpdateToLatestLedgerResponse::new(vec![], signed_ledger_info(&signer, 3), vec![]);
+        assert!(state.verify_response(&request, &response).is_err());
+
+        // So is a ledger signed by someone else.
+        let response = UpdateToLatestLedgerResponse::new(
+            vec![],
+            signed_ledger_info(&ValidatorSigner::random(), 8),
+            vec![],
+        );
+        assert!(state.verify_response(&request, &response).is_err());
+    }
+
+    /// A ledger of a single transaction emitting an event at `access_path` that hands the
+    /// validator set over to `new_signer`, signed by `signer`.
+    fn validator_change(
+        signer: &ValidatorSigner,
+        new_signer: &ValidatorSigner,
+        access_path: AccessPath,
+        epoch_num: u64,
+    ) -> ValidatorChangeEventWithProof {
+        let validator_set = ValidatorSet::new(vec![ValidatorPublicKeys::new(
+            new_signer.author(),
+            new_signer.public_key(),
+            None,
+            new_signer.public_key(),
+            x25519::generate_keypair().1,
+        )]);
+        let event = ContractEvent::new(
+            access_path,
+            0,
+            SimpleSerializer::serialize(&validator_set).unwrap(),
+        );
+        let transaction_info = TransactionInfo::new(
+            HashValue::random(),
+            HashValue::random(),
+            event.hash(),
+            0,
+            BlockMetadata::default(),
+            VMStatus::Execution(ExecutionStatus::Executed),
+        );
+        let ledger_info = LedgerInfo::new(
+            0,
+            transaction_info.hash(),
+            HashValue::zero(),
+            HashValue::zero(),
+            epoch_num,
+            0,
+        );
+        let proof = EventProof::new(
+            AccumulatorProof::new(vec![]),
+            transaction_info,
+            AccumulatorProof::new(vec![]),
+        );
+        ValidatorChangeEventWithProof::new(
+            sign_ledger_info(signer, ledger_info),
+            EventWithProof::new(0, 0, event, proof),
+        )
+    }
+
+    #[test]
+    fn test_validator_change() {
+        let signer = ValidatorSigner::random();
+        let new_signer = ValidatorSigner::random();
+        let state = TrustedState::new(Arc::new(ValidatorVerifier::new_single(
+            signer.author(),
+            signer.public_key(),
+        )));
+        let request = UpdateToLatestLedgerRequest::new(0, vec![]);
+        let response_with = |change| {
+            UpdateToLatestLedgerResponse::new(
+                vec![],
+                signed_ledger_info(&new_signer, 5),
+                vec![change],
+            )
+        };
+
+        let change = validator_change(
+            &signer,
+            &new_signer,
+            VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH.clone(),
+            1,
+        );
+        let new_state = state
+            .verify_response(&request, &response_with(change))
+            .unwrap();
+        assert_eq!(new_state.version(), 5);
+
+        // The same event emitted by any other account is a forgery.
+        let forged_change = validator_change(
+            &signer,
+            &new_signer,
+            AccessPath::new_for_sent_event(AccountAddress::random()),
+            1,
+        );
+        assert!(state
+            .verify_response(&request, &response_with(forged_change))
+            .is_err());
+
+        // A change has to start a newer epoch than the trusted one.
+        let stale_change = validator_change(
+            &signer,
+            &new_signer,
+            VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH.clone(),
+            0,
+        );
+        assert!(state
+            .verify_response(&request, &response_with(stale_change))
+            .is_err());
+    }
+}
@@ -1,10 +1,36 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use client::{client_proxy::ClientProxy, commands::*};
+use client::{
+    client_proxy::{ClientProxy, CLIENT_WALLET_MNEMONIC_FILE},
+    commands::*,
+};
+use libra_wallet::{
+    io_utils,
+    keystore::{read_new_passphrase, read_passphrase, Keystore, PASSPHRASE_ENV_VAR},
+};
 use logger::set_default_global_logger;
 use rustyline::{config::CompletionType, error::ReadlineError, Config, Editor};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    os::unix::io::FromRawFd,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex},
+};
 use structopt::StructOpt;
+use types::account_address::AccountAddress;
+
+/// Exit code of exec and batch mode when every command succeeded.
+const EXIT_SUCCESS: i32 = 0;
+/// Exit code of exec and batch mode when a command reported an error, or a transaction was
+/// rejected or failed to execute. The remaining commands are not run.
+const EXIT_COMMAND_FAILED: i32 = 1;
+/// Exit code of exec and batch mode when the client could not be set up, e.g. the validator is
+/// not reachable or the batch file can not be read.
+const EXIT_SETUP_FAILED: i32 = 2;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -32,7 +58,9 @@ struct Args {
     pub faucet_server: Option<String>,
     /// File location from which to load mnemonic word for user account address/key generation.
     /// If not passed, a new mnemonic file will be generated by libra_wallet in the current
-    /// directory.
+    /// directory. The file may be an encrypted keystore, the passphrase is then asked for at
+    /// startup or read from `LIBRA_WALLET_PASSPHRASE`. A plain text file is encrypted in place
+    /// when a passphrase is given, the faucet key file may be encrypted with the same passphrase.
     #[structopt(short = "n", long = "mnemonic_file")]
     pub mnemonic_file: Option<String>,
     /// File location from which to load config of trusted validators. It is used to verify
@@ -47,6 +75,38 @@ struct Args {
     /// If set, client will sync with validator during wallet recovery.
     #[structopt(short = "r", long = "sync")]
     pub sync: bool,
+    /// Account the USDS modules were published under at genesis, as passed to
+    /// `libra-config --usds-issuer`. If not passed, the modules have to be issued in this session.
+    #[structopt(short = "u", long = "usds_issuer")]
+    pub usds_issuer: Option<AccountAddress>,
+    /// If set, client will load the module registry saved next to the mnemonic file and re-fetch
+    /// the code of the registered modules from the validator.
+    #[structopt(short = "l", long = "load_modules")]
+    pub load_modules: bool,
+    /// In exec and batch mode, print the result of every command as one JSON object per line on
+    /// stdout. The human readable output goes to stderr instead.
+    #[structopt(short = "j", long = "json")]
+    pub json: bool,
+    /// Run commands non-interactively instead of starting the REPL.
+    #[structopt(subcommand)]
+    pub mode: Option<Mode>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Mode {
+    /// Run the given command lines in order, e.g. `exec "account create" "usds mint 0 100"`
+    #[structopt(name = "exec")]
+    Exec {
+        #[structopt(name = "COMMAND", raw(required = "true"))]
+        commands: Vec<String>,
+    },
+    /// Run the command lines of a file in order, blank lines and lines starting with `#` are
+    /// skipped
+    #[structopt(name = "batch")]
+    Batch {
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
 }
 
 fn main() -> std::io::Result<()> {
@@ -56,9 +116,34 @@ fn main() -> std::io::Result<()> {
     let (commands, alias_to_cmd) = get_commands();
 
     let args = Args::from_args();
+    let scripted = args.mode.is_some();
+    // Only the JSON results may end up on stdout.
+    let mut json_out = if scripted && args.json {
+        Some(redirect_stdout_to_stderr()?)
+    } else {
+        None
+    };
+    if let Some(out) = &json_out {
+        // Streaming commands emit their JSON lines while they run.
+        set_stream_output(out.try_clone()?);
+    }
     let faucet_account_file = args.faucet_account_file.unwrap_or_else(|| "".to_string());
+    let keystore_passphrase =
+        match keystore_passphrase(&args.mnemonic_file, &faucet_account_file, scripted) {
+            Ok(passphrase) => passphrase,
+            Err(e) if scripted => {
+                println!("Not able to read the keystore passphrase, error {:?}", e);
+                process::exit(EXIT_SETUP_FAILED);
+            }
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    &format!("{}", e)[..],
+                ));
+            }
+        };
 
-    let mut client_proxy = ClientProxy::new(
+    let mut client_proxy = match ClientProxy::new(
         &args.host,
         &args.port,
         &args.validator_set_file,
@@ -66,8 +151,23 @@ fn main() -> std::io::Result<()> {
         args.sync,
         args.faucet_server,
         args.mnemonic_file,
-    )
-    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, &format!("{}", e)[..]))?;
+        keystore_passphrase,
+    ) {
+        Ok(client_proxy) => client_proxy,
+        Err(e) if scripted => {
+            println!("Not able to create the client, error {:?}", e);
+            process::exit(EXIT_SETUP_FAILED);
+        }
+        Err(e) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                &format!("{}", e)[..],
+            ));
+        }
+    };
+    if let Some(usds_issuer) = args.usds_issuer {
+        client_proxy.set_usds_issuer(usds_issuer);
+    }
 
     // Test connection to validator
     let test_ret = client_proxy.test_validator_connection();
@@ -77,8 +177,40 @@ fn main() -> std::io::Result<()> {
             "Not able to connect to validator at {}:{}, error {:?}",
             args.host, args.port, e
         );
+        if scripted {
+            process::exit(EXIT_SETUP_FAILED);
+        }
         return Ok(());
     }
+    if args.load_modules {
+        match client_proxy.load_module_registry() {
+            Ok(names) => println!("Loaded modules: {:?}", names),
+            Err(e) => {
+                println!("Not able to load the module registry, error {:?}", e);
+                if scripted {
+                    process::exit(EXIT_SETUP_FAILED);
+                }
+            }
+        }
+    }
+    if let Some(mode) = args.mode {
+        let lines = match mode {
+            Mode::Exec { commands } => commands,
+            Mode::Batch { file } => match fs::read_to_string(&file) {
+                Ok(contents) => contents.lines().map(str::to_string).collect(),
+                Err(e) => {
+                    println!("Not able to read batch file {:?}, error {:?}", file, e);
+                    process::exit(EXIT_SETUP_FAILED);
+                }
+            },
+        };
+        process::exit(run_script(
+            &alias_to_cmd,
+            &mut client_proxy,
+            &lines,
+            json_out.as_mut(),
+        ));
+    }
     let cli_info = format!("Connected to validator at: {}:{}", args.host, args.port);
     print_help(&cli_info, &commands);
     println!("Please, input commands: \n");
@@ -89,19 +221,19 @@ fn main() -> std::io::Result<()> {
         .auto_add_history(true)
         .build();
     let mut rl = Editor::<()>::with_config(config);
+    // Shared with the commands which keep running in the background, e.g. the channel daemon.
+    let client_proxy: SharedClientProxy = Arc::new(Mutex::new(client_proxy));
     loop {
         let readline = rl.readline("libra% ");
         match readline {
             Ok(line) => {
                 let params = parse_cmd(&line);
-                match alias_to_cmd.get(params[0]) {
-                    Some(cmd) => cmd.execute(&mut client_proxy, &params),
-                    None => match params[0] {
-                        "quit" | "q!" => break,
-                        "help" | "h" => print_help(&cli_info, &commands),
-                        "" => continue,
-                        x => println!("Unknown command: {:?}", x),
-                    },
+                match params[0] {
+                    "quit" | "q!" => break,
+                    "help" | "h" => print_help(&cli_info, &commands),
+                    _ => {
+                        run_shared_command_line(&alias_to_cmd, &client_proxy, &line);
+                    }
                 }
             }
             Err(ReadlineError::Interrupted) => {
@@ -122,6 +254,76 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Run the command lines in order and stop at the first one that fails. Every result is written
+/// to `json_out` if given. Returns the exit code of the process.
+fn run_script(
+    alias_to_cmd: &HashMap<&'static str, Arc<dyn Command>>,
+    client_proxy: &mut ClientProxy,
+    lines: &[String],
+    mut json_out: Option<&mut File>,
+) -> i32 {
+    for line in lines {
+        if line.trim().starts_with('#') {
+            continue;
+        }
+        let output = match run_command_line(alias_to_cmd, client_proxy, line) {
+            Some(output) => output,
+            None => continue,
+        };
+        if let Some(out) = json_out.as_mut() {
+            let json = serde_json::to_string(&output).expect("Unable to serialize command output");
+            if let Err(e) = writeln!(out, "{}", json) {
+                println!("Not able to write the command output, error {:?}", e);
+                return EXIT_SETUP_FAILED;
+            }
+        }
+        if !output.is_success() {
+            return EXIT_COMMAND_FAILED;
+        }
+    }
+    EXIT_SUCCESS
+}
+
+/// The passphrase of the wallet and faucet key keystores. It is required if either file is
+/// encrypted, otherwise the user may give one to encrypt the wallet file with. Scripts only read
+/// it from `LIBRA_WALLET_PASSPHRASE`.
+fn keystore_passphrase(
+    mnemonic_file: &Option<String>,
+    faucet_account_file: &str,
+    scripted: bool,
+) -> failure::Result<Option<String>> {
+    let wallet_file = match mnemonic_file {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?.join(CLIENT_WALLET_MNEMONIC_FILE),
+    };
+    let faucet_encrypted =
+        fs::read(faucet_account_file).map_or(false, |bytes| Keystore::is_keystore(&bytes));
+    if io_utils::is_encrypted(&wallet_file) || faucet_encrypted {
+        return Ok(Some(read_passphrase("Keystore passphrase: ")?));
+    }
+    if scripted {
+        return Ok(std::env::var(PASSPHRASE_ENV_VAR)
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty()));
+    }
+    read_new_passphrase(&format!(
+        "The wallet file {:?} is not encrypted. Passphrase to encrypt it with (empty to keep it in \
+         plain text): ",
+        wallet_file
+    ))
+}
+
+/// Point stdout of the process to stderr and return a handle to the original stdout.
+fn redirect_stdout_to_stderr() -> std::io::Result<File> {
+    unsafe {
+        let stdout = libc::dup(libc::STDOUT_FILENO);
+        if stdout < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(File::from_raw_fd(stdout))
+    }
+}
+
 /// Print the help message for the client and underlying command.
 fn print_help(client_info: &str, commands: &[std::sync::Arc<dyn Command>]) {
     println!("{}", client_info);
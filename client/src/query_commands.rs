@@ -1,7 +1,12 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{client_proxy::ClientProxy, commands::*};
+use crate::{
+    client_proxy::ClientProxy,
+    commands::*,
+    event_watcher::EventWatcher,
+    resource_decoder::{resources_to_json, ResourceDecoder},
+};
 use types::account_config::get_account_resource_or_default;
 use vm_genesis::get_transaction_name;
 
@@ -15,7 +20,7 @@ impl Command for QueryCommand {
     fn get_description(&self) -> &'static str {
         "Query operations"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
         let commands: Vec<Box<dyn Command>> = vec![
             Box::new(QueryCommandGetBalance {}),
             Box::new(QueryCommandGetSeqNum {}),
@@ -23,9 +28,10 @@ impl Command for QueryCommand {
             Box::new(QueryCommandGetTxnByAccountSeq {}),
             Box::new(QueryCommandGetTxnByRange {}),
             Box::new(QueryCommandGetEvent {}),
+            Box::new(QueryCommandWatch {}),
         ];
 
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        subcommand_execute(&params[0], commands, client, &params[1..])
     }
 }
 
@@ -42,15 +48,20 @@ impl Command for QueryCommandGetBalance {
     fn get_description(&self) -> &'static str {
         "Get the current balance of an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() != 2 {
-            println!("Invalid number of arguments for balance query");
-            return;
+            output.report_usage_error("Invalid number of arguments for balance query");
+            return output;
         }
         match client.get_balance(&params) {
-            Ok(balance) => println!("Balance is: {}", balance),
-            Err(e) => report_error("Failed to get balance", e),
+            Ok(balance) => {
+                println!("Balance is: {}", balance);
+                output.record_value("balance", balance);
+            }
+            Err(e) => output.report_error("Failed to get balance", e),
         }
+        output
     }
 }
 
@@ -68,12 +79,17 @@ impl Command for QueryCommandGetSeqNum {
         "Get the current sequence number for an account, \
          and reset current sequence number in CLI (optional, default is false)"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Getting current sequence number");
         match client.get_sequence_number(&params) {
-            Ok(sn) => println!("Sequence number is: {}", sn),
-            Err(e) => report_error("Error getting sequence number", e),
+            Ok(sn) => {
+                println!("Sequence number is: {}", sn);
+                output.record_value("sequence_number", sn);
+            }
+            Err(e) => output.report_error("Error getting sequence number", e),
         }
+        output
     }
 }
 
@@ -90,25 +106,47 @@ impl Command for QueryCommandGetLatestAccountState {
     fn get_description(&self) -> &'static str {
         "Get the latest state for an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Getting latest account state");
         match client.get_latest_account_state(&params) {
             Ok((acc, version)) => match get_account_resource_or_default(&acc) {
-                Ok(_) => println!(
-                    "Latest account state is: \n \
-                     Account: {:#?}\n \
-                     State: {:#?}\n \
-                     Blockchain Version: {}\n",
-                    client
-                        .get_account_address_from_parameter(params[1])
-                        .expect("Unable to parse account parameter"),
-                    acc,
-                    version,
-                ),
-                Err(e) => report_error("Error converting account blob to account resource", e),
+                Ok(_) => {
+                    println!(
+                        "Latest account state is: \n \
+                         Account: {:#?}\n \
+                         State: {:#?}\n \
+                         Blockchain Version: {}\n",
+                        client
+                            .get_account_address_from_parameter(params[1])
+                            .expect("Unable to parse account parameter"),
+                        acc,
+                        version,
+                    );
+                    output.record_value("version", version);
+                    if let Some(blob) = acc {
+                        let module_registry = client.get_module_registry();
+                        match ResourceDecoder::from_registry(&module_registry).decode_blob(&blob) {
+                            Ok(resources) => {
+                                let resources = resources_to_json(&resources);
+                                println!(
+                                    "Resources: {}",
+                                    serde_json::to_string_pretty(&resources)
+                                        .expect("Unable to format resources")
+                                );
+                                output.record_value("resources", resources);
+                            }
+                            Err(e) => output.report_error("Error decoding account resources", e),
+                        }
+                    }
+                }
+                Err(e) => {
+                    output.report_error("Error converting account blob to account resource", e)
+                }
             },
-            Err(e) => report_error("Error getting latest account state", e),
+            Err(e) => output.report_error("Error getting latest account state", e),
         }
+        output
     }
 }
 
@@ -126,7 +164,8 @@ impl Command for QueryCommandGetTxnByAccountSeq {
         "Get the committed transaction by account and sequence number.  \
          Optionally also fetch events emitted by this transaction."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Getting committed transaction by account and sequence number");
         match client.get_committed_txn_by_acc_seq(&params) {
             Ok(txn_and_events) => {
@@ -146,11 +185,12 @@ impl Command for QueryCommandGetTxnByAccountSeq {
                     None => println!("Transaction not available"),
                 };
             }
-            Err(e) => report_error(
+            Err(e) => output.report_error(
                 "Error getting committed transaction by account and sequence number",
                 e,
             ),
         }
+        output
     }
 }
 
@@ -168,7 +208,8 @@ impl Command for QueryCommandGetTxnByRange {
         "Get the committed transactions by version range. \
          Optionally also fetch events emitted by these transactions."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Getting committed transaction by range");
         match client.get_committed_txn_by_range(&params) {
             Ok(comm_txns_and_events) => {
@@ -194,8 +235,9 @@ impl Command for QueryCommandGetTxnByRange {
                     cur_version += 1;
                 }
             }
-            Err(e) => report_error("Error getting committed transactions by range", e),
+            Err(e) => output.report_error("Error getting committed transactions by range", e),
         }
+        output
     }
 }
 
@@ -207,12 +249,14 @@ impl Command for QueryCommandGetEvent {
         vec!["event", "ev"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address> <sent|received> <start_sequence_number> <ascending=true|false> <limit>"
+        "<account_ref_id>|<account_address> <sent|received|etoken_sent|etoken_received|etoken_mint|etoken_burn> \
+         <start_sequence_number> <ascending=true|false> <limit>"
     }
     fn get_description(&self) -> &'static str {
-        "Get events by account and event type (sent|received)."
+        "Get events by account and event type (sent|received|etoken_sent|etoken_received|etoken_mint|etoken_burn)."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         println!(">> Getting events by account and event type.");
         match client.get_events_by_account_and_type(&params) {
             Ok((events, last_event_state)) => {
@@ -225,7 +269,66 @@ impl Command for QueryCommandGetEvent {
                 }
                 println!("Last event state: {:#?}", last_event_state);
             }
-            Err(e) => report_error("Error getting events by access path", e),
+            Err(e) => output.report_error("Error getting events by access path", e),
+        }
+        output
+    }
+}
+
+/// Sub command to follow the events and balance changes of an account as they are committed.
+pub struct QueryCommandWatch {}
+
+impl Command for QueryCommandWatch {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["watch", "w"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> [sent|received|etoken|all] [start_sequence_number]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Print new events and balance changes of an account as they are committed, \
+         until the client is stopped. By default sent and received events from now on."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() < 2 || params.len() > 4 {
+            output.report_usage_error("Invalid number of arguments for watch");
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("Unable to parse the account", e);
+                return output;
+            }
+        };
+        let kind = params.get(2).cloned().unwrap_or("all");
+        let start_sequence_number = match params.get(3).map(|start| start.parse::<u64>()) {
+            Some(Ok(start)) => Some(start),
+            Some(Err(e)) => {
+                output.report_error("Invalid start sequence number", e.into());
+                return output;
+            }
+            None => None,
+        };
+        let mut watcher = match EventWatcher::new(client, address, kind, start_sequence_number) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                output.report_error("Unable to watch the account", e);
+                return output;
+            }
+        };
+        println!(">> Watching {} events of {}", kind, address);
+        for cursor in watcher.cursors() {
+            println!(
+                "{} events from sequence number {}",
+                cursor.name, cursor.next_sequence_number
+            );
         }
+        watcher.run(&client.client, |update| {
+            println!("{}", update);
+            stream_value(&update.to_json());
+        });
+        output
     }
 }
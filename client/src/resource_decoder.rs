@@ -0,0 +1,415 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decode the resources of an account with the struct layouts of the published Move modules.
+//!
+//! The VM stores a resource as its fields in definition order: bool as one byte, u64 as 8 little
+//! endian bytes, string, bytearray and address as length prefixed bytes, and nested structs
+//! inline. The field names and types are read from the compiled modules, so every resource of a
+//! registered module can be shown without a hand written Rust mirror of its struct.
+
+use crate::{client_proxy::ModuleRegistryEntry, resource::resource_path};
+use bytecode_verifier::VerifiedModule;
+use canonical_serialization::{CanonicalDeserializer, SimpleDeserializer};
+use failure::prelude::*;
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::{TryFrom, TryInto},
+};
+use types::{
+    account_address::AccountAddress, account_state_blob::AccountStateBlob, byte_array::ByteArray,
+    language_storage::ModuleId,
+};
+use vm::{
+    access::ModuleAccess,
+    file_format::SignatureToken,
+    views::{ModuleView, StructDefinitionView, ViewInternals},
+};
+
+/// Layout of a struct, its fields in the order they are stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLayout {
+    pub module: ModuleId,
+    pub name: String,
+    pub fields: Vec<(String, FieldLayout)>,
+}
+
+/// Layout of a struct field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldLayout {
+    Bool,
+    U64,
+    String,
+    ByteArray,
+    Address,
+    Struct(StructLayout),
+}
+
+/// A decoded struct value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveStruct {
+    pub module: ModuleId,
+    pub name: String,
+    pub fields: Vec<(String, MoveValue)>,
+}
+
+/// A decoded field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveValue {
+    Bool(bool),
+    U64(u64),
+    String(String),
+    ByteArray(ByteArray),
+    Address(AccountAddress),
+    Struct(MoveStruct),
+}
+
+impl StructLayout {
+    /// The fully qualified name of the struct, `<address>.<module>.<struct>`.
+    pub fn qualified_name(&self) -> String {
+        qualified_name(&self.module, &self.name)
+    }
+
+    /// Decode the stored bytes of a value of this struct.
+    pub fn decode(&self, bytes: &[u8]) -> Result<MoveStruct> {
+        let mut deserializer = SimpleDeserializer::new(bytes);
+        self.decode_struct(&mut deserializer)
+    }
+
+    fn decode_struct(&self, deserializer: &mut SimpleDeserializer) -> Result<MoveStruct> {
+        let mut fields = vec![];
+        for (name, layout) in &self.fields {
+            let value = layout.decode(deserializer).map_err(|e| {
+                format_err!(
+                    "Unable to decode field {} of {}: {}",
+                    name,
+                    self.qualified_name(),
+                    e
+                )
+            })?;
+            fields.push((name.clone(), value));
+        }
+        Ok(MoveStruct {
+            module: self.module.clone(),
+            name: self.name.clone(),
+            fields,
+        })
+    }
+}
+
+impl FieldLayout {
+    fn decode(&self, deserializer: &mut SimpleDeserializer) -> Result<MoveValue> {
+        Ok(match self {
+            FieldLayout::Bool => MoveValue::Bool(deserializer.decode_bool()?),
+            FieldLayout::U64 => MoveValue::U64(deserializer.decode_u64()?),
+            FieldLayout::String => MoveValue::String(String::from_utf8(
+                deserializer.decode_variable_length_bytes()?,
+            )?),
+            FieldLayout::ByteArray => {
+                MoveValue::ByteArray(ByteArray::new(deserializer.decode_variable_length_bytes()?))
+            }
+            FieldLayout::Address => MoveValue::Address(AccountAddress::try_from(
+                deserializer.decode_variable_length_bytes()?,
+            )?),
+            FieldLayout::Struct(layout) => MoveValue::Struct(layout.decode_struct(deserializer)?),
+        })
+    }
+}
+
+impl MoveStruct {
+    /// The fully qualified name of the struct, `<address>.<module>.<struct>`.
+    pub fn qualified_name(&self) -> String {
+        qualified_name(&self.module, &self.name)
+    }
+
+    /// Find a field by name.
+    pub fn field(&self, name: &str) -> Option<&MoveValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// JSON object of the fields, bytearrays are hex encoded.
+    pub fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        for (name, value) in &self.fields {
+            map.insert(name.clone(), value.to_json());
+        }
+        Value::Object(map)
+    }
+}
+
+impl MoveValue {
+    pub fn to_json(&self) -> Value {
+        match self {
+            MoveValue::Bool(b) => Value::Bool(*b),
+            MoveValue::U64(i) => Value::from(*i),
+            MoveValue::String(s) => Value::String(s.clone()),
+            MoveValue::ByteArray(bytes) => Value::String(hex::encode(bytes.as_bytes())),
+            MoveValue::Address(address) => Value::String(address.to_string()),
+            MoveValue::Struct(s) => s.to_json(),
+        }
+    }
+}
+
+fn qualified_name(module: &ModuleId, name: &str) -> String {
+    format!("{}.{}.{}", module.address(), module.name(), name)
+}
+
+/// Resolves struct layouts across a set of modules and decodes the resources of account blobs.
+pub struct ResourceDecoder<'a> {
+    modules: HashMap<ModuleId, &'a VerifiedModule>,
+}
+
+impl<'a> ResourceDecoder<'a> {
+    /// Decoder over the given modules. Structs of other modules can not be resolved, so the
+    /// modules the given ones depend on have to be included, usually with the stdlib.
+    pub fn new(modules: impl IntoIterator<Item = &'a VerifiedModule>) -> Self {
+        let modules = modules
+            .into_iter()
+            .map(|module| (module.self_id(), module))
+            .collect();
+        ResourceDecoder { modules }
+    }
+
+    /// Decoder over the stdlib and the modules of the registry.
+    pub fn from_registry(module_registry: &'a [ModuleRegistryEntry]) -> Self {
+        Self::new(
+            stdlib::stdlib_modules().iter().chain(
+                module_registry
+                    .iter()
+                    .flat_map(|entry| entry.modules.iter()),
+            ),
+        )
+    }
+
+    /// Layout of the struct `name` of the module `module_id`.
+    pub fn struct_layout(&self, module_id: &ModuleId, name: &str) -> Result<StructLayout> {
+        let module = self.module(module_id)?;
+        let struct_def = ModuleView::new(module)
+            .structs()
+            .find(|struct_def| struct_def.name() == name)
+            .ok_or_else(|| format_err!("No struct {} in module {:?}", name, module_id))?;
+        self.layout_of(module, &struct_def)
+    }
+
+    fn module(&self, module_id: &ModuleId) -> Result<&'a VerifiedModule> {
+        self.modules.get(module_id).cloned().ok_or_else(|| {
+            format_err!(
+                "Unknown module {}.{}",
+                module_id.address(),
+                module_id.name()
+            )
+        })
+    }
+
+    fn layout_of(
+        &self,
+        module: &'a VerifiedModule,
+        struct_def: &StructDefinitionView<'a, VerifiedModule>,
+    ) -> Result<StructLayout> {
+        let mut fields = vec![];
+        for field in struct_def.fields() {
+            let token = field.type_signature().token();
+            let layout = match token.as_inner() {
+                SignatureToken::Bool => FieldLayout::Bool,
+                SignatureToken::U64 => FieldLayout::U64,
+                SignatureToken::String => FieldLayout::String,
+                SignatureToken::ByteArray => FieldLayout::ByteArray,
+                SignatureToken::Address => FieldLayout::Address,
+                SignatureToken::Struct(_) => {
+                    let handle = token.struct_handle().expect("struct token has a handle");
+                    FieldLayout::Struct(self.struct_layout(&handle.module_id(), handle.name())?)
+                }
+                SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => bail!(
+                    "Field {} of {} is a reference",
+                    field.name(),
+                    struct_def.name()
+                ),
+            };
+            fields.push((field.name().to_string(), layout));
+        }
+        Ok(StructLayout {
+            module: module.self_id(),
+            name: struct_def.name().to_string(),
+            fields,
+        })
+    }
+
+    /// Layouts of every resource struct the modules define.
+    pub fn resource_layouts(&self) -> Result<Vec<StructLayout>> {
+        let mut layouts = vec![];
+        for module in self.modules.values() {
+            for struct_def in ModuleView::new(*module).structs() {
+                if struct_def.is_resource() {
+                    layouts.push(self.layout_of(*module, &struct_def)?);
+                }
+            }
+        }
+        layouts.sort_by_key(|layout| layout.qualified_name());
+        Ok(layouts)
+    }
+
+    /// Decode every resource of a known struct published in the account.
+    pub fn decode_account(
+        &self,
+        account_map: &BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<Vec<MoveStruct>> {
+        let mut resources = vec![];
+        for layout in self.resource_layouts()? {
+            let path = resource_path(*layout.module.address(), layout.module.name(), &layout.name);
+            if let Some(bytes) = account_map.get(&path) {
+                resources.push(layout.decode(bytes)?);
+            }
+        }
+        Ok(resources)
+    }
+
+    /// The number of events emitted so far to an event access path, `<resource path>/<counter>/`,
+    /// read from the counter field of the resource. Zero if the resource is not published.
+    pub fn event_count(
+        &self,
+        account_map: &BTreeMap<Vec<u8>, Vec<u8>>,
+        event_path: &[u8],
+    ) -> Result<u64> {
+        for layout in self.resource_layouts()? {
+            let mut path =
+                resource_path(*layout.module.address(), layout.module.name(), &layout.name);
+            path.push(b'/');
+            if !event_path.starts_with(&path) || !event_path.ends_with(b"/") {
+                continue;
+            }
+            let counter = String::from_utf8(event_path[path.len()..event_path.len() - 1].to_vec())?;
+            let bytes = match account_map.get(&path[..path.len() - 1]) {
+                Some(bytes) => bytes,
+                None => return Ok(0),
+            };
+            return match layout.decode(bytes)?.field(&counter) {
+                Some(MoveValue::U64(count)) => Ok(*count),
+                _ => bail!(
+                    "{} has no event counter {}",
+                    layout.qualified_name(),
+                    counter
+                ),
+            };
+        }
+        bail!("No published resource for event path {:?}", event_path)
+    }
+
+    /// Decode every resource of a known struct published in the account blob.
+    pub fn decode_blob(&self, blob: &AccountStateBlob) -> Result<Vec<MoveStruct>> {
+        let account_map: BTreeMap<Vec<u8>, Vec<u8>> = blob.try_into()?;
+        self.decode_account(&account_map)
+    }
+}
+
+/// JSON object of the resources keyed by their fully qualified struct name.
+pub fn resources_to_json(resources: &[MoveStruct]) -> Value {
+    let mut map = Map::new();
+    for resource in resources {
+        map.insert(resource.qualified_name(), resource.to_json());
+    }
+    Value::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bytecode_verifier::verifier::VerifiedProgram;
+    use canonical_serialization::SimpleSerializer;
+    use types::{
+        account_address::AccountAddress,
+        account_config::{
+            account_received_event_path, account_resource_path, account_sent_event_path,
+            AccountResource,
+        },
+        byte_array::ByteArray,
+    };
+
+    use crate::{resource::ChannelResource, resource_decoder::*, usds_commands::do_compile_script};
+
+    #[test]
+    fn test_decode_account_resource() {
+        let decoder = ResourceDecoder::new(stdlib::stdlib_modules());
+        let account = AccountResource::new(100, 7, ByteArray::new(vec![1, 2, 3]), 2, 3);
+        let mut account_map = BTreeMap::new();
+        account_map.insert(
+            account_resource_path(),
+            SimpleSerializer::<Vec<u8>>::serialize(&account).unwrap(),
+        );
+
+        let resources = decoder.decode_account(&account_map).unwrap();
+        assert_eq!(resources.len(), 1);
+        let json = resources_to_json(&resources);
+        let resource = &json
+            ["0x0000000000000000000000000000000000000000000000000000000000000000.LibraAccount.T"];
+        assert_eq!(resource["authentication_key"], "010203");
+        assert_eq!(resource["balance"]["value"], 100);
+        assert_eq!(resource["sequence_number"], 7);
+        assert_eq!(resource["sent_events_count"], 2);
+        assert_eq!(resource["received_events_count"], 3);
+
+        assert_eq!(
+            decoder
+                .event_count(&account_map, &account_sent_event_path())
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            decoder
+                .event_count(&account_map, &account_received_event_path())
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            decoder
+                .event_count(&BTreeMap::new(), &account_sent_event_path())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_decode_channel_resource() {
+        let address = AccountAddress::random();
+        let (compiled_program, deps) =
+            do_compile_script(&address, include_str!("../move/channel.mvir"), &vec![]).unwrap();
+        let verified_program = VerifiedProgram::new(compiled_program, &deps).unwrap();
+        let entry = ModuleRegistryEntry {
+            name: "channel".to_string(),
+            account: address,
+            modules: verified_program.modules().to_vec(),
+        };
+        let module_registry = vec![entry];
+        let decoder = ResourceDecoder::from_registry(&module_registry);
+
+        let other = AccountAddress::random();
+        let channel = ChannelResource {
+            other,
+            coin: 50,
+            closed: true,
+            height: 12,
+            open_height: 4,
+            public_key: ByteArray::new(vec![9; 32]),
+        };
+        let layout = decoder
+            .struct_layout(&ModuleId::new(address, "Channel".to_string()), "T")
+            .unwrap();
+        let decoded = layout
+            .decode(&SimpleSerializer::<Vec<u8>>::serialize(&channel).unwrap())
+            .unwrap();
+        assert_eq!(decoded.field("other"), Some(&MoveValue::Address(other)));
+        assert_eq!(decoded.field("closed"), Some(&MoveValue::Bool(true)));
+        assert_eq!(decoded.field("height"), Some(&MoveValue::U64(12)));
+        assert_eq!(decoded.field("open_height"), Some(&MoveValue::U64(4)));
+        match decoded.field("coin") {
+            Some(MoveValue::Struct(coin)) => {
+                assert_eq!(coin.field("value"), Some(&MoveValue::U64(50)))
+            }
+            other => panic!("unexpected coin {:?}", other),
+        }
+    }
+}
@@ -7,18 +7,20 @@ impl Command for SubmitTransactionFromDiskCommand {
         vec!["submit", "submitb", "s", "sb"]
     }
     fn get_description(&self) -> &'static str {
-        "Load a RawTransaction from file and submit to the network"
+        "Load a RawTransaction from file, or a transaction signed offline, and submit to the network"
     }
     fn get_params_help(&self) -> &'static str {
-        "\n\t<signer_account_address>|<signer_account_ref_id> <path_to_raw_transaction> Suffix 'b' is for blocking. "
+        "\n\t<signer_account_address>|<signer_account_ref_id> <path_to_raw_transaction> \
+         \n\t<path_to_offline_signed_transaction> Suffix 'b' is for blocking. "
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 3 {
-            println!(
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() != 2 && params.len() != 3 {
+            output.report_usage_error(&format!(
                 "Invalid number of arguments for submitting transaction, got {}",
                 params.len()
-            );
-            return;
+            ));
+            return output;
         }
         let is_blocking = blocking_cmd(&params[0]);
         match client.submit_transaction_from_disk(params, is_blocking) {
@@ -34,7 +36,8 @@ impl Command for SubmitTransactionFromDiskCommand {
                     index_and_seq.account_index, index_and_seq.sequence_number
                 );
             }
-            Err(e) => report_error("Failed to perform transaction", e),
+            Err(e) => output.report_error("Failed to perform transaction", e),
         }
+        output
     }
 }
@@ -19,15 +19,16 @@ impl Command for TransferCommand {
     fn get_description(&self) -> &'static str {
         "Transfer coins (in libra) from account to another."
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
         if params.len() < 4 || params.len() > 6 {
-            println!("Invalid number of arguments for transfer");
+            output.report_usage_error("Invalid number of arguments for transfer");
             println!(
                 "{} {}",
                 self.get_aliases().join(" | "),
                 self.get_params_help()
             );
-            return;
+            return output;
         }
 
         println!(">> Transferring");
@@ -45,7 +46,8 @@ impl Command for TransferCommand {
                     index_and_seq.account_index, index_and_seq.sequence_number
                 );
             }
-            Err(e) => report_error("Failed to perform transaction", e),
+            Err(e) => output.report_error("Failed to perform transaction", e),
         }
+        output
     }
 }
@@ -1,41 +1,87 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use core::borrow::Borrow;
-use std::collections::BTreeMap;
-use std::convert::{TryFrom, TryInto};
-use std::fs;
-use std::path::Path;
-
-use bytecode_verifier::VerifiedModule;
-use bytecode_verifier::verifier::VerifiedProgram;
+use crate::{
+    account_state::AccountState,
+    client_proxy::*,
+    commands::*,
+    resource::{ETokenResource, ReserveAttestationResource, Resource, SupplyResource},
+    resource_decoder::{resources_to_json, ResourceDecoder},
+};
+use bytecode_verifier::{verifier::VerifiedProgram, VerifiedModule};
 use canonical_serialization::SimpleSerializer;
 use compiler::Compiler;
+use core::borrow::Borrow;
 use failure::prelude::*;
-use lazy_static::lazy_static;
-use types::access_path::AccessPath;
-use types::account_address::AccountAddress;
-use types::account_config::AccountResource;
-use types::byte_array::ByteArray;
-use types::transaction::{Program, RawTransaction, TransactionArgument};
-use types::write_set::{WriteOp, WriteSetMut};
-use vm::access::ScriptAccess;
-use vm::file_format::{CompiledProgram, FunctionSignature, SignatureToken};
-
-use crate::{client_proxy::*, commands::*, resource::ETokenResource, account_state::AccountState};
 use itertools::Itertools;
+use lazy_static::lazy_static;
+use libra_wallet::offline::OfflineTransaction;
+use proto_conv::IntoProtoBytes;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    fs,
+    path::Path,
+};
+use transaction_builder::batch_transfer::{
+    batch_transfer_args, batch_transfer_script, Payout, MAX_PAYOUTS_PER_SCRIPT,
+};
+use types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    byte_array::ByteArray,
+    transaction::{
+        MultiEd25519PublicKey, Program, RawTransaction, TransactionArgument,
+        TransactionStatus as VMTransactionStatus, MAX_TRANSACTION_SIZE_IN_BYTES,
+    },
+    vm_error::{ExecutionStatus, VMStatus},
+    write_set::{WriteOp, WriteSetMut},
+};
+use vm::{
+    access::ScriptAccess,
+    file_format::{CompiledProgram, FunctionSignature, SignatureToken},
+    gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS,
+};
 
 lazy_static! {
-    pub static ref ETOKEN_ISSUE_TEMPLATE: String = {include_str!("../move/eToken.mvir").to_string()};
-    pub static ref ETOKEN_INIT_TEMPLATE: String = {include_str!("../move/init.mvir").to_string()};
-    pub static ref ETOKEN_MINT_TEMPLATE: String = {include_str!("../move/mint.mvir").to_string()};
-    pub static ref ETOKEN_TRANSFER_TEMPLATE: String = {include_str!("../move/peer_to_peer_transfer.mvir").to_string()};
-    pub static ref ETOKEN_SELL_TEMPLATE: String = {include_str!("../move/sell.mvir").to_string()};
-    pub static ref ETOKEN_BUY_TEMPLATE: String = {include_str!("../move/buy.mvir").to_string()};
-    pub static ref ETOKEN_BURN_TEMPLATE: String = {include_str!("../move/burn.mvir").to_string()};
+    pub static ref ETOKEN_ISSUE_TEMPLATE: String = { stdlib::usds::ETOKEN_TEMPLATE.to_string() };
+    pub static ref ETOKEN_GENESIS_ISSUE_TEMPLATE: String =
+        { include_str!("../move/issue.mvir").to_string() };
+    pub static ref ETOKEN_INIT_TEMPLATE: String = { include_str!("../move/init.mvir").to_string() };
+    pub static ref ETOKEN_MINT_TEMPLATE: String = { include_str!("../move/mint.mvir").to_string() };
+    pub static ref ETOKEN_TRANSFER_TEMPLATE: String =
+        { include_str!("../move/peer_to_peer_transfer.mvir").to_string() };
+    pub static ref ETOKEN_SELL_TEMPLATE: String = { include_str!("../move/sell.mvir").to_string() };
+    pub static ref ETOKEN_BUY_TEMPLATE: String = { include_str!("../move/buy.mvir").to_string() };
+    pub static ref ETOKEN_CANCEL_ORDER_TEMPLATE: String =
+        { include_str!("../move/cancel_order.mvir").to_string() };
+    pub static ref ETOKEN_BURN_TEMPLATE: String = { include_str!("../move/burn.mvir").to_string() };
+    pub static ref ETOKEN_ATTEST_TEMPLATE: String =
+        { include_str!("../move/attest.mvir").to_string() };
+    pub static ref ETOKEN_SET_AUDITOR_TEMPLATE: String =
+        { include_str!("../move/set_auditor.mvir").to_string() };
+    pub static ref ETOKEN_GRANT_MINTER_TEMPLATE: String =
+        { include_str!("../move/grant_minter.mvir").to_string() };
+    pub static ref ETOKEN_REVOKE_MINTER_TEMPLATE: String =
+        { include_str!("../move/revoke_minter.mvir").to_string() };
+    pub static ref ETOKEN_BLACKLIST_TEMPLATE: String =
+        { include_str!("../move/blacklist.mvir").to_string() };
+    pub static ref ETOKEN_UNBLACKLIST_TEMPLATE: String =
+        { include_str!("../move/unblacklist.mvir").to_string() };
+    pub static ref ETOKEN_PROPOSE_OWNER_TEMPLATE: String =
+        { include_str!("../move/propose_owner.mvir").to_string() };
+    pub static ref ETOKEN_ACCEPT_OWNER_TEMPLATE: String =
+        { include_str!("../move/accept_owner.mvir").to_string() };
+    pub static ref ETOKEN_PAUSE_TEMPLATE: String =
+        { include_str!("../move/pause.mvir").to_string() };
+    pub static ref ETOKEN_UNPAUSE_TEMPLATE: String =
+        { include_str!("../move/unpause.mvir").to_string() };
+    pub static ref ROTATE_MULTISIG_TEMPLATE: String =
+        { include_str!("../move/rotate_multisig.mvir").to_string() };
 }
 
-
 /// Major command for usds operations.
 pub struct USDSCommand {}
 
@@ -46,21 +92,64 @@ impl Command for USDSCommand {
     fn get_description(&self) -> &'static str {
         "USDS operations"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
         let commands: Vec<Box<dyn Command>> = vec![
             Box::new(USDSCommandExecuteModule {}),
+            Box::new(USDSCommandRegistryModule {}),
             Box::new(USDSCommandGetLatestAccountState {}),
             Box::new(USDSCommandETokenIssue {}),
             Box::new(USDSCommandETokenInit {}),
             Box::new(USDSCommandETokenMint {}),
             Box::new(USDSCommandETokenTransfer {}),
+            Box::new(USDSCommandETokenBatchTransfer {}),
             Box::new(USDSCommandETokenSell {}),
             Box::new(USDSCommandETokenBuy {}),
+            Box::new(USDSCommandETokenCancel {}),
+            Box::new(USDSCommandETokenOrders {}),
             Box::new(USDSCommandETokenBurn {}),
+            Box::new(USDSCommandETokenSupply {}),
+            Box::new(USDSCommandETokenSetAuditor {}),
+            Box::new(USDSCommandETokenAttest {}),
+            Box::new(USDSCommandETokenGrantMinter {}),
+            Box::new(USDSCommandETokenRevokeMinter {}),
+            Box::new(USDSCommandETokenBlacklist {}),
+            Box::new(USDSCommandETokenUnblacklist {}),
+            Box::new(USDSCommandETokenProposeOwner {}),
+            Box::new(USDSCommandETokenAcceptOwner {}),
+            Box::new(USDSCommandETokenPause {}),
+            Box::new(USDSCommandETokenUnpause {}),
+            Box::new(USDSCommandMultisig {}),
             Box::new(USDSCommandWriteSet {}),
+            Box::new(USDSCommandSimulate {}),
         ];
 
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        subcommand_execute(&params[0], commands, client, &params[1..])
+    }
+}
+
+/// Execute the transactions of a usds command locally instead of submitting them.
+pub struct USDSCommandSimulate {}
+
+impl Command for USDSCommandSimulate {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["simulate", "dry_run"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<usds sub command> <sub command arguments>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Execute the transaction of a usds command against the latest ledger state and print the \
+         VM status, gas used, write set and events, without submitting it"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        if params.len() < 2 {
+            let mut output = CommandOutput::default();
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        let mut params = params.to_vec();
+        params.push("--dry-run");
+        USDSCommand {}.execute(client, &params)
     }
 }
 
@@ -76,16 +165,21 @@ impl Command for USDSCommandExecuteModule {
     fn get_description(&self) -> &'static str {
         "Execute a move script"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() < 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let path = Path::new(params[2]);
@@ -93,13 +187,63 @@ impl Command for USDSCommandExecuteModule {
         let source = match fs::read_to_string(path) {
             Ok(s) => s,
             Err(e) => {
-                report_error("Unable to read file", e.into());
-                return;
+                output.report_error("Unable to read file", e.into());
+                return output;
+            }
+        };
+        let script_args = params[3..params.len()]
+            .to_vec()
+            .iter()
+            .map(|str| str.to_string())
+            .collect();
+        match execute_script_with_resolver(
+            client,
+            &options,
+            &address,
+            source.as_str(),
+            param_parse_arg_resolver(script_args),
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
+    }
+}
+
+/// Fetch a module someone else published from chain and add it to the module registry.
+pub struct USDSCommandRegistryModule {}
+
+impl Command for USDSCommandRegistryModule {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["registry_module", "module"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<etoken|channel> <account_address>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Fetch a module published under an account and add it to the saved module registry"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() != 3 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[2]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        let script_args = params[3..params.len()].to_vec().iter().map(|str| str.to_string()).collect();
-        execute_script_with_resolver(client, &address, source.as_str(),
-                                     param_parse_arg_resolver(script_args)).map(handler_result).map_err(handler_err).ok();
+        match client.registry_module_from_chain(params[1].to_string(), address) {
+            Ok(_) => {
+                println!("Module {} registered at {}", params[1], address);
+                output.record_value("address", hex::encode(address));
+            }
+            Err(e) => output.report_error("Unable to registry module", e),
+        }
+        output
     }
 }
 
@@ -110,32 +254,78 @@ impl Command for USDSCommandETokenIssue {
         vec!["etoken_issue", "issue"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>"
+        "<account_ref_id>|<account_address> <owner_minter_allowance>"
     }
     fn get_description(&self) -> &'static str {
-        "Issue EToken to an account"
+        "Issue EToken to an account, or set up the issuer if the modules were published at genesis"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 2 {
-            println!("Invalid number of arguments for command");
-            return;
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        if params.len() != 3 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let owner_allowance = match ClientProxy::convert_to_micro_libras(params[2]) {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid allowance", e.into());
+                return output;
+            }
+        };
+        let args = vec![TransactionArgument::U64(owner_allowance)];
+        // The modules may already be published under this account at genesis.
+        let published = match client.try_registry_module_from_chain("etoken".to_string(), address) {
+            Ok(published) => published,
+            Err(e) => {
+                output.report_error("Unable to look up the etoken module", e);
+                return output;
             }
         };
+        if published {
+            match execute_script(
+                client,
+                &options,
+                &address,
+                &ETOKEN_GENESIS_ISSUE_TEMPLATE,
+                args,
+            ) {
+                Ok(result) => handler_result(&options, result),
+                Err(e) => output.report_error("execute command fail:", e),
+            }
+            return output;
+        }
         let mut template: String = ETOKEN_ISSUE_TEMPLATE.clone();
         let mut etoken_addr = "0x".to_owned();
         etoken_addr.push_str(address.to_string().as_str());
         template = template.replace("${etoken}", etoken_addr.as_str());
-        execute_script(client, &address, template.as_str(), vec![]).map(|(compiled_program, deps, seq)| {
-            let verified_program = VerifiedProgram::new(compiled_program.clone(), &deps).unwrap();
-            client.registry_module("etoken".to_string(), address.clone(), verified_program.modules().to_vec());
-            (compiled_program, deps, seq)
-        }).map(handler_result).map_err(handler_err).ok();
+        match execute_script(client, &options, &address, template.as_str(), args) {
+            Ok((compiled_program, deps, seq)) => {
+                // Nothing was published by a dry run.
+                if !options.dry_run {
+                    let verified_program =
+                        VerifiedProgram::new(compiled_program.clone(), &deps).unwrap();
+                    client.registry_module(
+                        "etoken".to_string(),
+                        address.clone(),
+                        verified_program.modules().to_vec(),
+                    );
+                }
+                handler_result(&options, (compiled_program, deps, seq));
+            }
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
@@ -152,23 +342,32 @@ impl Command for USDSCommandETokenInit {
     fn get_description(&self) -> &'static str {
         "Init the account for accept EToken"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 2 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken") {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_INIT_TEMPLATE, vec![]).map(handler_result).map_err(handler_err).ok();
+        match execute_script(client, &options, &address, &ETOKEN_INIT_TEMPLATE, vec![]) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
@@ -180,35 +379,51 @@ impl Command for USDSCommandETokenMint {
         vec!["etoken_mint", "mint"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id> <amount>"
+        "<account_ref_id>|<account_address> <amount> [--prepare <file>]"
     }
     fn get_description(&self) -> &'static str {
-        "Mint etoken for an account"
+        "Mint etoken for an account, or prepare the transaction for offline signing"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        let (params, prepare) = split_prepare_option(&params);
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[2]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
+                output.report_error("invalid amount", e.into());
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_MINT_TEMPLATE, vec![TransactionArgument::U64(amount)]).map(handler_result).map_err(handler_err).ok();
+        execute_or_prepare_script(
+            client,
+            &mut output,
+            &options,
+            &address,
+            &ETOKEN_MINT_TEMPLATE,
+            vec![TransactionArgument::U64(amount)],
+            &params,
+            prepare,
+        );
+        output
     }
 }
 
@@ -225,33 +440,705 @@ impl Command for USDSCommandETokenBurn {
     fn get_description(&self) -> &'static str {
         "Burn etoken as a minter"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[2]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
+                output.report_error("invalid amount", e.into());
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_BURN_TEMPLATE, vec![TransactionArgument::U64(amount)]).map(handler_result).map_err(handler_err).ok();
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &ETOKEN_BURN_TEMPLATE,
+            vec![TransactionArgument::U64(amount)],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
+// Show the total supply against the last reserve attestation
+pub struct USDSCommandETokenSupply {}
+
+impl USDSCommandETokenSupply {
+    fn do_execute(&self, client: &mut ClientProxy, output: &mut CommandOutput) -> Result<()> {
+        let etoken_address = client
+            .module_registry
+            .get("etoken")
+            .unwrap()
+            .account
+            .clone();
+        let blob = client
+            .get_account_state_and_update(etoken_address.clone())?
+            .0
+            .ok_or_else(|| {
+                format_err!("Unable to get account state by address {}", etoken_address)
+            })?;
+        let account_btree: BTreeMap<Vec<u8>, Vec<u8>> = blob.borrow().try_into()?;
+
+        let supply = SupplyResource::make_from(etoken_address.clone(), &account_btree)?;
+        println!("Total supply: {}", format_micro_amount(supply.total_supply));
+        output.record_value("total_supply", supply.total_supply);
+        match ReserveAttestationResource::make_from(etoken_address, &account_btree) {
+            Ok(ref attestation) if attestation.timestamp == 0 => {
+                println!("No reserve attestation published")
+            }
+            Ok(attestation) => {
+                println!(
+                    "Last reserve attestation: {} at timestamp {}, auditor public key: {}",
+                    format_micro_amount(attestation.amount),
+                    attestation.timestamp,
+                    hex::encode(attestation.auditor_public_key.as_bytes())
+                );
+                output.record_value("reserve_attestation", json!({
+                    "amount": attestation.amount,
+                    "timestamp": attestation.timestamp,
+                    "auditor_public_key": hex::encode(attestation.auditor_public_key.as_bytes()),
+                    "signature": hex::encode(attestation.signature.as_bytes()),
+                }));
+                if attestation.amount >= supply.total_supply {
+                    println!(
+                        "Reserve exceeds supply by: {}",
+                        format_micro_amount(attestation.amount - supply.total_supply)
+                    );
+                } else {
+                    println!(
+                        "[WARN] Supply exceeds reserve by: {}",
+                        format_micro_amount(supply.total_supply - attestation.amount)
+                    );
+                }
+            }
+            Err(_) => println!("No reserve attestation published"),
+        }
+        Ok(())
+    }
+}
+
+impl Command for USDSCommandETokenSupply {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_supply", "supply"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Show the total etoken supply versus the last reserve attestation"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() != 1 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        match self.do_execute(client, &mut output) {
+            Ok(_) => {}
+            Err(e) => {
+                output.report_error("execute command fail:", e);
+            }
+        }
+        output
+    }
+}
+
+// Set the auditor whose signature reserve attestations must carry as the owner
+pub struct USDSCommandETokenSetAuditor {}
+
+impl Command for USDSCommandETokenSetAuditor {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_set_auditor", "set-auditor"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <auditor_public_key_hex> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Set the ed25519 public key of the auditor that signs the reserve attestations"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        let (params, prepare) = split_prepare_option(&params);
+        if params.len() != 3 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let auditor_public_key = match hex::decode(params[2]) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                output.report_error("parse hex error.", e.into());
+                return output;
+            }
+        };
+        execute_or_prepare_script(
+            client,
+            &mut output,
+            &options,
+            &address,
+            &ETOKEN_SET_AUDITOR_TEMPLATE,
+            vec![TransactionArgument::ByteArray(ByteArray::new(
+                auditor_public_key,
+            ))],
+            &params,
+            prepare,
+        );
+        output
+    }
+}
+
+// Publish a reserve attestation signed by the auditor as the owner
+pub struct USDSCommandETokenAttest {}
+
+impl Command for USDSCommandETokenAttest {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_attest", "attest"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <amount> <timestamp> <signature_hex>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Publish a reserve attestation signed by the auditor set with set-auditor over the amount and timestamp"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        if params.len() != 5 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let amount = match ClientProxy::convert_to_micro_libras(params[2]) {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid amount", e.into());
+                return output;
+            }
+        };
+        let timestamp = match params[3].parse::<u64>() {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid timestamp", e.into());
+                return output;
+            }
+        };
+        let signature = match hex::decode(params[4]) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                output.report_error("parse hex error.", e.into());
+                return output;
+            }
+        };
+        let args = vec![
+            TransactionArgument::U64(amount),
+            TransactionArgument::U64(timestamp),
+            TransactionArgument::ByteArray(ByteArray::new(signature)),
+        ];
+        match execute_script(client, &options, &address, &ETOKEN_ATTEST_TEMPLATE, args) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
+    }
+}
+
+// Grant minter capability with an allowance to an account as the owner
+pub struct USDSCommandETokenGrantMinter {}
+
+impl Command for USDSCommandETokenGrantMinter {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_grant_minter", "grant-minter"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <account_ref_id>|<account_address> <allowance> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Grant minter capability with a minting allowance to an account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        let (params, prepare) = split_prepare_option(&params);
+        if params.len() != 4 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let receiver_address = match client.get_account_address_from_parameter(params[2]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let allowance = match ClientProxy::convert_to_micro_libras(params[3]) {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid allowance", e.into());
+                return output;
+            }
+        };
+        execute_or_prepare_script(
+            client,
+            &mut output,
+            &options,
+            &address,
+            &ETOKEN_GRANT_MINTER_TEMPLATE,
+            vec![
+                TransactionArgument::Address(receiver_address),
+                TransactionArgument::U64(allowance),
+            ],
+            &params,
+            prepare,
+        );
+        output
+    }
+}
+
+// Revoke minter capability and allowance from an account as the owner
+pub struct USDSCommandETokenRevokeMinter {}
+
+impl Command for USDSCommandETokenRevokeMinter {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_revoke_minter", "revoke-minter"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <account_ref_id>|<account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Revoke minter capability and the remaining allowance from an account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_address_script(client, &mut output, params, &ETOKEN_REVOKE_MINTER_TEMPLATE);
+        output
+    }
+}
+
+// Blacklist an account as the owner
+pub struct USDSCommandETokenBlacklist {}
+
+impl Command for USDSCommandETokenBlacklist {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_blacklist", "blacklist"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <account_ref_id>|<account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Blacklist an account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_address_script(client, &mut output, params, &ETOKEN_BLACKLIST_TEMPLATE);
+        output
+    }
+}
+
+// Remove an account from the blacklist as the owner
+pub struct USDSCommandETokenUnblacklist {}
+
+impl Command for USDSCommandETokenUnblacklist {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_unblacklist", "unblacklist"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <account_ref_id>|<account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Remove an account from the blacklist"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_address_script(client, &mut output, params, &ETOKEN_UNBLACKLIST_TEMPLATE);
+        output
+    }
+}
+
+// Propose a new owner as the current owner
+pub struct USDSCommandETokenProposeOwner {}
+
+impl Command for USDSCommandETokenProposeOwner {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_propose_owner", "propose-owner"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> <new_owner_account_ref_id>|<new_owner_account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Propose a new owner, who has to accept the ownership"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_address_script(client, &mut output, params, &ETOKEN_PROPOSE_OWNER_TEMPLATE);
+        output
+    }
+}
+
+// Accept the ownership proposed by the current owner
+pub struct USDSCommandETokenAcceptOwner {}
+
+impl Command for USDSCommandETokenAcceptOwner {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_accept_owner", "accept-owner"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<new_owner_account_ref_id>|<new_owner_account_address> <owner_account_ref_id>|<owner_account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Accept the ownership proposed by the current owner"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_address_script(client, &mut output, params, &ETOKEN_ACCEPT_OWNER_TEMPLATE);
+        output
+    }
+}
+
+// Pause etoken transfers, mints and burns as the owner
+pub struct USDSCommandETokenPause {}
+
+impl Command for USDSCommandETokenPause {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_pause", "pause"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Pause etoken transfers, mints and burns"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_pause_script(client, &mut output, params, &ETOKEN_PAUSE_TEMPLATE);
+        output
+    }
+}
+
+// Resume etoken transfers, mints and burns as the owner
+pub struct USDSCommandETokenUnpause {}
+
+impl Command for USDSCommandETokenUnpause {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_unpause", "unpause"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<owner_account_ref_id>|<owner_account_address> [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Resume etoken transfers, mints and burns"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        execute_pause_script(client, &mut output, params, &ETOKEN_UNPAUSE_TEMPLATE);
+        output
+    }
+}
+
+// Hand an account, e.g. the issuer or a minter, over to a multi-signature policy
+pub struct USDSCommandMultisig {}
+
+impl Command for USDSCommandMultisig {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["rotate_multisig", "multisig"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <threshold> <public_key>... [--prepare <file>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Require signatures of threshold of the hex encoded ed25519 public keys for the transactions of an account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        let (params, prepare) = split_prepare_option(&params);
+        if params.len() < 4 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let policy = match parse_multi_public_key(params[2], &params[3..]) {
+            Ok(policy) => policy,
+            Err(e) => {
+                output.report_error("invalid multi-signature policy", e);
+                return output;
+            }
+        };
+        let encoded = hex::encode(policy.to_bytes());
+        println!("Multi-ed25519 policy: {}", encoded);
+        println!("Sign the transactions of the account with: offline_signer --signer <address> --multi_public_key {}", encoded);
+        output.record_value("multi_public_key", encoded);
+        execute_or_prepare_script(
+            client,
+            &mut output,
+            &options,
+            &address,
+            &ROTATE_MULTISIG_TEMPLATE,
+            vec![TransactionArgument::ByteArray(ByteArray::new(
+                policy.to_bytes(),
+            ))],
+            &params,
+            prepare,
+        );
+        output
+    }
+}
+
+/// Build a multi-ed25519 policy from a threshold and hex encoded ed25519 public keys.
+fn parse_multi_public_key(threshold: &str, public_keys: &[&str]) -> Result<MultiEd25519PublicKey> {
+    let threshold = threshold.parse::<u8>()?;
+    let public_keys = public_keys
+        .iter()
+        .map(|key| crypto::PublicKey::from_slice(&hex::decode(key)?))
+        .collect::<Result<Vec<_>>>()?;
+    MultiEd25519PublicKey::new(public_keys, threshold)
+}
+
+/// Execute the pause or unpause script, params are `<owner>`, then report the resulting state.
+fn execute_pause_script(
+    client: &mut ClientProxy,
+    output: &mut CommandOutput,
+    params: &[&str],
+    script_template: &str,
+) {
+    let (params, options) = match split_transaction_options(output, params) {
+        Some(split) => split,
+        None => return,
+    };
+    let (params, prepare) = split_prepare_option(&params);
+    if params.len() != 2 {
+        output.report_usage_error("Invalid number of arguments for command");
+        return;
+    }
+    if let Err(e) = client.resolve_etoken_module() {
+        output.report_error("Unable to resolve etoken module", e);
+        return;
+    }
+    let address = match client.get_account_address_from_parameter(params[1]) {
+        Ok(address) => address,
+        Err(e) => {
+            output.report_error("get address fail.", e);
+            return;
+        }
+    };
+    if !execute_or_prepare_script(
+        client,
+        output,
+        &options,
+        &address,
+        script_template,
+        vec![],
+        &params,
+        prepare,
+    ) || prepare.is_some()
+    {
+        return;
+    }
+    let etoken_address = client
+        .module_registry
+        .get("etoken")
+        .unwrap()
+        .account
+        .clone();
+    match USDSCommandGetLatestAccountState::get_account_state(client, etoken_address) {
+        Ok(Some(account_state)) => match account_state.find_resource(|r| match r {
+            Resource::Paused(_) => true,
+            _ => false,
+        }) {
+            Some(Resource::Paused(Some(paused))) => {
+                println!("USDS paused: {}", paused.paused);
+                output.record_value("paused", paused.paused);
+            }
+            _ => println!("No pause switch published"),
+        },
+        Ok(None) => println!("Account State is None"),
+        Err(e) => output.report_error("Error getting latest account state", e),
+    }
+}
+
+/// Execute a script whose only argument is an address, params are `<sender> <address>`.
+fn execute_address_script(
+    client: &mut ClientProxy,
+    output: &mut CommandOutput,
+    params: &[&str],
+    script_template: &str,
+) {
+    let (params, options) = match split_transaction_options(output, params) {
+        Some(split) => split,
+        None => return,
+    };
+    let (params, prepare) = split_prepare_option(&params);
+    if params.len() != 3 {
+        output.report_usage_error("Invalid number of arguments for command");
+        return;
+    }
+    if let Err(e) = client.resolve_etoken_module() {
+        output.report_error("Unable to resolve etoken module", e);
+        return;
+    }
+    let address = match client.get_account_address_from_parameter(params[1]) {
+        Ok(address) => address,
+        Err(e) => {
+            output.report_error("get address fail.", e);
+            return;
+        }
+    };
+    let arg_address = match client.get_account_address_from_parameter(params[2]) {
+        Ok(address) => address,
+        Err(e) => {
+            output.report_error("get address fail.", e);
+            return;
+        }
+    };
+    execute_or_prepare_script(
+        client,
+        output,
+        &options,
+        &address,
+        script_template,
+        vec![TransactionArgument::Address(arg_address)],
+        &params,
+        prepare,
+    );
+}
+
+/// Split a trailing `--prepare <file>` option off the params.
+fn split_prepare_option<'a>(params: &[&'a str]) -> (Vec<&'a str>, Option<&'a str>) {
+    match params.iter().position(|param| *param == "--prepare") {
+        Some(i) if i + 2 == params.len() => (params[..i].to_vec(), Some(params[i + 1])),
+        _ => (params.to_vec(), None),
+    }
+}
+
+/// Execute the script, or with a `--prepare` file write it unsigned to the file for the offline
+/// signer. Returns whether it succeeded.
+fn execute_or_prepare_script(
+    client: &mut ClientProxy,
+    output: &mut CommandOutput,
+    options: &TransactionOptions,
+    address: &AccountAddress,
+    script_template: &str,
+    args: Vec<TransactionArgument>,
+    params: &[&str],
+    prepare: Option<&str>,
+) -> bool {
+    let result = match prepare {
+        Some(path) => prepare_script(
+            client,
+            options,
+            address,
+            script_template,
+            args,
+            &format!("usds {}", params.join(" ")),
+            Path::new(path),
+        ),
+        None => execute_script(client, options, address, script_template, args)
+            .map(|result| handler_result(options, result)),
+    };
+    result
+        .map_err(|e| output.report_error("execute command fail:", e))
+        .is_ok()
+}
+
+/// Compile the script and write the unsigned transaction to `path` for the offline signer.
+pub fn prepare_script(
+    client: &mut ClientProxy,
+    options: &TransactionOptions,
+    address: &AccountAddress,
+    script_template: &str,
+    args: Vec<TransactionArgument>,
+    description: &str,
+    path: &Path,
+) -> Result<()> {
+    let (compiled_program, _) = compile_script(script_template, client, address)?;
+    let program = create_transaction_program(&compiled_program, args)?;
+    let raw_txn = client.prepare_transaction(
+        *address,
+        program,
+        options.gas_unit_price,
+        options.max_gas_amount,
+    )?;
+    let script_source = parse_script(script_template, &client.get_module_registry());
+    OfflineTransaction::new(raw_txn, description.to_string(), Some(script_source))?.save(path)?;
+    println!("Unsigned transaction written to {:?}", path);
+    println!(
+        "Sign it with offline_signer and send it with: submit {}",
+        path.display()
+    );
+    Ok(())
+}
 
 // Transfer etoken to an account
 pub struct USDSCommandETokenTransfer {}
@@ -266,40 +1153,323 @@ impl Command for USDSCommandETokenTransfer {
     fn get_description(&self) -> &'static str {
         "Transfer etoken to an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 4 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let payee_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[3]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
+                output.report_error("invalid amount", e.into());
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_TRANSFER_TEMPLATE, vec![TransactionArgument::Address(payee_address), TransactionArgument::U64(amount)]).map(handler_result).map_err(handler_err).ok();
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &ETOKEN_TRANSFER_TEMPLATE,
+            vec![
+                TransactionArgument::Address(payee_address),
+                TransactionArgument::U64(amount),
+            ],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
+/// Room left in a batch transaction for sequence number, gas and expiration values that are only
+/// known when it is submitted.
+const BATCH_TRANSACTION_SIZE_MARGIN: usize = 64;
+
+// Transfer etoken to the rows of a CSV file, several payees per transaction
+pub struct USDSCommandETokenBatchTransfer {}
+
+impl Command for USDSCommandETokenBatchTransfer {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["batch-transfer", "batch_transfer"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <csv_file>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Transfer etoken to every address,amount row of a CSV file, with as many payees per \
+         transaction as its size and gas limits allow"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        if params.len() != 3 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let payouts = match fs::read_to_string(params[2])
+            .map_err(Error::from)
+            .and_then(|text| parse_payouts_csv(&text))
+        {
+            Ok(payouts) => payouts,
+            Err(e) => {
+                output.report_error("Unable to read the payouts", e);
+                return output;
+            }
+        };
+
+        let reports = match batch_transfer(client, &options, &address, &payouts) {
+            Ok(reports) => reports,
+            Err(e) => {
+                output.report_error("Unable to send the payouts", e);
+                return output;
+            }
+        };
+        for report in &reports {
+            println!("{}", report);
+        }
+        output.record_value(
+            "batch",
+            reports
+                .iter()
+                .map(BatchChunkReport::to_json)
+                .collect::<Vec<_>>(),
+        );
+        let paid = reports
+            .iter()
+            .filter(|report| report.result.is_ok())
+            .map(|report| report.num_payouts)
+            .sum::<usize>();
+        println!("Paid {} of {} payees", paid, payouts.len());
+        if let Some(Err(e)) = reports.last().map(|report| &report.result) {
+            output.report_error(
+                &format!(
+                    "Batch transfer stopped, the payees from row {} on were not paid",
+                    paid + 1
+                ),
+                format_err!("{}", e),
+            );
+        }
+        output
+    }
+}
+
+/// Parse the `address,amount` rows of a CSV file, amounts are in etoken like for `usds transfer`.
+/// Blank lines, `#` comments and an `address,amount` header are skipped.
+pub fn parse_payouts_csv(text: &str) -> Result<Vec<Payout>> {
+    let mut payouts = vec![];
+    let mut first_row = true;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        ensure!(
+            fields.len() == 2,
+            "line {}: expected address,amount but got {:?}",
+            i + 1,
+            line
+        );
+        if first_row && fields[0].eq_ignore_ascii_case("address") {
+            first_row = false;
+            continue;
+        }
+        first_row = false;
+        let payee = AccountAddress::try_from(fields[0].trim_start_matches("0x").to_string())
+            .map_err(|e| format_err!("line {}: invalid address {:?}: {}", i + 1, fields[0], e))?;
+        let amount = ClientProxy::convert_to_micro_libras(fields[1])
+            .map_err(|e| format_err!("line {}: invalid amount {:?}: {}", i + 1, fields[1], e))?;
+        payouts.push(Payout { payee, amount });
+    }
+    ensure!(!payouts.is_empty(), "No payouts in the file");
+    Ok(payouts)
+}
+
+/// What happened to one transaction of a batch transfer.
+pub struct BatchChunkReport {
+    /// Index of the first payout of the transaction in the file, from 0.
+    pub first_payout: usize,
+    /// Number of payouts of the transaction.
+    pub num_payouts: usize,
+    /// Sum of the amounts paid, in micro units.
+    pub amount: u64,
+    /// Sequence number of the transaction or why it was not sent.
+    pub result: std::result::Result<u64, String>,
+}
+
+impl BatchChunkReport {
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "first_payout": self.first_payout,
+            "num_payouts": self.num_payouts,
+            "amount": format_micro_amount(self.amount),
+        });
+        match &self.result {
+            Ok(sequence_number) => value["sequence_number"] = (*sequence_number).into(),
+            Err(e) => value["error"] = e.clone().into(),
+        }
+        value
+    }
+}
+
+impl std::fmt::Display for BatchChunkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "payees {}..{}, {} etoken: ",
+            self.first_payout + 1,
+            self.first_payout + self.num_payouts,
+            format_micro_amount(self.amount)
+        )?;
+        match &self.result {
+            Ok(sequence_number) => write!(f, "sent with sequence number {}", sequence_number),
+            Err(e) => write!(f, "failed, {}", e),
+        }
+    }
+}
+
+/// Send the payouts in as few transactions as possible, one after the other. Stops at the first
+/// transaction that fails, the report of which is the last one. Nothing is sent if the amounts add
+/// up to more than a u64.
+pub fn batch_transfer(
+    client: &mut ClientProxy,
+    options: &TransactionOptions,
+    sender: &AccountAddress,
+    payouts: &[Payout],
+) -> Result<Vec<BatchChunkReport>> {
+    payouts_amount(payouts)?;
+    let mut reports = vec![];
+    let mut start = 0;
+    while start < payouts.len() {
+        let (num_payouts, result) =
+            match plan_batch_chunk(client, options, sender, &payouts[start..]) {
+                Ok((num_payouts, program)) => (
+                    num_payouts,
+                    client
+                        .send_transaction(sender, program, options, true)
+                        .map(|index_and_seq| index_and_seq.sequence_number),
+                ),
+                Err(e) => (payouts.len() - start, Err(e)),
+            };
+        let chunk = &payouts[start..start + num_payouts];
+        let failed = result.is_err();
+        reports.push(BatchChunkReport {
+            first_payout: start,
+            num_payouts,
+            amount: payouts_amount(chunk)?,
+            result: result.map_err(|e| e.to_string()),
+        });
+        if failed {
+            break;
+        }
+        start += num_payouts;
+    }
+    Ok(reports)
+}
+
+/// Sum of the amounts of the payouts, in micro units.
+fn payouts_amount(payouts: &[Payout]) -> Result<u64> {
+    payouts.iter().try_fold(0u64, |amount, payout| {
+        amount.checked_add(payout.amount).ok_or_else(|| {
+            format_err!(
+                "The payouts add up to more than {} micro units",
+                std::u64::MAX
+            )
+        })
+    })
+}
+
+/// The program paying the longest prefix of `payouts` that fits in one transaction, halving the
+/// prefix until the transaction is small enough and does not run out of gas when executed locally.
+fn plan_batch_chunk(
+    client: &ClientProxy,
+    options: &TransactionOptions,
+    sender: &AccountAddress,
+    payouts: &[Payout],
+) -> Result<(usize, Program)> {
+    let etoken_address = client.module_registry["etoken"].account;
+    let max_gas_amount = options
+        .max_gas_amount
+        .unwrap_or(MAXIMUM_NUMBER_OF_GAS_UNITS);
+    let mut num_payouts = std::cmp::min(payouts.len(), MAX_PAYOUTS_PER_SCRIPT);
+    loop {
+        let chunk = &payouts[..num_payouts];
+        let script = batch_transfer_script(&etoken_address, num_payouts)?;
+        let (compiled_program, _) =
+            do_compile_script(sender, &script, &client.get_module_registry())?;
+        let program = create_transaction_program(&compiled_program, batch_transfer_args(chunk))?;
+        let size = RawTransaction::new(
+            *sender,
+            0,
+            program.clone(),
+            0,
+            0,
+            std::time::Duration::new(0, 0),
+        )
+        .into_proto_bytes()?
+        .len();
+        if num_payouts > 1 && size + BATCH_TRANSACTION_SIZE_MARGIN > MAX_TRANSACTION_SIZE_IN_BYTES {
+            num_payouts /= 2;
+            continue;
+        }
+        let (status, gas_used) = client.simulate_gas(*sender, program.clone())?;
+        match status {
+            VMTransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+                if gas_used <= max_gas_amount =>
+            {
+                return Ok((num_payouts, program));
+            }
+            VMTransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+            | VMTransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::OutOfGas))
+                if num_payouts > 1 =>
+            {
+                num_payouts /= 2;
+            }
+            status => bail!(
+                "the transaction would fail with {:?}, nothing was sent",
+                status
+            ),
+        }
+    }
+}
 
 // Sell etoken and create an order
 pub struct USDSCommandETokenSell {}
@@ -309,46 +1479,68 @@ impl Command for USDSCommandETokenSell {
         vec!["etoken_sell", "sell"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address> <amount> <price>"
+        "<account_ref_id>|<account_address> <amount> <unit_price>"
     }
     fn get_description(&self) -> &'static str {
-        "Sell etoken and create an order"
+        "Sell etoken and open an order, unit_price is the price in libra of one etoken"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
         if params.len() != 4 {
-            println!("Invalid number of arguments for command");
-            return;
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
         let amount = match ClientProxy::convert_to_micro_libras(params[2]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid amount", e.into());
-                return;
+                output.report_error("invalid amount", e.into());
+                return output;
             }
         };
-        let price = match ClientProxy::convert_to_micro_libras(params[3]) {
+        let unit_price = match ClientProxy::convert_to_micro_libras(params[3]) {
             Ok(i) => i,
             Err(e) => {
-                report_error("invalid price", e.into());
-                return;
+                output.report_error("invalid unit price", e.into());
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_SELL_TEMPLATE, vec![TransactionArgument::U64(amount), TransactionArgument::U64(price)]).map(handler_result).map_err(handler_err).ok();
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &ETOKEN_SELL_TEMPLATE,
+            vec![
+                TransactionArgument::U64(amount),
+                TransactionArgument::U64(unit_price),
+            ],
+        ) {
+            Ok(result) => {
+                // The order id is the sequence number of the sell transaction
+                println!("Order id: {}", result.2.sequence_number);
+                handler_result(&options, result);
+            }
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
     }
 }
 
-// Buy etoken from a order address
+// Buy etoken from an open order
 pub struct USDSCommandETokenBuy {}
 
 impl Command for USDSCommandETokenBuy {
@@ -356,105 +1548,323 @@ impl Command for USDSCommandETokenBuy {
         vec!["etoken_buy", "buy"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address> <order_account_ref_id>|<order_account_address>"
+        "<account_ref_id>|<account_address> <order_account_ref_id>|<order_account_address> <order_id> <amount>"
     }
     fn get_description(&self) -> &'static str {
-        "Buy etoken from a order address"
+        "Buy some or all etoken of an open order"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 3 {
-            println!("Invalid number of arguments for command");
-            return;
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        if params.len() != 5 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
         }
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         let address = match client.get_account_address_from_parameter(params[1]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        let payee_address = match client.get_account_address_from_parameter(params[2]) {
+        let order_address = match client.get_account_address_from_parameter(params[2]) {
             Ok(address) => address,
             Err(e) => {
-                report_error("get address fail.", e);
-                return;
+                output.report_error("get address fail.", e);
+                return output;
             }
         };
-        execute_script(client, &address, &ETOKEN_BUY_TEMPLATE, vec![TransactionArgument::Address(payee_address)]).map(handler_result).map_err(handler_err).ok();
+        let order_id = match params[3].parse::<u64>() {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid order id", e.into());
+                return output;
+            }
+        };
+        let amount = match ClientProxy::convert_to_micro_libras(params[4]) {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid amount", e.into());
+                return output;
+            }
+        };
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &ETOKEN_BUY_TEMPLATE,
+            vec![
+                TransactionArgument::Address(order_address),
+                TransactionArgument::U64(order_id),
+                TransactionArgument::U64(amount),
+            ],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
+    }
+}
+
+// Cancel an open order and take back its etoken
+pub struct USDSCommandETokenCancel {}
+
+impl Command for USDSCommandETokenCancel {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_cancel", "cancel"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <order_id>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Cancel an open order and return the etoken left in it"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let (params, options) = match split_transaction_options(&mut output, params) {
+            Some(split) => split,
+            None => return output,
+        };
+        if params.len() != 3 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        let address = match client.get_account_address_from_parameter(params[1]) {
+            Ok(address) => address,
+            Err(e) => {
+                output.report_error("get address fail.", e);
+                return output;
+            }
+        };
+        let order_id = match params[2].parse::<u64>() {
+            Ok(i) => i,
+            Err(e) => {
+                output.report_error("invalid order id", e.into());
+                return output;
+            }
+        };
+        match execute_script(
+            client,
+            &options,
+            &address,
+            &ETOKEN_CANCEL_ORDER_TEMPLATE,
+            vec![TransactionArgument::U64(order_id)],
+        ) {
+            Ok(result) => handler_result(&options, result),
+            Err(e) => output.report_error("execute command fail:", e),
+        }
+        output
+    }
+}
+
+// List the open orders of an account
+pub struct USDSCommandETokenOrders {}
+
+impl USDSCommandETokenOrders {
+    fn do_execute(
+        &self,
+        client: &mut ClientProxy,
+        output: &mut CommandOutput,
+        params: &[&str],
+    ) -> Result<()> {
+        let address = client.get_account_address_from_parameter(params[1])?;
+        let account_state =
+            USDSCommandGetLatestAccountState::get_account_state(client, address)?
+                .ok_or_else(|| format_err!("Unable to get account state by address {}", address))?;
+        let mut orders = vec![];
+        match account_state.find_resource(|r| match r {
+            Resource::OrderBook(_) => true,
+            _ => false,
+        }) {
+            Some(Resource::OrderBook(Some(order_book))) if !order_book.open_orders().is_empty() => {
+                for order in order_book.open_orders() {
+                    println!(
+                        "Order id: {}, amount: {}, unit price: {}",
+                        order.id,
+                        format_micro_amount(order.value),
+                        format_micro_amount(order.unit_price),
+                    );
+                    orders.push(json!({
+                        "id": order.id,
+                        "amount": order.value,
+                        "unit_price": order.unit_price,
+                    }));
+                }
+            }
+            _ => println!("No open orders"),
+        }
+        output.record_value("orders", orders);
+        Ok(())
+    }
+}
+
+impl Command for USDSCommandETokenOrders {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["etoken_orders", "orders"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address>"
+    }
+    fn get_description(&self) -> &'static str {
+        "List the open orders of an account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if params.len() != 2 {
+            output.report_usage_error("Invalid number of arguments for command");
+            return output;
+        }
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
+        }
+        if let Err(e) = self.do_execute(client, &mut output, params) {
+            output.report_error("Error listing orders", e);
+        }
+        output
     }
 }
 
-pub fn handler_err(e: Error) {
-    report_error("execute command fail:", e);
+/// Format micro units as a decimal amount, the same way account balances are shown.
+pub fn format_micro_amount(amount: u64) -> String {
+    format!("{}.{:0>6}", amount / 1_000_000, amount % 1_000_000)
 }
 
-pub fn handler_result(result: (CompiledProgram, Vec<VerifiedModule>, IndexAndSequence)) {
+pub fn handler_result(
+    options: &TransactionOptions,
+    result: (CompiledProgram, Vec<VerifiedModule>, IndexAndSequence),
+) {
+    if options.dry_run {
+        println!("Dry run finished, nothing was submitted");
+        return;
+    }
     let index_and_seq = result.2;
     println!("Finished transaction!");
     println!(
         "To query for transaction status, run: query txn_acc_seq {} {} \
-                     <fetch_events=true|false>",
+         <fetch_events=true|false>",
         index_and_seq.account_index, index_and_seq.sequence_number
     );
 }
 
-fn direct_arg_resolver(args: Vec<TransactionArgument>) -> Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>> {
-    return Box::new(|_compiled_program: &CompiledProgram| -> Result<Vec<TransactionArgument>>{
-        Ok(args)
-    });
+fn direct_arg_resolver(
+    args: Vec<TransactionArgument>,
+) -> Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>> {
+    return Box::new(
+        |_compiled_program: &CompiledProgram| -> Result<Vec<TransactionArgument>> { Ok(args) },
+    );
 }
 
-fn param_parse_arg_resolver(args: Vec<String>) -> Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>> {
-    return Box::new(move |compiled_program: &CompiledProgram| -> Result<Vec<TransactionArgument>>{
-        let script = compiled_program.script.borrow();
-        let script_mut = script.clone().into_inner();
-        let main_fun = script.main();
-        let main_signature: &FunctionSignature = script_mut.function_signatures.get(main_fun.function.0 as usize).unwrap();
-        if main_signature.arg_types.len() != args.len() {
-            bail!("miss script arguments, expect:{:#?} ", main_signature.arg_types.clone());
-        }
-        let tx_args: Result<Vec<_>> = main_signature.arg_types.iter().enumerate().map(|(idx, arg_type)| -> Result<TransactionArgument>{
-            match arg_type {
-                SignatureToken::String => Ok(TransactionArgument::String(args[idx].clone())),
-                SignatureToken::Address => Ok(TransactionArgument::Address(AccountAddress::try_from(args[idx].clone())?)),
-                SignatureToken::U64 => Ok(TransactionArgument::U64(args[idx].parse()?)),
-                SignatureToken::ByteArray => Ok(TransactionArgument::ByteArray(ByteArray::new(hex::decode(args[idx].clone())?))),
-                _ => bail!("unsupported arg type:{:#?}", arg_type)
+fn param_parse_arg_resolver(
+    args: Vec<String>,
+) -> Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>> {
+    return Box::new(
+        move |compiled_program: &CompiledProgram| -> Result<Vec<TransactionArgument>> {
+            let script = compiled_program.script.borrow();
+            let script_mut = script.clone().into_inner();
+            let main_fun = script.main();
+            let main_signature: &FunctionSignature = script_mut
+                .function_signatures
+                .get(main_fun.function.0 as usize)
+                .unwrap();
+            if main_signature.arg_types.len() != args.len() {
+                bail!(
+                    "miss script arguments, expect:{:#?} ",
+                    main_signature.arg_types.clone()
+                );
             }
-        }).collect();
-        Ok(tx_args?)
-        //Ok(vec![])
-    });
+            let tx_args: Result<Vec<_>> = main_signature
+                .arg_types
+                .iter()
+                .enumerate()
+                .map(|(idx, arg_type)| -> Result<TransactionArgument> {
+                    match arg_type {
+                        SignatureToken::String => {
+                            Ok(TransactionArgument::String(args[idx].clone()))
+                        }
+                        SignatureToken::Address => Ok(TransactionArgument::Address(
+                            AccountAddress::try_from(args[idx].clone())?,
+                        )),
+                        SignatureToken::U64 => Ok(TransactionArgument::U64(args[idx].parse()?)),
+                        SignatureToken::ByteArray => Ok(TransactionArgument::ByteArray(
+                            ByteArray::new(hex::decode(args[idx].clone())?),
+                        )),
+                        _ => bail!("unsupported arg type:{:#?}", arg_type),
+                    }
+                })
+                .collect();
+            Ok(tx_args?)
+            //Ok(vec![])
+        },
+    );
 }
 
-pub fn execute_script(client: &mut ClientProxy, address: &AccountAddress, script_template: &str, args: Vec<TransactionArgument>) -> Result<(CompiledProgram, Vec<VerifiedModule>, IndexAndSequence)> {
-    return execute_script_with_resolver(client, address, script_template, direct_arg_resolver(args));
+pub fn execute_script(
+    client: &mut ClientProxy,
+    options: &TransactionOptions,
+    address: &AccountAddress,
+    script_template: &str,
+    args: Vec<TransactionArgument>,
+) -> Result<(CompiledProgram, Vec<VerifiedModule>, IndexAndSequence)> {
+    return execute_script_with_resolver(
+        client,
+        options,
+        address,
+        script_template,
+        direct_arg_resolver(args),
+    );
 }
 
-pub fn execute_script_with_resolver(client: &mut ClientProxy, address: &AccountAddress, script_template: &str, arg_resolver: Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>>) -> Result<(CompiledProgram, Vec<VerifiedModule>, IndexAndSequence)> {
+pub fn execute_script_with_resolver(
+    client: &mut ClientProxy,
+    options: &TransactionOptions,
+    address: &AccountAddress,
+    script_template: &str,
+    arg_resolver: Box<dyn FnOnce(&CompiledProgram) -> Result<Vec<TransactionArgument>>>,
+) -> Result<(CompiledProgram, Vec<VerifiedModule>, IndexAndSequence)> {
     let (compiled_program, deps) = compile_script(script_template, client, &address)?;
     let is_blocking = true;
     let tx_args = arg_resolver(&compiled_program)?;
     println!("{:#?}", compiled_program);
     let program = create_transaction_program(&compiled_program, tx_args)?;
-    let result = client.send_transaction(&address, program, None, None, is_blocking)?;
+    let result = client.send_transaction(&address, program, options, is_blocking)?;
     return Ok((compiled_program, deps, result));
 }
 
-pub fn compile_script(script_template: &str, client: &mut ClientProxy, address: &AccountAddress) -> Result<(CompiledProgram, Vec<VerifiedModule>)> {
+pub fn compile_script(
+    script_template: &str,
+    client: &mut ClientProxy,
+    address: &AccountAddress,
+) -> Result<(CompiledProgram, Vec<VerifiedModule>)> {
     let module_registry = client.get_module_registry();
     do_compile_script(address, script_template, &module_registry)
 }
 
-pub fn do_compile_script(address: &AccountAddress, script_template: &str, module_registry: &Vec<ModuleRegistryEntry>) -> Result<(CompiledProgram, Vec<VerifiedModule>)> {
+pub fn do_compile_script(
+    address: &AccountAddress,
+    script_template: &str,
+    module_registry: &Vec<ModuleRegistryEntry>,
+) -> Result<(CompiledProgram, Vec<VerifiedModule>)> {
     let source = parse_script(script_template, module_registry);
     //let mut extra_deps:Vec<VerifiedModule> = vec![];
-    let extra_deps = module_registry.iter().map(|entry|entry.modules.as_slice()).collect_vec().as_slice().concat();
-        //.fold(extra_deps, |c, module|{c.extend(module);return c;});
+    let extra_deps = module_registry
+        .iter()
+        .map(|entry| entry.modules.as_slice())
+        .collect_vec()
+        .as_slice()
+        .concat();
+    //.fold(extra_deps, |c, module|{c.extend(module);return c;});
     let compiler = Compiler {
         address: address.clone(),
         code: &source,
@@ -463,23 +1873,28 @@ pub fn do_compile_script(address: &AccountAddress, script_template: &str, module
         extra_deps,
         ..Compiler::default()
     };
-    let (compiled_program, dependencies) = compiler
-        .into_compiled_program_and_deps()?;
+    let (compiled_program, dependencies) = compiler.into_compiled_program_and_deps()?;
     Ok((compiled_program, dependencies))
 }
 
 pub fn parse_script(script_template: &str, module_registry: &Vec<ModuleRegistryEntry>) -> String {
     let mut script = script_template.to_string();
-    for module in module_registry{
+    for module in module_registry {
         let mut address_str = "0x".to_owned();
         address_str.push_str(module.account.to_string().as_str());
-        script = script_template.replace(format!("${{{}}}",module.name).as_str(), address_str.as_str());
+        script = script.replace(
+            format!("${{{}}}", module.name).as_str(),
+            address_str.as_str(),
+        );
     }
     return script;
     //compiler::parser::parse_program(script.as_str())
 }
 
-fn create_transaction_program(program: &CompiledProgram, args: Vec<TransactionArgument>) -> Result<Program> {
+fn create_transaction_program(
+    program: &CompiledProgram,
+    args: Vec<TransactionArgument>,
+) -> Result<Program> {
     let mut script_blob = vec![];
     program.script.serialize(&mut script_blob)?;
 
@@ -500,46 +1915,72 @@ fn create_transaction_program(program: &CompiledProgram, args: Vec<TransactionAr
 pub struct USDSCommandGetLatestAccountState {}
 
 impl USDSCommandGetLatestAccountState {
-    fn do_execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+    /// Get the latest account state from validator and decode the registered modules' resources.
+    pub fn get_account_state(
+        client: &mut ClientProxy,
+        address: AccountAddress,
+    ) -> Result<Option<AccountState>> {
+        match client.get_account_state_and_update(address)?.0 {
+            Some(blob) => Ok(Some(AccountState::from_blob(
+                &blob,
+                &client.get_module_registry(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    fn do_execute(
+        &self,
+        client: &mut ClientProxy,
+        output: &mut CommandOutput,
+        params: &[&str],
+    ) -> Result<()> {
         println!(">> Getting latest account state");
         match client.get_latest_account_state(&params) {
             Ok((acc, version)) => match acc {
                 Some(blob) => {
-                    let account_state = AccountState::from_blob(&blob, &client.get_module_registry())?;
-
+                    let module_registry = client.get_module_registry();
+                    let resources = resources_to_json(
+                        &ResourceDecoder::from_registry(&module_registry).decode_blob(&blob)?,
+                    );
 
                     println!(
                         "Latest account state is: \n \
-                     Account: {:#?}\n \
-                     AccountState: {:#?}\n \
-                     Blockchain Version: {}\n",
+                         Account: {:#?}\n \
+                         Resources: {}\n \
+                         Blockchain Version: {}\n",
                         client
                             .get_account_address_from_parameter(params[1])
                             .expect("Unable to parse account parameter"),
-                        account_state,
+                        serde_json::to_string_pretty(&resources)?,
                         version,
                     );
+                    output.record_value("resources", resources);
+                    output.record_value("version", version);
 
-                    let account_btree:BTreeMap<Vec<u8>,Vec<u8>> = blob.borrow().try_into()?;
+                    let account_btree: BTreeMap<Vec<u8>, Vec<u8>> = blob.borrow().try_into()?;
                     println!("AccountStateBlob Tree:");
-                    account_btree.iter().map(|(k, v)| -> (String, String) {
-                        let mut key: String = "".to_owned();
-                        if k[0] == CODE_TAG {
-                            key.push_str("code_")
-                        } else if k[0] == RESOURCE_TAG {
-                            key.push_str("res_");
-                        }
-                        key.push_str(hex::encode(k).as_str());
-                        (key, hex::encode(v))
-                    }).for_each(|(k, v)| {
-                        println!("key:{:#?}, value:{:#?}", k, v);
-                    })
+                    account_btree
+                        .iter()
+                        .map(|(k, v)| -> (String, String) {
+                            let mut key: String = "".to_owned();
+                            if k[0] == CODE_TAG {
+                                key.push_str("code_")
+                            } else if k[0] == RESOURCE_TAG {
+                                key.push_str("res_");
+                            }
+                            key.push_str(hex::encode(k).as_str());
+                            (key, hex::encode(v))
+                        })
+                        .for_each(|(k, v)| {
+                            println!("key:{:#?}, value:{:#?}", k, v);
+                        })
                 }
                 None => {
                     println!("Account State is None");
                 }
             },
-            Err(e) => report_error("Error getting latest account state", e),
+            Err(e) => output.report_error("Error getting latest account state", e),
         };
         Ok(())
     }
@@ -558,23 +1999,23 @@ impl Command for USDSCommandGetLatestAccountState {
     fn get_description(&self) -> &'static str {
         "Get the latest state for an account"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match self.do_execute(client, params) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        match self.do_execute(client, &mut output, params) {
             Ok(_) => {}
             Err(e) => {
-                report_error("execute command fail:", e);
+                output.report_error("execute command fail:", e);
             }
         }
+        output
     }
 }
 
-
 pub struct USDSCommandWriteSet {}
 
 impl USDSCommandWriteSet {
     fn do_execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
-        let signer_account_address =
-            client.get_account_address_from_parameter(params[1])?;
+        let signer_account_address = client.get_account_address_from_parameter(params[1])?;
         let etoken_module = client.module_registry.get("etoken").unwrap();
         let path = ETokenResource::resource_path(etoken_module.account.clone());
         let ap = AccessPath::new(signer_account_address.clone(), path);
@@ -583,7 +2024,10 @@ impl USDSCommandWriteSet {
         let mut write_set = WriteSetMut::default();
         write_set.push((ap, WriteOp::Value(resource_bytes)));
         let ws = write_set.freeze()?;
-        let sequence = client.get_account_resource_and_update(signer_account_address.clone()).unwrap().sequence_number();
+        let sequence = client
+            .get_account_resource_and_update(signer_account_address.clone())
+            .unwrap()
+            .sequence_number();
         let tx = RawTransaction::new_write_set(signer_account_address, sequence, ws);
         client.submit_custom_transaction(signer_account_address, tx, true)?;
         Ok(())
@@ -600,38 +2044,41 @@ impl Command for USDSCommandWriteSet {
     fn get_description(&self) -> &'static str {
         "Directly save resource to account, this command will fail because of RejectedWriteSet error"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if !client.exist_module("etoken")  {
-            println!("Please issue etoken first.");
-            return;
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        if let Err(e) = client.resolve_etoken_module() {
+            output.report_error("Unable to resolve etoken module", e);
+            return output;
         }
         match self.do_execute(client, params) {
             Ok(_) => {}
             Err(e) => {
-                report_error("execute command fail:", e);
+                output.report_error("execute command fail:", e);
             }
         }
+        output
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use types::account_address::AccountAddress;
 
-    use crate::usds_commands::*;
-    use crate::client_proxy::*;
-    use vm::file_format::CompiledProgram;
-    use bytecode_verifier::VerifiedModule;
+    use crate::{client_proxy::*, usds_commands::*};
+    use bytecode_verifier::{verifier::VerifiedProgram, VerifiedModule};
     use failure::prelude::*;
-    use bytecode_verifier::verifier::VerifiedProgram;
+    use vm::file_format::CompiledProgram;
 
     #[test]
     fn test_parse_script() {
         //println!("{:?}", AccountAddress::random());
         //println!("{:?}",AccountAddress::default().to_string());
 
-        let entry = ModuleRegistryEntry{name:"etoken".to_string(), account: AccountAddress::random(), modules:vec![]};
+        let entry = ModuleRegistryEntry {
+            name: "etoken".to_string(),
+            account: AccountAddress::random(),
+            modules: vec![],
+        };
         let module_registry = vec![entry];
 
         let program = parse_script(&ETOKEN_ISSUE_TEMPLATE, &module_registry);
@@ -650,54 +2097,159 @@ mod tests {
         println!("{:?}", program);
     }
 
-    fn compile_etoken() -> Result<Vec<ModuleRegistryEntry>>{
+    fn compile_etoken() -> Result<Vec<ModuleRegistryEntry>> {
         let etoken_address = AccountAddress::random();
 
-        let mut entry = ModuleRegistryEntry{name:"etoken".to_string(), account: etoken_address.clone(), modules:vec![]};
+        let mut entry = ModuleRegistryEntry {
+            name: "etoken".to_string(),
+            account: etoken_address.clone(),
+            modules: vec![],
+        };
 
-        let (compiled_program,deps) = do_compile_script(&etoken_address, &ETOKEN_ISSUE_TEMPLATE, &vec![entry.clone()])?;
+        let (compiled_program, deps) = do_compile_script(
+            &etoken_address,
+            &ETOKEN_ISSUE_TEMPLATE,
+            &vec![entry.clone()],
+        )?;
         let verified_program = VerifiedProgram::new(compiled_program, &deps).unwrap();
-        entry.modules.append(verified_program.modules().to_vec().as_mut());
+        entry
+            .modules
+            .append(verified_program.modules().to_vec().as_mut());
 
         Ok(vec![entry])
     }
 
     #[test]
-    fn test_etoken_script(){
-        do_test_compile_scripts(vec![ETOKEN_INIT_TEMPLATE.to_string(), ETOKEN_MINT_TEMPLATE.to_string(), ETOKEN_TRANSFER_TEMPLATE.to_string(), ETOKEN_SELL_TEMPLATE.to_string(), ETOKEN_BUY_TEMPLATE.to_string(), ETOKEN_BURN_TEMPLATE.to_string()]).expect("test fail.");
+    fn test_etoken_script() {
+        do_test_compile_scripts(vec![
+            ETOKEN_INIT_TEMPLATE.to_string(),
+            ETOKEN_MINT_TEMPLATE.to_string(),
+            ETOKEN_TRANSFER_TEMPLATE.to_string(),
+            ETOKEN_SELL_TEMPLATE.to_string(),
+            ETOKEN_BUY_TEMPLATE.to_string(),
+            ETOKEN_BURN_TEMPLATE.to_string(),
+            ETOKEN_ATTEST_TEMPLATE.to_string(),
+            ETOKEN_SET_AUDITOR_TEMPLATE.to_string(),
+            ETOKEN_GRANT_MINTER_TEMPLATE.to_string(),
+            ETOKEN_REVOKE_MINTER_TEMPLATE.to_string(),
+            ETOKEN_BLACKLIST_TEMPLATE.to_string(),
+            ETOKEN_UNBLACKLIST_TEMPLATE.to_string(),
+            ETOKEN_PROPOSE_OWNER_TEMPLATE.to_string(),
+            ETOKEN_ACCEPT_OWNER_TEMPLATE.to_string(),
+            ETOKEN_PAUSE_TEMPLATE.to_string(),
+            ETOKEN_UNPAUSE_TEMPLATE.to_string(),
+            ETOKEN_GENESIS_ISSUE_TEMPLATE.to_string(),
+            ETOKEN_CANCEL_ORDER_TEMPLATE.to_string(),
+            ROTATE_MULTISIG_TEMPLATE.to_string(),
+        ])
+        .expect("test fail.");
     }
 
-    fn do_test_compile_scripts(scripts:Vec<String>)->Result<()>{
+    fn do_test_compile_scripts(scripts: Vec<String>) -> Result<()> {
         let address = AccountAddress::random();
         let module_registry = compile_etoken().expect("compile etoken fail.");
-        for script in scripts{
-            match do_compile_script(&address, &script, &module_registry){
-                Ok((program,_)) => {
-                    println!("{:#?}",program)
-                },
-                Err(e) => panic!("script:{} err:{:?}",script, e)
+        for script in scripts {
+            match do_compile_script(&address, &script, &module_registry) {
+                Ok((program, _)) => println!("{:#?}", program),
+                Err(e) => panic!("script:{} err:{:?}", script, e),
             }
         }
         Ok(())
     }
 
     #[test]
-    fn test_other_script(){
+    fn test_batch_transfer_script() {
         let address = AccountAddress::random();
         let module_registry = compile_etoken().expect("compile etoken fail.");
-
-        let scripts = vec![include_str!("../move/fake_token.mvir")];
-        for script in scripts {
-            match do_compile_script(&address, script, &module_registry){
-                Ok(_) => {},
-                Err(e) => panic!("script:{} err:{:?}",script, e)
+        for num_payouts in &[1, 3, MAX_PAYOUTS_PER_SCRIPT] {
+            let script = batch_transfer_script(&module_registry[0].account, *num_payouts).unwrap();
+            if let Err(e) = do_compile_script(&address, &script, &module_registry) {
+                panic!("batch of {} payouts err:{:?}", num_payouts, e)
             }
         }
     }
 
+    #[test]
+    fn test_parse_payouts_csv() {
+        let payee = AccountAddress::random();
+        let text = format!(
+            "address,amount\n# payroll\n0x{},1.5\n\n{}, 2\n",
+            payee, payee
+        );
+        let payouts = parse_payouts_csv(&text).unwrap();
+        assert_eq!(
+            payouts,
+            vec![
+                Payout {
+                    payee,
+                    amount: 1_500_000
+                },
+                Payout {
+                    payee,
+                    amount: 2_000_000
+                }
+            ]
+        );
+
+        let err = parse_payouts_csv(&format!("{},1\n{}\n", payee, payee)).unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+        assert!(parse_payouts_csv(&format!("{},abc", payee)).is_err());
+        assert!(parse_payouts_csv("address,amount\n").is_err());
+    }
+
+    #[test]
+    fn test_payouts_amount() {
+        let payee = AccountAddress::random();
+        let payouts = vec![
+            Payout {
+                payee,
+                amount: std::u64::MAX - 1,
+            },
+            Payout { payee, amount: 1 },
+        ];
+        assert_eq!(payouts_amount(&payouts).unwrap(), std::u64::MAX);
+        assert!(payouts_amount(&[payouts[0].clone(), payouts[0].clone()]).is_err());
+    }
+
+    #[test]
+    fn test_split_prepare_option() {
+        assert_eq!(
+            split_prepare_option(&["mint", "0", "10", "--prepare", "mint.json"]),
+            (vec!["mint", "0", "10"], Some("mint.json"))
+        );
+        assert_eq!(
+            split_prepare_option(&["mint", "0", "10"]),
+            (vec!["mint", "0", "10"], None)
+        );
+        // A missing file is left to the argument count check.
+        assert_eq!(
+            split_prepare_option(&["mint", "0", "10", "--prepare"]),
+            (vec!["mint", "0", "10", "--prepare"], None)
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_public_key() {
+        let keys: Vec<String> = (0..3)
+            .map(|_| hex::encode(crypto::signing::generate_keypair().1.to_slice()))
+            .collect();
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        let policy = parse_multi_public_key("2", &keys).unwrap();
+        assert_eq!(policy.threshold(), 2);
+        assert_eq!(policy.public_keys().len(), 3);
+        assert!(parse_multi_public_key("4", &keys).is_err());
+        assert!(parse_multi_public_key("1", &["00"]).is_err());
+    }
+
+    #[test]
+    fn test_format_micro_amount() {
+        assert_eq!(format_micro_amount(0), "0.000000");
+        assert_eq!(format_micro_amount(100_000_001), "100.000001");
+    }
+
     #[test]
     fn test_slice() {
         let a = ["0", "1", "2"];
         println!("{}", &a[3..a.len()].len());
     }
-}
\ No newline at end of file
+}
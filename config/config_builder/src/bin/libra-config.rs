@@ -3,7 +3,8 @@
 
 use clap::{value_t, App, Arg};
 use config_builder::swarm_config::SwarmConfigBuilder;
-use std::convert::TryInto;
+use std::{convert::TryInto, str::FromStr};
+use types::account_address::AccountAddress;
 
 const BASE_ARG: &str = "base";
 const NODES_ARG: &str = "nodes";
@@ -11,6 +12,7 @@ const OUTPUT_DIR_ARG: &str = "output-dir";
 const DISCOVERY_ARG: &str = "discovery";
 const KEY_SEED_ARG: &str = "key-seed";
 const FAUCET_ACCOUNT_FILE_ARG: &str = "faucet_account_file";
+const USDS_ISSUER_ARG: &str = "usds-issuer";
 
 fn main() {
     let args = App::new("Libra Config Tool")
@@ -60,6 +62,13 @@ fn main() {
                 .help("File location from which to load faucet account generated via generate_keypair tool")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(USDS_ISSUER_ARG)
+                .short("u")
+                .long(USDS_ISSUER_ARG)
+                .takes_value(true)
+                .help("Publish the USDS modules at genesis under this issuer account address"),
+        )
         .get_matches();
     let base_path = value_t!(args, BASE_ARG, String).expect("Path to base config");
     let nodes_count = value_t!(args, NODES_ARG, usize).unwrap();
@@ -88,6 +97,12 @@ fn main() {
         let seed = hex::decode(seed_hex).unwrap();
         config_builder.with_key_seed(seed[..32].try_into().unwrap());
     }
+    if args.is_present(USDS_ISSUER_ARG) {
+        let issuer = value_t!(args, USDS_ISSUER_ARG, String).expect("Missing USDS issuer");
+        config_builder.with_usds_issuer(
+            AccountAddress::from_str(&issuer).expect("Invalid USDS issuer address"),
+        );
+    }
     let generated_configs = config_builder.build().expect("Unable to generate configs");
 
     println!(
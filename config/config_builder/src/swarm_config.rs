@@ -11,6 +11,7 @@ use config::{
 use crypto::signing::KeyPair;
 use failure::prelude::*;
 use std::path::{Path, PathBuf};
+use types::account_address::AccountAddress;
 use vm_genesis::{default_config, open_config};
 
 pub struct SwarmConfig {
@@ -30,6 +31,7 @@ impl SwarmConfig {
         key_seed: Option<[u8; 32]>,
         output_dir: &Path,
         static_ports: bool,
+        usds_issuer: Option<AccountAddress>,
     ) -> Result<Self> {
         // Generate trusted peer configs + their private keys.
         template.base.data_dir_path = output_dir.into();
@@ -46,6 +48,7 @@ impl SwarmConfig {
             &output_dir.join(&template.execution.genesis_file_location),
             &faucet_key,
             &trusted_peers_config,
+            usds_issuer,
         )?;
 
         let mut configs = Vec::new();
@@ -134,6 +137,7 @@ pub struct SwarmConfigBuilder {
     key_seed: Option<[u8; 32]>,
     faucet_account_keypair_filepath: Option<PathBuf>,
     faucet_account_keypair: Option<KeyPair>,
+    usds_issuer: Option<AccountAddress>,
 }
 impl Default for SwarmConfigBuilder {
     fn default() -> Self {
@@ -147,6 +151,7 @@ impl Default for SwarmConfigBuilder {
             key_seed: None,
             faucet_account_keypair_filepath: None,
             faucet_account_keypair: None,
+            usds_issuer: None,
         }
     }
 }
@@ -211,6 +216,12 @@ impl SwarmConfigBuilder {
         self
     }
 
+    /// Publish the USDS modules at genesis, owned by `issuer`.
+    pub fn with_usds_issuer(&mut self, issuer: AccountAddress) -> &mut Self {
+        self.usds_issuer = Some(issuer);
+        self
+    }
+
     pub fn build(&self) -> Result<SwarmConfig> {
         // verify required fields
         let faucet_key_path = self.faucet_account_keypair_filepath.clone();
@@ -262,6 +273,7 @@ impl SwarmConfigBuilder {
             self.key_seed,
             &self.output_dir,
             self.static_ports,
+            self.usds_issuer,
         )
     }
 }
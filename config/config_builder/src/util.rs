@@ -10,12 +10,13 @@ use failure::prelude::*;
 use proto_conv::IntoProtoBytes;
 use std::{convert::TryFrom, fs::File, io::prelude::*, path::Path};
 use types::{account_address::AccountAddress, validator_public_keys::ValidatorPublicKeys};
-use vm_genesis::encode_genesis_transaction_with_validator;
+use vm_genesis::encode_genesis_transaction_with_usds;
 
 pub fn gen_genesis_transaction<P: AsRef<Path>>(
     path: P,
     faucet_account_keypair: &KeyPair,
     trusted_peer_config: &TrustedPeersConfig,
+    usds_issuer: Option<AccountAddress>,
 ) -> Result<()> {
     let validator_set = trusted_peer_config
         .peers
@@ -24,16 +25,18 @@ pub fn gen_genesis_transaction<P: AsRef<Path>>(
             ValidatorPublicKeys::new(
                 AccountAddress::try_from(peer_id.clone()).expect("[config] invalid peer_id"),
                 peer.get_consensus_public(),
+                peer.get_consensus_bls_public(),
                 peer.get_network_signing_public(),
                 peer.get_network_identity_public(),
             )
         })
         .collect();
-    let transaction = encode_genesis_transaction_with_validator(
+    let transaction = encode_genesis_transaction_with_usds(
         faucet_account_keypair.private_key(),
         faucet_account_keypair.public_key(),
         validator_set,
-    );
+        usds_issuer,
+    )?;
     let mut file = File::create(path)?;
     file.write_all(&transaction.into_proto_bytes()?)?;
     Ok(())
@@ -50,6 +53,7 @@ pub fn get_test_config() -> (NodeConfig, KeyPair) {
         &config.execution.genesis_file_location,
         &keypair,
         &TrustedPeersConfigHelpers::get_test_config(1, None).1,
+        None,
     )
     .expect("[config] failed to create genesis transaction");
     (config, keypair)
@@ -9,6 +9,7 @@ use std::{
     io::{Read, Write},
     path::{Path, PathBuf},
     string::ToString,
+    sync::Arc,
 };
 
 use crypto::{
@@ -16,6 +17,7 @@ use crypto::{
     x25519::{self, X25519PrivateKey, X25519PublicKey},
 };
 use logger::LoggerType;
+use nextgen_crypto::{bls12381::BLS12381PrivateKey, vrf::ecvrf::VRFPrivateKey};
 use serde::{Deserialize, Serialize};
 use tempfile::TempDir;
 use toml;
@@ -25,11 +27,14 @@ use proto_conv::FromProtoBytes;
 use types::transaction::{SignedTransaction, SCRIPT_HASH_LENGTH};
 
 use crate::{
-    config::ConsensusProposerType::{FixedProposer, RotatingProposer},
+    config::{
+        ConsensusProposerType::{FixedProposer, RotatingProposer, VrfProposer},
+        ConsensusSignatureScheme::Ed25519,
+    },
     seed_peers::{SeedPeersConfig, SeedPeersConfigHelpers},
     trusted_peers::{
-        deserialize_key, serialize_key, TrustedPeerPrivateKeys, TrustedPeersConfig,
-        TrustedPeersConfigHelpers,
+        deserialize_key, deserialize_opt_key, serialize_key, serialize_opt_key,
+        TrustedPeerPrivateKeys, TrustedPeersConfig, TrustedPeersConfigHelpers,
     },
     utils::get_available_port,
 };
@@ -115,12 +120,25 @@ pub struct KeyPairs {
     #[serde(deserialize_with = "deserialize_key")]
     network_identity_public_key: X25519PublicKey,
 
-    #[serde(serialize_with = "serialize_key")]
-    #[serde(deserialize_with = "deserialize_key")]
-    consensus_private_key: signing::PrivateKey,
+    // Left out when consensus signs through the secret service, see
+    // SecretServiceConfig::consensus_key_id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_private_key: Option<signing::PrivateKey>,
     #[serde(serialize_with = "serialize_key")]
     #[serde(deserialize_with = "deserialize_key")]
     consensus_public_key: signing::PublicKey,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_bls_private_key: Option<Arc<BLS12381PrivateKey>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_vrf_private_key: Option<Arc<VRFPrivateKey>>,
 }
 
 // required for serialization
@@ -133,8 +151,10 @@ impl Default for KeyPairs {
             network_signing_public_key: public_sig,
             network_identity_private_key: private_kex.clone(),
             network_identity_public_key: public_kex,
-            consensus_private_key: private_sig.clone(),
+            consensus_private_key: Some(private_sig.clone()),
             consensus_public_key: public_sig,
+            consensus_bls_private_key: None,
+            consensus_vrf_private_key: None,
         }
     }
 }
@@ -170,13 +190,17 @@ impl KeyPairs {
         let network_identity_public_key = (&network_identity_private_key).into();
         let consensus_private_key = private_keys.get_consensus_private();
         let consensus_public_key = (&consensus_private_key).into();
+        let consensus_bls_private_key = private_keys.get_consensus_bls_private();
+        let consensus_vrf_private_key = private_keys.get_consensus_vrf_private();
         Self {
             network_signing_private_key,
             network_signing_public_key,
             network_identity_private_key,
             network_identity_public_key,
-            consensus_private_key,
+            consensus_private_key: Some(consensus_private_key),
             consensus_public_key,
+            consensus_bls_private_key,
+            consensus_vrf_private_key,
         }
     }
     // getters for private keys
@@ -186,9 +210,15 @@ impl KeyPairs {
     pub fn get_network_identity_private(&self) -> X25519PrivateKey {
         self.network_identity_private_key.clone()
     }
-    pub fn get_consensus_private(&self) -> signing::PrivateKey {
+    pub fn get_consensus_private(&self) -> Option<signing::PrivateKey> {
         self.consensus_private_key.clone()
     }
+    pub fn get_consensus_bls_private(&self) -> Option<Arc<BLS12381PrivateKey>> {
+        self.consensus_bls_private_key.clone()
+    }
+    pub fn get_consensus_vrf_private(&self) -> Option<Arc<VRFPrivateKey>> {
+        self.consensus_vrf_private_key.clone()
+    }
     // getters for public keys
     pub fn get_network_signing_public(&self) -> signing::PublicKey {
         self.network_signing_public_key
@@ -212,8 +242,9 @@ impl KeyPairs {
             self.get_network_identity_public(),
         )
     }
-    pub fn get_consensus_keypair(&self) -> (signing::PrivateKey, signing::PublicKey) {
-        (self.get_consensus_private(), self.get_consensus_public())
+    pub fn get_consensus_keypair(&self) -> Option<(signing::PrivateKey, signing::PublicKey)> {
+        self.get_consensus_private()
+            .map(|private_key| (private_key, self.get_consensus_public()))
     }
 }
 
@@ -291,6 +322,36 @@ impl LoggerConfig {
 pub struct SecretServiceConfig {
     pub address: String,
     pub secret_service_port: u16,
+    // Directory the keys are persisted in, encrypted. The keys are kept in memory only when unset.
+    pub key_storage_dir: Option<PathBuf>,
+    // File holding the operator passphrase the key storage is encrypted with.
+    pub passphrase_file: Option<PathBuf>,
+    // File every signing request is recorded in.
+    pub audit_log_file: Option<PathBuf>,
+    // Hex encoded id of the consensus key in the secret service. When set, consensus signs through
+    // the secret service and the peer keypairs must not hold a consensus private key.
+    pub consensus_key_id: Option<String>,
+    // Hex encoded id of the consensus BLS12-381 key in the secret service. When set, the votes are
+    // signed for aggregation through the secret service and the peer keypairs must not hold a
+    // consensus BLS private key.
+    pub consensus_bls_key_id: Option<String>,
+    // Time a remote signing request may take, 1 second when unset.
+    pub signing_timeout_ms: Option<u64>,
+}
+
+impl SecretServiceConfig {
+    // Paths are either absolute or relative to the config location
+    fn resolve_paths(&mut self, config_path: &Path) {
+        for path in vec![
+            &mut self.key_storage_dir,
+            &mut self.passphrase_file,
+            &mut self.audit_log_file,
+        ] {
+            if let Some(path) = path {
+                *path = config_path.with_file_name(path.as_path());
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -345,14 +406,28 @@ pub struct ConsensusConfig {
     contiguous_rounds: u32,
     max_pruned_blocks_in_mem: Option<u64>,
     pacemaker_initial_timeout_ms: Option<u64>,
+    signature_scheme: Option<ConsensusSignatureScheme>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ConsensusProposerType {
     // Choose the smallest PeerId as the proposer
     FixedProposer,
     // Round robin rotation of proposers
     RotatingProposer,
+    // Proposer drawn from the VRF output of the block certified by the QC of the round
+    VrfProposer,
+}
+
+// Parsed along with the config, so that an unknown scheme is a config error
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ConsensusSignatureScheme {
+    // Quorum certificates carry the Ed25519 signature of every voter
+    #[serde(rename = "ed25519")]
+    Ed25519,
+    // Votes carry BLS12-381 signatures as well, quorum certificates carry their aggregate
+    #[serde(rename = "bls12381")]
+    BLS12381,
 }
 
 impl ConsensusConfig {
@@ -360,10 +435,15 @@ impl ConsensusConfig {
         match self.proposer_type.as_str() {
             "fixed_proposer" => FixedProposer,
             "rotating_proposer" => RotatingProposer,
+            "vrf_proposer" => VrfProposer,
             &_ => unimplemented!("Invalid proposer type: {}", self.proposer_type),
         }
     }
 
+    pub fn get_signature_scheme(&self) -> ConsensusSignatureScheme {
+        self.signature_scheme.unwrap_or(Ed25519)
+    }
+
     pub fn contiguous_rounds(&self) -> u32 {
         self.contiguous_rounds
     }
@@ -448,10 +528,59 @@ impl NodeConfig {
             config.network.listen_address =
                 get_local_ip().ok_or_else(|| ::failure::err_msg("No local IP"))?;
         }
+        config.secret_service.resolve_paths(path.as_ref());
+        config.check_consensus_keys()?;
         NodeConfigHelpers::update_data_dir_path_if_needed(&mut config, &path)?;
         Ok(config)
     }
 
+    /// Checks that every consensus private key is held either by the secret service or by the
+    /// peer keypairs, but not by both. The VRF proposer election is refused with the secret
+    /// service, which can not prove VRF outputs.
+    pub fn check_consensus_keys(&self) -> Result<()> {
+        match (
+            &self.secret_service.consensus_key_id,
+            self.base.peer_keypairs.consensus_private_key.is_some(),
+        ) {
+            (Some(_), true) => bail!(
+                "The consensus private key is held by the secret service (consensus_key_id) and \
+                 must not be in the peer keypairs as well"
+            ),
+            (None, false) => bail!(
+                "Neither a secret service consensus_key_id nor a consensus private key in the \
+                 peer keypairs is configured"
+            ),
+            _ => (),
+        }
+        if self.secret_service.consensus_bls_key_id.is_some()
+            && self.base.peer_keypairs.consensus_bls_private_key.is_some()
+        {
+            bail!(
+                "The consensus BLS private key is held by the secret service \
+                 (consensus_bls_key_id) and must not be in the peer keypairs as well"
+            );
+        }
+        if self.consensus.get_signature_scheme() == ConsensusSignatureScheme::BLS12381
+            && self.secret_service.consensus_bls_key_id.is_none()
+            && self.base.peer_keypairs.consensus_bls_private_key.is_none()
+        {
+            bail!(
+                "The BLS12-381 signature scheme requires a secret service consensus_bls_key_id or \
+                 a consensus BLS private key in the peer keypairs"
+            );
+        }
+        if self.secret_service.consensus_key_id.is_some()
+            && self.consensus.get_proposer_type() == VrfProposer
+        {
+            bail!(
+                "The VRF proposer election can not be used with a secret service consensus_key_id: \
+                 the secret service holds no VRF keys, so the VRF private key would have to stay \
+                 in the peer keypairs"
+            );
+        }
+        Ok(())
+    }
+
     pub fn save_config<P: AsRef<Path>>(&self, output_file: P) {
         let contents = toml::to_vec(&self).expect("Error serializing");
         let mut file = File::create(output_file).expect("Error opening file");
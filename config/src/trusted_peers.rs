@@ -6,6 +6,11 @@ use crypto::{
     utils::{encode_to_string, from_encoded_string},
     x25519::{self, X25519PrivateKey, X25519PublicKey},
 };
+use nextgen_crypto::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+    traits::Uniform,
+    vrf::ecvrf::{VRFPrivateKey, VRFPublicKey},
+};
 use rand::{rngs::StdRng, SeedableRng};
 use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
@@ -14,6 +19,7 @@ use std::{
     fs::File,
     io::{Read, Write},
     path::Path,
+    sync::Arc,
 };
 use types::account_address::AccountAddress;
 
@@ -32,12 +38,26 @@ pub struct TrustedPeer {
     #[serde(serialize_with = "serialize_key")]
     #[serde(deserialize_with = "deserialize_key")]
     consensus_pubkey: signing::PublicKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_bls_pubkey: Option<BLS12381PublicKey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_bls_proof_of_possession: Option<BLS12381Signature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    consensus_vrf_pubkey: Option<VRFPublicKey>,
 }
 
 pub struct TrustedPeerPrivateKeys {
     network_signing_private_key: signing::PrivateKey,
     network_identity_private_key: X25519PrivateKey,
     consensus_private_key: signing::PrivateKey,
+    consensus_bls_private_key: Option<Arc<BLS12381PrivateKey>>,
+    consensus_vrf_private_key: Option<Arc<VRFPrivateKey>>,
 }
 
 impl TrustedPeerPrivateKeys {
@@ -50,6 +70,12 @@ impl TrustedPeerPrivateKeys {
     pub fn get_consensus_private(&self) -> signing::PrivateKey {
         self.consensus_private_key.clone()
     }
+    pub fn get_consensus_bls_private(&self) -> Option<Arc<BLS12381PrivateKey>> {
+        self.consensus_bls_private_key.clone()
+    }
+    pub fn get_consensus_vrf_private(&self) -> Option<Arc<VRFPrivateKey>> {
+        self.consensus_vrf_private_key.clone()
+    }
 }
 
 impl TrustedPeer {
@@ -62,6 +88,25 @@ impl TrustedPeer {
     pub fn get_consensus_public(&self) -> signing::PublicKey {
         self.consensus_pubkey
     }
+    /// The consensus BLS12-381 public key along with the proof of possession of its private key,
+    /// which has to be checked before the key is aggregated. Panics if the proof is missing.
+    pub fn get_consensus_bls_public(&self) -> Option<(BLS12381PublicKey, BLS12381Signature)> {
+        self.consensus_bls_pubkey.clone().map(|public_key| {
+            let proof_of_possession = self
+                .consensus_bls_proof_of_possession
+                .clone()
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Missing proof of possession for the BLS public key {}",
+                        public_key
+                    )
+                });
+            (public_key, proof_of_possession)
+        })
+    }
+    pub fn get_consensus_vrf_public(&self) -> Option<VRFPublicKey> {
+        self.consensus_vrf_pubkey.clone()
+    }
 }
 
 pub fn serialize_key<S, K>(key: &K, serializer: S) -> Result<S::Ok, S::Error>
@@ -82,6 +127,25 @@ where
     Ok(from_encoded_string(encoded_key))
 }
 
+pub fn serialize_opt_key<S, K>(key: &Option<K>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Serialize,
+{
+    match key {
+        Some(key) => serialize_key(key, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_opt_key<'de, D, K>(deserializer: D) -> Result<Option<K>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: DeserializeOwned + 'static,
+{
+    deserialize_key(deserializer).map(Some)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TrustedPeersConfig {
     pub peers: HashMap<String, TrustedPeer>,
@@ -138,6 +202,42 @@ impl TrustedPeersConfig {
         res
     }
 
+    /// Returns a map of AccountAddress to its BLS12-381 PublicKey for consensus and the proof of
+    /// possession of the key, for the peers that have one.
+    pub fn get_trusted_consensus_bls_peers(
+        &self,
+    ) -> HashMap<AccountAddress, (BLS12381PublicKey, BLS12381Signature)> {
+        self.peers
+            .iter()
+            .filter_map(|(account, keys)| {
+                keys.get_consensus_bls_public().map(|public_key| {
+                    (
+                        AccountAddress::try_from(account.clone())
+                            .expect("Failed to parse account addr"),
+                        public_key,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a map of AccountAddress to its ECVRF PublicKey for the proposer election, for the
+    /// peers that have one.
+    pub fn get_trusted_consensus_vrf_peers(&self) -> HashMap<AccountAddress, VRFPublicKey> {
+        self.peers
+            .iter()
+            .filter_map(|(account, keys)| {
+                keys.consensus_vrf_pubkey.clone().map(|public_key| {
+                    (
+                        AccountAddress::try_from(account.clone())
+                            .expect("Failed to parse account addr"),
+                        public_key,
+                    )
+                })
+            })
+            .collect()
+    }
+
     /// Returns a map of AccountAddress to a pair of PublicKeys for network peering. The first
     /// PublicKey is the one used for signing, whereas the second is to determine eligible members
     /// of the network.
@@ -188,6 +288,7 @@ impl TrustedPeersConfigHelpers {
         };
 
         let mut fast_rng = StdRng::from_seed(seed);
+        let mut peer_ids = vec![];
         for _ in 0..number_of_peers {
             let (private0, public0) = signing::generate_keypair_for_testing(&mut fast_rng);
             let (private1, public1) = x25519::generate_keypair_for_testing(&mut fast_rng);
@@ -197,6 +298,9 @@ impl TrustedPeersConfigHelpers {
                 network_signing_pubkey: public0,
                 network_identity_pubkey: public1,
                 consensus_pubkey: public2,
+                consensus_bls_pubkey: None,
+                consensus_bls_proof_of_possession: None,
+                consensus_vrf_pubkey: None,
             };
             let peer_id = AccountAddress::from(peer.consensus_pubkey);
             peers.insert(peer_id.to_string(), peer);
@@ -205,8 +309,32 @@ impl TrustedPeersConfigHelpers {
                 network_signing_private_key: private0,
                 network_identity_private_key: private1,
                 consensus_private_key: private2,
+                consensus_bls_private_key: None,
+                consensus_vrf_private_key: None,
             };
             peers_private_keys.insert(peer_id.to_string(), private_keys);
+            peer_ids.push(peer_id.to_string());
+        }
+        // The BLS keys are drawn last so that the other keys of a seed stay the same.
+        for peer_id in &peer_ids {
+            let private_key = BLS12381PrivateKey::generate_for_testing(&mut fast_rng);
+            if let Some(peer) = peers.get_mut(peer_id) {
+                peer.consensus_bls_pubkey = Some((&private_key).into());
+                peer.consensus_bls_proof_of_possession = Some(private_key.proof_of_possession());
+            }
+            if let Some(private_keys) = peers_private_keys.get_mut(peer_id) {
+                private_keys.consensus_bls_private_key = Some(Arc::new(private_key));
+            }
+        }
+        // The VRF keys come after the BLS keys, for the same reason.
+        for peer_id in &peer_ids {
+            let private_key = VRFPrivateKey::generate_for_testing(&mut fast_rng);
+            if let Some(peer) = peers.get_mut(peer_id) {
+                peer.consensus_vrf_pubkey = Some((&private_key).into());
+            }
+            if let Some(private_keys) = peers_private_keys.get_mut(peer_id) {
+                private_keys.consensus_vrf_private_key = Some(Arc::new(private_key));
+            }
         }
         (peers_private_keys, TrustedPeersConfig { peers })
     }
@@ -26,3 +26,91 @@ fn verify_all_configs() {
         }
     }
 }
+
+#[test]
+fn verify_signature_scheme() {
+    let template = std::str::from_utf8(CONFIG_TEMPLATE).unwrap();
+    let config = NodeConfig::parse(template).unwrap();
+    assert_eq!(config.consensus.get_signature_scheme(), Ed25519);
+
+    let with_scheme = |scheme: &str| {
+        NodeConfig::parse(&template.replace(
+            "# signature_scheme = 'bls12381'",
+            &format!("signature_scheme = '{}'", scheme),
+        ))
+    };
+    assert_eq!(
+        with_scheme("bls12381")
+            .unwrap()
+            .consensus
+            .get_signature_scheme(),
+        ConsensusSignatureScheme::BLS12381
+    );
+    assert!(with_scheme("bls").is_err());
+}
+
+#[test]
+fn verify_consensus_keys() {
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false);
+    assert!(config.check_consensus_keys().is_ok());
+
+    // The consensus private key can not be both in the secret service and in the peer keypairs.
+    config.secret_service.consensus_key_id = Some(hex::encode(&[1u8; 32]));
+    assert!(config.check_consensus_keys().is_err());
+
+    config.base.peer_keypairs.consensus_private_key = None;
+    assert!(config.check_consensus_keys().is_ok());
+
+    config.secret_service.consensus_key_id = None;
+    assert!(config.check_consensus_keys().is_err());
+}
+
+#[test]
+fn verify_consensus_bls_keys() {
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false);
+    config.consensus.signature_scheme = Some(ConsensusSignatureScheme::BLS12381);
+    let bls_private_key = config.base.peer_keypairs.consensus_bls_private_key.clone();
+    assert!(bls_private_key.is_some());
+    assert!(config.check_consensus_keys().is_ok());
+
+    // The consensus BLS private key can not be both in the secret service and in the peer keypairs.
+    config.secret_service.consensus_bls_key_id = Some(hex::encode(&[2u8; 32]));
+    assert!(config.check_consensus_keys().is_err());
+
+    config.base.peer_keypairs.consensus_bls_private_key = None;
+    assert!(config.check_consensus_keys().is_ok());
+
+    config.secret_service.consensus_bls_key_id = None;
+    assert!(config.check_consensus_keys().is_err());
+
+    // Without BLS12-381 signatures no BLS key is needed.
+    config.consensus.signature_scheme = None;
+    assert!(config.check_consensus_keys().is_ok());
+}
+
+#[test]
+fn verify_vrf_proposer_without_secret_service() {
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false);
+    config.consensus.proposer_type = "vrf_proposer".to_string();
+    assert!(config.check_consensus_keys().is_ok());
+
+    // The secret service can not prove VRF outputs.
+    config.secret_service.consensus_key_id = Some(hex::encode(&[1u8; 32]));
+    config.base.peer_keypairs.consensus_private_key = None;
+    assert!(config.check_consensus_keys().is_err());
+
+    config.consensus.proposer_type = "rotating_proposer".to_string();
+    assert!(config.check_consensus_keys().is_ok());
+}
+
+#[test]
+fn verify_keypairs_without_consensus_private_key() {
+    let mut keypairs = KeyPairs::default();
+    keypairs.consensus_private_key = None;
+    let parsed = KeyPairs::parse(&toml::to_string(&keypairs).unwrap());
+    assert!(parsed.get_consensus_private().is_none());
+    assert_eq!(
+        parsed.get_consensus_public(),
+        keypairs.get_consensus_public()
+    );
+}
@@ -1,9 +1,48 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::TrustedPeersConfigHelpers;
+use super::{TrustedPeersConfig, TrustedPeersConfigHelpers};
+use nextgen_crypto::{bls12381::BLS12381PublicKey, vrf::ecvrf::VRFPublicKey};
 
 #[test]
 fn generate_test_config() {
     let (_, _) = TrustedPeersConfigHelpers::get_test_config(10, None);
 }
+
+#[test]
+fn test_consensus_bls_keys() {
+    let (private_keys, config) = TrustedPeersConfigHelpers::get_test_config(3, None);
+    for (peer_id, keys) in &private_keys {
+        let private_key = keys.get_consensus_bls_private().unwrap();
+        let (public_key, proof_of_possession) = config
+            .get_public_keys(peer_id)
+            .get_consensus_bls_public()
+            .unwrap();
+        assert_eq!(public_key, BLS12381PublicKey::from(private_key.as_ref()));
+        assert!(public_key
+            .verify_proof_of_possession(&proof_of_possession)
+            .is_ok());
+    }
+
+    let parsed = TrustedPeersConfig::parse(&toml::to_string(&config).unwrap());
+    let bls_peers = parsed.get_trusted_consensus_bls_peers();
+    assert_eq!(bls_peers.len(), 3);
+    assert_eq!(bls_peers, config.get_trusted_consensus_bls_peers());
+}
+
+#[test]
+fn test_consensus_vrf_keys() {
+    let (private_keys, config) = TrustedPeersConfigHelpers::get_test_config(3, None);
+    for (peer_id, keys) in &private_keys {
+        let private_key = keys.get_consensus_vrf_private().unwrap();
+        assert_eq!(
+            config.get_public_keys(peer_id).get_consensus_vrf_public(),
+            Some(VRFPublicKey::from(private_key.as_ref()))
+        );
+    }
+
+    let parsed = TrustedPeersConfig::parse(&toml::to_string(&config).unwrap());
+    let vrf_peers = parsed.get_trusted_consensus_vrf_peers();
+    assert_eq!(vrf_peers.len(), 3);
+    assert_eq!(vrf_peers, config.get_trusted_consensus_vrf_peers());
+}
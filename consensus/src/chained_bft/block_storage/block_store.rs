@@ -23,7 +23,10 @@ use std::{
     collections::{vec_deque::VecDeque, HashMap},
     sync::{Arc, RwLock},
 };
-use types::{ledger_info::LedgerInfo, validator_signer::ValidatorSigner};
+use types::{
+    ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
 
 #[cfg(test)]
 #[path = "block_store_test.rs"]
@@ -62,6 +65,9 @@ pub struct BlockStore<T> {
     /// The persistent storage backing up the in-memory data structure, every write should go
     /// through this before in-memory tree.
     storage: Arc<dyn PersistentStorage<T>>,
+    /// Set when the validators sign their votes with BLS12-381 keys: the votes of a quorum are
+    /// then aggregated into a single signature by this verifier.
+    bls_verifier: Option<Arc<ValidatorVerifier>>,
 }
 
 impl<T: Payload> BlockStore<T> {
@@ -72,6 +78,7 @@ impl<T: Payload> BlockStore<T> {
         state_computer: Arc<dyn StateComputer<Payload = T>>,
         enforce_increasing_timestamps: bool,
         max_pruned_blocks_in_mem: usize,
+        bls_verifier: Option<Arc<ValidatorVerifier>>,
     ) -> Self {
         let (root, blocks, quorum_certs) = initial_data.take();
         let inner = Arc::new(RwLock::new(
@@ -90,6 +97,7 @@ impl<T: Payload> BlockStore<T> {
             state_computer,
             enforce_increasing_timestamps,
             storage,
+            bls_verifier,
         }
     }
 
@@ -107,7 +115,12 @@ impl<T: Payload> BlockStore<T> {
             .collect::<HashMap<_, _>>();
         for block in blocks {
             let compute_res = state_computer
-                .compute(block.parent_id(), block.id(), block.get_payload())
+                .compute(
+                    block.parent_id(),
+                    block.id(),
+                    block.metadata(),
+                    block.get_payload(),
+                )
                 .await
                 .expect("fail to rebuild scratchpad");
             let version = tree
@@ -191,7 +204,7 @@ impl<T: Payload> BlockStore<T> {
         };
         let compute_res = self
             .state_computer
-            .compute(parent_id, block.id(), block.get_payload())
+            .compute(parent_id, block.id(), block.metadata(), block.get_payload())
             .await
             .map_err(|e| {
                 error!("Execution failure for block {}: {:?}", block, e);
@@ -287,10 +300,11 @@ impl<T: Payload> BlockStore<T> {
         vote_msg: VoteMsg,
         min_votes_for_qc: usize,
     ) -> VoteReceptionResult {
-        self.inner
-            .write()
-            .unwrap()
-            .insert_vote(&vote_msg, min_votes_for_qc)
+        self.inner.write().unwrap().insert_vote(
+            &vote_msg,
+            min_votes_for_qc,
+            self.bls_verifier.as_ref().map(Arc::as_ref),
+        )
     }
 
     /// Prune the tree up to next_root_id (keep next_root_id's block).  Any branches not part of
@@ -458,7 +472,7 @@ impl<T: Payload> BlockReader for BlockStore<T> {
         payload: Self::Payload,
         round: Round,
         timestamp_usecs: u64,
-    ) -> Block<Self::Payload> {
+    ) -> failure::Result<Block<Self::Payload>> {
         if self.enforce_increasing_timestamps {
             checked_precondition!(parent.timestamp_usecs() < timestamp_usecs);
         }
@@ -10,15 +10,20 @@ use crate::chained_bft::{
     },
     safety::vote_msg::VoteMsg,
     test_utils::{
-        build_empty_tree, build_empty_tree_with_custom_signing, placeholder_certificate_for_block,
-        placeholder_ledger_info, TreeInserter,
+        build_empty_tree, build_empty_tree_with_bls_verifier, build_empty_tree_with_custom_signing,
+        placeholder_certificate_for_block, placeholder_ledger_info, TreeInserter,
     },
 };
 use crypto::HashValue;
 use futures::executor::block_on;
+use nextgen_crypto::{bls12381::BLS12381PrivateKey, traits::Uniform};
 use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{cmp::min, collections::HashSet, sync::Arc};
-use types::{account_address::AccountAddress, validator_signer::ValidatorSigner};
+use types::{
+    account_address::AccountAddress, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
 
 fn build_simple_tree() -> (Vec<Arc<Block<Vec<usize>>>>, Arc<BlockStore<Vec<usize>>>) {
     let block_store = build_empty_tree();
@@ -52,7 +57,9 @@ fn build_simple_tree() -> (Vec<Arc<Block<Vec<usize>>>>, Arc<BlockStore<Vec<usize
 fn test_block_store_create_block() {
     let block_store = build_empty_tree();
     let genesis = block_store.root();
-    let a1 = block_store.create_block(Arc::clone(&genesis), vec![1], 1, 1);
+    let a1 = block_store
+        .create_block(Arc::clone(&genesis), vec![1], 1, 1)
+        .unwrap();
     assert_eq!(a1.parent_id(), genesis.id());
     assert_eq!(a1.round(), 1);
     assert_eq!(a1.height(), 1);
@@ -68,10 +75,13 @@ fn test_block_store_create_block() {
         block_store.signer().author(),
         placeholder_ledger_info(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(block_store.insert_vote_and_qc(vote_msg, 1));
 
-    let b1 = block_store.create_block(Arc::clone(&a1_ref), vec![2], 2, 2);
+    let b1 = block_store
+        .create_block(Arc::clone(&a1_ref), vec![2], 2, 2)
+        .unwrap();
     assert_eq!(b1.parent_id(), a1_ref.id());
     assert_eq!(b1.round(), 2);
     assert_eq!(b1.height(), 2);
@@ -87,7 +97,7 @@ fn test_highest_block_and_quorum_cert() {
     );
     assert_eq!(
         block_store.highest_quorum_cert().as_ref(),
-        &QuorumCert::certificate_for_genesis()
+        &QuorumCert::certificate_for_genesis().unwrap()
     );
 
     let genesis = block_store.root();
@@ -101,7 +111,7 @@ fn test_highest_block_and_quorum_cert() {
     );
     assert_eq!(
         block_store.highest_quorum_cert().as_ref(),
-        &QuorumCert::certificate_for_genesis()
+        &QuorumCert::certificate_for_genesis().unwrap()
     );
 
     // block_round_1 block and quorum certificate is now the highest
@@ -340,7 +350,8 @@ fn test_insert_vote() {
             voter.author(),
             placeholder_ledger_info(),
             voter,
-        );
+        )
+        .unwrap();
         let vote_res = block_on(block_store.insert_vote_and_qc(vote_msg.clone(), qc_size));
 
         // first vote of an author is accepted
@@ -363,7 +374,8 @@ fn test_insert_vote() {
         final_voter.author(),
         placeholder_ledger_info(),
         final_voter,
-    );
+    )
+    .unwrap();
     match block_on(block_store.insert_vote_and_qc(vote_msg, qc_size)) {
         VoteReceptionResult::NewQuorumCertificate(qc) => {
             assert_eq!(qc.certified_block_id(), block.id());
@@ -377,6 +389,82 @@ fn test_insert_vote() {
     assert_eq!(block_qc.certified_block_id(), block.id());
 }
 
+#[test]
+fn test_insert_bls_votes() {
+    // 4 validators signing with BLS12-381 keys, the quorum size is 3.
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let signers: Vec<ValidatorSigner> = (0..4)
+        .map(|_| {
+            ValidatorSigner::random()
+                .with_bls_private_key(Arc::new(BLS12381PrivateKey::generate_for_testing(&mut rng)))
+        })
+        .collect();
+    let validator = ValidatorVerifier::new(
+        signers
+            .iter()
+            .map(|signer| (signer.author(), signer.public_key()))
+            .collect(),
+    )
+    .with_bls_public_keys(
+        signers
+            .iter()
+            .map(|signer| {
+                (
+                    signer.author(),
+                    (
+                        signer.bls_public_key().unwrap(),
+                        signer.bls_proof_of_possession().unwrap(),
+                    ),
+                )
+            })
+            .collect(),
+    )
+    .unwrap();
+    let qc_size = validator.quorum_size();
+    let block_store =
+        build_empty_tree_with_bls_verifier(signers[0].clone(), Some(Arc::new(validator.clone())));
+    let genesis = block_store.root();
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let block = inserter.insert_block(genesis.as_ref(), 1);
+    let vote = |voter: &ValidatorSigner| {
+        VoteMsg::new(
+            block.id(),
+            block_store.get_state_for_block(block.id()).unwrap(),
+            block.round(),
+            voter.author(),
+            placeholder_ledger_info(),
+            voter,
+        )
+        .unwrap()
+    };
+
+    // A vote without a BLS signature is rejected.
+    let ed25519_voter = ValidatorSigner::random();
+    assert_eq!(
+        block_on(block_store.insert_vote_and_qc(vote(&ed25519_voter), qc_size)),
+        VoteReceptionResult::MissingBLSSignature,
+    );
+
+    for (i, voter) in signers.iter().enumerate().take(qc_size - 1) {
+        assert_eq!(
+            block_on(block_store.insert_vote_and_qc(vote(voter), qc_size)),
+            VoteReceptionResult::VoteAdded(i + 1),
+        );
+    }
+    match block_on(block_store.insert_vote_and_qc(vote(&signers[qc_size - 1]), qc_size)) {
+        VoteReceptionResult::NewQuorumCertificate(qc) => {
+            let ledger_info = qc.ledger_info();
+            assert!(ledger_info.signatures().is_empty());
+            let aggregated_signature = ledger_info.aggregated_signature().unwrap();
+            assert_eq!(aggregated_signature.num_signers(), qc_size);
+            assert!(qc.verify(&validator).is_ok());
+        }
+        _ => {
+            panic!("QC not formed!");
+        }
+    }
+}
+
 #[test]
 fn test_illegal_timestamp() {
     let block_store = build_empty_tree();
@@ -388,9 +476,10 @@ fn test_illegal_timestamp() {
         1,
         // This timestamp is illegal, it is the same as genesis
         genesis.timestamp_usecs(),
-        QuorumCert::certificate_for_genesis(),
+        QuorumCert::certificate_for_genesis().unwrap(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     let result = block_on(block_store.execute_and_insert_block(block_with_illegal_timestamp));
     assert!(result.is_err());
     assert_eq!(result.err().unwrap(), InsertError::NonIncreasingTimestamp);
@@ -429,7 +518,7 @@ fn test_need_fetch_for_qc() {
         HashValue::zero(),
         a3.round() + 1,
     );
-    let too_old_qc = QuorumCert::certificate_for_genesis();
+    let too_old_qc = QuorumCert::certificate_for_genesis().unwrap();
     let can_insert_qc =
         placeholder_certificate_for_block(vec![block_tree.signer().clone()], a3.id(), a3.round());
     let duplicate_qc = block_tree.get_quorum_cert_for_block(a2.id()).unwrap();
@@ -482,7 +571,10 @@ fn test_need_sync_for_qc() {
         false,
     );
     assert_eq!(
-        block_tree.need_sync_for_quorum_cert(genesis.id(), &QuorumCert::certificate_for_genesis()),
+        block_tree.need_sync_for_quorum_cert(
+            genesis.id(),
+            &QuorumCert::certificate_for_genesis().unwrap()
+        ),
         false
     );
 }
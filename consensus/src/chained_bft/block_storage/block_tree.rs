@@ -4,6 +4,7 @@
 use crate::{
     chained_bft::{
         block_storage::{BlockTreeError, VoteReceptionResult},
+        common::Author,
         consensus_types::{block::Block, quorum_cert::QuorumCert},
         safety::vote_msg::VoteMsg,
     },
@@ -15,6 +16,7 @@ use canonical_serialization::CanonicalSerialize;
 use crypto::HashValue;
 use logger::prelude::*;
 use mirai_annotations::checked_verify_eq;
+use nextgen_crypto::bls12381::BLS12381Signature;
 use serde::Serialize;
 use std::{
     collections::{
@@ -26,7 +28,14 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use types::ledger_info::LedgerInfoWithSignatures;
+use types::{ledger_info::LedgerInfoWithSignatures, validator_verifier::ValidatorVerifier};
+
+/// The votes collected for one vote digest.
+struct PendingVotes {
+    li_with_sig: LedgerInfoWithSignatures,
+    /// The BLS12-381 signatures of the votes, if they carry any.
+    bls_signatures: HashMap<Author, BLS12381Signature>,
+}
 
 /// This structure maintains a consistent block tree of parent and children links. Blocks contain
 /// parent links and are immutable.  For all parent links, a child link exists. This structure
@@ -60,8 +69,8 @@ pub struct BlockTree<T> {
     /// to be collected only for all the votes that have identical state id.
     /// The vote digest is a hash that covers both the proposal id and the state id.
     /// Thus, the structure of `id_to_votes` is as follows:
-    /// HashMap<proposed_block_id, HashMap<vote_digest, LedgerInfoWithSignatures>>
-    id_to_votes: HashMap<HashValue, HashMap<HashValue, LedgerInfoWithSignatures>>,
+    /// HashMap<proposed_block_id, HashMap<vote_digest, PendingVotes>>
+    id_to_votes: HashMap<HashValue, HashMap<HashValue, PendingVotes>>,
     /// Map of block id to its completed quorum certificate (2f + 1 votes)
     id_to_quorum_cert: HashMap<HashValue, Arc<QuorumCert>>,
     /// To keep the IDs of the elements that have been pruned from the tree but not cleaned up yet.
@@ -266,15 +275,21 @@ where
         Ok(())
     }
 
+    /// Adds a vote, see `BlockStore::insert_vote`. Given `bls_verifier`, the BLS12-381 signatures
+    /// of the votes are aggregated into the quorum certificate instead of the Ed25519 ones.
     pub(super) fn insert_vote(
         &mut self,
         vote_msg: &VoteMsg,
         min_votes_for_qc: usize,
+        bls_verifier: Option<&ValidatorVerifier>,
     ) -> VoteReceptionResult {
         let block_id = vote_msg.proposed_block_id();
         if let Some(old_qc) = self.id_to_quorum_cert.get(&block_id) {
             return VoteReceptionResult::OldQuorumCertificate(Arc::clone(old_qc));
         }
+        if bls_verifier.is_some() && vote_msg.bls_signature().is_none() {
+            return VoteReceptionResult::MissingBLSSignature;
+        }
 
         // All the votes collected for all the execution results of a given proposal.
         let block_votes = self
@@ -286,22 +301,45 @@ where
         // state id as well as the round number. In other words, if two different voters have the
         // same digest then they reached the same state following the same proposals.
         let digest = vote_msg.vote_hash();
-        let li_with_sig = block_votes.entry(digest).or_insert_with(|| {
-            LedgerInfoWithSignatures::new(vote_msg.ledger_info().clone(), HashMap::new())
+        let pending_votes = block_votes.entry(digest).or_insert_with(|| PendingVotes {
+            li_with_sig: LedgerInfoWithSignatures::new(
+                vote_msg.ledger_info().clone(),
+                HashMap::new(),
+            ),
+            bls_signatures: HashMap::new(),
         });
         let author = vote_msg.author();
-        if li_with_sig.signatures().contains_key(&author) {
+        if pending_votes.li_with_sig.signatures().contains_key(&author) {
             return VoteReceptionResult::DuplicateVote;
         }
-        li_with_sig.add_signature(author, vote_msg.signature().clone());
+        pending_votes
+            .li_with_sig
+            .add_signature(author, vote_msg.signature().clone());
+        if let Some(bls_signature) = vote_msg.bls_signature() {
+            pending_votes
+                .bls_signatures
+                .insert(author, bls_signature.clone());
+        }
 
-        let num_votes = li_with_sig.signatures().len();
+        let num_votes = pending_votes.li_with_sig.signatures().len();
         if num_votes >= min_votes_for_qc {
+            let li_with_sig = match bls_verifier {
+                Some(validator) => {
+                    match validator.aggregate_bls_signatures(&pending_votes.bls_signatures) {
+                        Ok(aggregated_signature) => LedgerInfoWithSignatures::new_aggregated(
+                            pending_votes.li_with_sig.ledger_info().clone(),
+                            aggregated_signature,
+                        ),
+                        Err(e) => return VoteReceptionResult::AggregationError(e),
+                    }
+                }
+                None => pending_votes.li_with_sig.clone(),
+            };
             let quorum_cert = QuorumCert::new(
                 block_id,
                 vote_msg.executed_state(),
                 vote_msg.round(),
-                li_with_sig.clone(),
+                li_with_sig,
             );
             // Note that the block might not be present locally, in which case we cannot calculate
             // time between block creation and qc
@@ -131,6 +131,10 @@ pub enum VoteReceptionResult {
     OldQuorumCertificate(Arc<QuorumCert>),
     /// This block has just been certified after adding the vote.
     NewQuorumCertificate(Arc<QuorumCert>),
+    /// The validators sign with BLS12-381 keys, but the vote has no BLS12-381 signature.
+    MissingBLSSignature,
+    /// The BLS12-381 signatures of the votes could not be aggregated into a quorum certificate.
+    AggregationError(VerifyError),
 }
 
 #[derive(Debug, Fail)]
@@ -193,6 +197,7 @@ pub trait BlockReader: Send + Sync {
     /// Note that it does not add the block to the tree, just generates it.
     /// The main reason we want this function in the BlockStore is the fact that the signer required
     /// for signing the newly created block is held by the block store.
+    /// The function fails if the signer can not sign the block.
     /// The function panics in the following cases:
     /// * If the parent or its quorum certificate are not present in the tree,
     /// * If the given round (which is typically calculated by Pacemaker) is not greater than that
@@ -203,7 +208,7 @@ pub trait BlockReader: Send + Sync {
         payload: Self::Payload,
         round: Round,
         timestamp_usecs: u64,
-    ) -> Block<Self::Payload>;
+    ) -> failure::Result<Block<Self::Payload>>;
 
     /// Return the certified block with the highest round.
     fn highest_certified_block(&self) -> Arc<Block<Self::Payload>>;
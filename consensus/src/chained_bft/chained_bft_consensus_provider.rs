@@ -16,11 +16,15 @@ use network::validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender}
 
 use crate::{
     chained_bft::{
-        chained_bft_smr::ChainedBftSMRConfig, common::Author, persistent_storage::StorageWriteProxy,
+        chained_bft_smr::ChainedBftSMRConfig, common::Author,
+        persistent_storage::StorageWriteProxy, secret_service_signer::SecretServiceSigner,
     },
     state_synchronizer::{setup_state_synchronizer, StateSynchronizer},
 };
-use config::config::{ConsensusProposerType::FixedProposer, NodeConfig};
+use config::config::{
+    ConsensusProposerType::{FixedProposer, VrfProposer},
+    ConsensusSignatureScheme, NodeConfig,
+};
 use execution_proto::proto::execution_grpc::ExecutionClient;
 use failure::prelude::*;
 use logger::prelude::*;
@@ -97,6 +101,7 @@ impl ChainedBftProvider {
             config,
             storage,
             initial_data,
+            Some(Arc::clone(&initial_setup.validator)).filter(|v| v.has_bls_public_keys()),
         );
         Self {
             smr,
@@ -114,17 +119,124 @@ impl ChainedBftProvider {
         let peer_id_str = node_config.base.peer_id.clone();
         let author =
             AccountAddress::try_from(peer_id_str).expect("Failed to parse peer id of a validator");
-        let private_key = node_config.base.peer_keypairs.get_consensus_private();
-        let public_key = node_config.base.peer_keypairs.get_consensus_public();
-        let signer = ValidatorSigner::new(author, public_key, private_key);
         let peers_with_public_keys = node_config.base.trusted_peers.get_trusted_consensus_peers();
+        let signer = match &node_config.secret_service.consensus_key_id {
+            Some(consensus_key_id) => {
+                let remote_signer =
+                    SecretServiceSigner::new(&node_config.secret_service, consensus_key_id)
+                        .expect("Failed to set up signing with the secret service");
+                let public_key = remote_signer
+                    .public_key()
+                    .expect("Failed to get the consensus public key from the secret service");
+                if let Some(trusted_public_key) = peers_with_public_keys.get(&author) {
+                    assert_eq!(
+                        &public_key, trusted_public_key,
+                        "The secret service consensus key is not the trusted one of this validator"
+                    );
+                }
+                info!(
+                    "[Consensus] Signing with the secret service key {}",
+                    consensus_key_id
+                );
+                ValidatorSigner::new_remote(author, public_key, Arc::new(remote_signer))
+            }
+            None => {
+                let private_key = node_config
+                    .base
+                    .peer_keypairs
+                    .get_consensus_private()
+                    .expect("Signing without the secret service requires a consensus private key");
+                let public_key = node_config.base.peer_keypairs.get_consensus_public();
+                ValidatorSigner::new(author, public_key, private_key)
+            }
+        };
         let peers = Arc::new(
             peers_with_public_keys
                 .keys()
                 .map(AccountAddress::clone)
                 .collect(),
         );
-        let validator = Arc::new(ValidatorVerifier::new(peers_with_public_keys));
+        let mut validator = ValidatorVerifier::new(peers_with_public_keys);
+        let signer = if node_config.consensus.get_signature_scheme()
+            == ConsensusSignatureScheme::BLS12381
+        {
+            let bls_peers = node_config
+                .base
+                .trusted_peers
+                .get_trusted_consensus_bls_peers();
+            let signer = match &node_config.secret_service.consensus_bls_key_id {
+                Some(consensus_bls_key_id) => {
+                    let remote_signer =
+                        SecretServiceSigner::new(&node_config.secret_service, consensus_bls_key_id)
+                            .expect("Failed to set up BLS signing with the secret service");
+                    let bls_public_key = remote_signer.bls_public_key().expect(
+                        "Failed to get the consensus BLS public key from the secret service",
+                    );
+                    info!(
+                        "[Consensus] Signing the votes with the secret service BLS key {}",
+                        consensus_bls_key_id
+                    );
+                    signer.with_remote_bls_signer(bls_public_key, Arc::new(remote_signer))
+                }
+                None => {
+                    let bls_private_key = node_config
+                        .base
+                        .peer_keypairs
+                        .get_consensus_bls_private()
+                        .expect(
+                            "Signing the votes without the secret service requires a consensus \
+                             BLS private key",
+                        );
+                    signer.with_bls_private_key(bls_private_key)
+                }
+            };
+            if let Some((trusted_public_key, _)) = bls_peers.get(&author) {
+                assert_eq!(
+                    signer.bls_public_key().as_ref(),
+                    Some(trusted_public_key),
+                    "The consensus BLS key is not the trusted one of this validator"
+                );
+            }
+            validator = validator.with_bls_public_keys(bls_peers).expect(
+                "Every trusted peer needs a consensus BLS public key and its proof of possession",
+            );
+            info!("[Consensus] Aggregating BLS12-381 vote signatures into the quorum certificates");
+            signer
+        } else {
+            signer
+        };
+        let signer = if node_config.consensus.get_proposer_type() == VrfProposer {
+            assert!(
+                node_config.secret_service.consensus_key_id.is_none(),
+                "The VRF proposer election can not be used with a secret service consensus key, \
+                 the secret service holds no VRF keys"
+            );
+            let vrf_peers = node_config
+                .base
+                .trusted_peers
+                .get_trusted_consensus_vrf_peers();
+            let vrf_private_key = node_config
+                .base
+                .peer_keypairs
+                .get_consensus_vrf_private()
+                .expect("The VRF proposer election requires a consensus VRF private key");
+            let signer = signer.with_vrf_private_key(vrf_private_key);
+            if let Some(trusted_public_key) = vrf_peers.get(&author) {
+                assert_eq!(
+                    signer.vrf_public_key().as_ref(),
+                    Some(trusted_public_key),
+                    "The consensus VRF private key is not the trusted one of this validator"
+                );
+            }
+            validator = validator
+                .with_vrf_public_keys(vrf_peers)
+                .expect("Every trusted peer needs a consensus VRF public key");
+            info!("[Consensus] Electing the proposers with their VRF outputs");
+            signer
+        } else {
+            signer
+        };
+        let validator = Arc::new(validator);
         counters::EPOCH_NUM.set(0); // No reconfiguration yet, so it is always zero
         counters::CURRENT_EPOCH_NUM_VALIDATORS.set(validator.len() as i64);
         counters::CURRENT_EPOCH_QUORUM_SIZE.set(validator.quorum_size() as i64);
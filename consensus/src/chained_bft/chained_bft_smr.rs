@@ -14,6 +14,7 @@ use crate::{
             proposer_election::{ProposalInfo, ProposerElection, ProposerInfo},
             rotating_proposer_election::RotatingProposer,
             timeout_msg::TimeoutMsg,
+            vrf_proposer_election::VrfProposer,
         },
         network::{
             BlockRetrievalRequest, ChunkRetrievalRequest, ConsensusNetworkImpl, NetworkReceivers,
@@ -34,9 +35,9 @@ use futures::{
     future::{FutureExt, TryFutureExt},
     stream::StreamExt,
 };
-use types::validator_signer::ValidatorSigner;
+use types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
 
-use config::config::ConsensusConfig;
+use config::config::{ConsensusConfig, ConsensusProposerType};
 use logger::prelude::*;
 use std::{
     sync::{Arc, RwLock},
@@ -57,6 +58,8 @@ pub struct ChainedBftSMRConfig {
     pub contiguous_rounds: u32,
     /// Max block size (number of transactions) that consensus pulls from mempool
     pub max_block_size: u64,
+    /// How the proposer of a round is elected
+    pub proposer_type: ConsensusProposerType,
 }
 
 impl ChainedBftSMRConfig {
@@ -67,6 +70,7 @@ impl ChainedBftSMRConfig {
             pacemaker_initial_timeout: Duration::from_millis(pacemaker_initial_timeout_ms),
             contiguous_rounds: cfg.contiguous_rounds(),
             max_block_size: cfg.max_block_size(),
+            proposer_type: cfg.get_proposer_type(),
         }
     }
 }
@@ -86,6 +90,7 @@ pub struct ChainedBftSMR<T, P> {
     config: ChainedBftSMRConfig,
     storage: Arc<dyn PersistentStorage<T>>,
     initial_data: Option<RecoveryData<T>>,
+    bls_verifier: Option<Arc<ValidatorVerifier>>,
 }
 
 #[allow(dead_code)]
@@ -100,6 +105,7 @@ impl<T: Payload, P: ProposerInfo> ChainedBftSMR<T, P> {
         config: ChainedBftSMRConfig,
         storage: Arc<dyn PersistentStorage<T>>,
         initial_data: RecoveryData<T>,
+        bls_verifier: Option<Arc<ValidatorVerifier>>,
     ) -> Self {
         Self {
             author,
@@ -112,6 +118,7 @@ impl<T: Payload, P: ProposerInfo> ChainedBftSMR<T, P> {
             config,
             storage,
             initial_data: Some(initial_data),
+            bls_verifier,
         }
     }
 
@@ -154,14 +161,22 @@ impl<T: Payload, P: ProposerInfo> ChainedBftSMR<T, P> {
     /// Create a proposer election handler based on proposers
     fn create_proposer_election(
         &self,
+        block_store: Arc<BlockStore<T>>,
         winning_proposals_sender: channel::Sender<ProposalInfo<T, P>>,
     ) -> Arc<dyn ProposerElection<T, P> + Send + Sync> {
         assert!(!self.proposers.is_empty());
-        Arc::new(RotatingProposer::new(
-            self.proposers.clone(),
-            self.config.contiguous_rounds,
-            winning_proposals_sender,
-        ))
+        match self.config.proposer_type {
+            ConsensusProposerType::VrfProposer => Arc::new(VrfProposer::new(
+                self.proposers.clone(),
+                block_store,
+                winning_proposals_sender,
+            )),
+            _ => Arc::new(RotatingProposer::new(
+                self.proposers.clone(),
+                self.config.contiguous_rounds,
+                winning_proposals_sender,
+            )),
+        }
     }
 
     async fn process_new_round_events(
@@ -444,6 +459,7 @@ impl<T: Payload, P: ProposerInfo> StateMachineReplication for ChainedBftSMR<T, P
             Arc::clone(&state_computer),
             true,
             self.config.max_pruned_blocks_in_mem,
+            self.bls_verifier.clone(),
         )));
         self.block_store = Some(Arc::clone(&block_store));
 
@@ -479,7 +495,8 @@ impl<T: Payload, P: ProposerInfo> StateMachineReplication for ChainedBftSMR<T, P
 
         let (winning_proposals_sender, winning_proposals_receiver) =
             channel::new(1_024, &counters::PENDING_WINNING_PROPOSALS);
-        let proposer_election = self.create_proposer_election(winning_proposals_sender);
+        let proposer_election =
+            self.create_proposer_election(Arc::clone(&block_store), winning_proposals_sender);
         let event_processor = Arc::new(futures_locks::RwLock::new(EventProcessor::new(
             self.author,
             Arc::clone(&block_store),
@@ -26,7 +26,9 @@ use crate::chained_bft::{
     persistent_storage::RecoveryData,
     test_utils::{consensus_runtime, with_smr_id},
 };
-use config::config::ConsensusProposerType::{self, FixedProposer, RotatingProposer};
+use config::config::ConsensusProposerType::{self, FixedProposer, RotatingProposer, VrfProposer};
+use nextgen_crypto::{traits::Uniform, vrf::ecvrf::VRFPrivateKey};
+use rand::{rngs::StdRng, SeedableRng};
 use std::{collections::HashMap, time::Duration};
 use tokio::runtime;
 use types::ledger_info::LedgerInfoWithSignatures;
@@ -38,6 +40,7 @@ struct SMRNode {
     validator: Arc<ValidatorVerifier>,
     peers: Arc<Vec<Author>>,
     proposer: Vec<Author>,
+    proposer_type: ConsensusProposerType,
     smr_id: usize,
     smr: ChainedBftSMR<TestPayload, Author>,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
@@ -54,6 +57,7 @@ impl SMRNode {
         validator: Arc<ValidatorVerifier>,
         peers: Arc<Vec<Author>>,
         proposer: Vec<Author>,
+        proposer_type: ConsensusProposerType,
         smr_id: usize,
         storage: Arc<MockStorage<TestPayload>>,
         initial_data: RecoveryData<TestPayload>,
@@ -83,6 +87,7 @@ impl SMRNode {
             pacemaker_initial_timeout: Duration::from_secs(3),
             contiguous_rounds: 2,
             max_block_size: 50,
+            proposer_type,
         };
         let mut smr = ChainedBftSMR::new(
             author,
@@ -94,6 +99,7 @@ impl SMRNode {
             config,
             storage.clone(),
             initial_data,
+            None,
         );
         let (commit_cb_sender, commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
         let mut mp = MockTransactionManager::new();
@@ -110,6 +116,7 @@ impl SMRNode {
             validator,
             peers,
             proposer,
+            proposer_type,
             smr_id,
             smr,
             commit_cb_receiver,
@@ -132,6 +139,7 @@ impl SMRNode {
             self.validator,
             self.peers,
             self.proposer,
+            self.proposer_type,
             self.smr_id + 10,
             self.storage,
             recover_data,
@@ -155,10 +163,29 @@ impl SMRNode {
             );
             signers.push(random_validator_signer);
         }
-        let validator_verifier = Arc::new(
+        let mut validator_verifier =
             ValidatorVerifier::new_with_quorum_size(author_to_public_keys, quorum_size)
-                .expect("Invalid quorum_size."),
-        );
+                .expect("Invalid quorum_size.");
+        if proposer_type == VrfProposer {
+            let mut rng = StdRng::from_seed([0u8; 32]);
+            signers = signers
+                .into_iter()
+                .map(|signer| {
+                    signer.with_vrf_private_key(Arc::new(VRFPrivateKey::generate_for_testing(
+                        &mut rng,
+                    )))
+                })
+                .collect();
+            validator_verifier = validator_verifier
+                .with_vrf_public_keys(
+                    signers
+                        .iter()
+                        .map(|signer| (signer.author(), signer.vrf_public_key().unwrap()))
+                        .collect(),
+                )
+                .expect("Missing VRF public key.");
+        }
+        let validator_verifier = Arc::new(validator_verifier);
         let peers: Arc<Vec<Author>> = Arc::new(
             signers
                 .clone()
@@ -169,7 +196,9 @@ impl SMRNode {
         let proposer = {
             match proposer_type {
                 FixedProposer => vec![peers[0]],
-                RotatingProposer => validator_verifier.get_ordered_account_addresses(),
+                RotatingProposer | VrfProposer => {
+                    validator_verifier.get_ordered_account_addresses()
+                }
             }
         };
         let mut nodes = vec![];
@@ -182,6 +211,7 @@ impl SMRNode {
                 Arc::clone(&validator_verifier),
                 Arc::clone(&peers),
                 proposer.clone(),
+                proposer_type,
                 smr_id,
                 storage,
                 initial_data,
@@ -291,6 +321,45 @@ fn basic_full_round() {
     });
 }
 
+#[test]
+/// With the VRF proposer election a single proposer, drawn from the VRF output of the certified
+/// block, proposes in each round and every block carries the VRF proof of its author.
+fn vrf_proposer_full_round() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.executor());
+    let _nodes = SMRNode::start_num_nodes(2, 2, &mut playground, VrfProposer);
+
+    block_on(async move {
+        let mut broadcast_proposals_1 = playground
+            .wait_for_messages(1, NetworkPlayground::proposals_only)
+            .await;
+        let proposal = ProposalInfo::<Vec<u64>, Author>::from_proto(
+            broadcast_proposals_1[0].1.take_proposal(),
+        )
+        .unwrap();
+        assert_eq!(proposal.proposal.round(), 1);
+        assert!(proposal.proposal.vrf_proof().is_some());
+        let mut votes_1 = playground
+            .wait_for_messages(1, NetworkPlayground::votes_only)
+            .await;
+        let vote = VoteMsg::from_proto(votes_1[0].1.take_vote()).unwrap();
+        assert_eq!(vote.proposed_block_id(), proposal.proposal.id());
+        let mut broadcast_proposals_2 = playground
+            .wait_for_messages(1, NetworkPlayground::proposals_only)
+            .await;
+        let next_proposal = ProposalInfo::<Vec<u64>, Author>::from_proto(
+            broadcast_proposals_2[0].1.take_proposal(),
+        )
+        .unwrap();
+        assert_eq!(next_proposal.proposal.round(), 2);
+        assert_eq!(
+            next_proposal.proposal.quorum_cert().certified_block_id(),
+            proposal.proposal.id()
+        );
+        assert!(next_proposal.proposal.vrf_proof().is_some());
+    });
+}
+
 /// Verify the basic e2e flow: blocks are committed, txn manager is notified, block tree is
 /// pruned, restart the node and we can still continue.
 #[test]
@@ -19,6 +19,7 @@ use crypto::{
 use failure::Result;
 use mirai_annotations::{checked_precondition, checked_precondition_eq};
 use network::proto::Block as ProtoBlock;
+use nextgen_crypto::vrf::ecvrf::Proof;
 use proto_conv::{FromProto, IntoProto};
 use rmp_serde::{from_slice, to_vec_named};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -28,6 +29,7 @@ use std::{
     fmt::{Display, Formatter},
 };
 use types::{
+    block_metadata::BlockMetadata,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
@@ -43,6 +45,8 @@ pub enum BlockVerificationError {
     QCVerificationError(VoteMsgVerificationError),
     /// The signature verification of this block failed.
     SigVerifyError,
+    /// The VRF proof of this block is missing or invalid.
+    VRFProofError,
 }
 
 /// Blocks are managed in a speculative tree, the committed blocks form a chain.
@@ -85,6 +89,9 @@ pub struct Block<T> {
     author: Author,
     /// Signature that the hash of this block has been authored by the owner of the private key
     signature: Signature,
+    /// VRF proof of the author over the quorum cert and the round, present when the proposers are
+    /// elected by VRF (see `VrfProposer`)
+    vrf_proof: Option<Vec<u8>>,
 }
 
 impl<T> Display for Block<T> {
@@ -139,6 +146,7 @@ where
             quorum_cert: genesis_quorum_cert,
             author: genesis_validator_signer.author(),
             signature,
+            vrf_proof: None,
         }
     }
 
@@ -152,7 +160,11 @@ where
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self> {
+        // Only signers with a VRF key (i.e. when the proposers are elected by VRF) prove.
+        let vrf_proof = validator_signer
+            .vrf_prove(&vrf_input(&quorum_cert, round))
+            .map(|proof| proof.to_bytes().to_vec());
         let block_internal = BlockSerializer {
             parent_id,
             payload: &payload,
@@ -161,14 +173,13 @@ where
             timestamp_usecs,
             quorum_cert: &quorum_cert,
             author: validator_signer.author(),
+            vrf_proof: vrf_proof.as_ref().map(Vec::as_slice),
         };
 
         let id = block_internal.hash();
-        let signature = validator_signer
-            .sign_message(id)
-            .expect("Failed to sign message");
+        let signature = validator_signer.sign_message(id)?;
 
-        Block {
+        Ok(Block {
             id,
             payload,
             parent_id,
@@ -178,7 +189,8 @@ where
             quorum_cert,
             author: validator_signer.author(),
             signature,
-        }
+            vrf_proof,
+        })
     }
 
     pub fn make_block(
@@ -188,7 +200,7 @@ where
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self> {
         // A block must carry a QC to its parent.
         checked_precondition_eq!(quorum_cert.certified_block_id(), parent_block.id());
         checked_precondition!(round > parent_block.round());
@@ -218,6 +230,23 @@ where
         validator
             .verify_signature(self.author(), self.hash(), self.signature())
             .map_err(|_| BlockVerificationError::SigVerifyError)?;
+        match &self.vrf_proof {
+            Some(vrf_proof) => {
+                let vrf_proof = Proof::try_from(vrf_proof.as_slice())
+                    .map_err(|_| BlockVerificationError::VRFProofError)?;
+                validator
+                    .verify_vrf_proof(
+                        self.author(),
+                        &vrf_input(&self.quorum_cert, self.round),
+                        &vrf_proof,
+                    )
+                    .map_err(|_| BlockVerificationError::VRFProofError)?;
+            }
+            None if validator.has_vrf_public_keys() => {
+                return Err(BlockVerificationError::VRFProofError);
+            }
+            None => (),
+        }
         self.quorum_cert
             .verify(validator)
             .map_err(BlockVerificationError::QCVerificationError)
@@ -243,6 +272,11 @@ where
         self.timestamp_usecs
     }
 
+    /// The metadata exposed to the transactions of this block when they are executed.
+    pub fn metadata(&self) -> BlockMetadata {
+        BlockMetadata::new(self.height, self.timestamp_usecs)
+    }
+
     pub fn quorum_cert(&self) -> &QuorumCert {
         &self.quorum_cert
     }
@@ -255,6 +289,14 @@ where
         &self.signature
     }
 
+    /// The VRF proof of the author over the quorum cert and the round, if the block has one.
+    /// Verified blocks only carry well-formed proofs.
+    pub fn vrf_proof(&self) -> Option<Proof> {
+        self.vrf_proof
+            .as_ref()
+            .and_then(|vrf_proof| Proof::try_from(vrf_proof.as_slice()).ok())
+    }
+
     pub fn is_genesis_block(&self) -> bool {
         self.id() == *GENESIS_BLOCK_ID
     }
@@ -275,6 +317,7 @@ where
             timestamp_usecs: self.timestamp_usecs,
             quorum_cert: &self.quorum_cert,
             author: self.author,
+            vrf_proof: self.vrf_proof.as_ref().map(Vec::as_slice),
         };
         block_internal.hash()
     }
@@ -290,6 +333,7 @@ struct BlockSerializer<'a, T> {
     timestamp_usecs: u64,
     quorum_cert: &'a QuorumCert,
     author: Author,
+    vrf_proof: Option<&'a [u8]>,
 }
 
 impl<'a, T> CryptoHash for BlockSerializer<'a, T>
@@ -320,6 +364,11 @@ where
             .encode_raw_bytes(self.parent_id.as_ref())?
             .encode_raw_bytes(self.quorum_cert.certified_block_id().as_ref())?
             .encode_struct(&self.author)?;
+        // Only the blocks proposed with a VRF key hash their proof, the ids of the other blocks
+        // are the same as before the proof was added.
+        if let Some(vrf_proof) = self.vrf_proof {
+            serializer.encode_variable_length_bytes(vrf_proof)?;
+        }
         Ok(())
     }
 }
@@ -356,6 +405,9 @@ where
         proto.set_quorum_cert(self.quorum_cert().clone().into_proto());
         proto.set_signature(self.signature().to_compact().as_ref().into());
         proto.set_author(self.author.into());
+        if let Some(vrf_proof) = self.vrf_proof {
+            proto.set_vrf_proof(vrf_proof.into());
+        }
         proto
     }
 }
@@ -376,6 +428,11 @@ where
         let quorum_cert = QuorumCert::from_proto(object.take_quorum_cert())?;
         let author = Author::try_from(object.take_author())?;
         let signature = Signature::from_compact(object.get_signature())?;
+        let vrf_proof = if object.get_vrf_proof().is_empty() {
+            None
+        } else {
+            Some(object.get_vrf_proof().to_vec())
+        };
         Ok(Block {
             id,
             parent_id,
@@ -386,6 +443,15 @@ where
             quorum_cert,
             author,
             signature,
+            vrf_proof,
         })
     }
 }
+
+/// The input of the VRF proof of a block: the block certified by its quorum cert, i.e. the QC the
+/// round starts from, and the round.
+fn vrf_input(quorum_cert: &QuorumCert, round: Round) -> Vec<u8> {
+    let mut input = quorum_cert.certified_block_id().to_vec();
+    input.extend_from_slice(&round.to_le_bytes());
+    input
+}
@@ -7,13 +7,24 @@ use crate::chained_bft::{
     test_utils::placeholder_certificate_for_block,
 };
 
-use crypto::{HashValue, PrivateKey, PublicKey};
+use canonical_serialization::{CanonicalSerializer, SimpleSerializer};
+use crypto::{
+    hash::{BlockHasher, CryptoHasher},
+    HashValue, PrivateKey, PublicKey,
+};
+use nextgen_crypto::{traits::Uniform, vrf::ecvrf::VRFPrivateKey};
 use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{
+    collections::HashMap,
     panic,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use types::validator_signer::{self, ValidatorSigner};
+use types::{
+    validator_signer::{self, ValidatorSigner},
+    validator_verifier::ValidatorVerifier,
+};
 
 type LinearizedBlockForest<T> = Vec<Block<T>>;
 
@@ -41,9 +52,9 @@ prop_compose! {
             round,
             height,
             get_current_timestamp().as_micros() as u64,
-            QuorumCert::certificate_for_genesis(),
+            QuorumCert::certificate_for_genesis().unwrap(),
             &signer,
-        )
+        ).unwrap()
     }
 }
 
@@ -90,6 +101,7 @@ prop_compose! {
                 quorum_cert: block.quorum_cert().clone(),
                 author: block.author(),
                 signature: *block.signature(),
+                vrf_proof: block.vrf_proof.clone(),
             }
         }
 }
@@ -200,7 +212,7 @@ fn test_block_relation() {
     let signer = ValidatorSigner::random();
     // Test genesis and the next block
     let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
     let payload = 101;
     let next_block = Block::make_block(
         &genesis_block,
@@ -209,7 +221,8 @@ fn test_block_relation() {
         get_current_timestamp().as_micros() as u64,
         quorum_cert,
         &signer,
-    );
+    )
+    .unwrap();
     assert_eq!(next_block.round(), 1);
     assert_eq!(next_block.height(), 1);
     assert_eq!(genesis_block.is_parent_of(&next_block), true);
@@ -229,7 +242,7 @@ fn test_block_qc() {
     let signer = ValidatorSigner::random();
     // Test genesis and the next block
     let genesis_block = Block::make_genesis_block();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
 
     let payload = 42;
     let a1 = Block::make_block(
@@ -239,7 +252,8 @@ fn test_block_qc() {
         get_current_timestamp().as_micros() as u64,
         genesis_qc.clone(),
         &signer,
-    );
+    )
+    .unwrap();
     let a1_qc = placeholder_certificate_for_block(vec![signer.clone()], a1.id(), a1.round());
 
     let result = panic::catch_unwind(|| {
@@ -251,7 +265,8 @@ fn test_block_qc() {
             get_current_timestamp().as_micros() as u64,
             genesis_qc.clone(),
             &signer,
-        );
+        )
+        .unwrap();
     });
     assert!(result.is_err());
 
@@ -263,10 +278,96 @@ fn test_block_qc() {
         get_current_timestamp().as_micros() as u64,
         a1_qc.clone(),
         &signer,
-    );
+    )
+    .unwrap();
     assert_eq!(a2.height(), 2);
 }
 
+#[test]
+fn test_block_vrf_proof() {
+    let signer = ValidatorSigner::random();
+    let vrf_private_key = VRFPrivateKey::generate_for_testing(&mut StdRng::from_seed([0u8; 32]));
+    let vrf_signer = signer
+        .clone()
+        .with_vrf_private_key(Arc::new(vrf_private_key));
+    let validator = ValidatorVerifier::new_single(signer.author(), signer.public_key());
+    let mut author_to_vrf_public_keys = HashMap::new();
+    author_to_vrf_public_keys.insert(signer.author(), vrf_signer.vrf_public_key().unwrap());
+    let vrf_validator = validator
+        .clone()
+        .with_vrf_public_keys(author_to_vrf_public_keys)
+        .unwrap();
+
+    let genesis_block = Block::make_genesis_block();
+    let genesis_qc = placeholder_certificate_for_block(
+        vec![signer.clone()],
+        genesis_block.id(),
+        genesis_block.round(),
+    );
+    let make_block = |signer: &ValidatorSigner| {
+        Block::make_block(
+            &genesis_block,
+            42,
+            1,
+            get_current_timestamp().as_micros() as u64,
+            genesis_qc.clone(),
+            signer,
+        )
+        .unwrap()
+    };
+
+    // The signers with a VRF key prove, the proofs are checked against the VRF keys of the
+    // authors and required once the authors have VRF keys.
+    let a1 = make_block(&vrf_signer);
+    assert!(a1.vrf_proof().is_some());
+    assert!(a1.verify(&vrf_validator).is_ok());
+    assert!(a1.verify(&validator).is_err());
+
+    let unproven_a1 = make_block(&signer);
+    assert!(unproven_a1.vrf_proof().is_none());
+    assert!(unproven_a1.verify(&validator).is_ok());
+    assert!(unproven_a1.verify(&vrf_validator).is_err());
+}
+
+#[test]
+fn test_block_id_without_vrf_proof() {
+    // The blocks without a VRF proof keep the ids they had before the proof was added, so that
+    // the persisted blocks and QCs still match their ids.
+    let signer = ValidatorSigner::random();
+    let genesis_block = Block::make_genesis_block();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
+    let block = Block::make_block(
+        &genesis_block,
+        vec![42],
+        1,
+        get_current_timestamp().as_micros() as u64,
+        genesis_qc.clone(),
+        &signer,
+    )
+    .unwrap();
+    assert!(block.vrf_proof().is_none());
+
+    let mut serializer = SimpleSerializer::<Vec<u8>>::new();
+    serializer
+        .encode_u64(block.timestamp_usecs())
+        .unwrap()
+        .encode_u64(block.round())
+        .unwrap()
+        .encode_u64(block.height())
+        .unwrap()
+        .encode_struct(block.get_payload())
+        .unwrap()
+        .encode_raw_bytes(genesis_block.id().as_ref())
+        .unwrap()
+        .encode_raw_bytes(genesis_qc.certified_block_id().as_ref())
+        .unwrap()
+        .encode_struct(&signer.author())
+        .unwrap();
+    let mut state = BlockHasher::default();
+    state.write(&serializer.get_output());
+    assert_eq!(block.id(), state.finish());
+}
+
 // Using current_timestamp in this test
 // because it's a bit hard to generate incremental timestamps in proptests
 fn get_current_timestamp() -> Duration {
@@ -94,7 +94,7 @@ impl QuorumCert {
     /// - the accumulator root hash of the LedgerInfo is set to `ACCUMULATOR_PLACEHOLDER_HASH`
     ///   constant.
     /// - the map of signatures is empty because genesis block is implicitly agreed.
-    pub fn certificate_for_genesis() -> QuorumCert {
+    pub fn certificate_for_genesis() -> Result<QuorumCert> {
         let genesis_digest =
             VoteMsg::vote_digest(*GENESIS_BLOCK_ID, ExecutedState::state_for_genesis(), 0);
         let signer = ValidatorSigner::genesis();
@@ -106,17 +106,15 @@ impl QuorumCert {
             0,
             0,
         );
-        let signature = signer
-            .sign_message(li.hash())
-            .expect("Fail to sign genesis ledger info");
+        let signature = signer.sign_message(li.hash())?;
         let mut signatures = HashMap::new();
         signatures.insert(signer.author(), signature);
-        QuorumCert::new(
+        Ok(QuorumCert::new(
             *GENESIS_BLOCK_ID,
             ExecutedState::state_for_genesis(),
             0,
             LedgerInfoWithSignatures::new(li, signatures),
-        )
+        ))
     }
 
     pub fn verify(
@@ -19,7 +19,7 @@ fn test_put_get() {
 
     db.save_state(vec![0x01, 0x02, 0x03]).unwrap();
 
-    let qcs = vec![QuorumCert::certificate_for_genesis()];
+    let qcs = vec![QuorumCert::certificate_for_genesis().unwrap()];
 
     db.save_blocks_and_quorum_certificates(blocks, qcs).unwrap();
 
@@ -40,7 +40,7 @@ fn test_delete_block_and_qc() {
     let blocks = vec![Block::<i64>::make_genesis_block()];
     let block_id = blocks[0].id();
 
-    let qcs = vec![QuorumCert::certificate_for_genesis()];
+    let qcs = vec![QuorumCert::certificate_for_genesis().unwrap()];
     let qc_id = qcs[0].certified_block_id();
 
     db.save_blocks_and_quorum_certificates(blocks, qcs).unwrap();
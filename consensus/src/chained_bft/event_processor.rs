@@ -24,8 +24,11 @@ use crate::{
     },
     counters,
     state_replication::{StateComputer, TxnManager},
-    util::time_service::{
-        duration_since_epoch, wait_if_possible, TimeService, WaitingError, WaitingSuccess,
+    util::{
+        blocking::run_blocking,
+        time_service::{
+            duration_since_epoch, wait_if_possible, TimeService, WaitingError, WaitingSuccess,
+        },
     },
 };
 use crypto::HashValue;
@@ -130,10 +133,11 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
                 counters::TIMEOUT_ROUNDS_COUNT.inc();
             }
         };
-        let proposer_info = match self
-            .proposer_election
-            .is_valid_proposer(self.author, new_round_event.round)
-        {
+        let proposer_info = match self.proposer_election.is_valid_proposer(
+            self.author,
+            new_round_event.round,
+            self.block_store.highest_quorum_cert().certified_block_id(),
+        ) {
             Some(pi) => pi,
             None => {
                 return;
@@ -200,18 +204,6 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
             );
             return ProcessProposalResult::Done;
         }
-        if self
-            .proposer_election
-            .is_valid_proposer(proposal.proposer_info, proposal.proposal.round())
-            .is_none()
-        {
-            warn!(
-                "Proposer {} for block {} is not a valid proposer for this round",
-                proposal.proposal.author(),
-                proposal.proposal
-            );
-            return ProcessProposalResult::Done;
-        }
 
         let deadline = self.pacemaker.current_round_deadline();
         if let Some(committed_block_id) = proposal.highest_ledger_info.committed_block_id() {
@@ -280,6 +272,23 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
             );
             return;
         }
+        // The proposers may depend on the certified block, which is only known at this point.
+        if self
+            .proposer_election
+            .is_valid_proposer(
+                proposal.proposer_info,
+                proposal.proposal.round(),
+                qc.certified_block_id(),
+            )
+            .is_none()
+        {
+            warn!(
+                "Proposer {} for block {} is not a valid proposer for this round",
+                proposal.proposal.author(),
+                proposal.proposal
+            );
+            return;
+        }
 
         self.proposer_election.process_proposal(proposal).await;
     }
@@ -419,15 +428,29 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
             "Round {} timed out and {}, expected round proposer was {:?}, broadcasting new round to all replicas",
             round,
             if last_vote_round == round { "already executed and voted at this round" } else { "will never vote at this round" },
-            self.proposer_election.get_valid_proposers(round),
+            self.proposer_election.get_valid_proposers(
+                round,
+                self.block_store.highest_quorum_cert().certified_block_id(),
+            ),
         );
 
-        Some(TimeoutMsg::new(
-            self.block_store.highest_quorum_cert().as_ref().clone(),
-            self.block_store.highest_ledger_info().as_ref().clone(),
-            PacemakerTimeout::new(round, self.block_store.signer()),
-            self.block_store.signer(),
-        ))
+        let signer = self.block_store.signer();
+        let timeout_msg = run_blocking(|| {
+            TimeoutMsg::new(
+                self.block_store.highest_quorum_cert().as_ref().clone(),
+                self.block_store.highest_ledger_info().as_ref().clone(),
+                PacemakerTimeout::new(round, signer)?,
+                signer,
+            )
+        })
+        .await;
+        match timeout_msg {
+            Ok(timeout_msg) => Some(timeout_msg),
+            Err(e) => {
+                error!("Failed to sign the timeout of round {}: {:?}", round, e);
+                None
+            }
+        }
     }
 
     /// This function processes a proposal that was chosen as a representative of its round:
@@ -557,18 +580,29 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
         let ledger_info_placeholder = self
             .block_store
             .ledger_info_placeholder(vote_info.potential_commit_id());
-        let vote_msg = VoteMsg::new(
-            proposal_id,
-            executed_state,
-            block.round(),
-            self.author.get_author(),
-            ledger_info_placeholder,
-            self.block_store.signer(),
-        );
+        let vote_msg = match run_blocking(|| {
+            VoteMsg::new(
+                proposal_id,
+                executed_state,
+                block.round(),
+                self.author.get_author(),
+                ledger_info_placeholder,
+                self.block_store.signer(),
+            )
+        })
+        .await
+        {
+            Ok(vote_msg) => vote_msg,
+            Err(e) => {
+                // The round goes on without our vote, it times out if no quorum forms.
+                error!("Failed to sign the vote for {}: {:?}", block, e);
+                return;
+            }
+        };
 
         let recipients: Vec<Author> = self
             .proposer_election
-            .get_valid_proposers(block.round() + 1)
+            .get_valid_proposers(block.round() + 1, block.id())
             .iter()
             .map(ProposerInfo::get_author)
             .collect();
@@ -591,12 +625,15 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
     /// 3. Once the QC successfully formed, notify the Pacemaker.
     #[allow(clippy::collapsible_if)] // Collapsing here would make if look ugly
     pub async fn process_vote(&self, vote: VoteMsg, quorum_size: usize) {
-        // Check whether this validator is a valid recipient of the vote.
+        // Check whether this validator is a valid recipient of the vote. The proposers may depend
+        // on the voted block, the votes for a block that is not known yet are kept (the block is
+        // fetched along with the QC).
         let next_round = vote.round() + 1;
-        if self
-            .proposer_election
-            .is_valid_proposer(self.author, next_round)
-            .is_none()
+        if self.block_store.block_exists(vote.proposed_block_id())
+            && self
+                .proposer_election
+                .is_valid_proposer(self.author, next_round, vote.proposed_block_id())
+                .is_none()
         {
             debug!(
                 "Received {}, but I am not a valid proposer for round {}, ignore.",
@@ -625,6 +662,15 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
                     .data(vote)
                     .log();
             }
+            VoteReceptionResult::MissingBLSSignature => {
+                security_log(SecurityEvent::InvalidConsensusVote)
+                    .error(VoteReceptionResult::MissingBLSSignature)
+                    .data(vote)
+                    .log();
+            }
+            VoteReceptionResult::AggregationError(e) => {
+                error!("Error aggregating the signatures of {}: {:?}", vote, e);
+            }
             VoteReceptionResult::NewQuorumCertificate(qc) => {
                 if self.block_store.need_fetch_for_quorum_cert(&qc) == NeedFetchResult::NeedFetch {
                     if let Err(e) = self
@@ -8,8 +8,11 @@ pub(crate) mod proposal_generator;
 pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
 pub(crate) mod timeout_msg;
+pub(crate) mod vrf_proposer_election;
 
 #[cfg(test)]
 mod local_pacemaker_test;
 #[cfg(test)]
 mod rotating_proposer_test;
+#[cfg(test)]
+mod vrf_proposer_test;
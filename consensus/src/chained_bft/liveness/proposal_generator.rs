@@ -7,7 +7,10 @@ use crate::{
     chained_bft::{block_storage::BlockReader, common::Payload},
     counters,
     state_replication::TxnManager,
-    util::time_service::{wait_if_possible, TimeService, WaitingError, WaitingSuccess},
+    util::{
+        blocking::run_blocking,
+        time_service::{wait_if_possible, TimeService, WaitingError, WaitingSuccess},
+    },
 };
 use logger::prelude::*;
 use std::{
@@ -37,6 +40,9 @@ pub enum ProposalGenerationError {
     /// Already proposed at this round (only a single proposal per round is allowed)
     #[fail(display = "CurrentTimeTooOld")]
     AlreadyProposed(Round),
+    /// The signer failed to sign the block, e.g. the secret service did not answer
+    #[fail(display = "SigningError")]
+    SigningError,
 }
 
 /// ProposalGenerator is responsible for generating the proposed block on demand: it's typically
@@ -207,12 +213,14 @@ impl<T: Payload> ProposalGenerator<T> {
             .pull_txns(self.max_block_size, exclude_payload)
             .await
         {
-            Ok(txns) => Ok(block_store.create_block(
-                hqc_block,
-                txns,
-                round,
-                block_timestamp.as_micros() as u64,
-            )),
+            Ok(txns) => run_blocking(|| {
+                block_store.create_block(hqc_block, txns, round, block_timestamp.as_micros() as u64)
+            })
+            .await
+            .map_err(|e| {
+                error!("Failed to sign the proposal of round {}: {:?}", round, e);
+                ProposalGenerationError::SigningError
+            }),
             Err(_) => Err(ProposalGenerationError::TxnRetrievalError),
         }
     }
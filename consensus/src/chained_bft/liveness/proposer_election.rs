@@ -6,6 +6,7 @@ use crate::chained_bft::{
     consensus_types::{block::Block, quorum_cert::QuorumCert},
     liveness::timeout_msg::PacemakerTimeoutCertificate,
 };
+use crypto::HashValue;
 use failure::Result;
 use futures::Future;
 use network::proto::Proposal as ProtoProposal;
@@ -79,15 +80,23 @@ where
 /// ProposerElection incorporates the logic of choosing a leader among multiple candidates.
 /// We are open to a possibility for having multiple proposers per round, the ultimate choice
 /// of a proposal is exposed by the election protocol via the stream of proposals.
+///
+/// The proposers of a round may depend on the block certified by the QC the round starts from
+/// (i.e. the block the proposals of the round extend), which is passed as `certified_block_id`.
 pub trait ProposerElection<T, P> {
     /// If a given author is a valid candidate for being a proposer, generate the info,
     /// otherwise return None.
     /// Note that this function is synchronous.
-    fn is_valid_proposer(&self, author: P, round: Round) -> Option<P>;
+    fn is_valid_proposer(
+        &self,
+        author: P,
+        round: Round,
+        certified_block_id: HashValue,
+    ) -> Option<P>;
 
     /// Return all the possible valid proposers for a given round (this information can be
     /// used by e.g., voters for choosing the destinations for sending their votes to).
-    fn get_valid_proposers(&self, round: Round) -> Vec<P>;
+    fn get_valid_proposers(&self, round: Round, certified_block_id: HashValue) -> Vec<P>;
 
     /// Notify proposer election about a new proposal. The function doesn't return any information:
     /// proposer election is going to notify the client about the chosen proposal via a dedicated
@@ -33,7 +33,8 @@ fn test_rotating_proposer() {
 
     // Test genesis and the next block
     let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
+    let genesis_id = genesis_block.id();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -43,7 +44,8 @@ fn test_rotating_proposer() {
             1,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -56,7 +58,8 @@ fn test_rotating_proposer() {
             2,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -69,7 +72,8 @@ fn test_rotating_proposer() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -83,15 +87,18 @@ fn test_rotating_proposer() {
             winning_proposals_receiver.take(2).collect::<Vec<_>>().await,
             vec![good_proposal, next_good_proposal],
         );
-        assert_eq!(pe.is_valid_proposer(chosen_author, 1), None);
+        assert_eq!(pe.is_valid_proposer(chosen_author, 1, genesis_id), None);
         assert_eq!(
-            pe.is_valid_proposer(another_author, 1),
+            pe.is_valid_proposer(another_author, 1, genesis_id),
             Some(another_author)
         );
-        assert_eq!(pe.is_valid_proposer(chosen_author, 2), Some(chosen_author));
-        assert_eq!(pe.is_valid_proposer(another_author, 2), None);
-        assert_eq!(pe.get_valid_proposers(1), vec![another_author]);
-        assert_eq!(pe.get_valid_proposers(2), vec![chosen_author]);
+        assert_eq!(
+            pe.is_valid_proposer(chosen_author, 2, genesis_id),
+            Some(chosen_author)
+        );
+        assert_eq!(pe.is_valid_proposer(another_author, 2, genesis_id), None);
+        assert_eq!(pe.get_valid_proposers(1, genesis_id), vec![another_author]);
+        assert_eq!(pe.get_valid_proposers(2, genesis_id), vec![chosen_author]);
     });
 }
 
@@ -114,7 +121,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
 
     // Test genesis and the next block
     let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
+    let genesis_id = genesis_block.id();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -124,7 +132,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             1,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -137,7 +146,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             2,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -150,7 +160,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -164,12 +175,18 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             winning_proposals_receiver.take(2).collect::<Vec<_>>().await,
             vec![good_proposal, next_good_proposal],
         );
-        assert_eq!(pe.is_valid_proposer(another_author, 1), None);
-        assert_eq!(pe.is_valid_proposer(chosen_author, 1), Some(chosen_author));
-        assert_eq!(pe.is_valid_proposer(chosen_author, 2), Some(chosen_author));
-        assert_eq!(pe.is_valid_proposer(another_author, 2), None);
-        assert_eq!(pe.get_valid_proposers(1), vec![chosen_author]);
-        assert_eq!(pe.get_valid_proposers(2), vec![chosen_author]);
+        assert_eq!(pe.is_valid_proposer(another_author, 1, genesis_id), None);
+        assert_eq!(
+            pe.is_valid_proposer(chosen_author, 1, genesis_id),
+            Some(chosen_author)
+        );
+        assert_eq!(
+            pe.is_valid_proposer(chosen_author, 2, genesis_id),
+            Some(chosen_author)
+        );
+        assert_eq!(pe.is_valid_proposer(another_author, 2, genesis_id), None);
+        assert_eq!(pe.get_valid_proposers(1, genesis_id), vec![chosen_author]);
+        assert_eq!(pe.get_valid_proposers(2, genesis_id), vec![chosen_author]);
     });
 }
 
@@ -191,7 +208,8 @@ fn test_fixed_proposer() {
 
     // Test genesis and the next block
     let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
+    let genesis_id = genesis_block.id();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -201,7 +219,8 @@ fn test_fixed_proposer() {
             1,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -214,7 +233,8 @@ fn test_fixed_proposer() {
             2,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -227,7 +247,8 @@ fn test_fixed_proposer() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -241,8 +262,11 @@ fn test_fixed_proposer() {
             winning_proposals_receiver.take(2).collect::<Vec<_>>().await,
             vec![good_proposal, next_good_proposal],
         );
-        assert_eq!(pe.is_valid_proposer(chosen_author, 1), Some(chosen_author));
-        assert_eq!(pe.is_valid_proposer(another_author, 1), None);
-        assert_eq!(pe.get_valid_proposers(1), vec![chosen_author]);
+        assert_eq!(
+            pe.is_valid_proposer(chosen_author, 1, genesis_id),
+            Some(chosen_author)
+        );
+        assert_eq!(pe.is_valid_proposer(another_author, 1, genesis_id), None);
+        assert_eq!(pe.get_valid_proposers(1, genesis_id), vec![chosen_author]);
     });
 }
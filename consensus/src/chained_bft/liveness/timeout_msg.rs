@@ -59,18 +59,16 @@ pub struct PacemakerTimeout {
 }
 
 impl PacemakerTimeout {
-    /// Creates new PacemakerTimeout
-    pub fn new(round: Round, validator_signer: &ValidatorSigner) -> Self {
+    /// Creates new PacemakerTimeout, fails when the signer can not sign
+    pub fn new(round: Round, validator_signer: &ValidatorSigner) -> failure::Result<Self> {
         let author = validator_signer.author();
         let digest = PacemakerTimeoutSerializer { round, author }.hash();
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeout");
-        PacemakerTimeout {
+        let signature = validator_signer.sign_message(digest)?;
+        Ok(PacemakerTimeout {
             round,
             author,
             signature,
-        }
+        })
     }
 
     fn pacemaker_timeout_digest(author: AccountAddress, round: Round) -> HashValue {
@@ -175,28 +173,26 @@ pub struct TimeoutMsg {
 }
 
 impl TimeoutMsg {
-    /// Creates new TimeoutMsg
+    /// Creates new TimeoutMsg, fails when the signer can not sign
     pub fn new(
         highest_quorum_certificate: QuorumCert,
         highest_ledger_info: QuorumCert,
         pacemaker_timeout: PacemakerTimeout,
         validator_signer: &ValidatorSigner,
-    ) -> TimeoutMsg {
+    ) -> failure::Result<TimeoutMsg> {
         let author = validator_signer.author();
         let digest = Self::new_round_digest(
             highest_quorum_certificate.certified_block_id(),
             pacemaker_timeout.digest(),
         );
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeoutMsg");
-        TimeoutMsg {
+        let signature = validator_signer.sign_message(digest)?;
+        Ok(TimeoutMsg {
             highest_quorum_certificate,
             highest_ledger_info,
             pacemaker_timeout,
             author,
             signature,
-        }
+        })
     }
 
     fn new_round_digest(
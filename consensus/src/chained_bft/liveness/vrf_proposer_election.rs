@@ -0,0 +1,129 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chained_bft::{
+    block_storage::BlockReader,
+    common::{Payload, Round},
+    liveness::proposer_election::{ProposalInfo, ProposerElection, ProposerInfo},
+};
+use channel;
+use crypto::{
+    hash::{CryptoHasher, ProposerElectionHasher},
+    HashValue,
+};
+use futures::{Future, FutureExt, SinkExt};
+use logger::prelude::*;
+use nextgen_crypto::vrf::ecvrf::Output;
+use std::{pin::Pin, sync::Arc};
+
+/// The VRF proposer election makes the proposer of a round unpredictable until the block the
+/// round extends is proposed. Every block carries the VRF proof of its author over its QC and its
+/// round (see `Block::vrf_proof`), and the proposer of a round is drawn from the VRF output of the
+/// block certified by the round's QC. All the replicas extending the same certified block agree
+/// on the proposer, and nobody (the author of the certified block included) can bias the draw:
+/// the VRF output is unique for the author, the QC and the round.
+///
+/// The proposals of a round have to extend the highest certified block known to the replica, so
+/// that a proposer cannot elect itself by choosing an older QC.
+pub struct VrfProposer<T, P> {
+    // All the proposers (all honest replicas must agree on this)
+    proposers: Vec<P>,
+    // The certified blocks the proposers are drawn from
+    block_store: Arc<dyn BlockReader<Payload = T>>,
+    // Output stream to send the chosen proposals
+    winning_proposals_sender: channel::Sender<ProposalInfo<T, P>>,
+}
+
+impl<T: Payload, P: ProposerInfo> VrfProposer<T, P> {
+    pub fn new(
+        proposers: Vec<P>,
+        block_store: Arc<dyn BlockReader<Payload = T>>,
+        winning_proposals_sender: channel::Sender<ProposalInfo<T, P>>,
+    ) -> Self {
+        Self {
+            proposers,
+            block_store,
+            winning_proposals_sender,
+        }
+    }
+
+    /// The proposer of `round` extending the block `certified_block_id`, None if the block is
+    /// unknown or not older than the round.
+    fn get_proposer(&self, round: Round, certified_block_id: HashValue) -> Option<P> {
+        let certified_block = self.block_store.get_block(certified_block_id)?;
+        if certified_block.round() >= round {
+            return None;
+        }
+        let mut state = ProposerElectionHasher::default();
+        match certified_block.vrf_proof() {
+            Some(vrf_proof) => {
+                state.write(&Output::from(&vrf_proof).to_bytes());
+            }
+            // Only the genesis block has no VRF proof (the other blocks are verified).
+            None => {
+                state.write(&certified_block.id().to_vec());
+            }
+        }
+        state.write(&round.to_le_bytes());
+        let mut index = [0u8; 8];
+        index.copy_from_slice(&state.finish().to_vec()[..8]);
+        Some(self.proposers[(u64::from_le_bytes(index) % self.proposers.len() as u64) as usize])
+    }
+}
+
+impl<T: Payload, P: ProposerInfo> ProposerElection<T, P> for VrfProposer<T, P> {
+    fn is_valid_proposer(
+        &self,
+        author: P,
+        round: Round,
+        certified_block_id: HashValue,
+    ) -> Option<P> {
+        match self.get_proposer(round, certified_block_id) {
+            Some(proposer) if proposer.get_author() == author.get_author() => Some(author),
+            _ => None,
+        }
+    }
+
+    fn get_valid_proposers(&self, round: Round, certified_block_id: HashValue) -> Vec<P> {
+        self.get_proposer(round, certified_block_id)
+            .into_iter()
+            .collect()
+    }
+
+    fn process_proposal(
+        &self,
+        proposal: ProposalInfo<T, P>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        // The proposal is processed in the context of the caller task, the QC it carries is in
+        // the block store by now.
+        let quorum_cert = proposal.proposal.quorum_cert();
+        let highest_certified_round = self
+            .block_store
+            .highest_quorum_cert()
+            .certified_block_round();
+        if quorum_cert.certified_block_round() < highest_certified_round {
+            warn!(
+                "Proposal {} does not extend the highest certified round {}, ignore.",
+                proposal, highest_certified_round
+            );
+            return async {}.boxed();
+        }
+        if self
+            .is_valid_proposer(
+                proposal.proposer_info,
+                proposal.proposal.round(),
+                quorum_cert.certified_block_id(),
+            )
+            .is_none()
+        {
+            return async {}.boxed();
+        }
+        let mut sender = self.winning_proposals_sender.clone();
+        async move {
+            if let Err(e) = sender.send(proposal).await {
+                debug!("Error in sending the winning proposal: {:?}", e);
+            }
+        }
+            .boxed()
+    }
+}
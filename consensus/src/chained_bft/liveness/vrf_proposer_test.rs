@@ -0,0 +1,126 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chained_bft::{
+    block_storage::BlockReader,
+    common::Author,
+    consensus_types::block::Block,
+    liveness::{
+        proposer_election::{ProposalInfo, ProposerElection},
+        vrf_proposer_election::VrfProposer,
+    },
+    test_utils::{
+        build_empty_tree_with_custom_signing, placeholder_certificate_for_block, TestPayload,
+        TreeInserter,
+    },
+};
+use channel;
+use crypto::HashValue;
+use futures::{executor::block_on, StreamExt};
+use nextgen_crypto::{traits::Uniform, vrf::ecvrf::VRFPrivateKey};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashSet, sync::Arc};
+use types::validator_signer::ValidatorSigner;
+
+fn make_proposal(
+    signer: &ValidatorSigner,
+    parent: &Block<TestPayload>,
+    round: u64,
+) -> ProposalInfo<TestPayload, Author> {
+    let quorum_cert =
+        placeholder_certificate_for_block(vec![signer.clone()], parent.id(), parent.round());
+    let proposal = Block::make_block(
+        parent,
+        vec![],
+        round,
+        parent.timestamp_usecs() + 1,
+        quorum_cert.clone(),
+        signer,
+    )
+    .unwrap();
+    ProposalInfo {
+        proposal,
+        proposer_info: signer.author(),
+        timeout_certificate: None,
+        highest_ledger_info: quorum_cert,
+    }
+}
+
+#[test]
+fn test_vrf_proposer() {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let signers: Vec<_> = (0..4)
+        .map(|_| {
+            ValidatorSigner::random()
+                .with_vrf_private_key(Arc::new(VRFPrivateKey::generate_for_testing(&mut rng)))
+        })
+        .collect();
+    let proposers: Vec<_> = signers.iter().map(ValidatorSigner::author).collect();
+    let signer_of = |author: Author| {
+        signers
+            .iter()
+            .find(|signer| signer.author() == author)
+            .unwrap()
+    };
+    let block_store = build_empty_tree_with_custom_signing(signers[0].clone());
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let (winning_proposals_sender, mut winning_proposals_receiver) = channel::new_test(1_024);
+    let pe = VrfProposer::<TestPayload, Author>::new(
+        proposers.clone(),
+        block_store.clone(),
+        winning_proposals_sender,
+    );
+
+    // A single proposer per round.
+    let genesis = block_store.root();
+    let genesis_proposers = pe.get_valid_proposers(1, genesis.id());
+    assert_eq!(genesis_proposers.len(), 1);
+    for author in &proposers {
+        assert_eq!(
+            pe.is_valid_proposer(*author, 1, genesis.id()).is_some(),
+            *author == genesis_proposers[0]
+        );
+    }
+    // Nobody proposes in a round extending an unknown block or a block that is not older.
+    assert!(pe.get_valid_proposers(1, HashValue::random()).is_empty());
+    assert!(pe.get_valid_proposers(0, genesis.id()).is_empty());
+
+    // The proposers are drawn from the VRF output of the certified block, which only depends on
+    // its author, its QC and its round: another block of the same author extending the same QC
+    // in the same round does not change the draw.
+    let a1 = inserter.insert_block(genesis.as_ref(), 1);
+    let a1_fork = inserter.insert_block(genesis.as_ref(), 1);
+    assert_ne!(a1.id(), a1_fork.id());
+    let mut elected = HashSet::new();
+    for round in 2..100 {
+        let round_proposers = pe.get_valid_proposers(round, a1.id());
+        assert_eq!(round_proposers, pe.get_valid_proposers(round, a1_fork.id()));
+        elected.extend(round_proposers);
+    }
+    // Every proposer gets its share of the rounds.
+    assert_eq!(elected.len(), proposers.len());
+
+    // Only the proposals of the elected proposer extending the highest certified block win.
+    let a2 = inserter.insert_block(a1.as_ref(), 2);
+    let leader = signer_of(pe.get_valid_proposers(3, a2.id())[0]);
+    let good_proposal = make_proposal(leader, a2.as_ref(), 3);
+    let other_signer = signers
+        .iter()
+        .find(|signer| signer.author() != leader.author())
+        .unwrap();
+    let bad_proposal = make_proposal(other_signer, a2.as_ref(), 3);
+    let stale_proposal = make_proposal(
+        signer_of(pe.get_valid_proposers(3, genesis.id())[0]),
+        genesis.as_ref(),
+        3,
+    );
+    block_on(async move {
+        pe.process_proposal(bad_proposal).await;
+        pe.process_proposal(stale_proposal).await;
+        pe.process_proposal(good_proposal.clone()).await;
+        assert_eq!(
+            winning_proposals_receiver.next().await.unwrap(),
+            good_proposal
+        );
+    });
+}
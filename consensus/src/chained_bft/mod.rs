@@ -13,6 +13,7 @@ pub use consensus_types::quorum_cert::QuorumCert;
 mod chained_bft_smr;
 mod event_processor;
 mod network;
+mod secret_service_signer;
 
 pub mod persistent_storage;
 mod sync_manager;
@@ -26,4 +27,6 @@ mod network_tests;
 #[cfg(test)]
 mod proto_test;
 #[cfg(test)]
+mod secret_service_signer_test;
+#[cfg(test)]
 pub mod test_utils;
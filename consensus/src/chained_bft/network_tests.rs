@@ -30,11 +30,13 @@ use std::{
 use tokio::runtime::TaskExecutor;
 use types::{
     account_address::AccountAddress,
+    block_metadata::BlockMetadata,
     proto::ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     test_helpers::transaction_test_helpers::get_test_signed_txn,
     transaction::{SignedTransaction, TransactionInfo, TransactionListWithProof},
     validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
+    vm_error::{ExecutionStatus, VMStatus},
 };
 
 /// `NetworkPlayground` mocks the network implementation and provides convenience
@@ -360,12 +362,13 @@ fn test_network_api() {
         peers[0],
         placeholder_ledger_info(),
         &signers[0],
-    );
+    )
+    .unwrap();
     let proposal = ProposalInfo {
         proposal: Block::make_genesis_block(),
         proposer_info: ValidatorSigner::genesis().author(),
         timeout_certificate: None,
-        highest_ledger_info: QuorumCert::certificate_for_genesis(),
+        highest_ledger_info: QuorumCert::certificate_for_genesis().unwrap(),
     };
     block_on(async move {
         nodes[0].send_vote(vote.clone(), peers[2..5].to_vec()).await;
@@ -463,8 +466,14 @@ fn test_rpc() {
             let proto_txn =
                 get_test_signed_txn(AccountAddress::random(), 0, keypair.0, keypair.1, None);
             let txn = SignedTransaction::from_proto(proto_txn).unwrap();
-            let info =
-                TransactionInfo::new(HashValue::zero(), HashValue::zero(), HashValue::zero(), 0);
+            let info = TransactionInfo::new(
+                HashValue::zero(),
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                BlockMetadata::default(),
+                VMStatus::Execution(ExecutionStatus::Executed),
+            );
             request
                 .response_sender
                 .send(Ok(TransactionListWithProof::new(
@@ -327,7 +327,10 @@ impl<T: Payload> PersistentStorage<T> for StorageWriteProxy {
         // bootstrap the empty store with genesis block and qc.
         if blocks.is_empty() && quorum_certs.is_empty() {
             blocks.push(Block::make_genesis_block());
-            quorum_certs.push(QuorumCert::certificate_for_genesis());
+            quorum_certs.push(
+                QuorumCert::certificate_for_genesis()
+                    .expect("unable to sign the genesis quorum certificate"),
+            );
             proxy
                 .save_tree(vec![blocks[0].clone()], vec![quorum_certs[0].clone()])
                 .expect("unable to bootstrap the storage with genesis block");
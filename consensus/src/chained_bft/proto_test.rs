@@ -11,7 +11,10 @@ use crate::{
     state_replication::ExecutedState,
 };
 use crypto::HashValue;
+use nextgen_crypto::{traits::Uniform, vrf::ecvrf::VRFPrivateKey};
 use proto_conv::test_helper::assert_protobuf_encode_decode;
+use rand::{rngs::StdRng, SeedableRng};
+use std::sync::Arc;
 use types::validator_signer::ValidatorSigner;
 
 #[test]
@@ -27,11 +30,29 @@ fn test_proto_convert_proposal() {
         proposal: Block::<u64>::make_genesis_block(),
         proposer_info: author,
         timeout_certificate: None,
-        highest_ledger_info: QuorumCert::certificate_for_genesis(),
+        highest_ledger_info: QuorumCert::certificate_for_genesis().unwrap(),
     };
     assert_protobuf_encode_decode(&proposal);
 }
 
+#[test]
+fn test_proto_convert_block_with_vrf_proof() {
+    let signer = ValidatorSigner::random().with_vrf_private_key(Arc::new(
+        VRFPrivateKey::generate_for_testing(&mut StdRng::from_seed([0u8; 32])),
+    ));
+    let block = Block::make_block(
+        &Block::<u64>::make_genesis_block(),
+        1,
+        1,
+        1,
+        QuorumCert::certificate_for_genesis().unwrap(),
+        &signer,
+    )
+    .unwrap();
+    assert!(block.vrf_proof().is_some());
+    assert_protobuf_encode_decode(&block);
+}
+
 #[test]
 fn test_proto_convert_vote() {
     let signer = ValidatorSigner::random();
@@ -42,6 +63,7 @@ fn test_proto_convert_vote() {
         signer.author(),
         placeholder_ledger_info(),
         &signer,
-    );
+    )
+    .unwrap();
     assert_protobuf_encode_decode(&vote);
 }
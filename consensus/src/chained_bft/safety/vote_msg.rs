@@ -12,6 +12,7 @@ use crypto::{
 };
 use failure::Result as ProtoResult;
 use network::proto::Vote as ProtoVote;
+use nextgen_crypto::bls12381::BLS12381Signature;
 use proto_conv::{FromProto, IntoProto};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -33,6 +34,9 @@ pub enum VoteMsgVerificationError {
     /// The signature doesn't pass verification
     #[fail(display = "SigVerifyError: {}", _0)]
     SigVerifyError(VerifyError),
+    /// The validators sign with BLS12-381 keys, but the vote has no BLS12-381 signature.
+    #[fail(display = "MissingBLSSignature")]
+    MissingBLSSignature,
 }
 
 // Internal use only. Contains all the fields in VoteMsgSerializer that contributes to the
@@ -84,6 +88,8 @@ pub struct VoteMsg {
     ledger_info: LedgerInfo,
     /// Signature of the LedgerInfo
     signature: Signature,
+    /// BLS12-381 signature of the LedgerInfo, if the voter has a BLS12-381 key
+    bls_signature: Option<BLS12381Signature>,
 }
 
 impl Display for VoteMsg {
@@ -100,6 +106,8 @@ impl Display for VoteMsg {
 }
 
 impl VoteMsg {
+    /// Fails when the signer can not sign, e.g. when the secret service holding the consensus key
+    /// does not answer.
     pub fn new(
         proposed_block_id: HashValue,
         executed_state: ExecutedState,
@@ -107,23 +115,23 @@ impl VoteMsg {
         author: Author,
         mut ledger_info_placeholder: LedgerInfo,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> failure::Result<Self> {
         ledger_info_placeholder.set_consensus_data_hash(Self::vote_digest(
             proposed_block_id,
             executed_state,
             round,
         ));
-        let li_sig = validator_signer
-            .sign_message(ledger_info_placeholder.hash())
-            .expect("Failed to sign LedgerInfo");
-        Self {
+        let li_sig = validator_signer.sign_message(ledger_info_placeholder.hash())?;
+        let li_bls_sig = validator_signer.sign_bls_message(ledger_info_placeholder.hash())?;
+        Ok(Self {
             proposed_block_id,
             executed_state,
             round,
             author,
             ledger_info: ledger_info_placeholder,
             signature: li_sig,
-        }
+            bls_signature: li_bls_sig,
+        })
     }
 
     /// Return the proposed block id
@@ -156,15 +164,30 @@ impl VoteMsg {
         &self.signature
     }
 
+    /// Return the BLS12-381 signature of the vote, if any
+    pub fn bls_signature(&self) -> Option<&BLS12381Signature> {
+        self.bls_signature.as_ref()
+    }
+
     /// Verifies that the consensus data hash of LedgerInfo corresponds to the vote info,
-    /// and then verifies the signature.
+    /// and then verifies the signature. If the validators sign with BLS12-381 keys, the vote has
+    /// to carry a valid BLS12-381 signature as well.
     pub fn verify(&self, validator: &ValidatorVerifier) -> Result<(), VoteMsgVerificationError> {
         if self.ledger_info.consensus_data_hash() != self.vote_hash() {
             return Err(VoteMsgVerificationError::ConsensusDataMismatch);
         }
         validator
             .verify_signature(self.author(), self.ledger_info.hash(), self.signature())
-            .map_err(VoteMsgVerificationError::SigVerifyError)
+            .map_err(VoteMsgVerificationError::SigVerifyError)?;
+        match &self.bls_signature {
+            Some(bls_signature) => validator
+                .verify_bls_signature(self.author(), self.ledger_info.hash(), bls_signature)
+                .map_err(VoteMsgVerificationError::SigVerifyError),
+            None if validator.has_bls_public_keys() => {
+                Err(VoteMsgVerificationError::MissingBLSSignature)
+            }
+            None => Ok(()),
+        }
     }
 
     /// Return the hash of this struct
@@ -199,6 +222,9 @@ impl IntoProto for VoteMsg {
         proto.set_author(self.author.into());
         proto.set_ledger_info(self.ledger_info.into_proto());
         proto.set_signature(self.signature.to_compact().as_ref().into());
+        if let Some(bls_signature) = self.bls_signature {
+            proto.set_bls_signature(bls_signature.to_bytes().to_vec());
+        }
         proto
     }
 }
@@ -214,6 +240,11 @@ impl FromProto for VoteMsg {
         let author = Author::try_from(object.take_author())?;
         let ledger_info = LedgerInfo::from_proto(object.take_ledger_info())?;
         let signature = Signature::from_compact(object.get_signature())?;
+        let bls_signature = if object.get_bls_signature().is_empty() {
+            None
+        } else {
+            Some(BLS12381Signature::try_from(object.get_bls_signature())?)
+        };
         Ok(VoteMsg {
             proposed_block_id,
             executed_state: ExecutedState { state_id, version },
@@ -221,6 +252,7 @@ impl FromProto for VoteMsg {
             author,
             ledger_info,
             signature,
+            bls_signature,
         })
     }
 }
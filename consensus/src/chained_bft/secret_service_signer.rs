@@ -0,0 +1,117 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters;
+use config::config::SecretServiceConfig;
+use crypto::{HashValue, PublicKey, Signature};
+use failure::prelude::*;
+use logger::prelude::*;
+use nextgen_crypto::{
+    bls12381::{BLS12381PublicKey, BLS12381Signature},
+    traits::ValidKey,
+};
+use secret_service::{
+    crypto_wrappers::{GenericPublicKey, GenericSignature, KeyID},
+    secret_service_client::{create_secret_service_client, ConsensusKeyManager},
+};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use types::validator_signer::RemoteSigner;
+
+const DEFAULT_SIGNING_TIMEOUT_MS: u64 = 1000;
+
+/// Signs consensus messages with a consensus key held by the secret service, so that the private
+/// key never enters the consensus process. The key is either the ed25519 consensus key or the
+/// BLS12-381 key the votes are signed with for aggregation. Every signature is a blocking gRPC
+/// request, so consensus signs through `run_blocking`; the latency and failures are reported in
+/// the consensus counters.
+pub struct SecretServiceSigner {
+    key_manager: ConsensusKeyManager,
+}
+
+impl SecretServiceSigner {
+    /// Connects to the secret service of the config to sign with the key `consensus_key_id`, the
+    /// hex encoded key id.
+    pub fn new(config: &SecretServiceConfig, consensus_key_id: &str) -> Result<Self> {
+        let keyid = KeyID(
+            HashValue::from_slice(&hex::decode(consensus_key_id)?)
+                .map_err(|_| format_err!("Invalid consensus key id {}", consensus_key_id))?,
+        );
+        let client = create_secret_service_client(&config.address, config.secret_service_port);
+        let timeout = Duration::from_millis(
+            config
+                .signing_timeout_ms
+                .unwrap_or(DEFAULT_SIGNING_TIMEOUT_MS),
+        );
+        Ok(Self {
+            key_manager: ConsensusKeyManager::with_key_id(Arc::new(client), keyid, timeout),
+        })
+    }
+
+    /// Asks the secret service for the public key of the consensus key, which has to be an
+    /// ed25519 key.
+    pub fn public_key(&self) -> Result<PublicKey> {
+        match self.key_manager.get_consensus_public_key()? {
+            GenericPublicKey::Ed(public_key) => PublicKey::from_slice(&public_key.to_bytes()),
+            GenericPublicKey::BLS(_) => bail!("The consensus key is not an ed25519 key"),
+        }
+    }
+
+    /// Asks the secret service for the public key of the consensus key, which has to be a
+    /// BLS12-381 key.
+    pub fn bls_public_key(&self) -> Result<BLS12381PublicKey> {
+        match self.key_manager.get_consensus_public_key()? {
+            GenericPublicKey::BLS(public_key) => Ok(public_key),
+            GenericPublicKey::Ed(_) => bail!("The consensus key is not a BLS12-381 key"),
+        }
+    }
+
+    fn sign<S>(
+        &self,
+        message: HashValue,
+        convert: impl FnOnce(GenericSignature) -> Result<S>,
+    ) -> Result<S> {
+        counters::REMOTE_SIGNING_COUNT.inc();
+        let pre_signing_instant = Instant::now();
+        let signature = self
+            .key_manager
+            .sign_consensus_message(&message)
+            .and_then(convert);
+        counters::REMOTE_SIGNING_DURATION_MS
+            .observe(pre_signing_instant.elapsed().as_millis() as f64);
+        if let Err(e) = &signature {
+            counters::REMOTE_SIGNING_FAILURE_COUNT.inc();
+            error!("Signing {} with the secret service failed: {}", message, e);
+        }
+        signature
+    }
+}
+
+impl RemoteSigner for SecretServiceSigner {
+    fn sign_message(&self, message: HashValue) -> Result<Signature> {
+        self.sign(message, |signature| match signature {
+            GenericSignature::Ed(signature) => Signature::from_compact(&signature.to_bytes()),
+            GenericSignature::BLS(_) => bail!("The consensus key is not an ed25519 key"),
+        })
+    }
+
+    fn sign_bls_message(&self, message: HashValue) -> Result<BLS12381Signature> {
+        self.sign(message, |signature| match signature {
+            GenericSignature::BLS(signature) => Ok(signature),
+            GenericSignature::Ed(_) => bail!("The consensus key is not a BLS12-381 key"),
+        })
+    }
+}
+
+impl fmt::Debug for SecretServiceSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SecretServiceSigner {{ consensus_key_id: {:x} }}",
+            self.key_manager.signing_keyid().0
+        )
+    }
+}
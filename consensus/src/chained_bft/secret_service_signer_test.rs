@@ -0,0 +1,107 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        liveness::timeout_msg::PacemakerTimeout, secret_service_signer::SecretServiceSigner,
+    },
+    counters,
+};
+use config::{config::NodeConfigHelpers, utils::get_available_port};
+use crypto::HashValue;
+use grpc_helpers::{spawn_service_thread, ServerHandle};
+use nextgen_crypto::traits::VerifyingKey;
+use secret_service::{
+    proto::{secret_service::KeyType, secret_service_grpc::create_secret_service},
+    secret_service_server::SecretServiceServer,
+};
+use std::sync::Arc;
+use types::{
+    account_address::AccountAddress,
+    validator_signer::{RemoteSigner, ValidatorSigner},
+};
+
+fn start_secret_service() -> (SecretServiceServer, u16, ServerHandle) {
+    let server = SecretServiceServer::new();
+    let port = get_available_port();
+    let handle = spawn_service_thread(
+        create_secret_service(server.clone()),
+        "localhost".to_string(),
+        port,
+        "secret_service_test",
+    );
+    (server, port, handle)
+}
+
+#[test]
+fn test_sign_with_secret_service() {
+    let (mut server, port, _handle) = start_secret_service();
+    let keyid = server.generate_key_inner(KeyType::Ed25519).unwrap();
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false).secret_service;
+    config.secret_service_port = port;
+
+    let remote_signer = SecretServiceSigner::new(&config, &format!("{:x}", keyid.0)).unwrap();
+    let public_key = remote_signer.public_key().unwrap();
+    let signer = ValidatorSigner::new_remote(
+        AccountAddress::from(public_key),
+        public_key,
+        Arc::new(remote_signer),
+    );
+
+    let signing_count = counters::REMOTE_SIGNING_COUNT.get();
+    let message = HashValue::random();
+    let signature = signer.sign_message(message).unwrap();
+    assert!(signer.verify_message(message, &signature).is_ok());
+    assert!(counters::REMOTE_SIGNING_COUNT.get() > signing_count);
+}
+
+#[test]
+fn test_sign_bls_with_secret_service() {
+    let (mut server, port, _handle) = start_secret_service();
+    let keyid = server.generate_key_inner(KeyType::BLS12381).unwrap();
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false).secret_service;
+    config.secret_service_port = port;
+
+    let remote_signer = SecretServiceSigner::new(&config, &format!("{:x}", keyid.0)).unwrap();
+    let bls_public_key = remote_signer.bls_public_key().unwrap();
+    // A BLS key can not sign ed25519 consensus messages.
+    assert!(remote_signer.public_key().is_err());
+    assert!(remote_signer.sign_message(HashValue::random()).is_err());
+
+    let signer = ValidatorSigner::random()
+        .with_remote_bls_signer(bls_public_key.clone(), Arc::new(remote_signer));
+    assert_eq!(signer.bls_public_key(), Some(bls_public_key.clone()));
+    assert!(signer.bls_proof_of_possession().is_none());
+
+    let message = HashValue::random();
+    let signature = signer.sign_bls_message(message).unwrap().unwrap();
+    assert!(bls_public_key
+        .verify_signature(&message, &signature)
+        .is_ok());
+}
+
+#[test]
+fn test_unknown_consensus_key() {
+    let (_server, port, _handle) = start_secret_service();
+    let mut config = NodeConfigHelpers::get_single_node_test_config(false).secret_service;
+    config.secret_service_port = port;
+
+    let remote_signer =
+        SecretServiceSigner::new(&config, &format!("{:x}", HashValue::random())).unwrap();
+    assert!(remote_signer.public_key().is_err());
+
+    let failure_count = counters::REMOTE_SIGNING_FAILURE_COUNT.get();
+    assert!(remote_signer.sign_message(HashValue::random()).is_err());
+    assert!(counters::REMOTE_SIGNING_FAILURE_COUNT.get() > failure_count);
+
+    // Consensus messages that can not be signed are refused rather than panicking.
+    let signer = ValidatorSigner::new_remote(
+        AccountAddress::random(),
+        ValidatorSigner::random().public_key(),
+        Arc::new(remote_signer),
+    );
+    assert!(PacemakerTimeout::new(1, &signer).is_err());
+
+    assert!(SecretServiceSigner::new(&config, "not hex").is_err());
+    assert!(SecretServiceSigner::new(&config, "abcd").is_err());
+}
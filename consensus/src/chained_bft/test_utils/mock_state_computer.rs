@@ -12,7 +12,10 @@ use futures::{channel::mpsc, Future, FutureExt};
 use logger::prelude::*;
 use std::pin::Pin;
 use termion::color::*;
-use types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionListWithProof};
+use types::{
+    block_metadata::BlockMetadata, ledger_info::LedgerInfoWithSignatures,
+    transaction::TransactionListWithProof,
+};
 
 pub struct MockStateComputer {
     commit_callback: mpsc::UnboundedSender<LedgerInfoWithSignatures>,
@@ -30,6 +33,7 @@ impl StateComputer for MockStateComputer {
         &self,
         _parent_id: HashValue,
         _block_id: HashValue,
+        _block_metadata: BlockMetadata,
         _transactions: &Self::Payload,
     ) -> Pin<Box<dyn Future<Output = Result<StateComputeResult>> + Send>> {
         async move {
@@ -156,7 +156,7 @@ impl<T: Payload> PersistentStorage<T> for MockStorage<T> {
         storage
             .save_tree(
                 vec![Block::make_genesis_block()],
-                vec![QuorumCert::certificate_for_genesis()],
+                vec![QuorumCert::certificate_for_genesis().unwrap()],
             )
             .unwrap();
         (
@@ -200,7 +200,7 @@ impl<T: Payload> PersistentStorage<T> for EmptyStorage {
 
     fn start(_: &NodeConfig) -> (Arc<Self>, RecoveryData<T>) {
         let genesis = Block::make_genesis_block();
-        let genesis_qc = QuorumCert::certificate_for_genesis();
+        let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
         let htc = HighestTimeoutCertificates::new(None, None);
         (
             Arc::new(EmptyStorage),
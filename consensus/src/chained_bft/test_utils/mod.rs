@@ -20,6 +20,7 @@ use tools::output_capture::OutputCapture;
 use types::{
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
 };
 
 mod mock_state_computer;
@@ -39,6 +40,13 @@ pub fn build_empty_tree() -> Arc<BlockStore<Vec<usize>>> {
 
 pub fn build_empty_tree_with_custom_signing(
     my_signer: ValidatorSigner,
+) -> Arc<BlockStore<Vec<usize>>> {
+    build_empty_tree_with_bls_verifier(my_signer, None)
+}
+
+pub fn build_empty_tree_with_bls_verifier(
+    my_signer: ValidatorSigner,
+    bls_verifier: Option<Arc<ValidatorVerifier>>,
 ) -> Arc<BlockStore<Vec<usize>>> {
     let (commit_cb_sender, _commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
     let (storage, initial_data) = EmptyStorage::start_for_testing();
@@ -49,6 +57,7 @@ pub fn build_empty_tree_with_custom_signing(
         Arc::new(MockStateComputer::new(commit_cb_sender)),
         true,
         10, // max pruned blocks in mem
+        bls_verifier,
     )))
 }
 
@@ -90,15 +99,16 @@ impl TreeInserter {
         round: Round,
     ) -> Arc<Block<Vec<usize>>> {
         self.payload_val += 1;
-        block_on(self.block_store.insert_block_with_qc(Block::make_block(
+        let new_block = Block::make_block(
             parent,
             vec![self.payload_val],
             round,
             parent.timestamp_usecs() + 1,
             parent_qc,
             self.block_store.signer(),
-        )))
-        .unwrap()
+        )
+        .unwrap();
+        block_on(self.block_store.insert_block_with_qc(new_block)).unwrap()
     }
 
     pub fn insert_pre_made_block(
@@ -122,7 +132,8 @@ impl TreeInserter {
             block.timestamp_usecs(),
             parent_qc,
             block_signer,
-        );
+        )
+        .unwrap();
         block_on(self.block_store.insert_block_with_qc(new_block)).unwrap()
     }
 }
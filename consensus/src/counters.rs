@@ -81,6 +81,18 @@ pub static ref BLOCK_RETRIEVAL_DURATION_MS: Histogram = OP_COUNTERS.histogram("b
 /// Histogram of state sync duration.
 pub static ref STATE_SYNC_DURATION_MS: Histogram = OP_COUNTERS.histogram("state_sync_duration_ms");
 
+//////////////////////
+// REMOTE SIGNING COUNTERS
+//////////////////////
+/// Count of the signing requests sent to the secret service.
+pub static ref REMOTE_SIGNING_COUNT: IntCounter = OP_COUNTERS.counter("remote_signing_count");
+
+/// Count of the signing requests to the secret service that failed.
+pub static ref REMOTE_SIGNING_FAILURE_COUNT: IntCounter = OP_COUNTERS.counter("remote_signing_failure_count");
+
+/// Histogram of the duration of signing requests to the secret service, including failed ones.
+pub static ref REMOTE_SIGNING_DURATION_MS: Histogram = OP_COUNTERS.histogram("remote_signing_duration_ms");
+
 //////////////////////
 // RECONFIGURATION COUNTERS
 //////////////////////
@@ -17,6 +17,7 @@ use futures::{compat::Future01CompatExt, Future, FutureExt};
 use proto_conv::{FromProto, IntoProto};
 use std::{pin::Pin, sync::Arc, time::Instant};
 use types::{
+    block_metadata::BlockMetadata,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{SignedTransaction, TransactionListWithProof, TransactionStatus},
 };
@@ -83,12 +84,16 @@ impl StateComputer for ExecutionProxy {
         parent_block_id: HashValue,
         // The id of a current block.
         block_id: HashValue,
+        // The metadata of a current block, exposed to the transactions.
+        block_metadata: BlockMetadata,
         // Transactions to execute.
         transactions: &Self::Payload,
     ) -> Pin<Box<dyn Future<Output = Result<StateComputeResult>> + Send>> {
         let mut exec_req = ExecuteBlockRequest::new();
         exec_req.set_parent_block_id(parent_block_id.to_vec());
         exec_req.set_block_id(block_id.to_vec());
+        exec_req.set_block_height(block_metadata.height());
+        exec_req.set_block_timestamp_usecs(block_metadata.timestamp_usecs());
         exec_req.set_transactions(::protobuf::RepeatedField::from_vec(
             transactions
                 .clone()
@@ -9,6 +9,7 @@ use futures::Future;
 use serde::{Deserialize, Serialize};
 use std::{pin::Pin, sync::Arc};
 use types::{
+    block_metadata::BlockMetadata,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{TransactionListWithProof, Version},
     validator_set::ValidatorSet,
@@ -101,6 +102,8 @@ pub trait StateComputer: Send + Sync {
         parent_block_id: HashValue,
         // The id of a current block.
         block_id: HashValue,
+        // The metadata of a current block, exposed to the transactions.
+        block_metadata: BlockMetadata,
         // Transactions to execute.
         transactions: &Self::Payload,
     ) -> Pin<Box<dyn Future<Output = Result<StateComputeResult>> + Send>>;
@@ -13,9 +13,11 @@ use std::{
 };
 use types::{
     account_address::AccountAddress,
+    block_metadata::BlockMetadata,
     proof::AccumulatorProof,
     test_helpers::transaction_test_helpers::get_test_signed_txn,
     transaction::{SignedTransaction, TransactionInfo, TransactionListWithProof},
+    vm_error::{ExecutionStatus, VMStatus},
 };
 use vm_genesis::{encode_transfer_program, GENESIS_KEYPAIR};
 
@@ -51,7 +53,14 @@ pub fn gen_txn_list(sequence_number: u64) -> TransactionListWithProof {
         Some(program),
     );
 
-    let txn_info = TransactionInfo::new(HashValue::zero(), HashValue::zero(), HashValue::zero(), 0);
+    let txn_info = TransactionInfo::new(
+        HashValue::zero(),
+        HashValue::zero(),
+        HashValue::zero(),
+        0,
+        BlockMetadata::default(),
+        VMStatus::Execution(ExecutionStatus::Executed),
+    );
     let accumulator_proof = AccumulatorProof::new(vec![]);
     TransactionListWithProof::new(
         vec![(
@@ -0,0 +1,25 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::compat::Future01CompatExt;
+use tokio::prelude::future::poll_fn;
+use tokio_threadpool::blocking;
+
+/// Runs `f`, which may block the thread, e.g. on a signing request to the secret service.
+/// On a worker of the tokio thread pool the other tasks of the worker are handed over to another
+/// thread while `f` runs, so that they are not held up. Elsewhere, e.g. on the single threaded
+/// executors of the tests, `f` simply runs in place.
+pub async fn run_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let mut f = Some(f);
+    let result = poll_fn(|| blocking(|| (f.take().expect("Blocking closure ran twice"))()))
+        .compat()
+        .await;
+    match result {
+        Ok(output) => output,
+        // Not on a thread pool worker, `f` has not run.
+        Err(_) => (f.take().expect("Blocking closure ran twice"))(),
+    }
+}
@@ -551,6 +551,11 @@ define_hasher! {
     (VoteMsgHasher, VOTE_MSG_HASHER, b"VoteMsg")
 }
 
+define_hasher! {
+    /// The hasher used to draw the proposer of a round from the VRF output of a certified block.
+    (ProposerElectionHasher, PROPOSER_ELECTION_HASHER, b"ProposerElection")
+}
+
 define_hasher! {
     /// The hasher used to compute the hash of a ContractEvent object.
     (ContractEventHasher, CONTRACT_EVENT_HASHER, b"ContractEvent")
@@ -9,6 +9,7 @@
 
 pub mod hash;
 pub mod hkdf;
+pub mod passphrase;
 pub mod signing;
 pub mod utils;
 pub mod x25519;
@@ -0,0 +1,114 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for secrets kept on disk under a passphrase, shared by the wallet keystore and the
+//! key storage of the secret service.
+//!
+//! The encryption key is derived from the passphrase with scrypt. The parameters and the random
+//! salt are stored along with the secret, so that files stay readable when the defaults are
+//! raised.
+
+use failure::prelude::*;
+use rand::{rngs::EntropyRng, RngCore};
+use rust_crypto::scrypt::{scrypt, ScryptParams};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+const SALT_LENGTH: usize = 32;
+
+/// Parameters of the scrypt KDF deriving an encryption key from a passphrase.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScryptKdf {
+    /// Log2 of the CPU/memory cost.
+    pub log_n: u8,
+    /// Block size.
+    pub r: u32,
+    /// Parallelization.
+    pub p: u32,
+    /// Hex encoded salt, random for every set of parameters.
+    pub salt: String,
+}
+
+impl ScryptKdf {
+    /// The parameters new secrets are sealed with, about 32 MiB and a second of work.
+    pub fn new() -> Self {
+        Self::with_cost(15, 8, 1)
+    }
+
+    /// Parameters with a fresh salt and the given cost.
+    pub fn with_cost(log_n: u8, r: u32, p: u32) -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        EntropyRng::new().fill_bytes(&mut salt);
+        ScryptKdf {
+            log_n,
+            r,
+            p,
+            salt: hex::encode(salt),
+        }
+    }
+
+    /// Fills `output` with key material derived from the passphrase.
+    pub fn derive_key(&self, passphrase: &[u8], output: &mut [u8]) -> Result<()> {
+        ensure!(
+            self.log_n > 0 && self.log_n < 32 && self.r > 0 && self.p > 0,
+            "Invalid scrypt parameters"
+        );
+        scrypt(
+            passphrase,
+            &hex::decode(&self.salt)?,
+            &ScryptParams::new(self.log_n, self.r, self.p),
+            output,
+        );
+        Ok(())
+    }
+}
+
+impl Default for ScryptKdf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The path of a file next to `path`, named like it with `suffix` appended, e.g. `wallet.tmp` for
+/// `wallet` and `wallet.keystore.tmp` for `wallet.keystore`. Unlike replacing the extension, files
+/// that only differ in their extension get different siblings.
+pub fn sibling_path(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let mut file_name = path
+        .file_name()
+        .ok_or_else(|| format_err!("{:?} is not a file path", path))?
+        .to_os_string();
+    file_name.push(suffix);
+    Ok(path.with_file_name(file_name))
+}
+
+/// Writes a file readable by the owner only. An existing file is replaced once the new one is
+/// completely written.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp_path = sibling_path(path, ".tmp")?;
+    // A temporary file left behind by an interrupted write may have any permissions, it is
+    // removed so that the new one is created with the owner only permissions.
+    if let Err(e) = fs::remove_file(&tmp_path) {
+        ensure!(
+            e.kind() == ErrorKind::NotFound,
+            "Unable to remove {:?}: {}",
+            tmp_path,
+            e
+        );
+    }
+    {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
@@ -2,5 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod hkdf_test;
+mod passphrase_test;
 mod signing_test;
 mod x25519_test;
@@ -0,0 +1,24 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::passphrase::ScryptKdf;
+
+#[test]
+fn test_scrypt_kdf() {
+    let kdf = ScryptKdf::with_cost(4, 8, 1);
+    let (mut key, mut same_key, mut other_key) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+    kdf.derive_key(b"passphrase", &mut key).unwrap();
+    kdf.derive_key(b"passphrase", &mut same_key).unwrap();
+    kdf.derive_key(b"other passphrase", &mut other_key).unwrap();
+    assert_eq!(key, same_key);
+    assert_ne!(key, other_key);
+
+    // Every set of parameters gets its own salt.
+    ScryptKdf::with_cost(4, 8, 1)
+        .derive_key(b"passphrase", &mut other_key)
+        .unwrap();
+    assert_ne!(key, other_key);
+
+    let invalid = ScryptKdf { log_n: 0, ..kdf };
+    assert!(invalid.derive_key(b"passphrase", &mut key).is_err());
+}
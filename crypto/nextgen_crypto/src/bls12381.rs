@@ -27,8 +27,15 @@
 //! **Note**: The above example generates a private key using a private function intended only for
 //! testing purposes. Production code should find an alternate means for secure key generation.
 //!
-//! This module is not currently used, but could be included in the future for improved
-//! performance in consensus.
+//! Signatures of a common message can be aggregated into a single signature, verified against
+//! the aggregate of the signers' public keys (see [`BLS12381Signature::aggregate`]). Consensus
+//! uses this to certify blocks with a single signature when configured for BLS12-381.
+//!
+//! **Note**: Aggregating public keys is only safe for keys whose owners are known to hold the
+//! matching private keys, otherwise a rogue key can forge an aggregate. A key is therefore only
+//! accepted along with its proof of possession (see [`BLS12381PrivateKey::proof_of_possession`]),
+//! which has to be checked with [`BLS12381PublicKey::verify_proof_of_possession`] before the key
+//! is aggregated.
 
 use crate::traits::*;
 use bincode::{deserialize, serialize};
@@ -38,8 +45,8 @@ use crypto_derive::{SilentDebug, SilentDisplay};
 use derive_deref::Deref;
 use failure::prelude::*;
 use pairing::{
-    bls12_381::{Fr, FrRepr},
-    PrimeField,
+    bls12_381::{Fr, FrRepr, G1Compressed, G2Compressed},
+    CurveAffine, CurveProjective, EncodedPoint, PrimeField,
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -66,6 +73,37 @@ impl BLS12381PublicKey {
     pub fn to_bytes(&self) -> [u8; threshold_crypto::PK_SIZE] {
         self.0.to_bytes()
     }
+
+    /// Deserializes a BLS12381PublicKey serialized by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, CryptoMaterialError> {
+        if bytes.len() != threshold_crypto::PK_SIZE {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        let mut tmp = [0u8; threshold_crypto::PK_SIZE];
+        tmp.copy_from_slice(bytes);
+        let public_key = threshold_crypto::PublicKey::from_bytes(&tmp)
+            .map_err(|_err| CryptoMaterialError::ValidationError)?;
+        Ok(BLS12381PublicKey(public_key))
+    }
+
+    /// Checks that `proof` was made by [`BLS12381PrivateKey::proof_of_possession`] with the
+    /// private key of this public key.
+    pub fn verify_proof_of_possession(&self, proof: &BLS12381Signature) -> Result<()> {
+        proof
+            .verify_arbitrary_msg(&proof_of_possession_message(self), self)
+            .map_err(|_| format_err!("Invalid proof of possession for the BLS12-381 key {}", self))
+    }
+
+    /// Aggregates public keys into the key verifying the aggregate of their signatures on a
+    /// common message. Fails if no key is given. Only keys whose proof of possession has been
+    /// verified may be aggregated.
+    pub fn aggregate<'a>(
+        public_keys: impl IntoIterator<Item = &'a BLS12381PublicKey>,
+    ) -> Result<Self> {
+        let points: Vec<_> = public_keys.into_iter().map(Self::to_bytes).collect();
+        let sum = sum_points::<G1Compressed>(points.iter().map(|point| &point[..]))?;
+        Ok(Self::from_bytes(sum.as_ref())?)
+    }
 }
 
 impl BLS12381Signature {
@@ -73,9 +111,61 @@ impl BLS12381Signature {
     pub fn to_bytes(&self) -> [u8; threshold_crypto::SIG_SIZE] {
         self.0.to_bytes()
     }
+
+    /// Aggregates signatures of a common message into one signature, valid for the aggregate of
+    /// the signers' public keys (see [`BLS12381PublicKey::aggregate`]). Fails if no signature is
+    /// given.
+    pub fn aggregate<'a>(
+        signatures: impl IntoIterator<Item = &'a BLS12381Signature>,
+    ) -> Result<Self> {
+        let points: Vec<_> = signatures.into_iter().map(Self::to_bytes).collect();
+        let sum = sum_points::<G2Compressed>(points.iter().map(|point| &point[..]))?;
+        Ok(Self::try_from(sum.as_ref())?)
+    }
+}
+
+/// Adds up compressed curve points, as encoded by threshold_crypto.
+fn sum_points<'a, E: EncodedPoint>(points: impl Iterator<Item = &'a [u8]>) -> Result<E> {
+    let mut sum = <E::Affine as CurveAffine>::Projective::zero();
+    let mut num_points = 0;
+    for point in points {
+        let mut encoded = E::empty();
+        ensure!(
+            point.len() == E::size(),
+            "Invalid point length: {}",
+            point.len()
+        );
+        encoded.as_mut().copy_from_slice(point);
+        let affine = encoded
+            .into_affine()
+            .map_err(|e| format_err!("Invalid point: {}", e))?;
+        sum.add_assign_mixed(&affine);
+        num_points += 1;
+    }
+    ensure!(num_points > 0, "Nothing to aggregate");
+    Ok(E::from_affine(sum.into_affine()))
+}
+
+/// Prefix of the message signed by a proof of possession, so that the proof is not a signature of
+/// anything else. Consensus messages are hashes, which are shorter than this message.
+const PROOF_OF_POSSESSION_PREFIX: &[u8] = b"BLS12381_PROOF_OF_POSSESSION";
+
+fn proof_of_possession_message(public_key: &BLS12381PublicKey) -> Vec<u8> {
+    let mut message = PROOF_OF_POSSESSION_PREFIX.to_vec();
+    message.extend_from_slice(&public_key.to_bytes());
+    message
 }
 
 impl BLS12381PrivateKey {
+    /// Signs the public key of this private key. The signature proves that whoever registers the
+    /// public key holds its private key, i.e. that the key is not a rogue key derived from the
+    /// public keys of others to forge aggregated signatures.
+    pub fn proof_of_possession(&self) -> BLS12381Signature {
+        let public_key = BLS12381PublicKey::from(self);
+        let secret_key: &ThresholdBLSPrivateKey = self;
+        BLS12381Signature(secret_key.sign(proof_of_possession_message(&public_key)))
+    }
+
     #[allow(dead_code)]
     /// Deserialize a [`BLS12381PrivateKey`]. This method DOES NOT check for key validity.
     fn from_bytes_unchecked(
@@ -149,9 +239,9 @@ impl TryFrom<&[u8]> for BLS12381PrivateKey {
     }
 }
 impl ValidKey for BLS12381PrivateKey {
-    // TODO(ladi): implement!
+    /// The serialized key, in the format read back by `TryFrom<&[u8]>`.
     fn to_bytes(&self) -> Vec<u8> {
-        unimplemented!("ask ladi!")
+        serialize(self).expect("Serialization of a BLS12381PrivateKey should not fail")
     }
 }
 
@@ -41,6 +41,18 @@ proptest! {
             prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
         }
     }
+
+    #[test]
+    fn test_public_key_bytes(keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()) {
+        let deserialized = BLS12381PublicKey::from_bytes(&keypair.public_key.to_bytes());
+        prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
+    }
+
+    #[test]
+    fn test_private_key_to_bytes(keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()) {
+        let deserialized = BLS12381PrivateKey::try_from(keypair.private_key.to_bytes().as_slice());
+        prop_assert_eq!(Some(keypair.private_key), deserialized.ok());
+    }
 }
 
 proptest! {
@@ -68,3 +80,57 @@ proptest! {
         prop_assert!(keypair.public_key.verify_signature(&hash, &deserialized).is_ok());
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_aggregate_and_verify(
+        hash in any::<HashValue>(),
+        keypairs in proptest::collection::vec(
+            uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+            2..5
+        )
+    ) {
+        let signatures: Vec<_> = keypairs
+            .iter()
+            .map(|keypair| keypair.private_key.sign_message(&hash))
+            .collect();
+        let signature = BLS12381Signature::aggregate(&signatures).unwrap();
+        let public_key =
+            BLS12381PublicKey::aggregate(keypairs.iter().map(|keypair| &keypair.public_key))
+                .unwrap();
+        prop_assert!(signature.verify(&hash, &public_key).is_ok());
+
+        // The aggregate does not verify without all the signers' keys.
+        let partial_public_key =
+            BLS12381PublicKey::aggregate(keypairs[1..].iter().map(|keypair| &keypair.public_key))
+                .unwrap();
+        prop_assert!(signature.verify(&hash, &partial_public_key).is_err());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_proof_of_possession(
+        hash in any::<HashValue>(),
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+        other_keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        let proof = keypair.private_key.proof_of_possession();
+        prop_assert!(keypair.public_key.verify_proof_of_possession(&proof).is_ok());
+
+        // Neither a proof for another key nor a signature of a message proves possession.
+        prop_assert!(other_keypair.public_key.verify_proof_of_possession(&proof).is_err());
+        let signature = keypair.private_key.sign_message(&hash);
+        prop_assert!(keypair.public_key.verify_proof_of_possession(&signature).is_err());
+    }
+}
+
+#[test]
+fn test_aggregate_nothing() {
+    assert!(BLS12381Signature::aggregate(&[]).is_err());
+    assert!(BLS12381PublicKey::aggregate(&[]).is_err());
+}
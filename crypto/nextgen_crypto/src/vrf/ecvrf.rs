@@ -74,7 +74,7 @@ pub const PROOF_LENGTH: usize = 80;
 pub struct VRFPrivateKey(ed25519_PrivateKey);
 
 /// An ECVRF public key
-#[derive(Serialize, Deserialize, Deref, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Deref, Clone, Debug, PartialEq, Eq)]
 pub struct VRFPublicKey(ed25519_PublicKey);
 
 /// A longer private key which is slightly optimized for proof generation.
@@ -237,6 +237,7 @@ impl<'a> From<&'a VRFPrivateKey> for VRFExpandedPrivateKey {
 }
 
 /// A VRF proof that can be used to validate an input with a public key
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Proof {
     gamma: EdwardsPoint,
     c: ed25519_Scalar,
@@ -263,6 +264,9 @@ impl TryFrom<&[u8]> for Proof {
     type Error = CryptoMaterialError;
 
     fn try_from(bytes: &[u8]) -> std::result::Result<Proof, CryptoMaterialError> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
         let mut c_buf = [0u8; 32];
         c_buf[..16].copy_from_slice(&bytes[32..48]);
         let mut s_buf = [0u8; 32];
@@ -270,7 +274,7 @@ impl TryFrom<&[u8]> for Proof {
         Ok(Proof {
             gamma: CompressedEdwardsY::from_slice(&bytes[..32])
                 .decompress()
-                .unwrap(),
+                .ok_or(CryptoMaterialError::DeserializationError)?,
             c: ed25519_Scalar::from_bits(c_buf),
             s: ed25519_Scalar::from_bits(s_buf),
         })
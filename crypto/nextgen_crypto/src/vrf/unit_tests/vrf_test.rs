@@ -166,6 +166,19 @@ fn test_verify() {
     }
 }
 
+#[test]
+fn test_proof_from_bytes() {
+    for tv in TESTVECTORS.iter() {
+        let pi = from_string!(Proof, tv.pi);
+        assert_eq!(Proof::try_from(&pi.to_bytes()[..]).unwrap(), pi);
+    }
+    assert!(Proof::try_from(&[0u8; PROOF_LENGTH - 1][..]).is_err());
+    // 2 is not the y coordinate of a curve point.
+    let mut bytes = [0u8; PROOF_LENGTH];
+    bytes[0] = 2;
+    assert!(Proof::try_from(&bytes[..]).is_err());
+}
+
 #[test]
 fn test_output_from_proof() {
     for tv in TESTVECTORS.iter() {
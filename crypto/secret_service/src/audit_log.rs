@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An append-only log of the signing requests served by the secret service.
+//!
+//! Every `Sign` request is recorded as one line, whether it is answered with a signature or not:
+//! `<unix time in ms> sign key_id=<hex> message_hash=<hex> code=<ErrorCode>`. The key id and
+//! message hash are written as received, so malformed requests can be told apart as well.
+
+use crate::proto::secret_service::ErrorCode;
+use failure::prelude::*;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Appends the signing requests to a file.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Opens the log for appending, creating it readable by the owner only if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o600)
+            .open(path)
+            .with_context(|_| format!("Cannot open the audit log {:?}", path))?;
+        Ok(AuditLog {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Records a signing request and how it was answered. The entry is flushed to disk before
+    /// returning, a request that can not be recorded must not be answered with a signature.
+    pub fn record_sign(&self, key_id: &[u8], message_hash: &[u8], code: ErrorCode) -> Result<()> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let entry = format!(
+            "{} sign key_id={} message_hash={} code={:?}\n",
+            timestamp,
+            hex::encode(key_id),
+            hex::encode(message_hash),
+            code
+        );
+        let mut file = self.file.lock().expect("[audit log] acquire file lock");
+        file.write_all(entry.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
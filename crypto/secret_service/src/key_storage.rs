@@ -0,0 +1,311 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage backends for the secret keys held by the secret service.
+//!
+//! `InMemoryStorage` loses its keys when the process exits and is meant for tests.
+//! `EncryptedFileStorage` keeps one file per key in a directory. The keys are sealed with
+//! ChaCha20-Poly1305 under a storage key derived with scrypt from the operator passphrase; the
+//! scrypt parameters and salt are kept in `storage.json` next to the keys, together with a check
+//! value derived along with the storage key so that a wrong passphrase is noticed even before any
+//! key is stored. The key id and the key type are authenticated along with every key, so a key file
+//! can not be swapped for another one.
+
+use crate::crypto_wrappers::{GenericPrivateKey, KeyID};
+pub use crypto::passphrase::ScryptKdf;
+use crypto::{hash::HashValue, passphrase::write_atomically};
+use failure::prelude::*;
+use nextgen_crypto::{bls12381::BLS12381PrivateKey, ed25519::Ed25519PrivateKey, traits::*};
+use rand::{rngs::EntropyRng, RngCore};
+use rust_crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    chacha20poly1305::ChaCha20Poly1305,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(test)]
+#[path = "unit_tests/key_storage_test.rs"]
+mod key_storage_test;
+
+/// Version of the key file format written by `EncryptedFileStorage`.
+pub const KEY_FILE_VERSION: u32 = 1;
+
+const HEADER_FILE_NAME: &str = "storage.json";
+const KEY_FILE_EXTENSION: &str = "key";
+const CIPHER: &str = "chacha20poly1305";
+const KEY_LENGTH: usize = 32;
+const CHECK_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 8;
+const TAG_LENGTH: usize = 16;
+
+/// A store of secret keys addressed by their key id.
+pub trait KeyStorage: Send + Sync {
+    /// Stores a new key, persisting it before returning.
+    fn insert(&mut self, keyid: KeyID, key: GenericPrivateKey) -> Result<()>;
+
+    /// Returns the key with the given id.
+    fn get(&self, keyid: &KeyID) -> Option<&GenericPrivateKey>;
+
+    /// Removes a key, returns whether it was present.
+    fn remove(&mut self, keyid: &KeyID) -> Result<bool>;
+
+    /// Ids of all the stored keys.
+    fn key_ids(&self) -> Vec<KeyID>;
+}
+
+/// Keeps the keys in memory only.
+#[derive(Default)]
+pub struct InMemoryStorage {
+    keys: HashMap<KeyID, GenericPrivateKey>,
+}
+
+impl InMemoryStorage {
+    /// An empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStorage for InMemoryStorage {
+    fn insert(&mut self, keyid: KeyID, key: GenericPrivateKey) -> Result<()> {
+        self.keys.insert(keyid, key);
+        Ok(())
+    }
+
+    fn get(&self, keyid: &KeyID) -> Option<&GenericPrivateKey> {
+        self.keys.get(keyid)
+    }
+
+    fn remove(&mut self, keyid: &KeyID) -> Result<bool> {
+        Ok(self.keys.remove(keyid).is_some())
+    }
+
+    fn key_ids(&self) -> Vec<KeyID> {
+        self.keys.keys().cloned().collect()
+    }
+}
+
+/// Derives the storage key and the check value telling whether the passphrase is right.
+fn derive_storage_key(kdf: &ScryptKdf, passphrase: &[u8]) -> Result<([u8; KEY_LENGTH], Vec<u8>)> {
+    let mut output = [0u8; KEY_LENGTH + CHECK_LENGTH];
+    kdf.derive_key(passphrase, &mut output)?;
+    let mut key = [0u8; KEY_LENGTH];
+    key.copy_from_slice(&output[..KEY_LENGTH]);
+    Ok((key, output[KEY_LENGTH..].to_vec()))
+}
+
+/// Content of `storage.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct StorageHeader {
+    kdf: ScryptKdf,
+    /// Hex encoded check value derived along with the storage key.
+    check: String,
+}
+
+/// A sealed key as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    /// `ed25519` or `bls12381`.
+    key_type: String,
+    cipher: String,
+    /// Hex encoded nonce of the cipher.
+    nonce: String,
+    /// Hex encoded encrypted private key.
+    ciphertext: String,
+    /// Hex encoded authentication tag.
+    tag: String,
+}
+
+/// Keeps every key sealed in its own file of a directory. All the keys are unsealed when the
+/// storage is opened and kept in memory afterwards.
+pub struct EncryptedFileStorage {
+    dir: PathBuf,
+    storage_key: [u8; KEY_LENGTH],
+    keys: HashMap<KeyID, GenericPrivateKey>,
+}
+
+impl EncryptedFileStorage {
+    /// Opens the storage in `dir` with the default KDF parameters for a new directory.
+    pub fn open(dir: &Path, passphrase: &[u8]) -> Result<Self> {
+        Self::open_with_kdf(dir, passphrase, ScryptKdf::new())
+    }
+
+    /// Opens the storage in `dir`, creating the directory if needed. `kdf` is only used when the
+    /// directory is new, otherwise the parameters it was set up with are read back. Fails when the
+    /// passphrase is wrong or a key can not be unsealed.
+    pub fn open_with_kdf(dir: &Path, passphrase: &[u8], kdf: ScryptKdf) -> Result<Self> {
+        ensure!(
+            !passphrase.is_empty(),
+            "An empty passphrase does not protect anything"
+        );
+        fs::create_dir_all(dir)
+            .with_context(|_| format!("Cannot create the key storage directory {:?}", dir))?;
+        let header_path = dir.join(HEADER_FILE_NAME);
+        let storage_key = if header_path.exists() {
+            let header: StorageHeader = serde_json::from_slice(&fs::read(&header_path)?)
+                .with_context(|_| format!("Cannot parse {:?}", header_path))?;
+            let (storage_key, check) = derive_storage_key(&header.kdf, passphrase)?;
+            ensure!(
+                hex::encode(check) == header.check,
+                "Wrong passphrase for the key storage {:?}",
+                dir
+            );
+            storage_key
+        } else {
+            let (storage_key, check) = derive_storage_key(&kdf, passphrase)?;
+            let header = StorageHeader {
+                kdf,
+                check: hex::encode(check),
+            };
+            write_atomically(
+                &header_path,
+                serde_json::to_string_pretty(&header)?.as_bytes(),
+            )?;
+            storage_key
+        };
+
+        let mut storage = EncryptedFileStorage {
+            dir: dir.to_path_buf(),
+            storage_key,
+            keys: HashMap::new(),
+        };
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(KEY_FILE_EXTENSION) {
+                continue;
+            }
+            let keyid = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem).ok())
+                .and_then(|bytes| HashValue::from_slice(&bytes).ok())
+                .map(KeyID)
+                .ok_or_else(|| format_err!("Unexpected key file name {:?}", path))?;
+            let key = storage
+                .unseal(&keyid, &fs::read(&path)?)
+                .with_context(|_| format!("Cannot unseal the key file {:?}", path))?;
+            storage.keys.insert(keyid, key);
+        }
+        Ok(storage)
+    }
+
+    fn key_path(&self, keyid: &KeyID) -> PathBuf {
+        self.dir
+            .join(format!("{:x}", keyid.0))
+            .with_extension(KEY_FILE_EXTENSION)
+    }
+
+    /// The key type and key id make up the additional authenticated data.
+    fn associated_data(key_type: &str, keyid: &KeyID) -> Vec<u8> {
+        let mut data = key_type.as_bytes().to_vec();
+        data.extend_from_slice(&keyid.to_vec());
+        data
+    }
+
+    fn seal(&self, keyid: &KeyID, key: &GenericPrivateKey) -> Result<Vec<u8>> {
+        let key_type = match key {
+            GenericPrivateKey::Ed(_) => "ed25519",
+            GenericPrivateKey::BLS(_) => "bls12381",
+        };
+        let secret = key.to_bytes();
+        let mut nonce = [0u8; NONCE_LENGTH];
+        EntropyRng::new().fill_bytes(&mut nonce);
+        let mut ciphertext = vec![0u8; secret.len()];
+        let mut tag = [0u8; TAG_LENGTH];
+        ChaCha20Poly1305::new(
+            &self.storage_key,
+            &nonce,
+            &Self::associated_data(key_type, keyid),
+        )
+        .encrypt(&secret, &mut ciphertext, &mut tag);
+        let file = KeyFile {
+            version: KEY_FILE_VERSION,
+            key_type: key_type.to_string(),
+            cipher: CIPHER.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            tag: hex::encode(tag),
+        };
+        Ok(serde_json::to_vec_pretty(&file)?)
+    }
+
+    fn unseal(&self, keyid: &KeyID, bytes: &[u8]) -> Result<GenericPrivateKey> {
+        let file: KeyFile = serde_json::from_slice(bytes)?;
+        ensure!(
+            file.version == KEY_FILE_VERSION,
+            "Unsupported key file version {}",
+            file.version
+        );
+        ensure!(
+            file.cipher == CIPHER,
+            "Unsupported key file cipher {}",
+            file.cipher
+        );
+        let nonce = hex::decode(&file.nonce)?;
+        let tag = hex::decode(&file.tag)?;
+        ensure!(
+            nonce.len() == NONCE_LENGTH && tag.len() == TAG_LENGTH,
+            "Invalid key file nonce or tag"
+        );
+        let ciphertext = hex::decode(&file.ciphertext)?;
+        let mut secret = vec![0u8; ciphertext.len()];
+        if !ChaCha20Poly1305::new(
+            &self.storage_key,
+            &nonce,
+            &Self::associated_data(&file.key_type, keyid),
+        )
+        .decrypt(&ciphertext, &mut secret, &tag)
+        {
+            bail!("Wrong passphrase or corrupted key file");
+        }
+        Ok(match file.key_type.as_str() {
+            "ed25519" => GenericPrivateKey::Ed(Ed25519PrivateKey::try_from(secret.as_slice())?),
+            "bls12381" => GenericPrivateKey::BLS(BLS12381PrivateKey::try_from(secret.as_slice())?),
+            key_type => bail!("Unknown key type {}", key_type),
+        })
+    }
+}
+
+impl KeyStorage for EncryptedFileStorage {
+    fn insert(&mut self, keyid: KeyID, key: GenericPrivateKey) -> Result<()> {
+        write_atomically(&self.key_path(&keyid), &self.seal(&keyid, &key)?)?;
+        self.keys.insert(keyid, key);
+        Ok(())
+    }
+
+    fn get(&self, keyid: &KeyID) -> Option<&GenericPrivateKey> {
+        self.keys.get(keyid)
+    }
+
+    fn remove(&mut self, keyid: &KeyID) -> Result<bool> {
+        if !self.keys.contains_key(keyid) {
+            return Ok(false);
+        }
+        // The key is only dropped from memory once its file is gone, a failed delete leaves both
+        // in place rather than a key that comes back on the next start.
+        fs::remove_file(self.key_path(keyid))?;
+        self.keys.remove(keyid);
+        Ok(true)
+    }
+
+    fn key_ids(&self) -> Vec<KeyID> {
+        self.keys.keys().cloned().collect()
+    }
+}
+
+/// Reads the operator passphrase file. A trailing newline is not part of the passphrase.
+pub fn read_passphrase_file(path: &Path) -> Result<Vec<u8>> {
+    let mut passphrase =
+        fs::read(path).with_context(|_| format!("Cannot read the passphrase file {:?}", path))?;
+    while passphrase.last() == Some(&b'\n') || passphrase.last() == Some(&b'\r') {
+        passphrase.pop();
+    }
+    Ok(passphrase)
+}
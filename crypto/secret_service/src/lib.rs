@@ -4,7 +4,9 @@
 #![deny(missing_docs)]
 //! A secret service providing cryptographic operations on secret keys, will be used in future
 //! releases.
+pub mod audit_log;
 pub mod crypto_wrappers;
+pub mod key_storage;
 pub mod proto;
 pub mod secret_service_client;
 
@@ -3,26 +3,38 @@
 
 //! ConsensusKeyManager gives a simple interface for consensus to interact with the secret service.
 //! This simple key manager will become more complicated in future versions,
-//! now it asks the secret service to generate an ed25519 key on creation,
-//! it can then transfer to the secret service the requests to get consensus public key and to sign
-//! a consensus message.
+//! now it either asks the secret service to generate an ed25519 key on creation or uses a key
+//! provisioned earlier (ed25519 or BLS12-381), it can then transfer to the secret service the
+//! requests to get consensus public key and to sign a consensus message.
 
 use crate::{
     crypto_wrappers::{GenericPublicKey, GenericSignature, KeyID},
     proto::{
-        secret_service::{GenerateKeyRequest, KeyType, PublicKeyRequest, SignRequest},
+        secret_service::{ErrorCode, GenerateKeyRequest, KeyType, PublicKeyRequest, SignRequest},
         secret_service_grpc::SecretServiceClient,
     },
 };
 use crypto::hash::HashValue;
 use failure::prelude::*;
-use nextgen_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
-use std::{convert::TryFrom, sync::Arc};
+use grpcio::{CallOption, ChannelBuilder, EnvBuilder};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
 
 /// A consensus key manager - interface between consensus and the secret service.
 pub struct ConsensusKeyManager {
     secret_service: Arc<SecretServiceClient>,
     signing_keyid: KeyID,
+    call_option: CallOption,
+}
+
+/// Connects to the secret service listening on `address:port`.
+pub fn create_secret_service_client(address: &str, port: u16) -> SecretServiceClient {
+    let env = Arc::new(
+        EnvBuilder::new()
+            .name_prefix("grpc-secret-service-")
+            .build(),
+    );
+    let channel = ChannelBuilder::new(env).connect(&format!("{}:{}", address, port));
+    SecretServiceClient::new(channel)
 }
 
 impl ConsensusKeyManager {
@@ -37,21 +49,44 @@ impl ConsensusKeyManager {
                 gen_req.set_spec(KeyType::Ed25519);
 
                 let response = secret_service.generate_key(&gen_req)?;
+                ensure_success(response.get_code())?;
                 KeyID(HashValue::from_slice(response.get_key_id())?)
             },
+            call_option: CallOption::default(),
         })
     }
 
+    /// Uses the key `signing_keyid` the secret service already holds, e.g. a key generated and
+    /// registered as the validator's consensus key beforehand. Requests that are not answered
+    /// within `timeout` fail.
+    pub fn with_key_id(
+        secret_service: Arc<SecretServiceClient>,
+        signing_keyid: KeyID,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            secret_service,
+            signing_keyid,
+            call_option: CallOption::default().timeout(timeout),
+        }
+    }
+
+    /// Id of the consensus key in the secret service.
+    pub fn signing_keyid(&self) -> &KeyID {
+        &self.signing_keyid
+    }
+
     /// Asks the secret service for the public key and returns it.
     pub fn get_consensus_public_key(&self) -> Result<GenericPublicKey> {
         let mut pk_req: PublicKeyRequest = PublicKeyRequest::new();
         pk_req.set_key_id(self.signing_keyid.to_vec());
-        let response = self.secret_service.get_public_key(&pk_req)?;
+        let response = self
+            .secret_service
+            .get_public_key_opt(&pk_req, self.call_option.clone())?;
+        ensure_success(response.get_code())?;
         let public_key: &[u8] = response.get_public_key();
 
-        Ok(GenericPublicKey::Ed(Ed25519PublicKey::try_from(
-            public_key,
-        )?))
+        Ok(GenericPublicKey::try_from(public_key)?)
     }
 
     /// Asks the secret service to sign a hash of the consensus message.
@@ -59,9 +94,21 @@ impl ConsensusKeyManager {
         let mut sig_req: SignRequest = SignRequest::new();
         sig_req.set_key_id(self.signing_keyid.to_vec());
         sig_req.set_message_hash(message.to_vec());
-        let response = self.secret_service.sign(&sig_req)?;
+        let response = self
+            .secret_service
+            .sign_opt(&sig_req, self.call_option.clone())?;
+        ensure_success(response.get_code())?;
         let signature = response.get_signature();
 
-        Ok(GenericSignature::Ed(Ed25519Signature::try_from(signature)?))
+        Ok(GenericSignature::try_from(signature)?)
     }
 }
+
+fn ensure_success(code: ErrorCode) -> Result<()> {
+    ensure!(
+        code == ErrorCode::Success,
+        "The secret service answered with {:?}",
+        code
+    );
+    Ok(())
+}
@@ -3,28 +3,33 @@
 
 //! The Secret service server stores the secret key and performs operations on these keys.
 //! Right now the service supports requests to generate the secret key (of Ed25519 or BLS12-381
-//! type), return the corresponding public key and sign.
+//! type), return the corresponding public key, sign, list the stored keys and delete a key.
+//! The keys are held by a `KeyStorage` backend, see key_storage.rs, and every signing request can
+//! be recorded in an audit log.
 
 use crate::{
+    audit_log::AuditLog,
     crypto_wrappers::{GenericPrivateKey, GenericPublicKey, GenericSignature, KeyID},
+    key_storage::{read_passphrase_file, EncryptedFileStorage, InMemoryStorage, KeyStorage},
     proto::{
         secret_service::{
-            ErrorCode, GenerateKeyRequest, GenerateKeyResponse, KeyType, PublicKeyRequest,
-            PublicKeyResponse, SignRequest, SignResponse,
+            DeleteKeyRequest, DeleteKeyResponse, ErrorCode, GenerateKeyRequest,
+            GenerateKeyResponse, KeyInfo, KeyType, ListKeysRequest, ListKeysResponse,
+            PublicKeyRequest, PublicKeyResponse, SignRequest, SignResponse,
         },
         secret_service_grpc,
     },
 };
+use config::config::SecretServiceConfig;
 use crypto::hash::HashValue;
 use failure::prelude::*;
 use grpc_helpers::provide_grpc_response;
+use logger::prelude::*;
 use nextgen_crypto::{bls12381::BLS12381PrivateKey, ed25519::Ed25519PrivateKey, traits::*};
+use protobuf::RepeatedField;
 use rand::{rngs::EntropyRng, Rng};
 use rand_chacha::ChaChaRng;
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use std::sync::{Arc, RwLock};
 
 #[cfg(test)]
 #[path = "unit_tests/secret_service_test.rs"]
@@ -32,26 +37,69 @@ mod secret_service_test;
 
 /// Secret service server that holds the secret keys and implements the necessary operations on
 /// them.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct SecretServiceServer {
     // RwLock is chosen over Mutex because the RwLock won't get poisoned if a panic occurs during
     // read
-    keys: Arc<RwLock<HashMap<KeyID, GenericPrivateKey>>>, /* Arc for shared ownership by clones;
-                                                           * RwLock for being write-accessible
-                                                           * by one
-                                                           * thread at a time */
+    keys: Arc<RwLock<Box<dyn KeyStorage>>>, /* Arc for shared ownership by clones;
+                                             * RwLock for being write-accessible
+                                             * by one
+                                             * thread at a time */
+    audit_log: Option<Arc<AuditLog>>,
+}
+
+impl Default for SecretServiceServer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// SecretServiceServer matches the API of proto/secret_service.proto but operates on our own Crypto
 /// API structures
 impl SecretServiceServer {
-    /// A fresh secret service creates an empty HashMap for the keys.
+    /// A fresh secret service keeps the keys in memory and does not audit signing requests.
     pub fn new() -> Self {
+        Self::with_storage(Box::new(InMemoryStorage::new()), None)
+    }
+
+    /// A secret service holding the keys of `storage` and recording signing requests in
+    /// `audit_log` if given.
+    pub fn with_storage(storage: Box<dyn KeyStorage>, audit_log: Option<AuditLog>) -> Self {
         SecretServiceServer {
-            keys: Arc::new(RwLock::new(HashMap::new())),
+            keys: Arc::new(RwLock::new(storage)),
+            audit_log: audit_log.map(Arc::new),
         }
     }
 
+    /// Sets up the storage and audit log from the config. Without a key storage directory the
+    /// keys are kept in memory only.
+    pub fn from_config(config: &SecretServiceConfig) -> Result<Self> {
+        let storage: Box<dyn KeyStorage> = match &config.key_storage_dir {
+            Some(dir) => {
+                let passphrase_file = config.passphrase_file.as_ref().ok_or_else(|| {
+                    format_err!("A passphrase file is required to encrypt the key storage")
+                })?;
+                let storage =
+                    EncryptedFileStorage::open(dir, &read_passphrase_file(passphrase_file)?)?;
+                info!(
+                    "Opened the key storage {:?} holding {} keys",
+                    dir,
+                    storage.key_ids().len()
+                );
+                Box::new(storage)
+            }
+            None => {
+                warn!("No key storage directory configured, keys are lost on exit");
+                Box::new(InMemoryStorage::new())
+            }
+        };
+        let audit_log = match &config.audit_log_file {
+            Some(path) => Some(AuditLog::open(path)?),
+            None => None,
+        };
+        Ok(Self::with_storage(storage, audit_log))
+    }
+
     /// Generates a new secret key (for now this is the code for testing).
     pub fn generate_key_inner(&mut self, spec: KeyType) -> Result<KeyID> {
         let seed: [u8; 32] = EntropyRng::new().gen();
@@ -85,7 +133,7 @@ impl SecretServiceServer {
             .keys
             .write()
             .expect("[generating new key] acquire keys lock");
-        keys.insert(keyid, private_key);
+        keys.insert(keyid, private_key)?;
         Ok(result)
     }
 
@@ -107,6 +155,26 @@ impl SecretServiceServer {
         keys.get(keyid)
             .map(|secret_key| secret_key.sign_message(message))
     }
+
+    /// Returns the ids of all the stored keys along with their public keys, ordered by key id.
+    pub fn list_keys_inner(&self) -> Vec<(KeyID, GenericPublicKey)> {
+        let keys = self.keys.read().expect("[listing keys] acquire keys lock");
+        let mut key_ids = keys.key_ids();
+        key_ids.sort_by_key(|keyid| keyid.to_vec());
+        key_ids
+            .into_iter()
+            .filter_map(|keyid| {
+                let public_key = keys.get(&keyid).map(GenericPublicKey::from)?;
+                Some((keyid, public_key))
+            })
+            .collect()
+    }
+
+    /// Deletes a secret key, returns whether it existed.
+    pub fn delete_key_inner(&mut self, keyid: &KeyID) -> Result<bool> {
+        let mut keys = self.keys.write().expect("[deleting key] acquire keys lock");
+        keys.remove(keyid)
+    }
 }
 
 /// SecretServiceServer implements the proto trait secret_service_grpc::SecretService.
@@ -189,6 +257,71 @@ impl secret_service_grpc::SecretService for SecretServiceServer {
         } else {
             response.set_code(ErrorCode::Unspecified);
         }
+        if let Some(audit_log) = &self.audit_log {
+            if let Err(e) =
+                audit_log.record_sign(keyid_raw_bytes, message_raw_bytes, response.get_code())
+            {
+                error!(
+                    "Unable to record the signing request in the audit log: {}",
+                    e
+                );
+                response = SignResponse::new();
+                response.set_code(ErrorCode::Unspecified);
+            }
+        }
+        provide_grpc_response(Ok(response), ctx, sink);
+    }
+
+    /// Lists the stored keys answering a ListKeysRequest with a ListKeysResponse.
+    fn list_keys(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: ListKeysRequest,
+        sink: ::grpcio::UnarySink<ListKeysResponse>,
+    ) {
+        let mut response = ListKeysResponse::new();
+        let keys = self
+            .list_keys_inner()
+            .into_iter()
+            .map(|(keyid, public_key)| {
+                let mut key_info = KeyInfo::new();
+                key_info.set_key_id(keyid.to_vec());
+                key_info.set_key_type(match public_key {
+                    GenericPublicKey::Ed(_) => KeyType::Ed25519,
+                    GenericPublicKey::BLS(_) => KeyType::BLS12381,
+                });
+                key_info.set_public_key(public_key.to_bytes());
+                key_info
+            })
+            .collect();
+        response.set_keys(RepeatedField::from_vec(keys));
+        response.set_code(ErrorCode::Success);
+        provide_grpc_response(Ok(response), ctx, sink);
+    }
+
+    /// Deletes a key answering a DeleteKeyRequest with a DeleteKeyResponse.
+    fn delete_key(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: DeleteKeyRequest,
+        sink: ::grpcio::UnarySink<DeleteKeyResponse>,
+    ) {
+        let mut response = DeleteKeyResponse::new();
+        let keyid_raw_bytes = req.get_key_id();
+        if keyid_raw_bytes.len() != HashValue::LENGTH {
+            response.set_code(ErrorCode::WrongLength);
+        } else if let Ok(keyid) = HashValue::from_slice(keyid_raw_bytes) {
+            match self.delete_key_inner(&KeyID(keyid)) {
+                Ok(true) => response.set_code(ErrorCode::Success),
+                Ok(false) => response.set_code(ErrorCode::KeyIdNotFound),
+                Err(e) => {
+                    error!("Unable to delete a key: {}", e);
+                    response.set_code(ErrorCode::Unspecified);
+                }
+            }
+        } else {
+            response.set_code(ErrorCode::Unspecified);
+        }
         provide_grpc_response(Ok(response), ctx, sink);
     }
 }
@@ -0,0 +1,149 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    crypto_wrappers::{GenericPrivateKey, GenericPublicKey, KeyID},
+    key_storage::{read_passphrase_file, EncryptedFileStorage, KeyStorage, ScryptKdf},
+};
+use crypto::hash::HashValue;
+use nextgen_crypto::{bls12381::BLS12381PrivateKey, ed25519::Ed25519PrivateKey, traits::*};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{fs, path::Path};
+use tempfile::{NamedTempFile, TempDir};
+
+fn light_kdf() -> ScryptKdf {
+    ScryptKdf::with_cost(4, 8, 1)
+}
+
+fn open(dir: &Path, passphrase: &[u8]) -> EncryptedFileStorage {
+    EncryptedFileStorage::open_with_kdf(dir, passphrase, light_kdf()).unwrap()
+}
+
+fn public_key(storage: &EncryptedFileStorage, keyid: &KeyID) -> GenericPublicKey {
+    storage.get(keyid).map(GenericPublicKey::from).unwrap()
+}
+
+#[test]
+fn test_keys_survive_reopening() {
+    let dir = TempDir::new().unwrap();
+    let mut rng = StdRng::from_seed([7u8; 32]);
+    let ed_keyid = KeyID(HashValue::random());
+    let bls_keyid = KeyID(HashValue::random());
+
+    let mut storage = open(dir.path(), b"operator passphrase");
+    storage
+        .insert(
+            ed_keyid.clone(),
+            GenericPrivateKey::Ed(Ed25519PrivateKey::generate_for_testing(&mut rng)),
+        )
+        .unwrap();
+    storage
+        .insert(
+            bls_keyid.clone(),
+            GenericPrivateKey::BLS(BLS12381PrivateKey::generate_for_testing(&mut rng)),
+        )
+        .unwrap();
+    let ed_public_key = public_key(&storage, &ed_keyid);
+    let bls_public_key = public_key(&storage, &bls_keyid);
+
+    let reopened = open(dir.path(), b"operator passphrase");
+    assert_eq!(reopened.key_ids().len(), 2);
+    assert_eq!(public_key(&reopened, &ed_keyid), ed_public_key);
+    assert_eq!(public_key(&reopened, &bls_keyid), bls_public_key);
+
+    // The key material is not stored in plain text.
+    let key_file = fs::read_to_string(dir.path().join(format!("{:x}.key", ed_keyid.0))).unwrap();
+    let private_key = hex::encode(reopened.get(&ed_keyid).unwrap().to_bytes());
+    assert!(!key_file.contains(&private_key));
+}
+
+#[test]
+fn test_wrong_passphrase() {
+    let dir = TempDir::new().unwrap();
+    open(dir.path(), b"operator passphrase");
+
+    // Noticed even without any key stored.
+    assert!(
+        EncryptedFileStorage::open_with_kdf(dir.path(), b"wrong passphrase", light_kdf()).is_err()
+    );
+    assert!(EncryptedFileStorage::open_with_kdf(dir.path(), b"", light_kdf()).is_err());
+}
+
+#[test]
+fn test_swapped_key_files() {
+    let dir = TempDir::new().unwrap();
+    let mut rng = StdRng::from_seed([8u8; 32]);
+    let keyid1 = KeyID(HashValue::random());
+    let keyid2 = KeyID(HashValue::random());
+
+    let mut storage = open(dir.path(), b"operator passphrase");
+    for keyid in &[&keyid1, &keyid2] {
+        storage
+            .insert(
+                (*keyid).clone(),
+                GenericPrivateKey::Ed(Ed25519PrivateKey::generate_for_testing(&mut rng)),
+            )
+            .unwrap();
+    }
+
+    let path1 = dir.path().join(format!("{:x}.key", keyid1.0));
+    let path2 = dir.path().join(format!("{:x}.key", keyid2.0));
+    let content1 = fs::read(&path1).unwrap();
+    fs::write(&path1, fs::read(&path2).unwrap()).unwrap();
+    fs::write(&path2, content1).unwrap();
+    assert!(
+        EncryptedFileStorage::open_with_kdf(dir.path(), b"operator passphrase", light_kdf())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_remove() {
+    let dir = TempDir::new().unwrap();
+    let mut rng = StdRng::from_seed([9u8; 32]);
+    let keyid = KeyID(HashValue::random());
+
+    let mut storage = open(dir.path(), b"operator passphrase");
+    storage
+        .insert(
+            keyid.clone(),
+            GenericPrivateKey::Ed(Ed25519PrivateKey::generate_for_testing(&mut rng)),
+        )
+        .unwrap();
+    assert!(storage.remove(&keyid).unwrap());
+    assert!(!storage.remove(&keyid).unwrap());
+    assert!(storage.get(&keyid).is_none());
+
+    let reopened = open(dir.path(), b"operator passphrase");
+    assert!(reopened.key_ids().is_empty());
+}
+
+#[test]
+fn test_remove_keeps_key_when_file_delete_fails() {
+    let dir = TempDir::new().unwrap();
+    let mut rng = StdRng::from_seed([10u8; 32]);
+    let keyid = KeyID(HashValue::random());
+
+    let mut storage = open(dir.path(), b"operator passphrase");
+    storage
+        .insert(
+            keyid.clone(),
+            GenericPrivateKey::Ed(Ed25519PrivateKey::generate_for_testing(&mut rng)),
+        )
+        .unwrap();
+    for entry in fs::read_dir(dir.path()).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+    assert!(storage.remove(&keyid).is_err());
+    assert!(storage.get(&keyid).is_some());
+}
+
+#[test]
+fn test_read_passphrase_file() {
+    let file = NamedTempFile::new().unwrap();
+    fs::write(file.path(), b"operator passphrase\n").unwrap();
+    assert_eq!(
+        read_passphrase_file(file.path()).unwrap(),
+        b"operator passphrase".to_vec()
+    );
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::proto::{
-    secret_service::{ErrorCode, GenerateKeyRequest, PublicKeyRequest},
+    secret_service::{
+        DeleteKeyRequest, ErrorCode, GenerateKeyRequest, ListKeysRequest, PublicKeyRequest,
+    },
     secret_service_grpc::SecretServiceClient,
 };
 use config::config::{NodeConfig, NodeConfigHelpers};
@@ -149,3 +151,37 @@ fn test_generate_and_retrieve_key() {
     let is_successful = response.get_code() == ErrorCode::KeyIdNotFound;
     assert!(is_successful);
 }
+
+#[test]
+fn test_list_and_delete_key() {
+    let node_config = NodeConfigHelpers::get_single_node_test_config(true);
+    let client = create_secret_service_node_and_client(node_config.clone());
+
+    let mut gen_req: GenerateKeyRequest = GenerateKeyRequest::new();
+    gen_req.set_spec(KeyType::BLS12381);
+    let response = client.generate_key(&gen_req).unwrap();
+    assert_eq!(response.get_code(), ErrorCode::Success);
+    let keyid = response.get_key_id().to_vec();
+
+    let response = client.list_keys(&ListKeysRequest::new()).unwrap();
+    assert_eq!(response.get_code(), ErrorCode::Success);
+    let keys = response.get_keys();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].get_key_id(), keyid.as_slice());
+    assert_eq!(keys[0].get_key_type(), KeyType::BLS12381);
+
+    let mut del_req: DeleteKeyRequest = DeleteKeyRequest::new();
+    del_req.set_key_id(keyid.clone());
+    let response = client.delete_key(&del_req).unwrap();
+    assert_eq!(response.get_code(), ErrorCode::Success);
+
+    // deleting again or with a malformed keyid fails
+    let response = client.delete_key(&del_req).unwrap();
+    assert_eq!(response.get_code(), ErrorCode::KeyIdNotFound);
+    del_req.set_key_id([0, 1, 2, 4].to_vec());
+    let response = client.delete_key(&del_req).unwrap();
+    assert_eq!(response.get_code(), ErrorCode::WrongLength);
+
+    let response = client.list_keys(&ListKeysRequest::new()).unwrap();
+    assert!(response.get_keys().is_empty());
+}
@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    proto::secret_service::KeyType,
+    audit_log::AuditLog,
+    key_storage::{EncryptedFileStorage, ScryptKdf},
+    proto::secret_service::{ErrorCode, KeyType},
     secret_service_server::{KeyID, SecretServiceServer},
 };
 use crypto::hash::HashValue;
 use nextgen_crypto::traits::{Signature, ValidKey};
+use std::fs;
+use tempfile::{NamedTempFile, TempDir};
 
 /////////////////////////////////////////////////////////////////////////////////////
 // These tests check interoperability of key_generation,                           //
@@ -160,3 +164,100 @@ fn test_ed25519_sign() {
         );
     }
 }
+
+#[test]
+fn test_list_and_delete_keys() {
+    let mut ss_service = SecretServiceServer::new();
+    assert!(ss_service.list_keys_inner().is_empty());
+
+    let keyid1 = ss_service.generate_key_inner(KeyType::Ed25519).unwrap();
+    let keyid2 = ss_service.generate_key_inner(KeyType::BLS12381).unwrap();
+    let listed = ss_service.list_keys_inner();
+    assert_eq!(listed.len(), 2);
+    for (keyid, public_key) in &listed {
+        assert_eq!(
+            Some(public_key),
+            ss_service.get_public_key_inner(keyid).as_ref(),
+            "Listed public key does not match"
+        );
+    }
+    assert!(
+        listed[0].0.to_vec() < listed[1].0.to_vec(),
+        "Keys are not ordered"
+    );
+
+    assert!(ss_service.delete_key_inner(&keyid1).unwrap());
+    assert!(!ss_service.delete_key_inner(&keyid1).unwrap());
+    assert!(ss_service.get_public_key_inner(&keyid1).is_none());
+    assert!(ss_service
+        .sign_inner(&keyid1, &HashValue::random())
+        .is_none());
+    let listed = ss_service.list_keys_inner();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].0 == keyid2);
+}
+
+#[test]
+fn test_keys_persist_across_restarts() {
+    let dir = TempDir::new().unwrap();
+    let open_server = || {
+        let storage = EncryptedFileStorage::open_with_kdf(
+            dir.path(),
+            b"operator passphrase",
+            ScryptKdf::with_cost(4, 8, 1),
+        )
+        .unwrap();
+        SecretServiceServer::with_storage(Box::new(storage), None)
+    };
+
+    let mut ss_service = open_server();
+    let keyid = ss_service.generate_key_inner(KeyType::Ed25519).unwrap();
+    let public_key = ss_service.get_public_key_inner(&keyid).unwrap();
+    drop(ss_service);
+
+    let ss_service = open_server();
+    let message_hash = HashValue::random();
+    let signature = ss_service.sign_inner(&keyid, &message_hash).unwrap();
+    assert!(
+        signature.verify(&message_hash, &public_key).is_ok(),
+        "Key restored from disk does not sign"
+    );
+}
+
+#[test]
+fn test_audit_log() {
+    let file = NamedTempFile::new().unwrap();
+    let audit_log = AuditLog::open(file.path()).unwrap();
+    let message_hash = HashValue::random();
+    audit_log
+        .record_sign(
+            &[1; HashValue::LENGTH],
+            &message_hash.to_vec(),
+            ErrorCode::Success,
+        )
+        .unwrap();
+    audit_log
+        .record_sign(&[2, 3], &[], ErrorCode::WrongLength)
+        .unwrap();
+
+    // Entries are appended to an existing log.
+    let audit_log = AuditLog::open(file.path()).unwrap();
+    audit_log
+        .record_sign(
+            &[4; HashValue::LENGTH],
+            &[5; HashValue::LENGTH],
+            ErrorCode::KeyIdNotFound,
+        )
+        .unwrap();
+
+    let content = fs::read_to_string(file.path()).unwrap();
+    let lines: Vec<_> = content.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(&format!(
+        "sign key_id={} message_hash={:x} code=Success",
+        hex::encode([1; HashValue::LENGTH]),
+        message_hash
+    )));
+    assert!(lines[1].ends_with("sign key_id=0203 message_hash= code=WrongLength"));
+    assert!(lines[2].ends_with("code=KeyIdNotFound"));
+}
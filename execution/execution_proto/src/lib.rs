@@ -13,6 +13,7 @@ use failure::prelude::*;
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
 use types::{
+    block_metadata::BlockMetadata,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{SignedTransaction, TransactionListWithProof, TransactionStatus, Version},
     validator_set::ValidatorSet,
@@ -30,6 +31,12 @@ pub struct ExecuteBlockRequest {
 
     /// Id of current block.
     pub block_id: HashValue,
+
+    /// Height of current block.
+    pub block_height: u64,
+
+    /// Timestamp the proposer put in current block.
+    pub block_timestamp_usecs: u64,
 }
 
 impl ExecuteBlockRequest {
@@ -37,13 +44,20 @@ impl ExecuteBlockRequest {
         transactions: Vec<SignedTransaction>,
         parent_block_id: HashValue,
         block_id: HashValue,
+        block_metadata: BlockMetadata,
     ) -> Self {
         ExecuteBlockRequest {
             transactions,
             parent_block_id,
             block_id,
+            block_height: block_metadata.height(),
+            block_timestamp_usecs: block_metadata.timestamp_usecs(),
         }
     }
+
+    pub fn block_metadata(&self) -> BlockMetadata {
+        BlockMetadata::new(self.block_height, self.block_timestamp_usecs)
+    }
 }
 
 #[derive(Arbitrary, Clone, Debug, Eq, PartialEq)]
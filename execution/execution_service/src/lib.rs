@@ -51,11 +51,13 @@ impl execution_proto::proto::execution_grpc::Execution for ExecutionService {
     ) {
         match ExecuteBlockRequest::from_proto(request) {
             Ok(req) => {
+                let block_metadata = req.block_metadata();
                 let fut = process_response(
                     self.executor.execute_block(
                         req.transactions,
                         req.parent_block_id,
                         req.block_id,
+                        block_metadata,
                     ),
                     sink,
                 )
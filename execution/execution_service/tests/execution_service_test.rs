@@ -17,6 +17,7 @@ use std::sync::Arc;
 use types::{
     account_address::AccountAddress,
     account_config,
+    block_metadata::BlockMetadata,
     transaction::{RawTransaction, SignedTransaction},
 };
 use vm_genesis::encode_mint_program;
@@ -58,7 +59,8 @@ fn test_execution_service_basic() {
     let txns = (0..version)
         .map(|i| encode_mint_transaction(i, &faucet_keypair))
         .collect();
-    let execute_block_request = ExecuteBlockRequest::new(txns, parent_block_id, block_id);
+    let execute_block_request =
+        ExecuteBlockRequest::new(txns, parent_block_id, block_id, BlockMetadata::default());
     let execute_block_response = execution_client
         .execute_block(execute_block_request)
         .unwrap();
@@ -21,6 +21,7 @@ use types::{
     account_address::AccountAddress,
     account_config::{association_address, get_account_resource_or_default},
     account_state_blob::AccountStateWithProof,
+    block_metadata::BlockMetadata,
     get_with_proof::{verify_update_to_latest_ledger_response, RequestItem},
     test_helpers::transaction_test_helpers::get_test_signed_txn as get_test_signed_txn_proto,
     transaction::{
@@ -146,8 +147,12 @@ fn test_execution_with_storage() {
         ));
     }
 
-    let execute_block_request =
-        ExecuteBlockRequest::new(block1.clone(), *GENESIS_BLOCK_ID, block1_id);
+    let execute_block_request = ExecuteBlockRequest::new(
+        block1.clone(),
+        *GENESIS_BLOCK_ID,
+        block1_id,
+        BlockMetadata::new(1, 1),
+    );
     let execute_block_response = execution_client
         .execute_block(execute_block_request)
         .unwrap();
@@ -379,7 +384,12 @@ fn test_execution_with_storage() {
     assert_eq!(account3_received_events.len(), 3);
 
     // Execution the 2nd block.
-    let execute_block_request = ExecuteBlockRequest::new(block2.clone(), block1_id, block2_id);
+    let execute_block_request = ExecuteBlockRequest::new(
+        block2.clone(),
+        block1_id,
+        block2_id,
+        BlockMetadata::new(2, 2),
+    );
     let execute_block_response = execution_client
         .execute_block(execute_block_request)
         .unwrap();
@@ -28,6 +28,7 @@ use storage_client::{StorageRead, StorageWrite, VerifiedStateView};
 use types::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     ledger_info::LedgerInfoWithSignatures,
     proof::SparseMerkleProof,
     transaction::{
@@ -186,6 +187,7 @@ where
                 transactions,
                 parent_id,
                 id,
+                block_metadata,
                 resp_sender,
             } => {
                 if let Mode::Syncing = self.mode {
@@ -202,7 +204,13 @@ where
                         block.queue_execute_block_response_sender(resp_sender);
                     }
                     None => {
-                        let block = TransactionBlock::new(transactions, parent_id, id, resp_sender);
+                        let block = TransactionBlock::new(
+                            transactions,
+                            parent_id,
+                            id,
+                            block_metadata,
+                            resp_sender,
+                        );
                         // If `add_block` errors, we return the error immediately. Otherwise the
                         // response will be returned once the block is executed.
                         if let Err(err) = self.block_tree.add_block(block) {
@@ -312,69 +320,74 @@ where
             .skip(num_txns_to_skip as usize)
             .unzip();
 
-        // Construct a StateView and pass the transactions to VM.
-        let db_root_hash = self.committed_state_tree.root_hash();
-        let state_view = VerifiedStateView::new(
-            Arc::clone(&self.storage_read_client),
-            db_root_hash,
-            &self.committed_state_tree,
-        );
-        let vm_outputs = {
-            let time = std::time::Instant::now();
-            let out = V::execute_block(transactions.clone(), &self.vm_config, &state_view);
-            OP_COUNTERS.observe(
-                "vm_execute_chunk_time_us",
-                time.elapsed().as_micros() as f64,
-            );
-            out
-        };
+        // A chunk may span several blocks. Every transaction has to see the block metadata it saw
+        // when consensus executed it, so we execute the transactions of each block separately,
+        // each on top of the state left by the previous one.
+        let mut state_tree = Rc::clone(&self.committed_state_tree);
+        let mut transaction_accumulator = Rc::clone(&self.committed_transaction_accumulator);
+        let mut txns_to_commit = vec![];
+        let mut start = 0;
+        while start < transactions.len() {
+            let block_metadata = infos[start].block_metadata();
+            let end = infos[start..]
+                .iter()
+                .position(|info| info.block_metadata() != block_metadata)
+                .map_or(infos.len(), |len| start + len);
+            let output = self.execute_chunk_transactions(
+                &transactions[start..end],
+                block_metadata,
+                Rc::clone(&state_tree),
+                Rc::clone(&transaction_accumulator),
+            )?;
 
-        // Since other validators have committed these transactions, their status should all be
-        // TransactionStatus::Keep.
-        for output in &vm_outputs {
-            if let TransactionStatus::Discard(_) = output.status() {
-                bail!("Syncing transactions that should be discarded.");
+            // Since we have verified the proofs, we just need to verify that each TransactionInfo
+            // object matches what we have computed locally.
+            for ((txn, txn_data), (i, txn_info)) in itertools::zip_eq(
+                itertools::zip_eq(&transactions[start..end], output.transaction_data()),
+                infos[start..end].iter().enumerate(),
+            ) {
+                let i = start + i;
+                ensure!(
+                    txn_info.state_root_hash() == txn_data.state_root_hash(),
+                    "State root hashes do not match for {}-th transaction in chunk.",
+                    i,
+                );
+                ensure!(
+                    txn_info.event_root_hash() == txn_data.event_root_hash(),
+                    "Event root hashes do not match for {}-th transaction in chunk.",
+                    i,
+                );
+                ensure!(
+                    txn_info.gas_used() == txn_data.gas_used(),
+                    "Gas used do not match for {}-th transaction in chunk.",
+                    i,
+                );
+                let vm_status = match txn_data.status() {
+                    TransactionStatus::Keep(vm_status) => vm_status,
+                    TransactionStatus::Discard(vm_status) => bail!(
+                        "The {}-th transaction in chunk was discarded with status {:?}.",
+                        i,
+                        vm_status,
+                    ),
+                };
+                ensure!(
+                    txn_info.vm_status() == vm_status,
+                    "VM statuses do not match for {}-th transaction in chunk.",
+                    i,
+                );
+                txns_to_commit.push(TransactionToCommit::new(
+                    txn.clone(),
+                    txn_data.account_blobs().clone(),
+                    txn_data.events().to_vec(),
+                    txn_data.gas_used(),
+                    block_metadata,
+                    vm_status.clone(),
+                ));
             }
-        }
-
-        let (account_to_btree, account_to_proof) = state_view.into();
-        let output = Self::process_vm_outputs(
-            account_to_btree,
-            account_to_proof,
-            &transactions,
-            vm_outputs,
-            Rc::clone(&self.committed_state_tree),
-            Rc::clone(&self.committed_transaction_accumulator),
-        )?;
 
-        // Since we have verified the proofs, we just need to verify that each TransactionInfo
-        // object matches what we have computed locally.
-        let mut txns_to_commit = vec![];
-        for ((txn, txn_data), (i, txn_info)) in itertools::zip_eq(
-            itertools::zip_eq(transactions, output.transaction_data()),
-            infos.into_iter().enumerate(),
-        ) {
-            ensure!(
-                txn_info.state_root_hash() == txn_data.state_root_hash(),
-                "State root hashes do not match for {}-th transaction in chunk.",
-                i,
-            );
-            ensure!(
-                txn_info.event_root_hash() == txn_data.event_root_hash(),
-                "Event root hashes do not match for {}-th transaction in chunk.",
-                i,
-            );
-            ensure!(
-                txn_info.gas_used() == txn_data.gas_used(),
-                "Gas used do not match for {}-th transaction in chunk.",
-                i,
-            );
-            txns_to_commit.push(TransactionToCommit::new(
-                txn,
-                txn_data.account_blobs().clone(),
-                txn_data.events().to_vec(),
-                txn_data.gas_used(),
-            ));
+            state_tree = output.clone_state_tree();
+            transaction_accumulator = output.clone_transaction_accumulator();
+            start = end;
         }
 
         // If this is the last chunk corresponding to this ledger info, send the ledger info to
@@ -390,7 +403,7 @@ where
                 ledger_info_with_sigs
                     .ledger_info()
                     .transaction_accumulator_hash()
-                    == output.clone_transaction_accumulator().root_hash(),
+                    == transaction_accumulator.root_hash(),
                 "Root hash in ledger info does not match local computation."
             );
             Some(ledger_info_with_sigs)
@@ -403,8 +416,8 @@ where
             ledger_info_to_commit.clone(),
         )?;
 
-        self.committed_state_tree = output.clone_state_tree();
-        self.committed_transaction_accumulator = output.clone_transaction_accumulator();
+        self.committed_state_tree = state_tree;
+        self.committed_transaction_accumulator = transaction_accumulator;
         if let Some(ledger_info_with_sigs) = ledger_info_to_commit {
             self.committed_timestamp_usecs = ledger_info_with_sigs.ledger_info().timestamp_usecs();
             self.block_tree
@@ -419,6 +432,57 @@ where
         Ok(())
     }
 
+    /// Executes transactions of a single block received during synchronization with the metadata
+    /// of that block, on top of the given state tree and transaction accumulator.
+    fn execute_chunk_transactions(
+        &self,
+        transactions: &[SignedTransaction],
+        block_metadata: BlockMetadata,
+        previous_state_tree: Rc<SparseMerkleTree>,
+        previous_transaction_accumulator: Rc<Accumulator<TransactionAccumulatorHasher>>,
+    ) -> Result<ProcessedVMOutput> {
+        // Construct a StateView and pass the transactions to VM.
+        let db_root_hash = self.committed_state_tree.root_hash();
+        let state_view = VerifiedStateView::new(
+            Arc::clone(&self.storage_read_client),
+            db_root_hash,
+            &previous_state_tree,
+        );
+        let vm_outputs = {
+            let time = std::time::Instant::now();
+            let out = V::execute_block_with_metadata(
+                transactions.to_vec(),
+                block_metadata,
+                &self.vm_config,
+                &state_view,
+            );
+            OP_COUNTERS.observe(
+                "vm_execute_chunk_time_us",
+                time.elapsed().as_micros() as f64,
+            );
+            out
+        };
+
+        // Since other validators have committed these transactions, their status should all be
+        // TransactionStatus::Keep.
+        for output in &vm_outputs {
+            if let TransactionStatus::Discard(_) = output.status() {
+                bail!("Syncing transactions that should be discarded.");
+            }
+        }
+
+        let (account_to_btree, account_to_proof) = state_view.into();
+        Self::process_vm_outputs(
+            account_to_btree,
+            account_to_proof,
+            transactions,
+            vm_outputs,
+            block_metadata,
+            previous_state_tree,
+            previous_transaction_accumulator,
+        )
+    }
+
     /// Verifies the proofs using provided ledger info. Also verifies that the version of the first
     /// transaction matches the lastest committed transaction. If the first few transaction happens
     /// to be older, returns how many need to be skipped and the first version to be committed.
@@ -495,12 +559,14 @@ where
                     .expect("All blocks in self.blocks_to_store should have finished execution.")
                     .transaction_data(),
             ) {
-                if let TransactionStatus::Keep(_) = txn_data.status() {
+                if let TransactionStatus::Keep(vm_status) = txn_data.status() {
                     txns_to_commit.push(TransactionToCommit::new(
                         txn.clone(),
                         txn_data.account_blobs().clone(),
                         txn_data.events().to_vec(),
                         txn_data.gas_used(),
+                        block.block_metadata(),
+                        vm_status.clone(),
                     ));
                     num_accounts_created += txn_data.num_account_created();
                 }
@@ -604,10 +670,12 @@ where
             db_root_hash,
             &previous_state_tree,
         );
+        let block_metadata = block_to_execute.block_metadata();
         let vm_outputs = {
             let time = std::time::Instant::now();
-            let out = V::execute_block(
+            let out = V::execute_block_with_metadata(
                 block_to_execute.transactions().to_vec(),
+                block_metadata,
                 &self.vm_config,
                 &state_view,
             );
@@ -633,6 +701,7 @@ where
             account_to_proof,
             block_to_execute.transactions(),
             vm_outputs,
+            block_metadata,
             previous_state_tree,
             previous_transaction_accumulator,
         ) {
@@ -694,6 +763,7 @@ where
         account_to_proof: HashMap<HashValue, SparseMerkleProof>,
         transactions: &[SignedTransaction],
         vm_outputs: Vec<TransactionOutput>,
+        block_metadata: BlockMetadata,
         previous_state_tree: Rc<SparseMerkleTree>,
         previous_transaction_accumulator: Rc<Accumulator<TransactionAccumulatorHasher>>,
     ) -> Result<ProcessedVMOutput> {
@@ -723,7 +793,7 @@ where
                 .append(vm_output.events().iter().map(CryptoHash::hash).collect());
 
             match vm_output.status() {
-                TransactionStatus::Keep(_) => {
+                TransactionStatus::Keep(vm_status) => {
                     ensure!(
                         !vm_output.write_set().is_empty(),
                         "Transaction with empty write set should be discarded.",
@@ -735,6 +805,8 @@ where
                         state_tree.root_hash(),
                         event_tree.root_hash(),
                         vm_output.gas_used(),
+                        block_metadata,
+                        vm_status.clone(),
                     );
                     txn_info_hashes.push(txn_info.hash());
                 }
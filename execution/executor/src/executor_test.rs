@@ -25,6 +25,7 @@ use storage_proto::proto::storage_grpc::create_storage;
 use storage_service::StorageService;
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
+    block_metadata::BlockMetadata,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::SignedTransaction,
 };
@@ -86,9 +87,10 @@ fn execute_and_commit_block(executor: &TestExecutor, txn_index: u64) {
     };
     let id = gen_block_id(txn_index + 1);
 
-    let response = block_on(executor.execute_block(vec![txn], parent_block_id, id))
-        .unwrap()
-        .unwrap();
+    let response =
+        block_on(executor.execute_block(vec![txn], parent_block_id, id, BlockMetadata::default()))
+            .unwrap()
+            .unwrap();
     assert_eq!(response.version(), txn_index + 1);
 
     let ledger_info = gen_ledger_info(txn_index + 1, response.root_hash(), id, txn_index + 1);
@@ -179,10 +181,14 @@ fn test_executor_status() {
     let parent_block_id = *GENESIS_BLOCK_ID;
     let block_id = gen_block_id(1);
 
-    let response =
-        block_on(executor.execute_block(vec![txn0, txn1, txn2], parent_block_id, block_id))
-            .unwrap()
-            .unwrap();
+    let response = block_on(executor.execute_block(
+        vec![txn0, txn1, txn2],
+        parent_block_id,
+        block_id,
+        BlockMetadata::default(),
+    ))
+    .unwrap()
+    .unwrap();
 
     assert_eq!(
         vec![KEEP_STATUS, KEEP_STATUS, DISCARD_STATUS],
@@ -201,7 +207,8 @@ fn test_executor_one_block() {
     let txns = (0..version)
         .map(|i| encode_mint_transaction(gen_address(i), 100))
         .collect();
-    let execute_block_future = executor.execute_block(txns, parent_block_id, block_id);
+    let execute_block_future =
+        executor.execute_block(txns, parent_block_id, block_id, BlockMetadata::default());
     let execute_block_response = block_on(execute_block_future).unwrap().unwrap();
     assert_eq!(execute_block_response.version(), 100);
 
@@ -233,8 +240,12 @@ fn test_executor_execute_same_block_multiple_times() {
         let executor = TestExecutor::new();
         let mut responses = vec![];
         for _i in 0..100 {
-            let execute_block_future =
-                executor.execute_block(txns.clone(), parent_block_id, block_id);
+            let execute_block_future = executor.execute_block(
+                txns.clone(),
+                parent_block_id,
+                block_id,
+                BlockMetadata::default(),
+            );
             let execute_block_response = block_on(execute_block_future).unwrap().unwrap();
             responses.push(execute_block_response);
         }
@@ -245,8 +256,12 @@ fn test_executor_execute_same_block_multiple_times() {
         let executor = TestExecutor::new();
         let mut futures = vec![];
         for _i in 0..100 {
-            let execute_block_future =
-                executor.execute_block(txns.clone(), parent_block_id, block_id);
+            let execute_block_future = executor.execute_block(
+                txns.clone(),
+                parent_block_id,
+                block_id,
+                BlockMetadata::default(),
+            );
             futures.push(execute_block_future);
         }
         let mut responses: Vec<_> = futures
@@ -288,13 +303,30 @@ fn test_executor_execute_chunk() {
             let txn = encode_mint_transaction(gen_address(i), 100);
             txns.push(txn);
         }
-        let id = gen_block_id(1);
-
-        let response = block_on(executor.execute_block(txns.clone(), *GENESIS_BLOCK_ID, id))
-            .unwrap()
-            .unwrap();
+        // Put the transactions in two blocks with different metadata, so the second batch spans
+        // both blocks.
+        let (block1_txns, block2_txns) = txns.split_at(first_batch_size as usize + 10);
+        let block1_id = gen_block_id(1);
+        let block2_id = gen_block_id(2);
+
+        block_on(executor.execute_block(
+            block1_txns.to_vec(),
+            *GENESIS_BLOCK_ID,
+            block1_id,
+            BlockMetadata::new(1, 100),
+        ))
+        .unwrap()
+        .unwrap();
+        let response = block_on(executor.execute_block(
+            block2_txns.to_vec(),
+            block1_id,
+            block2_id,
+            BlockMetadata::new(2, 200),
+        ))
+        .unwrap()
+        .unwrap();
         let ledger_version = txns.len() as u64;
-        let ledger_info = gen_ledger_info(ledger_version, response.root_hash(), id, 1);
+        let ledger_info = gen_ledger_info(ledger_version, response.root_hash(), block2_id, 1);
         block_on(executor.commit_block(ledger_info.clone()))
             .unwrap()
             .unwrap();
@@ -409,6 +441,7 @@ fn run_transactions_naive(transactions: Vec<SignedTransaction>) -> HashValue {
         },
         *GENESIS_BLOCK_ID,
         gen_block_id(1),
+        BlockMetadata::default(),
     ))
     .unwrap()
     .unwrap();
@@ -418,9 +451,14 @@ fn run_transactions_naive(transactions: Vec<SignedTransaction>) -> HashValue {
         let parent_block_id = gen_block_id(i as u64 + 1);
         // when i = 0, id should be 2.
         let id = gen_block_id(i as u64 + 2);
-        let response = block_on(executor.execute_block(vec![txn], parent_block_id, id))
-            .unwrap()
-            .unwrap();
+        let response = block_on(executor.execute_block(
+            vec![txn],
+            parent_block_id,
+            id,
+            BlockMetadata::default(),
+        ))
+        .unwrap()
+        .unwrap();
 
         root_hash = response.root_hash();
         let ledger_info = gen_ledger_info(i as u64 + 2, root_hash, id, i as u64 + 1);
@@ -452,15 +490,15 @@ proptest! {
         let executor = TestExecutor::new();
 
         let response_a = block_on(executor.execute_block(
-            block_a.txns.clone(), block_a.parent_id, block_a.id,
+            block_a.txns.clone(), block_a.parent_id, block_a.id, BlockMetadata::default(),
         )).unwrap().unwrap();
         prop_assert_eq!(response_a.version(), a_size);
         let response_b = block_on(executor.execute_block(
-            block_b.txns.clone(), block_b.parent_id, block_b.id,
+            block_b.txns.clone(), block_b.parent_id, block_b.id, BlockMetadata::default(),
         )).unwrap().unwrap();
         prop_assert_eq!(response_b.version(), a_size + b_size);
         let response_c = block_on(executor.execute_block(
-            block_c.txns.clone(), block_c.parent_id, block_c.id,
+            block_c.txns.clone(), block_c.parent_id, block_c.id, BlockMetadata::default(),
         )).unwrap().unwrap();
         prop_assert_eq!(response_c.version(), a_size + c_size);
 
@@ -501,7 +539,7 @@ proptest! {
         {
             let executor = create_executor(&config);
             let response_a = block_on(executor.execute_block(
-                block_a.txns.clone(), block_a.parent_id, block_a.id,
+                block_a.txns.clone(), block_a.parent_id, block_a.id, BlockMetadata::default(),
             )).unwrap().unwrap();
             let root_hash = response_a.root_hash();
             let ledger_info = gen_ledger_info(block_a.txns.len() as u64, root_hash, block_a.id, 1);
@@ -512,7 +550,7 @@ proptest! {
         let root_hash = {
             let executor = create_executor(&config);
             let response_b = block_on(executor.execute_block(
-                block_b.txns.clone(), block_b.parent_id, block_b.id,
+                block_b.txns.clone(), block_b.parent_id, block_b.id, BlockMetadata::default(),
             )).unwrap().unwrap();
             let root_hash = response_b.root_hash();
             let ledger_info = gen_ledger_info(
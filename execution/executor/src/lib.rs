@@ -28,6 +28,7 @@ use std::{
 };
 use storage_client::{StorageRead, StorageWrite};
 use types::{
+    block_metadata::BlockMetadata,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{SignedTransaction, TransactionListWithProof},
 };
@@ -141,6 +142,7 @@ where
             vec![genesis_txn],
             *PRE_GENESIS_BLOCK_ID,
             *GENESIS_BLOCK_ID,
+            BlockMetadata::default(),
         ))
         .expect("Response sender was unexpectedly dropped.")
         .expect("Failed to execute genesis block.");
@@ -162,12 +164,14 @@ where
         info!("GENESIS transaction is committed.")
     }
 
-    /// Executes a block.
+    /// Executes a block. `block_metadata` comes from the consensus block and is exposed to the
+    /// transactions.
     pub fn execute_block(
         &self,
         transactions: Vec<SignedTransaction>,
         parent_id: HashValue,
         id: HashValue,
+        block_metadata: BlockMetadata,
     ) -> oneshot::Receiver<Result<ExecuteBlockResponse>> {
         debug!(
             "Received request to execute block. Parent id: {:x}. Id: {:x}.",
@@ -186,6 +190,7 @@ where
                     transactions,
                     parent_id,
                     id,
+                    block_metadata,
                     resp_sender,
                 })
                 .expect("Did block processor thread panic?"),
@@ -284,6 +289,7 @@ enum Command {
         transactions: Vec<SignedTransaction>,
         parent_id: HashValue,
         id: HashValue,
+        block_metadata: BlockMetadata,
         resp_sender: oneshot::Sender<Result<ExecuteBlockResponse>>,
     },
     CommitBlock {
@@ -18,6 +18,7 @@ use std::{
 use types::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{SignedTransaction, TransactionStatus},
@@ -41,6 +42,9 @@ pub struct TransactionBlock {
     /// The transactions themselves.
     transactions: Vec<SignedTransaction>,
 
+    /// The metadata of the consensus block, exposed to and stored with the transactions.
+    block_metadata: BlockMetadata,
+
     /// The result of processing VM's output.
     output: Option<ProcessedVMOutput>,
 
@@ -65,6 +69,7 @@ impl TransactionBlock {
         transactions: Vec<SignedTransaction>,
         parent_id: HashValue,
         id: HashValue,
+        block_metadata: BlockMetadata,
         execute_response_sender: oneshot::Sender<Result<ExecuteBlockResponse>>,
     ) -> Self {
         TransactionBlock {
@@ -73,6 +78,7 @@ impl TransactionBlock {
             parent_id,
             children: HashSet::new(),
             transactions,
+            block_metadata,
             output: None,
             ledger_info_with_sigs: None,
             execute_response: None,
@@ -86,6 +92,11 @@ impl TransactionBlock {
         &self.transactions
     }
 
+    /// Returns the metadata of the block.
+    pub fn block_metadata(&self) -> BlockMetadata {
+        self.block_metadata
+    }
+
     /// Returns the output of the block.
     pub fn output(&self) -> &Option<ProcessedVMOutput> {
         &self.output
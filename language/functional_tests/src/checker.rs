@@ -12,6 +12,7 @@ use std::{slice::SliceConcatExt, str::FromStr};
 /// Directives are extracted from comments starting with "//".
 #[derive(Debug, Clone)]
 pub enum Directive {
+    /// Matches the start of the next transaction in the output. Acts as a barrier.
     Transaction,
     /// Matches the specified stage in the output. Acts as a barrier.
     Stage(Stage),
@@ -37,8 +38,7 @@ impl FromStr for Directive {
             return Ok(Directive::Stage(Stage::parse(s)?));
         }
         if s == "transaction" {
-            // TODO: implement transaction directive
-            unimplemented!();
+            return Ok(Directive::Transaction);
         }
         Ok(Directive::Check(s.to_string()))
     }
@@ -105,8 +105,36 @@ pub fn check(res: &EvaluationResult, directives: &[Directive]) -> Result<()> {
                     }
                 }
             },
-            // TODO: implement transaction directive
-            Directive::Transaction => unimplemented!(),
+            Directive::Transaction => {
+                // the transaction the cursor is at is the current one, not the next
+                if let Some(EvaluationOutput::Transaction) = res.outputs.get(i) {
+                    i += 1;
+                }
+                loop {
+                    if i >= res.outputs.len() {
+                        return Err(ErrorKind::Other(
+                            "no transaction left in the output".to_string(),
+                        )
+                        .into());
+                    }
+                    match &res.outputs[i] {
+                        EvaluationOutput::Transaction => {
+                            did_run_checks |=
+                                run_filecheck(&outputs.join("\n"), &checks.join("\n"))?;
+                            checks.clear();
+                            outputs.clear();
+                            break;
+                        }
+                        EvaluationOutput::Output(s) | EvaluationOutput::Error(s) => {
+                            outputs.push(s.to_string());
+                            i += 1;
+                        }
+                        EvaluationOutput::Stage(_) => {
+                            i += 1;
+                        }
+                    }
+                }
+            }
         }
     }
 
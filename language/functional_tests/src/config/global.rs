@@ -4,9 +4,10 @@
 // The config holds the options that define the testing environment.
 // A config entry starts with "//!", differentiating it from a directive.
 
-use crate::errors::*;
+use crate::{config::repository_path, errors::*};
 use std::{
     collections::{btree_map, BTreeMap},
+    path::PathBuf,
     str::FromStr,
 };
 use vm_runtime_tests::account::AccountData;
@@ -25,11 +26,22 @@ pub struct AccountDefinition {
     pub sequence_number: Option<u64>,
 }
 
+/// Modules of a Move IR file, published under an account before the first transaction.
+#[derive(Debug)]
+pub struct ModuleDefinition {
+    /// Name of the account the modules are published under.
+    pub sender: String,
+    /// Path of the file, relative to the repository root.
+    pub path: String,
+}
+
 /// A raw entry extracted from the input. Used to build the global config table.
 #[derive(Debug)]
 pub enum Entry {
     /// Defines an account that can be used in tests.
     AccountDefinition(AccountDefinition),
+    /// Publishes modules too large to be published by a transaction, like the genesis does.
+    ModuleDefinition(ModuleDefinition),
 }
 
 impl FromStr for Entry {
@@ -68,6 +80,19 @@ impl FromStr for Entry {
                 sequence_number,
             }));
         }
+        if s2.starts_with("publish:") {
+            let v: Vec<_> = s2[8..].split_whitespace().collect();
+            if v.len() != 2 {
+                return Err(ErrorKind::Other(
+                    "config 'publish' takes an account and a file".to_string(),
+                )
+                .into());
+            }
+            return Ok(Entry::ModuleDefinition(ModuleDefinition {
+                sender: v[0].to_ascii_lowercase(),
+                path: v[1].to_string(),
+            }));
+        }
         Err(ErrorKind::Other(format!("failed to parse '{}' as global config entry", s)).into())
     }
 }
@@ -77,11 +102,15 @@ impl FromStr for Entry {
 pub struct Config {
     /// A map from account names to account data
     pub accounts: BTreeMap<String, AccountData>,
+    /// The Move IR files whose modules are published before the first transaction, with the
+    /// names of the accounts publishing them
+    pub modules: Vec<(String, PathBuf)>,
 }
 
 impl Config {
     pub fn build(entries: &[Entry]) -> Result<Self> {
         let mut accounts = BTreeMap::new();
+        let mut modules = vec![];
         for entry in entries {
             match entry {
                 Entry::AccountDefinition(def) => {
@@ -104,12 +133,22 @@ impl Config {
                         }
                     }
                 }
+                Entry::ModuleDefinition(def) => {
+                    modules.push((def.sender.clone(), repository_path(&def.path)));
+                }
             }
         }
 
         if let btree_map::Entry::Vacant(entry) = accounts.entry("default".to_string()) {
             entry.insert(AccountData::new(DEFAULT_BALANCE, 0));
         }
-        Ok(Config { accounts })
+        for (sender, _) in &modules {
+            if !accounts.contains_key(sender) {
+                return Err(
+                    ErrorKind::Other(format!("account '{}' does not exist", sender)).into(),
+                );
+            }
+        }
+        Ok(Config { accounts, modules })
     }
 }
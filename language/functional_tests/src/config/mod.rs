@@ -1,5 +1,14 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::{Path, PathBuf};
+
 pub mod global;
 pub mod transaction;
+
+/// Resolves a path relative to the root of the repository, e.g. `client/move/channel.mvir`.
+pub fn repository_path(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path)
+}
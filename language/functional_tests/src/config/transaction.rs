@@ -1,8 +1,11 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{config::global::Config as GlobalConfig, errors::*};
-use std::str::FromStr;
+use crate::{
+    config::{global::Config as GlobalConfig, repository_path},
+    errors::*,
+};
+use std::{path::PathBuf, str::FromStr};
 use transaction_builder::transaction_argument::parse_as_transaction_argument;
 use types::transaction::TransactionArgument;
 
@@ -34,6 +37,8 @@ pub enum Entry {
     NoExecute,
     Sender(String),
     Arguments(Vec<Argument>),
+    /// Path of a Move IR file holding the program, relative to the repository root.
+    File(String),
 }
 
 impl FromStr for Entry {
@@ -63,6 +68,13 @@ impl FromStr for Entry {
             }
             return Ok(Entry::Sender(s.to_ascii_lowercase()));
         }
+        if s.starts_with("file:") {
+            let s = s[5..].trim_start().trim_end();
+            if s.is_empty() {
+                return Err(ErrorKind::Other("file cannot be empty".to_string()).into());
+            }
+            return Ok(Entry::File(s.to_string()));
+        }
         if s.starts_with("args:") {
             let res: Result<Vec<_>> = s[5..]
                 .split(',')
@@ -107,6 +119,8 @@ pub struct Config {
     pub no_execute: bool,
     pub sender: String,
     pub args: Vec<TransactionArgument>,
+    /// The file to load the program from instead of the inline text.
+    pub file: Option<PathBuf>,
 }
 
 impl Config {
@@ -116,6 +130,7 @@ impl Config {
         let mut no_execute = None;
         let mut sender = None;
         let mut args = None;
+        let mut file = None;
 
         for entry in entries {
             match entry {
@@ -181,6 +196,12 @@ impl Config {
                         .into())
                     }
                 },
+                Entry::File(path) => match file {
+                    None => {
+                        file = Some(repository_path(path));
+                    }
+                    _ => return Err(ErrorKind::Other("file already set".to_string()).into()),
+                },
             }
         }
 
@@ -189,6 +210,7 @@ impl Config {
             no_execute: no_execute.unwrap_or(false),
             sender: sender.unwrap_or_else(|| "default".to_string()),
             args: args.unwrap_or_else(|| vec![]),
+            file,
         })
     }
 }
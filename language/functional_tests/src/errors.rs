@@ -18,6 +18,8 @@ pub enum ErrorKind {
     CheckerFailure,
     #[fail(display = "verification error {:?}", _0)]
     VerificationFailure(Vec<VerificationStatus>),
+    #[fail(display = "state check failed: {}", _0)]
+    StateCheckFailure(String),
     #[fail(display = "other error: {}", _0)]
     #[allow(dead_code)]
     Other(String),
@@ -4,29 +4,35 @@
 use crate::{
     config::{global::Config as GlobalConfig, transaction::Config as TransactionConfig},
     errors::*,
+    state_checks::StateCheck,
+    utils::substitute_addresses,
 };
 use bytecode_verifier::verifier::{VerifiedModule, VerifiedProgram};
 use config::config::VMPublishingOption;
 use ir_to_bytecode::{compiler::compile_program, parser::parse_program};
-use std::time::Duration;
+use std::{cmp, collections::BTreeMap, fs, path::Path, time::Duration};
 use stdlib::stdlib_modules;
 use transaction_builder::transaction::make_transaction_program;
 use types::{
+    account_address::AccountAddress,
     transaction::{RawTransaction, TransactionArgument, TransactionOutput, TransactionStatus},
     vm_error::{ExecutionStatus, VMStatus},
 };
-use vm::file_format::CompiledProgram;
+use vm::{
+    access::ModuleAccess, file_format::CompiledProgram, gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS,
+};
 use vm_runtime_tests::{
     account::{AccountData, AccountResource},
     executor::FakeExecutor,
 };
 
 /// A transaction to be evaluated by the testing infra.
-/// Contains code and a transaction config.
+/// Contains code, a transaction config and the checks on the state after the transaction.
 #[derive(Debug)]
 pub struct Transaction {
     pub config: TransactionConfig,
     pub program: String,
+    pub checks: Vec<StateCheck>,
 }
 
 /// Indicates one step in the pipeline the given move module/program goes through.
@@ -105,6 +111,22 @@ fn do_verify_program(program: CompiledProgram, deps: &[VerifiedModule]) -> Resul
     Ok(VerifiedProgram::new(program, deps).map_err(ErrorKind::VerificationFailure)?)
 }
 
+/// Compiles and verifies the modules of a Move IR file published by `sender`. The script of the
+/// file is not run.
+fn compile_modules(
+    sender: &AccountAddress,
+    path: &Path,
+    accounts: &BTreeMap<String, AccountData>,
+    deps: &[VerifiedModule],
+) -> Result<Vec<VerifiedModule>> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| ErrorKind::Other(format!("failed to read {}: {}", path.display(), e)))?;
+    let parsed_program = parse_program(&substitute_addresses(accounts, &source))?;
+    let compiled_program = compile_program(sender, &parsed_program, deps)?;
+    let verified_program = do_verify_program(compiled_program, deps)?;
+    Ok(verified_program.modules().to_vec())
+}
+
 /// Runs a single transaction using the fake executor.
 fn run_transaction(
     exec: &mut FakeExecutor,
@@ -121,7 +143,11 @@ fn run_transaction(
         *data.address(),
         AccountResource::read_sequence_number(&account_resource),
         program,
-        AccountResource::read_balance(&account_resource),
+        // The whole balance can pay for gas, up to the most a transaction may use.
+        cmp::min(
+            AccountResource::read_balance(&account_resource),
+            MAXIMUM_NUMBER_OF_GAS_UNITS,
+        ),
         1,
         Duration::from_secs(u64::max_value()),
     )
@@ -173,6 +199,17 @@ pub fn eval(config: &GlobalConfig, transactions: &[Transaction]) -> Result<Evalu
     // needed to compile transaction programs
     let mut deps = stdlib_modules().to_vec();
 
+    // publish the modules of the global config, the way the genesis publishes modules that are
+    // too large for a transaction
+    for (sender, path) in &config.modules {
+        let data = config.accounts.get(sender).unwrap();
+        let modules = compile_modules(data.address(), path, &config.accounts, &deps)?;
+        for module in &modules {
+            exec.add_module(&module.self_id(), module.as_inner());
+        }
+        deps.extend(modules);
+    }
+
     for transaction in transactions {
         // get the account data of the sender
         let data = config.accounts.get(&transaction.config.sender).unwrap();
@@ -228,6 +265,12 @@ pub fn eval(config: &GlobalConfig, transactions: &[Transaction]) -> Result<Evalu
 
             // apply the writeset
             exec.apply_write_set(txn_output.write_set());
+
+            // check the state after the transaction
+            // a failed check fails the test, whatever the directives expect
+            for check in &transaction.checks {
+                check.verify(&exec, &config.accounts, &deps, txn_output.events())?;
+            }
         }
     }
 
@@ -11,6 +11,7 @@ pub mod checker;
 pub mod config;
 pub mod errors;
 pub mod evaluator;
+pub mod state_checks;
 pub mod utils;
 
 #[cfg(test)]
@@ -0,0 +1,523 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// State checks assert on the global state and the emitted events after a transaction.
+// They are extracted from comments following the program of the transaction:
+//
+//     // check-resource: <account> <Module>.<Struct> [<field>=<value> ...]
+//     // check-no-resource: <account> <Module>.<Struct>
+//     // check-event: <account> <Module>.<Struct> [<field>=<value> ...]
+//
+// Modules are looked up by name among the stdlib and the modules published so far, the latest
+// one wins. Nested fields are named by their path, e.g. `balance.value`. Values are written as in
+// Move IR, `true`, `90` or `0x...` for addresses and bytearrays, or `{{name}}` for the address of
+// an account.
+
+use crate::errors::*;
+use bytecode_verifier::verifier::VerifiedModule;
+use canonical_serialization::{CanonicalDeserializer, SimpleDeserializer};
+use std::{collections::BTreeMap, convert::TryFrom, fmt, str::FromStr};
+use types::{
+    access_path::{AccessPath, Accesses},
+    account_address::AccountAddress,
+    byte_array::ByteArray,
+    contract_event::ContractEvent,
+    language_storage::{ModuleId, StructTag},
+};
+use vm::{
+    access::ModuleAccess,
+    file_format::SignatureToken,
+    views::{ModuleView, ViewInternals},
+};
+use vm_runtime_tests::{account::AccountData, executor::FakeExecutor};
+
+/// Expected value of a (possibly nested) field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCheck {
+    pub path: Vec<String>,
+    pub value: String,
+}
+
+impl FromStr for FieldCheck {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let v: Vec<_> = s.splitn(2, '=').collect();
+        if v.len() != 2 || v[0].is_empty() || v[1].is_empty() {
+            return Err(ErrorKind::Other(format!(
+                "field check '{}' must be of the form <field>=<value>",
+                s
+            ))
+            .into());
+        }
+        Ok(FieldCheck {
+            path: v[0].split('.').map(|s| s.to_string()).collect(),
+            value: v[1].to_string(),
+        })
+    }
+}
+
+impl fmt::Display for FieldCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.path.join("."), self.value)
+    }
+}
+
+/// A check on the state after a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateCheck {
+    /// The account holds the resource and its fields have the given values.
+    Resource {
+        account: String,
+        module: String,
+        name: String,
+        fields: Vec<FieldCheck>,
+    },
+    /// The account does not hold the resource.
+    NoResource {
+        account: String,
+        module: String,
+        name: String,
+    },
+    /// The transaction emitted an event of the struct to one of the account's event streams of
+    /// the module. Events only carry their data, so structs of the same layout are told apart by
+    /// the field values.
+    Event {
+        account: String,
+        module: String,
+        name: String,
+        fields: Vec<FieldCheck>,
+    },
+}
+
+const CHECK_RESOURCE: &str = "check-resource";
+const CHECK_NO_RESOURCE: &str = "check-no-resource";
+const CHECK_EVENT: &str = "check-event";
+
+/// Splits a comment into the kind of state check and its parameters, if it is one.
+fn split_state_check(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start().trim_end();
+    if !s.starts_with("//") || s.starts_with("//!") {
+        return None;
+    }
+    let s = s[2..].trim_start();
+    let idx = s.find(':')?;
+    let kind = &s[..idx];
+    if kind == CHECK_RESOURCE || kind == CHECK_NO_RESOURCE || kind == CHECK_EVENT {
+        Some((kind, &s[idx + 1..]))
+    } else {
+        None
+    }
+}
+
+impl FromStr for StateCheck {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = split_state_check(s)
+            .ok_or_else(|| ErrorKind::Other(format!("failed to parse '{}' as state check", s)))?;
+        let mut params = params.split_whitespace();
+        let account = match params.next() {
+            Some(account) => account.to_ascii_lowercase(),
+            None => {
+                return Err(ErrorKind::Other(format!("{} needs an account", kind)).into());
+            }
+        };
+        let (module, name) = match params.next().map(|s| s.split('.').collect::<Vec<_>>()) {
+            Some(ref v) if v.len() == 2 && !v[0].is_empty() && !v[1].is_empty() => {
+                (v[0].to_string(), v[1].to_string())
+            }
+            _ => {
+                return Err(ErrorKind::Other(format!(
+                    "{} needs a struct of the form <Module>.<Struct>",
+                    kind
+                ))
+                .into());
+            }
+        };
+        let fields = params
+            .map(|s| s.parse::<FieldCheck>())
+            .collect::<Result<Vec<_>>>()?;
+        match kind {
+            CHECK_RESOURCE => Ok(StateCheck::Resource {
+                account,
+                module,
+                name,
+                fields,
+            }),
+            CHECK_NO_RESOURCE => {
+                if !fields.is_empty() {
+                    return Err(
+                        ErrorKind::Other(format!("{} does not take field values", kind)).into(),
+                    );
+                }
+                Ok(StateCheck::NoResource {
+                    account,
+                    module,
+                    name,
+                })
+            }
+            _ => Ok(StateCheck::Event {
+                account,
+                module,
+                name,
+                fields,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for StateCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (kind, account, module, name, fields) = match self {
+            StateCheck::Resource {
+                account,
+                module,
+                name,
+                fields,
+            } => (CHECK_RESOURCE, account, module, name, &fields[..]),
+            StateCheck::NoResource {
+                account,
+                module,
+                name,
+            } => (CHECK_NO_RESOURCE, account, module, name, &[][..]),
+            StateCheck::Event {
+                account,
+                module,
+                name,
+                fields,
+            } => (CHECK_EVENT, account, module, name, &fields[..]),
+        };
+        write!(f, "{}: {} {}.{}", kind, account, module, name)?;
+        for field in fields {
+            write!(f, " {}", field)?;
+        }
+        Ok(())
+    }
+}
+
+impl StateCheck {
+    /// Parses the line as a state check. Returns `None` if the line is not a state check.
+    pub fn try_parse(s: &str) -> Result<Option<Self>> {
+        if split_state_check(s).is_some() {
+            Ok(Some(s.parse::<StateCheck>()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Verifies the check against the state of the executor and the events of the transaction.
+    pub fn verify(
+        &self,
+        exec: &FakeExecutor,
+        accounts: &BTreeMap<String, AccountData>,
+        deps: &[VerifiedModule],
+        events: &[ContractEvent],
+    ) -> Result<()> {
+        match self {
+            StateCheck::Resource {
+                account,
+                module,
+                name,
+                fields,
+            } => {
+                let address = account_address(accounts, account)?;
+                let module = find_module(deps, module)?;
+                let layout = struct_layout(deps, module, name)?;
+                let path = resource_path(&module.self_id(), name);
+                let value = match exec.read_from_access_path(&AccessPath::new(address, path)) {
+                    Some(bytes) => Value::decode(&layout, &bytes)?,
+                    None => return Err(self.failure("the resource does not exist")),
+                };
+                for field in fields {
+                    if let Some(reason) = mismatch(accounts, &value, field)? {
+                        return Err(self.failure(&reason));
+                    }
+                }
+                Ok(())
+            }
+            StateCheck::NoResource {
+                account,
+                module,
+                name,
+            } => {
+                let address = account_address(accounts, account)?;
+                let module = find_module(deps, module)?;
+                struct_layout(deps, module, name)?;
+                let path = resource_path(&module.self_id(), name);
+                match exec.read_from_access_path(&AccessPath::new(address, path)) {
+                    Some(_) => Err(self.failure("the resource exists")),
+                    None => Ok(()),
+                }
+            }
+            StateCheck::Event {
+                account,
+                module,
+                name,
+                fields,
+            } => {
+                let address = account_address(accounts, account)?;
+                let module = find_module(deps, module)?;
+                let layout = struct_layout(deps, module, name)?;
+                let module_id = module.self_id();
+                let streams: Vec<Vec<u8>> = ModuleView::new(module)
+                    .structs()
+                    .filter(|struct_def| struct_def.is_resource())
+                    .map(|struct_def| {
+                        let mut path = resource_path(&module_id, struct_def.name());
+                        path.push(b'/');
+                        path
+                    })
+                    .collect();
+                for event in events {
+                    let access_path = event.access_path();
+                    if access_path.address != address
+                        || !streams
+                            .iter()
+                            .any(|stream| access_path.path.starts_with(stream))
+                    {
+                        continue;
+                    }
+                    let value = match Value::decode(&layout, event.event_data()) {
+                        Ok(value) => value,
+                        Err(_) => continue,
+                    };
+                    if value.size() != event.event_data().len() {
+                        continue;
+                    }
+                    let mut matches = true;
+                    for field in fields {
+                        if mismatch(accounts, &value, field)?.is_some() {
+                            matches = false;
+                            break;
+                        }
+                    }
+                    if matches {
+                        return Ok(());
+                    }
+                }
+                Err(self.failure("no such event was emitted"))
+            }
+        }
+    }
+
+    fn failure(&self, reason: &str) -> Error {
+        ErrorKind::StateCheckFailure(format!("'{}': {}", self, reason)).into()
+    }
+}
+
+fn account_address(accounts: &BTreeMap<String, AccountData>, name: &str) -> Result<AccountAddress> {
+    match accounts.get(name) {
+        Some(data) => Ok(*data.address()),
+        None => Err(ErrorKind::Other(format!("account '{}' does not exist", name)).into()),
+    }
+}
+
+fn find_module<'a>(deps: &'a [VerifiedModule], name: &str) -> Result<&'a VerifiedModule> {
+    deps.iter()
+        .rev()
+        .find(|module| module.self_id().name() == name)
+        .ok_or_else(|| ErrorKind::Other(format!("module '{}' has not been published", name)).into())
+}
+
+fn resource_path(module_id: &ModuleId, name: &str) -> Vec<u8> {
+    AccessPath::resource_access_vec(
+        &StructTag {
+            address: *module_id.address(),
+            module: module_id.name().to_string(),
+            name: name.to_string(),
+            type_params: vec![],
+        },
+        &Accesses::empty(),
+    )
+}
+
+/// Layout of a stored value. Structs are stored as their fields in definition order.
+#[derive(Debug, Clone)]
+enum Layout {
+    Bool,
+    U64,
+    String,
+    ByteArray,
+    Address,
+    Struct(Vec<(String, Layout)>),
+}
+
+fn struct_layout(deps: &[VerifiedModule], module: &VerifiedModule, name: &str) -> Result<Layout> {
+    let struct_def = match ModuleView::new(module)
+        .structs()
+        .find(|struct_def| struct_def.name() == name)
+    {
+        Some(struct_def) => struct_def,
+        None => {
+            return Err(ErrorKind::Other(format!(
+                "no struct '{}' in module '{}'",
+                name,
+                module.self_id().name()
+            ))
+            .into());
+        }
+    };
+    let mut fields = vec![];
+    for field in struct_def.fields() {
+        let token = field.type_signature().token();
+        let layout = match token.as_inner() {
+            SignatureToken::Bool => Layout::Bool,
+            SignatureToken::U64 => Layout::U64,
+            SignatureToken::String => Layout::String,
+            SignatureToken::ByteArray => Layout::ByteArray,
+            SignatureToken::Address => Layout::Address,
+            SignatureToken::Struct(_) => {
+                let handle = token.struct_handle().expect("struct token has a handle");
+                let module_id = handle.module_id();
+                let module = match deps.iter().rev().find(|dep| dep.self_id() == module_id) {
+                    Some(module) => module,
+                    None => {
+                        return Err(ErrorKind::Other(format!(
+                            "module '{}' has not been published",
+                            module_id.name()
+                        ))
+                        .into());
+                    }
+                };
+                struct_layout(deps, module, handle.name())?
+            }
+            SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => {
+                return Err(ErrorKind::Other(format!(
+                    "field '{}' of '{}' is a reference",
+                    field.name(),
+                    name
+                ))
+                .into());
+            }
+        };
+        fields.push((field.name().to_string(), layout));
+    }
+    Ok(Layout::Struct(fields))
+}
+
+/// A decoded value.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Bool(bool),
+    U64(u64),
+    String(String),
+    ByteArray(ByteArray),
+    Address(AccountAddress),
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn decode(layout: &Layout, bytes: &[u8]) -> Result<Self> {
+        Self::decode_from(layout, &mut SimpleDeserializer::new(bytes))
+    }
+
+    fn decode_from(layout: &Layout, deserializer: &mut SimpleDeserializer) -> Result<Self> {
+        Ok(match layout {
+            Layout::Bool => Value::Bool(deserializer.decode_bool()?),
+            Layout::U64 => Value::U64(deserializer.decode_u64()?),
+            Layout::String => Value::String(String::from_utf8(
+                deserializer.decode_variable_length_bytes()?,
+            )?),
+            Layout::ByteArray => {
+                Value::ByteArray(ByteArray::new(deserializer.decode_variable_length_bytes()?))
+            }
+            Layout::Address => Value::Address(AccountAddress::try_from(
+                deserializer.decode_variable_length_bytes()?,
+            )?),
+            Layout::Struct(fields) => {
+                let mut values = vec![];
+                for (name, layout) in fields {
+                    values.push((name.clone(), Self::decode_from(layout, deserializer)?));
+                }
+                Value::Struct(values)
+            }
+        })
+    }
+
+    /// Number of bytes the value is stored in, variable length bytes have a 4 byte length prefix.
+    fn size(&self) -> usize {
+        match self {
+            Value::Bool(_) => 1,
+            Value::U64(_) => 8,
+            Value::String(s) => 4 + s.len(),
+            Value::ByteArray(bytes) => 4 + bytes.len(),
+            Value::Address(address) => 4 + address.to_vec().len(),
+            Value::Struct(fields) => fields.iter().map(|(_, value)| value.size()).sum(),
+        }
+    }
+
+    fn field(&self, path: &[String]) -> Option<&Value> {
+        match path.split_first() {
+            None => Some(self),
+            Some((name, rest)) => match self {
+                Value::Struct(fields) => fields
+                    .iter()
+                    .find(|(field, _)| field == name)
+                    .and_then(|(_, value)| value.field(rest)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::U64(i) => write!(f, "{}", i),
+            Value::String(s) => write!(f, "{}", s),
+            Value::ByteArray(bytes) => write!(f, "{:?}", bytes),
+            Value::Address(address) => write!(f, "0x{}", address),
+            Value::Struct(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Compares a field of the value with the expected value. Returns why they differ, if they do.
+fn mismatch(
+    accounts: &BTreeMap<String, AccountData>,
+    value: &Value,
+    check: &FieldCheck,
+) -> Result<Option<String>> {
+    let path = check.path.join(".");
+    let actual = match value.field(&check.path) {
+        Some(Value::Struct(_)) => {
+            return Err(ErrorKind::Other(format!(
+                "field '{}' is a struct, name one of its fields",
+                path
+            ))
+            .into());
+        }
+        Some(actual) => actual,
+        None => return Ok(Some(format!("there is no field '{}'", path))),
+    };
+    let expected = if check.value.starts_with("{{") && check.value.ends_with("}}") {
+        let name = check.value[2..check.value.len() - 2].to_ascii_lowercase();
+        format!("0x{}", account_address(accounts, &name)?)
+    } else {
+        check.value.clone()
+    };
+    let rendered = actual.to_string();
+    let equal = match actual {
+        Value::ByteArray(_) | Value::Address(_) => rendered.eq_ignore_ascii_case(&expected),
+        _ => rendered == expected,
+    };
+    if equal {
+        Ok(None)
+    } else {
+        Ok(Some(format!(
+            "field '{}' is {} instead of {}",
+            path, rendered, expected
+        )))
+    }
+}
@@ -26,6 +26,8 @@ fn parse_directives() {
         "// stage: verifier",
         "// stage: runtime",
         "// stage:   runtime  ",
+        "// transaction",
+        "  //  transaction ",
     ] {
         s.parse::<Directive>().unwrap();
     }
@@ -152,3 +154,48 @@ fn check_no_stage() {
         // stage: runtime
     ")).unwrap_err();
 }
+
+#[rustfmt::skip]
+#[test]
+fn check_transaction() {
+    let res = EvaluationResult {
+        outputs: vec![
+            EvaluationOutput::Transaction,
+            EvaluationOutput::Stage(Stage::Runtime),
+            EvaluationOutput::Output("foo".to_string()),
+            EvaluationOutput::Transaction,
+            EvaluationOutput::Stage(Stage::Runtime),
+            EvaluationOutput::Output("bar".to_string()),
+        ],
+        status: Status::Success,
+    };
+
+    check(&res, &make_directives(r"
+        // check: foo
+        // transaction
+        // check: bar
+    ")).unwrap();
+
+    check(&res, &make_directives(r"
+        // stage: runtime
+        // check: foo
+        // transaction
+        // stage: runtime
+        // check: bar
+    ")).unwrap();
+
+    check(&res, &make_directives(r"
+        // transaction
+        // check: foo
+    ")).unwrap_err();
+
+    check(&res, &make_directives(r"
+        // check: bar
+        // transaction
+    ")).unwrap_err();
+
+    check(&res, &make_directives(r"
+        // transaction
+        // transaction
+    ")).unwrap_err();
+}
@@ -26,6 +26,24 @@ fn parse_account() {
     }
 }
 
+#[test]
+fn parse_publish() {
+    for s in &[
+        "//! publish: etoken language/stdlib/usds/eToken.mvir",
+        "//!publish:Channel   client/move/channel.mvir ",
+    ] {
+        s.parse::<Entry>().unwrap();
+    }
+
+    for s in &[
+        "//! publish:",
+        "//! publish: etoken",
+        "//! publish: etoken language/stdlib/usds/eToken.mvir client/move/channel.mvir",
+    ] {
+        s.parse::<Entry>().unwrap_err();
+    }
+}
+
 /// Parses each line in the given input as an entry and build global config.
 pub fn parse_and_build_config(s: &str) -> Result<Config> {
     Config::build(&parse_each_line_as::<Entry>(s)?)
@@ -75,3 +93,20 @@ fn build_global_config_4() {
     let default = config.accounts.get("default").unwrap();
     assert!(default.balance() == 50);
 }
+
+#[rustfmt::skip]
+#[test]
+fn build_global_config_publish() {
+    let config = parse_and_build_config(r"
+        //! publish: etoken language/stdlib/usds/eToken.mvir
+        //! account: etoken
+    ").unwrap();
+
+    assert!(config.modules.len() == 1);
+    assert!(config.modules[0].0 == "etoken");
+    assert!(config.modules[0].1.ends_with("language/stdlib/usds/eToken.mvir"));
+
+    parse_and_build_config(r"
+        //! publish: etoken language/stdlib/usds/eToken.mvir
+    ").unwrap_err();
+}
@@ -3,6 +3,7 @@
 
 mod checker_tests;
 mod global_config_tests;
+mod state_checks_tests;
 mod transaction_config_tests;
 mod utils_tests;
 
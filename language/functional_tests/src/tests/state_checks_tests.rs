@@ -0,0 +1,66 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state_checks::{FieldCheck, StateCheck};
+
+#[test]
+fn parse_state_checks() {
+    for s in &[
+        "// check-resource: holder EToken.T",
+        "// check-resource: holder EToken.T value=90",
+        "  //check-resource:  Holder  LibraAccount.T balance.value=10 sequence_number=2 ",
+        "// check-no-resource: holder EToken.OrderBook",
+        "// check-event: holder EToken.ReceivedEvent payer={{etoken}} amount=10",
+    ] {
+        s.parse::<StateCheck>().unwrap();
+    }
+
+    for s in &[
+        "// check: foo",
+        "//! check-resource: holder EToken.T",
+        "// check-resource:",
+        "// check-resource: holder",
+        "// check-resource: holder EToken",
+        "// check-resource: holder EToken.T.value",
+        "// check-resource: holder EToken.T value",
+        "// check-resource: holder EToken.T value=",
+        "// check-no-resource: holder EToken.T value=0",
+    ] {
+        s.parse::<StateCheck>().unwrap_err();
+    }
+}
+
+#[test]
+fn parse_state_check_fields() {
+    let check = "// check-resource: Holder LibraAccount.T balance.value=10"
+        .parse::<StateCheck>()
+        .unwrap();
+    assert_eq!(
+        check,
+        StateCheck::Resource {
+            account: "holder".to_string(),
+            module: "LibraAccount".to_string(),
+            name: "T".to_string(),
+            fields: vec![FieldCheck {
+                path: vec!["balance".to_string(), "value".to_string()],
+                value: "10".to_string(),
+            }],
+        }
+    );
+    assert_eq!(
+        check.to_string(),
+        "check-resource: holder LibraAccount.T balance.value=10"
+    );
+}
+
+#[test]
+fn try_parse_state_checks() {
+    assert!(StateCheck::try_parse("// check: foo").unwrap().is_none());
+    assert!(StateCheck::try_parse("main() {}").unwrap().is_none());
+    assert!(
+        StateCheck::try_parse("// check-event: holder EToken.SentEvent")
+            .unwrap()
+            .is_some()
+    );
+    StateCheck::try_parse("// check-event: holder").unwrap_err();
+}
@@ -22,6 +22,7 @@ fn parse_simple_positive() {
         "//! no-execute",
         "//! sender: alice",
         "//! sender:foobar42",
+        "//! file: language/stdlib/usds/eToken.mvir",
     ] {
         s.parse::<Entry>().unwrap();
     }
@@ -29,7 +30,7 @@ fn parse_simple_positive() {
 
 #[test]
 fn parse_simple_negative() {
-    for s in &["//!", "//! ", "//! garbage", "//! sender:"] {
+    for s in &["//!", "//! ", "//! garbage", "//! sender:", "//! file: "] {
         s.parse::<Entry>().unwrap_err();
     }
 }
@@ -109,3 +110,19 @@ fn build_transaction_config_3() {
         //! args: {{bob}}
     ").unwrap_err();
 }
+
+#[rustfmt::skip]
+#[test]
+fn build_transaction_config_file() {
+    let global = parse_and_build_global_config("").unwrap();
+
+    let config = parse_and_build_config(&global, r"
+        //! file: client/move/mint.mvir
+    ").unwrap();
+    assert!(config.file.unwrap().ends_with("client/move/mint.mvir"));
+
+    parse_and_build_config(&global, r"
+        //! file: client/move/mint.mvir
+        //! file: client/move/burn.mvir
+    ").unwrap_err();
+}
@@ -55,3 +55,63 @@ fn parse_input_empty_transaction_with_config() {
         main() {}
     ").unwrap_err();
 }
+
+#[rustfmt::skip]
+#[test]
+fn parse_input_file() {
+    let (config, _, transactions) = parse_input(r"
+        //! account: etoken
+        //! sender: etoken
+        //! file: client/move/mint.mvir
+        //! args: 100
+        // check-resource: etoken EToken.T value=100
+
+        //! new-transaction
+        //! sender: etoken
+        //! file: client/move/burn.mvir
+        //! args: 10
+    ").unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].checks.len(), 1);
+    assert!(transactions[1].checks.is_empty());
+
+    let address = format!("0x{}", config.accounts["etoken"].address());
+    assert!(transactions[0].program.contains(&format!("import {}.EToken;", address)));
+    assert!(!transactions[0].program.contains("${etoken}"));
+}
+
+#[rustfmt::skip]
+#[test]
+fn parse_input_file_and_text() {
+    parse_input(r"
+        //! file: client/move/mint.mvir
+        main() {}
+    ").unwrap_err();
+}
+
+#[rustfmt::skip]
+#[test]
+fn parse_input_state_checks() {
+    parse_input(r"
+        main() {}
+        // check-resource: default LibraAccount.T sequence_number=1
+        // check-no-resource: default LibraAccount.T
+    ").unwrap();
+
+    parse_input(r"
+        main() {}
+        // check-resource: default
+    ").unwrap_err();
+
+    parse_input(r"
+        //! no-execute
+        main() {}
+        // check-resource: default LibraAccount.T
+    ").unwrap_err();
+
+    parse_input(r"
+        // check-resource: default LibraAccount.T
+        //! new-transaction
+        main() {}
+    ").unwrap_err();
+}
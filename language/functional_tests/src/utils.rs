@@ -11,18 +11,21 @@ use crate::{
     },
     errors::*,
     evaluator::Transaction,
+    state_checks::StateCheck,
 };
 use regex::{Captures, Regex};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fs};
 use vm_runtime_tests::account::AccountData;
 
-/// Substitutes the placeholders (account names in double curly brackets) with addresses.
+/// Substitutes the placeholders (account names in double curly brackets, or in curly brackets
+/// after a dollar sign as in the Move sources of the client) with addresses.
 pub fn substitute_addresses(accounts: &BTreeMap<String, AccountData>, text: &str) -> String {
     lazy_static! {
-        static ref PAT: Regex = Regex::new(r"\{\{([A-Za-z][A-Za-z0-9]*)\}\}").unwrap();
+        static ref PAT: Regex =
+            Regex::new(r"\{\{([A-Za-z][A-Za-z0-9]*)\}\}|\$\{([A-Za-z][A-Za-z0-9_]*)\}").unwrap();
     }
     PAT.replace_all(text, |caps: &Captures| {
-        let name = &caps[1];
+        let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
         match accounts.get(name) {
             Some(data) => format!("0x{}", data.address()),
             // TODO: find a way to return an error instead of panicking
@@ -35,20 +38,28 @@ pub fn substitute_addresses(accounts: &BTreeMap<String, AccountData>, text: &str
     .to_string()
 }
 
+fn has_file(transaction_config: &[TransactionConfigEntry]) -> bool {
+    transaction_config.iter().any(|entry| match entry {
+        TransactionConfigEntry::File(_) => true,
+        _ => false,
+    })
+}
+
 /// Parses the input string into three parts: a global config, directives and transactions.
 pub fn parse_input(s: &str) -> Result<(GlobalConfig, Vec<Directive>, Vec<Transaction>)> {
     let mut global_config = vec![];
     let mut directives = vec![];
     let mut text = vec![];
     let mut transaction_config = vec![];
+    let mut checks = vec![];
     let mut transactions = vec![];
 
     let mut first_transaction = true;
 
     for line in s.lines() {
         if is_new_transaction(line) {
-            if text.is_empty() {
-                if !transaction_config.is_empty() {
+            if text.is_empty() && !has_file(&transaction_config) {
+                if !transaction_config.is_empty() || !checks.is_empty() {
                     return Err(ErrorKind::Other(
                         "config options attached to empty transaction".to_string(),
                     )
@@ -61,9 +72,10 @@ pub fn parse_input(s: &str) -> Result<(GlobalConfig, Vec<Directive>, Vec<Transac
                 return Err(ErrorKind::Other("empty transaction".to_string()).into());
             }
             first_transaction = false;
-            transactions.push((transaction_config, text));
+            transactions.push((transaction_config, text, checks));
             text = vec![];
             transaction_config = vec![];
+            checks = vec![];
             continue;
         }
         if let Ok(entry) = line.parse::<GlobalConfigEntry>() {
@@ -74,6 +86,10 @@ pub fn parse_input(s: &str) -> Result<(GlobalConfig, Vec<Directive>, Vec<Transac
             transaction_config.push(entry);
             continue;
         }
+        if let Some(check) = StateCheck::try_parse(line)? {
+            checks.push(check);
+            continue;
+        }
         if let Ok(directive) = line.parse::<Directive>() {
             directives.push(directive);
             continue;
@@ -83,9 +99,9 @@ pub fn parse_input(s: &str) -> Result<(GlobalConfig, Vec<Directive>, Vec<Transac
         }
     }
 
-    if text.is_empty() {
+    if text.is_empty() && !has_file(&transaction_config) {
         return Err(ErrorKind::Other(
-            (if transaction_config.is_empty() {
+            (if transaction_config.is_empty() && checks.is_empty() {
                 "empty transaction"
             } else {
                 "config options attached to empty transaction"
@@ -94,16 +110,38 @@ pub fn parse_input(s: &str) -> Result<(GlobalConfig, Vec<Directive>, Vec<Transac
         )
         .into());
     }
-    transactions.push((transaction_config, text));
+    transactions.push((transaction_config, text, checks));
 
     let global_config = GlobalConfig::build(&global_config)?;
     let transactions = transactions
-        .iter()
-        .map(|(config, text)| {
+        .into_iter()
+        .map(|(config, text, checks)| {
             let config = TransactionConfig::build(&global_config, &config)?;
+            if config.no_execute && !checks.is_empty() {
+                return Err(ErrorKind::Other(
+                    "state checks attached to a transaction that is not executed".to_string(),
+                )
+                .into());
+            }
+            let text = match &config.file {
+                Some(path) => {
+                    if !text.is_empty() {
+                        return Err(ErrorKind::Other(
+                            "a transaction cannot have both a file and an inline program"
+                                .to_string(),
+                        )
+                        .into());
+                    }
+                    fs::read_to_string(path).map_err(|e| {
+                        ErrorKind::Other(format!("failed to read {}: {}", path.display(), e))
+                    })?
+                }
+                None => text.join("\n"),
+            };
             Ok(Transaction {
                 config,
-                program: substitute_addresses(&global_config.accounts, &text.join("\n")),
+                program: substitute_addresses(&global_config.accounts, &text),
+                checks,
             })
         })
         .collect::<Result<Vec<_>>>()?;
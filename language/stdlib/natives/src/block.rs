@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dispatch::{CostedReturnType, NativeReturnType, Result};
+use types::block_metadata::BlockMetadata;
+use vm::gas_schedule::BLOCK_METADATA_GAS_UNITS;
+
+/// Reading the block metadata does not depend on any input, so it has a fixed cost.
+pub const BLOCK_METADATA_COST: u64 = BLOCK_METADATA_GAS_UNITS;
+
+pub fn native_get_current_height(block_metadata: &BlockMetadata) -> Result<CostedReturnType> {
+    let native_return = NativeReturnType::U64(block_metadata.height());
+    Ok(CostedReturnType::new(BLOCK_METADATA_COST, native_return))
+}
+
+pub fn native_get_current_timestamp(block_metadata: &BlockMetadata) -> Result<CostedReturnType> {
+    let native_return = NativeReturnType::U64(block_metadata.timestamp_usecs());
+    Ok(CostedReturnType::new(BLOCK_METADATA_COST, native_return))
+}
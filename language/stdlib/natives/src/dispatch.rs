@@ -1,16 +1,22 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{hash, primitive_helpers, signature};
+use crate::{block, hash, primitive_helpers, signature};
 pub use failure::Error;
 use failure::*;
-use types::{account_address::AccountAddress, byte_array::ByteArray};
+use types::{
+    account_address::AccountAddress, block_metadata::BlockMetadata, byte_array::ByteArray,
+};
 
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 pub enum NativeReturnType {
     ByteArray(ByteArray),
     Bool(bool),
+    U64(u64),
+    /// The arguments are invalid: the transaction aborts with the error code, as a failed assert
+    /// would, and is charged for the gas used so far and the cost of the call.
+    Abort(u64),
 }
 
 pub struct CostedReturnType {
@@ -40,6 +46,7 @@ pub trait StackAccessor {
 
 pub fn dispatch_native_call<T: StackAccessor>(
     accessor: T,
+    block_metadata: &BlockMetadata,
     module_name: &str,
     function_name: &str,
 ) -> Result<CostedReturnType> {
@@ -73,6 +80,7 @@ pub fn dispatch_native_call<T: StackAccessor>(
         },
         "U64Util" => match function_name {
             "u64_to_bytes" => primitive_helpers::native_u64_to_bytes(accessor),
+            "bytes_to_u64" => primitive_helpers::native_bytes_to_u64(accessor),
             &_ => bail!(
                 "Unknown native function `{}.{}'",
                 module_name,
@@ -81,6 +89,17 @@ pub fn dispatch_native_call<T: StackAccessor>(
         },
         "BytearrayUtil" => match function_name {
             "bytearray_concat" => primitive_helpers::native_bytearray_concat(accessor),
+            "bytearray_len" => primitive_helpers::native_bytearray_len(accessor),
+            "bytearray_slice" => primitive_helpers::native_bytearray_slice(accessor),
+            &_ => bail!(
+                "Unknown native function `{}.{}'",
+                module_name,
+                function_name
+            ),
+        },
+        "Block" => match function_name {
+            "get_current_height" => block::native_get_current_height(block_metadata),
+            "get_current_timestamp" => block::native_get_current_timestamp(block_metadata),
             &_ => bail!(
                 "Unknown native function `{}.{}'",
                 module_name,
@@ -1,6 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod block;
 pub mod dispatch;
 pub mod hash;
 pub mod primitive_helpers;
@@ -1,7 +1,12 @@
 use crate::dispatch::{CostedReturnType, NativeReturnType, Result, StackAccessor};
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
 use types::byte_array::ByteArray;
 
+/// Abort code of `BytearrayUtil.bytearray_slice` when the range is out of the bytearray.
+pub const SLICE_OUT_OF_RANGE: u64 = 1;
+/// Abort code of `U64Util.bytes_to_u64` when the bytearray is not 8 bytes long.
+pub const BYTES_TO_U64_INVALID_LENGTH: u64 = 2;
+
 pub fn native_bytearray_concat<T: StackAccessor>(mut accessor: T) -> Result<CostedReturnType> {
     let arg2 = accessor.get_byte_array()?;
     let arg1 = accessor.get_byte_array()?;
@@ -14,6 +19,33 @@ pub fn native_bytearray_concat<T: StackAccessor>(mut accessor: T) -> Result<Cost
     Ok(CostedReturnType::new(native_cost, native_return))
 }
 
+pub fn native_bytearray_len<T: StackAccessor>(mut accessor: T) -> Result<CostedReturnType> {
+    let arg = accessor.get_byte_array()?;
+
+    let native_cost = 1;
+    let native_return = NativeReturnType::U64(arg.len() as u64);
+    Ok(CostedReturnType::new(native_cost, native_return))
+}
+
+pub fn native_bytearray_slice<T: StackAccessor>(mut accessor: T) -> Result<CostedReturnType> {
+    let end = accessor.get_u64()? as usize;
+    let start = accessor.get_u64()? as usize;
+    let arg = accessor.get_byte_array()?;
+    let bytes = arg.as_bytes();
+    if start > end || end > bytes.len() {
+        return Ok(CostedReturnType::new(
+            1,
+            NativeReturnType::Abort(SLICE_OUT_OF_RANGE),
+        ));
+    }
+    let return_val = bytes[start..end].to_vec();
+
+    // TODO: Figure out the gas cost for slicing.
+    let native_cost = return_val.len() as u64;
+    let native_return = NativeReturnType::ByteArray(ByteArray::new(return_val));
+    Ok(CostedReturnType::new(native_cost, native_return))
+}
+
 pub fn native_address_to_bytes<T: StackAccessor>(mut accessor: T) -> Result<CostedReturnType> {
     let arg = accessor.get_address()?;
     let return_val = arg.to_vec();
@@ -34,3 +66,19 @@ pub fn native_u64_to_bytes<T: StackAccessor>(mut accessor: T) -> Result<CostedRe
     let native_return = NativeReturnType::ByteArray(ByteArray::new(return_val));
     Ok(CostedReturnType::new(native_cost, native_return))
 }
+
+pub fn native_bytes_to_u64<T: StackAccessor>(mut accessor: T) -> Result<CostedReturnType> {
+    let arg = accessor.get_byte_array()?;
+    let bytes = arg.as_bytes();
+    if bytes.len() != 8 {
+        return Ok(CostedReturnType::new(
+            1,
+            NativeReturnType::Abort(BYTES_TO_U64_INVALID_LENGTH),
+        ));
+    }
+
+    // TODO: Figure out the gas cost for conversion.
+    let native_cost = bytes.len() as u64;
+    let native_return = NativeReturnType::U64(LittleEndian::read_u64(bytes));
+    Ok(CostedReturnType::new(native_cost, native_return))
+}
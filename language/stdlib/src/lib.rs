@@ -3,6 +3,7 @@
 
 pub mod stdlib;
 pub mod transaction_scripts;
+pub mod usds;
 
 use bytecode_verifier::{verify_module_dependencies, VerifiedModule};
 use ir_to_bytecode::compiler::compile_module;
@@ -28,6 +28,7 @@ lazy_static! {
         make_module_definition!("../modules/u64_util.mvir");
     static ref BYTEARRAY_UTIL_MODULE: ModuleDefinition =
         make_module_definition!("../modules/bytearray_util.mvir");
+    static ref BLOCK_MODULE: ModuleDefinition = make_module_definition!("../modules/block.mvir");
     static ref MODULE_DEFS: Vec<&'static ModuleDefinition> = {
         vec![
             &*COIN_MODULE,
@@ -38,6 +39,7 @@ lazy_static! {
             &*ADDRESS_UTIL_MODULE,
             &*U64_UTIL_MODULE,
             &*BYTEARRAY_UTIL_MODULE,
+            &*BLOCK_MODULE,
         ]
     };
 }
@@ -74,6 +76,10 @@ pub fn bytearray_util_module() -> ModuleDefinition {
     BYTEARRAY_UTIL_MODULE.clone()
 }
 
+pub fn block_module() -> ModuleDefinition {
+    BLOCK_MODULE.clone()
+}
+
 pub fn module_defs() -> &'static [&'static ModuleDefinition] {
     &*MODULE_DEFS
 }
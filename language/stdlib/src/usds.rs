@@ -0,0 +1,6 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// The EToken and ETokenCapability modules followed by a script issuing USDS from the sender. The
+/// `${etoken}` placeholders stand for the address of the issuer, which owns the modules.
+pub const ETOKEN_TEMPLATE: &str = include_str!("../usds/eToken.mvir");
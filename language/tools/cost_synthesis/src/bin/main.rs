@@ -14,8 +14,9 @@ use cost_synthesis::{
     with_loaded_vm,
 };
 use csv;
-use move_ir_natives::hash;
+use move_ir_natives::{block, hash};
 use std::{collections::HashMap, convert::TryFrom, path::Path, time::Instant, u64};
+use types::block_metadata::BlockMetadata;
 use vm::{
     errors::VMErrorKind,
     file_format::{
@@ -183,6 +184,23 @@ macro_rules! bench_native {
     };
 }
 
+macro_rules! bench_block_native {
+    ($name:expr, $function:path, $table:ident) => {
+        let block_metadata = BlockMetadata::new(u64::MAX, u64::MAX);
+        let time = (0..NUM_ITERS).fold(0, |acc, _| {
+            let before = Instant::now();
+            let _ = $function(&block_metadata).unwrap();
+            acc + before.elapsed().as_nanos()
+        });
+        // The cost does not depend on any input, so there is a single entry averaged over the
+        // number of iterations that we performed.
+        $table.insert(
+            $name,
+            vec![u64::try_from(time).unwrap() / u64::from(NUM_ITERS)],
+        );
+    };
+}
+
 fn natives() {
     let mut cost_table = HashMap::new();
     bench_native!(
@@ -206,6 +224,23 @@ fn natives() {
         cost_table
     );
     output_to_csv(Path::new("data/native_function_costs.csv"), cost_table);
+
+    // Block natives have a fixed cost, so they are kept apart from the per byte costs.
+    let mut block_cost_table = HashMap::new();
+    bench_block_native!(
+        "block_get_current_height".to_string(),
+        block::native_get_current_height,
+        block_cost_table
+    );
+    bench_block_native!(
+        "block_get_current_timestamp".to_string(),
+        block::native_get_current_timestamp,
+        block_cost_table
+    );
+    output_to_csv(
+        Path::new("data/block_native_function_costs.csv"),
+        block_cost_table,
+    );
 }
 
 pub fn main() {
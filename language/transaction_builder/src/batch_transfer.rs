@@ -0,0 +1,62 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scripts paying several payees of an EToken module in one transaction.
+//!
+//! Move IR has no vectors, so a batch script takes a `payee_<i>: address, amount_<i>: u64` pair
+//! of arguments per payout and calls `EToken.pay_from_sender` once for each of them.
+
+use crate::errors::*;
+use types::{account_address::AccountAddress, transaction::TransactionArgument};
+
+/// Most payouts of one script. Every payout takes two locals of `main`, which are indexed by a
+/// byte, and the transaction usually hits its size limit first.
+pub const MAX_PAYOUTS_PER_SCRIPT: usize = 64;
+
+/// An amount of EToken, in micro units, to pay to a payee.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payout {
+    pub payee: AccountAddress,
+    pub amount: u64,
+}
+
+/// Source of a script paying `num_payouts` payees with the EToken module published under
+/// `etoken_address`.
+pub fn batch_transfer_script(etoken_address: &AccountAddress, num_payouts: usize) -> Result<String> {
+    if num_payouts == 0 || num_payouts > MAX_PAYOUTS_PER_SCRIPT {
+        return Err(ErrorKind::InvalidBatch(format!(
+            "a batch pays 1 to {} payees, got {}",
+            MAX_PAYOUTS_PER_SCRIPT, num_payouts
+        ))
+        .into());
+    }
+    let params = (0..num_payouts)
+        .map(|i| format!("payee_{}: address, amount_{}: u64", i, i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut script = format!(
+        "import 0x{}.EToken;\nmain({}) {{\n",
+        etoken_address, params
+    );
+    for i in 0..num_payouts {
+        script.push_str(&format!(
+            "  EToken.pay_from_sender(move(payee_{}), move(amount_{}));\n",
+            i, i
+        ));
+    }
+    script.push_str("  return;\n}\n");
+    Ok(script)
+}
+
+/// Arguments of the batch script for `payouts`, in the order of its parameters.
+pub fn batch_transfer_args(payouts: &[Payout]) -> Vec<TransactionArgument> {
+    payouts
+        .iter()
+        .flat_map(|payout| {
+            vec![
+                TransactionArgument::Address(payout.payee),
+                TransactionArgument::U64(payout.amount),
+            ]
+        })
+        .collect()
+}
@@ -12,4 +12,6 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum ErrorKind {
     #[fail(display = "ParseError: {}", _0)]
     ParseError(String),
+    #[fail(display = "InvalidBatch: {}", _0)]
+    InvalidBatch(String),
 }
@@ -3,6 +3,7 @@
 
 #![feature(repeat_generic_slice)]
 
+pub mod batch_transfer;
 mod errors;
 #[cfg(test)]
 mod tests;
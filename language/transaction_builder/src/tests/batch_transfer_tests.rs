@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::batch_transfer::*;
+use types::{account_address::AccountAddress, transaction::TransactionArgument};
+
+#[test]
+fn batch_script() {
+    let etoken = AccountAddress::random();
+    let script = batch_transfer_script(&etoken, 2).unwrap();
+    assert!(script.starts_with(&format!("import 0x{}.EToken;", etoken)));
+    assert!(script.contains("main(payee_0: address, amount_0: u64, payee_1: address, amount_1: u64)"));
+    assert_eq!(script.matches("EToken.pay_from_sender(").count(), 2);
+    assert!(script.contains("EToken.pay_from_sender(move(payee_1), move(amount_1));"));
+
+    batch_transfer_script(&etoken, MAX_PAYOUTS_PER_SCRIPT).unwrap();
+    batch_transfer_script(&etoken, 0).unwrap_err();
+    batch_transfer_script(&etoken, MAX_PAYOUTS_PER_SCRIPT + 1).unwrap_err();
+}
+
+#[test]
+fn batch_args() {
+    let payouts = vec![
+        Payout {
+            payee: AccountAddress::random(),
+            amount: 1,
+        },
+        Payout {
+            payee: AccountAddress::random(),
+            amount: 2,
+        },
+    ];
+    assert_eq!(
+        batch_transfer_args(&payouts),
+        vec![
+            TransactionArgument::Address(payouts[0].payee),
+            TransactionArgument::U64(1),
+            TransactionArgument::Address(payouts[1].payee),
+            TransactionArgument::U64(2),
+        ]
+    );
+}
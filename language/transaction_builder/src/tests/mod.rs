@@ -1 +1,2 @@
+mod batch_transfer_tests;
 mod transaction_argument_tests;
@@ -5,7 +5,8 @@
 //!
 //! It is important to note that the cost schedule defined in this file does not track hashing
 //! operations or other native operations; the cost of each native operation will be returned by the
-//! native function itself.
+//! native function itself. Natives that only read the execution context are priced here, next to
+//! the instructions doing the same.
 use crate::{
     file_format::{
         AddressPoolIndex, ByteArrayPoolIndex, Bytecode, FieldDefinitionIndex, FunctionHandleIndex,
@@ -66,6 +67,11 @@ pub const DEFAULT_ACCOUNT_SIZE: AbstractMemorySize = 32;
 /// Any transaction over this size will be charged `INTRINSIC_GAS_PER_BYTE` per byte
 pub const LARGE_TRANSACTION_CUTOFF: AbstractMemorySize = 600;
 
+/// The cost of the `Block` natives reading the height or timestamp of the current block. They
+/// read a u64 handed to the VM with the transaction, just like `GetTxnSequenceNumber`, so they
+/// cost its computational plus memory gas from `GAS_SCHEDULE`.
+pub const BLOCK_METADATA_GAS_UNITS: GasUnits = 29 + 1;
+
 /// The cost tables, keyed by the serialized form of the bytecode instruction.  We use the
 /// serialized form as opposed to the instruction enum itself as the key since this will be the
 /// on-chain representation of bytecode instructions in the future.
@@ -1,27 +1,32 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crypto::{signing::generate_genesis_keypair, PublicKey};
-use types::{account_address::AccountAddress, transaction::SignedTransaction};
+use crypto::signing::generate_genesis_keypair;
+use types::{
+    account_address::AccountAddress, block_metadata::BlockMetadata, transaction::SignedTransaction,
+};
 
 pub struct TransactionMetadata {
     pub sender: AccountAddress,
-    pub public_key: PublicKey,
+    /// The ed25519 public key or multi-ed25519 policy the sender authenticated with.
+    pub public_key_bytes: Vec<u8>,
     pub sequence_number: u64,
     pub max_gas_amount: u64,
     pub gas_unit_price: u64,
     pub transaction_size: u64,
+    pub block_metadata: BlockMetadata,
 }
 
 impl TransactionMetadata {
-    pub fn new(txn: &SignedTransaction) -> Self {
+    pub fn new(txn: &SignedTransaction, block_metadata: BlockMetadata) -> Self {
         Self {
             sender: txn.sender(),
-            public_key: txn.public_key(),
+            public_key_bytes: txn.public_key_bytes(),
             sequence_number: txn.sequence_number(),
             max_gas_amount: txn.max_gas_amount(),
             gas_unit_price: txn.gas_unit_price(),
             transaction_size: txn.raw_txn_bytes_len() as u64,
+            block_metadata,
         }
     }
 
@@ -37,8 +42,8 @@ impl TransactionMetadata {
         self.sender.to_owned()
     }
 
-    pub fn public_key(&self) -> &PublicKey {
-        &self.public_key
+    pub fn public_key_bytes(&self) -> &[u8] {
+        &self.public_key_bytes
     }
 
     pub fn sequence_number(&self) -> u64 {
@@ -48,6 +53,10 @@ impl TransactionMetadata {
     pub fn transaction_size(&self) -> u64 {
         self.transaction_size
     }
+
+    pub fn block_metadata(&self) -> &BlockMetadata {
+        &self.block_metadata
+    }
 }
 
 impl Default for TransactionMetadata {
@@ -55,11 +64,12 @@ impl Default for TransactionMetadata {
         let (_, public_key) = generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            public_key_bytes: public_key.to_slice().to_vec(),
             sequence_number: 0,
             max_gas_amount: 100_000_000,
             gas_unit_price: 0,
             transaction_size: 0,
+            block_metadata: BlockMetadata::default(),
         }
     }
 }
@@ -1,10 +1,14 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use bytecode_verifier::VerifiedModule;
 use config::config::{VMConfig, VMPublishingOption};
 use crypto::{signing, PrivateKey, PublicKey};
 use failure::prelude::*;
-use ir_to_bytecode::{compiler::compile_program, parser::ast};
+use ir_to_bytecode::{
+    compiler::compile_program,
+    parser::{ast, parse_program},
+};
 use lazy_static::lazy_static;
 use rand::{rngs::StdRng, SeedableRng};
 use state_view::StateView;
@@ -15,6 +19,7 @@ use stdlib::{
         CREATE_ACCOUNT_TXN_BODY, MINT_TXN_BODY, PEER_TO_PEER_TRANSFER_TXN_BODY,
         ROTATE_AUTHENTICATION_KEY_TXN_BODY,
     },
+    usds::ETOKEN_TEMPLATE,
 };
 use tiny_keccak::Keccak;
 use types::{
@@ -315,8 +320,39 @@ pub fn encode_genesis_transaction_with_validator(
     public_key: PublicKey,
     validator_set: Vec<ValidatorPublicKeys>,
 ) -> SignatureCheckedTransaction {
+    encode_genesis_transaction_with_usds(private_key, public_key, validator_set, None)
+        .expect("Genesis without USDS modules failed")
+}
+
+/// Compiles the EToken and ETokenCapability modules with `issuer` as their owner and address.
+pub fn usds_modules(issuer: &AccountAddress) -> Result<Vec<VerifiedModule>> {
+    let source = ETOKEN_TEMPLATE.replace("${etoken}", &format!("0x{}", issuer));
+    let program = parse_program(&source)?;
+    compile_program(issuer, &program, stdlib_modules())?
+        .modules
+        .into_iter()
+        .map(|module| {
+            VerifiedModule::new(module)
+                .map_err(|(_, errors)| format_err!("USDS modules failed to verify: {:?}", errors))
+        })
+        .collect()
+}
+
+/// Same as `encode_genesis_transaction_with_validator`, but when `usds_issuer` is set the issuer
+/// account is created and the USDS modules are published under it. Fails if the USDS modules
+/// don't compile.
+pub fn encode_genesis_transaction_with_usds(
+    private_key: &PrivateKey,
+    public_key: PublicKey,
+    validator_set: Vec<ValidatorPublicKeys>,
+    usds_issuer: Option<AccountAddress>,
+) -> Result<SignatureCheckedTransaction> {
     assert!(validator_set.len() <= VALIDATOR_SIZE_LIMIT);
     const INIT_BALANCE: u64 = 1_000_000_000;
+    let issuer_modules = match usds_issuer {
+        Some(issuer) => usds_modules(&issuer)?,
+        None => vec![],
+    };
 
     // Compile the needed stdlib modules.
     let modules = stdlib_modules();
@@ -374,6 +410,18 @@ pub fn encode_genesis_transaction_with_validator(
                             Local::bytearray(ByteArray::new(
                                 key.consensus_public_key().to_slice().to_vec(),
                             )),
+                            Local::bytearray(ByteArray::new(
+                                key.consensus_bls_public_key()
+                                    .map_or_else(Vec::new, |(key, _)| key.to_bytes().to_vec()),
+                            )),
+                            Local::bytearray(ByteArray::new(
+                                key.consensus_bls_public_key().map_or_else(
+                                    Vec::new,
+                                    |(_, proof_of_possession)| {
+                                        proof_of_possession.to_bytes().to_vec()
+                                    },
+                                ),
+                            )),
                             Local::bytearray(ByteArray::new(
                                 key.network_signing_public_key().to_slice().to_vec(),
                             )),
@@ -396,6 +444,8 @@ pub fn encode_genesis_transaction_with_validator(
                             Local::bytearray(ByteArray::new(vec![])),
                             Local::bytearray(ByteArray::new(vec![])),
                             Local::bytearray(ByteArray::new(vec![])),
+                            Local::bytearray(ByteArray::new(vec![])),
+                            Local::bytearray(ByteArray::new(vec![])),
                         ],
                     )
                     .unwrap()
@@ -409,8 +459,13 @@ pub fn encode_genesis_transaction_with_validator(
                 .unwrap()
                 .unwrap();
 
+            if let Some(issuer) = usds_issuer {
+                txn_executor.create_account(issuer).unwrap().unwrap();
+            }
+
             let stdlib_modules = modules
                 .iter()
+                .chain(issuer_modules.iter())
                 .map(|m| {
                     let mut module_vec = vec![];
                     m.serialize(&mut module_vec).unwrap();
@@ -428,5 +483,5 @@ pub fn encode_genesis_transaction_with_validator(
     };
     let transaction =
         RawTransaction::new_write_set(genesis_addr, 0, genesis_write_set.freeze().unwrap());
-    transaction.sign(private_key, public_key).unwrap()
+    Ok(transaction.sign(private_key, public_key).unwrap())
 }
@@ -1,13 +1,18 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::encode_genesis_transaction_with_validator;
+use crate::{encode_genesis_transaction_with_usds, encode_genesis_transaction_with_validator};
 use canonical_serialization::SimpleDeserializer;
 use crypto::signing::generate_keypair;
 use proptest::{collection::vec, prelude::*};
 use types::{
-    access_path::VALIDATOR_SET_ACCESS_PATH, transaction::TransactionPayload,
-    validator_public_keys::ValidatorPublicKeys, validator_set::ValidatorSet, write_set::WriteOp,
+    access_path::{AccessPath, VALIDATOR_SET_ACCESS_PATH},
+    account_address::AccountAddress,
+    language_storage::ModuleId,
+    transaction::TransactionPayload,
+    validator_public_keys::ValidatorPublicKeys,
+    validator_set::ValidatorSet,
+    write_set::WriteOp,
 };
 
 proptest! {
@@ -31,3 +36,21 @@ proptest! {
         prop_assert_eq!(validator_set.payload(), keys.as_slice());
     }
 }
+
+#[test]
+fn test_usds_modules_published_at_issuer() {
+    let (priv_key, pub_key) = generate_keypair();
+    let issuer = AccountAddress::random();
+    let writeset =
+        match encode_genesis_transaction_with_usds(&priv_key, pub_key, vec![], Some(issuer))
+            .unwrap()
+            .payload()
+        {
+            TransactionPayload::WriteSet(ws) => ws.clone(),
+            _ => panic!("Unexpected Transaction"),
+        };
+    for name in &["EToken", "ETokenCapability"] {
+        let code_path = AccessPath::code_access_path(&ModuleId::new(issuer, name.to_string()));
+        assert!(writeset.iter().any(|(ap, _)| *ap == code_path));
+    }
+}
@@ -16,6 +16,7 @@ use logger::prelude::*;
 use rayon::prelude::*;
 use state_view::StateView;
 use types::{
+    block_metadata::BlockMetadata,
     transaction::{
         SignatureCheckedTransaction, SignedTransaction, TransactionOutput, TransactionStatus,
     },
@@ -26,6 +27,7 @@ use vm_cache_map::Arena;
 
 pub fn execute_block<'alloc>(
     txn_block: Vec<SignedTransaction>,
+    block_metadata: BlockMetadata,
     code_cache: &VMModuleCache<'alloc>,
     script_cache: &ScriptCache<'alloc>,
     data_view: &dyn StateView,
@@ -74,6 +76,7 @@ pub fn execute_block<'alloc>(
         let output = match transaction {
             Ok(t) => transaction_flow(
                 t,
+                block_metadata,
                 &module_cache,
                 script_cache,
                 &data_cache,
@@ -105,6 +108,7 @@ pub fn execute_block<'alloc>(
 /// cache this `txn`
 fn transaction_flow<'alloc, P>(
     txn: SignatureCheckedTransaction,
+    block_metadata: BlockMetadata,
     module_cache: P,
     script_cache: &ScriptCache<'alloc>,
     data_cache: &BlockDataCache<'_>,
@@ -115,7 +119,8 @@ where
     P: ModuleCache<'alloc>,
 {
     let arena = Arena::new();
-    let process_txn = ProcessTransaction::new(txn, &module_cache, data_cache, &arena);
+    let process_txn =
+        ProcessTransaction::new(txn, &module_cache, data_cache, &arena, block_metadata);
 
     let validated_txn = match process_txn.validate(mode, publishing_option) {
         Ok(validated_txn) => validated_txn,
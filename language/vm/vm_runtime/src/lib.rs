@@ -134,6 +134,7 @@ pub use txn_executor::execute_function;
 use config::config::VMConfig;
 use state_view::StateView;
 use types::{
+    block_metadata::BlockMetadata,
     transaction::{SignedTransaction, TransactionOutput},
     vm_error::VMStatus,
 };
@@ -173,4 +174,15 @@ pub trait VMExecutor {
         config: &VMConfig,
         state_view: &dyn StateView,
     ) -> Vec<TransactionOutput>;
+
+    /// Executes a block of transactions like `execute_block`, exposing `block_metadata` to them.
+    /// VMs that don't support block metadata can rely on the default implementation.
+    fn execute_block_with_metadata(
+        transactions: Vec<SignedTransaction>,
+        _block_metadata: BlockMetadata,
+        config: &VMConfig,
+        state_view: &dyn StateView,
+    ) -> Vec<TransactionOutput> {
+        Self::execute_block(transactions, config, state_view)
+    }
 }
@@ -5,6 +5,7 @@ use crate::{loaded_data::loaded_module::LoadedModule, runtime::VMRuntime, VMExec
 use state_view::StateView;
 use std::sync::Arc;
 use types::{
+    block_metadata::BlockMetadata,
     transaction::{SignedTransaction, TransactionOutput},
     vm_error::VMStatus,
 };
@@ -59,13 +60,29 @@ impl VMExecutor for MoveVM {
         transactions: Vec<SignedTransaction>,
         config: &VMConfig,
         state_view: &dyn StateView,
+    ) -> Vec<TransactionOutput> {
+        Self::execute_block_with_metadata(
+            transactions,
+            BlockMetadata::default(),
+            config,
+            state_view,
+        )
+    }
+
+    fn execute_block_with_metadata(
+        transactions: Vec<SignedTransaction>,
+        block_metadata: BlockMetadata,
+        config: &VMConfig,
+        state_view: &dyn StateView,
     ) -> Vec<TransactionOutput> {
         let vm = MoveVMImpl::new(Box::new(Arena::new()), |arena| {
             // XXX This means that scripts and modules are NOT tested against the whitelist! This
             // needs to be fixed.
             VMRuntime::new(&*arena, config)
         });
-        vm.rent(|runtime| runtime.execute_block_transactions(transactions, state_view))
+        vm.rent(|runtime| {
+            runtime.execute_block_transactions(transactions, block_metadata, state_view)
+        })
     }
 }
 
@@ -4,7 +4,7 @@ use crate::{
 };
 use config::config::VMPublishingOption;
 use std::marker::PhantomData;
-use types::transaction::SignatureCheckedTransaction;
+use types::{block_metadata::BlockMetadata, transaction::SignatureCheckedTransaction};
 use vm_cache_map::Arena;
 
 pub mod execute;
@@ -25,6 +25,7 @@ where
     module_cache: P,
     data_cache: &'txn dyn RemoteCache,
     allocator: &'txn Arena<LoadedModule>,
+    block_metadata: BlockMetadata,
     phantom: PhantomData<&'alloc ()>,
 }
 
@@ -33,18 +34,21 @@ where
     'alloc: 'txn,
     P: ModuleCache<'alloc>,
 {
-    /// Creates a new instance of `ProcessTransaction`.
+    /// Creates a new instance of `ProcessTransaction`. `block_metadata` describes the block the
+    /// transaction is executed in.
     pub fn new(
         txn: SignatureCheckedTransaction,
         module_cache: P,
         data_cache: &'txn dyn RemoteCache,
         allocator: &'txn Arena<LoadedModule>,
+        block_metadata: BlockMetadata,
     ) -> Self {
         Self {
             txn,
             module_cache,
             data_cache,
             allocator,
+            block_metadata,
             phantom: PhantomData,
         }
     }
@@ -76,6 +76,7 @@ where
             module_cache,
             data_cache,
             allocator,
+            block_metadata,
             ..
         } = process_txn;
 
@@ -190,7 +191,7 @@ where
                     }
                 }
 
-                let metadata = TransactionMetadata::new(&txn);
+                let metadata = TransactionMetadata::new(&txn, block_metadata);
                 let mut txn_state =
                     ValidatedTransactionState::new(metadata, module_cache, data_cache, allocator);
 
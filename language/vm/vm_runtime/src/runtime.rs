@@ -17,6 +17,7 @@ use config::config::{VMConfig, VMPublishingOption};
 use logger::prelude::*;
 use state_view::StateView;
 use types::{
+    block_metadata::BlockMetadata,
     transaction::{SignedTransaction, TransactionOutput},
     vm_error::{VMStatus, VMValidationStatus},
 };
@@ -78,8 +79,14 @@ impl<'alloc> VMRuntime<'alloc> {
             Err(_) => return Some(VMStatus::Validation(VMValidationStatus::InvalidSignature)),
         };
 
-        let process_txn =
-            ProcessTransaction::new(signature_verified_txn, module_cache, &data_cache, &arena);
+        // The block the transaction will be included in is not known yet.
+        let process_txn = ProcessTransaction::new(
+            signature_verified_txn,
+            module_cache,
+            &data_cache,
+            &arena,
+            BlockMetadata::default(),
+        );
         let mode = if data_view.is_genesis() {
             ValidationMode::Genesis
         } else {
@@ -106,14 +113,17 @@ impl<'alloc> VMRuntime<'alloc> {
     /// input vector. The discarded transactions will be marked as `TransactionStatus::Discard` and
     /// have an empty writeset. Also the data view is immutable, and also does not have interior
     /// mutability. writes to be applied to the data view are encoded in the write set part of a
-    /// transaction output.
+    /// transaction output. `block_metadata` is exposed to the transactions through the `Block`
+    /// natives.
     pub fn execute_block_transactions(
         &self,
         txn_block: Vec<SignedTransaction>,
+        block_metadata: BlockMetadata,
         data_view: &dyn StateView,
     ) -> Vec<TransactionOutput> {
         execute_block(
             txn_block,
+            block_metadata,
             &self.code_cache,
             &self.script_cache,
             data_view,
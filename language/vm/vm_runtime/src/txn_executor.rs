@@ -252,6 +252,7 @@ where
                         let function_name: &str = callee_function_ref.name();
                         let native_return = dispatch_native_call(
                             &mut self.execution_stack,
+                            self.txn_data.block_metadata(),
                             module_name,
                             function_name,
                         )
@@ -270,6 +271,17 @@ where
                                 // Call stack is not reconstructed for a native call, so we just
                                 // proceed on to next instruction.
                             }
+                            NativeReturnType::U64(value) => {
+                                self.execution_stack.push(Local::u64(value));
+                                // Call stack is not reconstructed for a native call, so we just
+                                // proceed on to next instruction.
+                            }
+                            NativeReturnType::Abort(error_code) => {
+                                return Ok(Err(VMRuntimeError {
+                                    loc: self.execution_stack.location()?,
+                                    err: VMErrorKind::AssertionFailure(error_code),
+                                }));
+                            }
                         }
                     } else {
                         self.execution_stack.top_frame_mut()?.jump(pc);
@@ -455,7 +467,7 @@ where
                 }
                 Bytecode::GetTxnPublicKey => {
                     self.execution_stack.push(Local::bytearray(ByteArray::new(
-                        self.txn_data.public_key().to_slice().to_vec(),
+                        self.txn_data.public_key_bytes().to_vec(),
                     )));
                 }
                 Bytecode::BorrowGlobal(idx) => {
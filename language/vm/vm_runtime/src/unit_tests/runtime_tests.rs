@@ -7,7 +7,10 @@ use crate::{
 };
 use bytecode_verifier::{VerifiedModule, VerifiedScript};
 use std::collections::HashMap;
-use types::{access_path::AccessPath, account_address::AccountAddress, byte_array::ByteArray};
+use types::{
+    access_path::AccessPath, account_address::AccountAddress, block_metadata::BlockMetadata,
+    byte_array::ByteArray,
+};
 use vm::{
     file_format::{
         AddressPoolIndex, Bytecode, CodeUnit, CompiledModuleMut, CompiledScript, CompiledScriptMut,
@@ -708,11 +711,12 @@ fn test_transaction_info() {
         let (_, public_key) = crypto::signing::generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            public_key_bytes: public_key.to_slice().to_vec(),
             sequence_number: 10,
             max_gas_amount: 100_000_009,
             gas_unit_price: 5,
             transaction_size: 100,
+            block_metadata: BlockMetadata::default(),
         }
     };
     let data_cache = FakeDataCache::new();
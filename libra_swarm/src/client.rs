@@ -168,6 +168,7 @@ impl InProcessTestClient {
                 false,
                 /* faucet server */ None,
                 Some(mnemonic_file_path.to_string()),
+                /* keystore passphrase */ None,
             )
             .unwrap(),
             alias_to_cmd,
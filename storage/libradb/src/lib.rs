@@ -424,15 +424,25 @@ impl LibraDB {
             .iter()
             .map(TransactionToCommit::gas_used)
             .collect::<Vec<_>>();
+        let block_metadatas = txns_to_commit
+            .iter()
+            .map(TransactionToCommit::block_metadata)
+            .collect::<Vec<_>>();
+        let vm_statuses = txns_to_commit
+            .iter()
+            .map(|txn_to_commit| txn_to_commit.vm_status().clone())
+            .collect::<Vec<_>>();
 
         // Transaction accumulator updates. Get result root hash.
         let txn_infos = izip!(
             txn_hashes,
             state_root_hashes,
             event_root_hashes,
-            gas_amounts
+            gas_amounts,
+            block_metadatas,
+            vm_statuses
         )
-        .map(|(t, s, e, g)| TransactionInfo::new(t, s, e, g))
+        .map(|(t, s, e, g, b, v)| TransactionInfo::new(t, s, e, g, b, v))
         .collect::<Vec<_>>();
         assert_eq!(txn_infos.len(), txns_to_commit.len());
 
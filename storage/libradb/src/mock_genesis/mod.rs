@@ -15,9 +15,11 @@ use std::collections::HashMap;
 use types::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::SparseMerkleLeafNode,
     transaction::{Program, RawTransaction, TransactionInfo, TransactionToCommit},
+    vm_error::{ExecutionStatus, VMStatus},
 };
 
 fn gen_mock_genesis() -> (
@@ -51,6 +53,8 @@ fn gen_mock_genesis() -> (
         account_states.clone(),
         vec![], /* events */
         0,      /* gas_used */
+        BlockMetadata::default(),
+        VMStatus::Execution(ExecutionStatus::Executed),
     );
 
     // The genesis state tree has a single leaf node, so the root hash is the hash of that node.
@@ -60,6 +64,8 @@ fn gen_mock_genesis() -> (
         state_root_hash,
         *ACCUMULATOR_PLACEHOLDER_HASH,
         0,
+        BlockMetadata::default(),
+        VMStatus::Execution(ExecutionStatus::Executed),
     );
 
     let ledger_info = LedgerInfo::new(
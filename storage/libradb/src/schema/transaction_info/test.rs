@@ -4,7 +4,11 @@
 use super::*;
 use crypto::HashValue;
 use schemadb::schema::assert_encode_decode;
-use types::transaction::TransactionInfo;
+use types::{
+    block_metadata::BlockMetadata,
+    transaction::TransactionInfo,
+    vm_error::{ExecutionStatus, VMStatus},
+};
 
 #[test]
 fn test_encode_decode() {
@@ -13,6 +17,8 @@ fn test_encode_decode() {
         HashValue::random(),
         HashValue::random(),
         7,
+        BlockMetadata::new(3, 5),
+        VMStatus::Execution(ExecutionStatus::AssertionFailure(2)),
     );
     assert_encode_decode::<TransactionInfoSchema>(&0u64, &txn_info);
 }
@@ -47,6 +47,8 @@ fn to_blocks_to_commit(
                     state_root_hash,
                     event_root_hash,
                     txn_to_commit.gas_used(),
+                    txn_to_commit.block_metadata(),
+                    txn_to_commit.vm_status().clone(),
                 );
                 let txn_accu_hash =
                     db.ledger_store
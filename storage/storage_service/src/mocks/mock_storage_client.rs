@@ -186,6 +186,8 @@ fn get_mock_response_item(request_item: &ProtoRequestItem) -> Result<ProtoRespon
                         HashValue::zero(),
                         HashValue::zero(),
                         0,
+                        types::block_metadata::BlockMetadata::default(),
+                        types::vm_error::VMStatus::Execution(types::vm_error::ExecutionStatus::Executed),
                     );
                     let transaction_info_to_account_proof = types::proof::SparseMerkleProof::new(None, vec![]);
                     types::proof::AccountStateProof::new(
@@ -261,5 +263,7 @@ fn get_transaction_info() -> types::transaction::TransactionInfo {
         HashValue::zero(),
         HashValue::zero(),
         0,
+        types::block_metadata::BlockMetadata::default(),
+        types::vm_error::VMStatus::Execution(types::vm_error::ExecutionStatus::Executed),
     )
 }
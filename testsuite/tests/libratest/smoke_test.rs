@@ -1,11 +1,19 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 #![allow(unused_mut)]
-use cli::client_proxy::ClientProxy;
+use cli::{
+    client_proxy::ClientProxy,
+    commands::{get_commands, run_command_line, run_shared_command_line, CommandOutput},
+};
 use libra_swarm::swarm::LibraSwarm;
 use num_traits::cast::FromPrimitive;
 use rust_decimal::Decimal;
-use std::str::FromStr;
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use tempfile::TempDir;
 
 fn setup_swarm_and_client_proxy(
     num_nodes: usize,
@@ -44,6 +52,7 @@ fn setup_swarm_and_client_proxy(
                 .unwrap()
                 .to_string(),
         ),
+        /* keystore passphrase */ None,
     )
     .unwrap();
     (swarm, client_proxy)
@@ -239,6 +248,7 @@ fn test_basic_state_synchronization() {
                 .unwrap()
                 .to_string(),
         ),
+        /* keystore passphrase */ None,
     )
     .unwrap();
     client_proxy2.set_accounts(client_proxy.copy_all_accounts());
@@ -251,3 +261,95 @@ fn test_basic_state_synchronization() {
         Decimal::from_str(&client_proxy2.get_balance(&["b", "1"]).unwrap()).ok()
     );
 }
+
+/// Client with its own wallet and stores in `dir`, connected to the first validator of `swarm`.
+fn client_proxy_in_dir(swarm: &LibraSwarm, faucet_key_file_path: &str, dir: &Path) -> ClientProxy {
+    let port = *swarm.get_validators_public_ports().get(0).unwrap();
+    ClientProxy::new(
+        "localhost",
+        port.to_string().as_str(),
+        &swarm.get_trusted_peers_config_path(),
+        faucet_key_file_path,
+        false,
+        /* faucet server */ None,
+        Some(dir.join("client.mnemonic").to_str().unwrap().to_string()),
+        /* keystore passphrase */ None,
+    )
+    .unwrap()
+}
+
+fn assert_success(line: &str, output: Option<CommandOutput>) -> CommandOutput {
+    let output = output.unwrap();
+    assert!(output.is_success(), "{} failed: {:?}", line, output);
+    output
+}
+
+#[test]
+fn test_channel_daemon_two_clients() {
+    ::logger::init_for_e2e_testing();
+    let (faucet_account_keypair, faucet_key_file_path, _faucet_dir) =
+        generate_keypair::load_faucet_key_or_create_default(None);
+    let swarm = LibraSwarm::launch_swarm(
+        1,
+        false, /* disable_logging */
+        faucet_account_keypair,
+        true, /* tee_logs */
+        None, /* config_dir */
+    );
+    // The module registry and the channel states are stored next to the wallet, so every client
+    // needs its own directory.
+    let (dir_a, dir_b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let mut client_a = client_proxy_in_dir(&swarm, &faucet_key_file_path, dir_a.path());
+    let mut client_b = client_proxy_in_dir(&swarm, "", dir_b.path());
+    let (_, alias_to_cmd) = get_commands();
+
+    client_a.create_next_account(false).unwrap();
+    client_b.create_next_account(false).unwrap();
+    let address_a = client_a.get_account_address_from_parameter("0").unwrap();
+    let address_b = client_b.get_account_address_from_parameter("0").unwrap();
+    client_a.mint_coins(&["mintb", "0", "20"], true).unwrap();
+    client_a
+        .transfer_coins(&["tb", "0", &address_b.to_string(), "10"], true)
+        .unwrap();
+
+    let mut run_a =
+        |line: String| assert_success(&line, run_command_line(&alias_to_cmd, &mut client_a, &line));
+    run_a("channel deploy 0".to_string());
+    run_a(format!("channel open 0 {} 5", address_b));
+
+    // B answers in the background, like in the interactive mode, while A pays.
+    let client_b = Arc::new(Mutex::new(client_b));
+    let run_b = |line: String| {
+        assert_success(
+            &line,
+            run_shared_command_line(&alias_to_cmd, &client_b, &line),
+        )
+    };
+    run_b(format!("usds module channel {}", address_a));
+    run_b(format!("channel open 0 {} 5", address_a));
+    let output = run_b("channel listen 0 /ip4/127.0.0.1/tcp/0 1".to_string());
+    let listen_addr = output.values["listen_addr"].as_str().unwrap().to_string();
+    run_a(format!("channel pay 0 {} {} 1", address_b, listen_addr));
+
+    // Both parties saved the countersigned state.
+    let data_a = client_a
+        .get_account_data(address_a)
+        .and_then(|account_data| account_data.get_channel(&address_b))
+        .and_then(|channel| channel.data.clone())
+        .unwrap();
+    let data_b = client_b
+        .lock()
+        .unwrap()
+        .get_account_data(address_b)
+        .and_then(|account_data| account_data.get_channel(&address_a))
+        .and_then(|channel| channel.data.clone())
+        .unwrap();
+    assert_eq!(data_a.version, 1);
+    assert_eq!(data_b.version, 1);
+    assert_eq!(data_a.self_balance, 4_000_000);
+    assert_eq!(data_a.other_balance, 6_000_000);
+    assert_eq!(data_b.self_balance, 6_000_000);
+    assert_eq!(data_b.other_balance, 4_000_000);
+    assert_eq!(data_a.self_signature, data_b.other_signature);
+    assert_eq!(data_a.other_signature, data_b.self_signature);
+}
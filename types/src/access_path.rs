@@ -45,7 +45,7 @@ use crate::{
         association_address,
     },
     language_storage::{ModuleId, ResourceKey, StructTag},
-    validator_set::validator_set_path,
+    validator_set::{validator_set_change_event_path, validator_set_path},
 };
 use canonical_serialization::{
     CanonicalDeserialize, CanonicalDeserializer, CanonicalSerialize, CanonicalSerializer,
@@ -235,6 +235,10 @@ lazy_static! {
     /// The access path where the Validator Set resource is stored.
     pub static ref VALIDATOR_SET_ACCESS_PATH: AccessPath =
         AccessPath::new(association_address(), validator_set_path());
+
+    /// The access path of the events announcing a new validator set.
+    pub static ref VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH: AccessPath =
+        AccessPath::new(association_address(), validator_set_change_event_path());
 }
 
 #[derive(
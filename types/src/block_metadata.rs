@@ -0,0 +1,32 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use proptest_derive::Arbitrary;
+
+/// Information about the consensus block a transaction is executed in, exposed to Move through the
+/// `Block` natives. It is decided by the proposer of the block and stored with every committed
+/// transaction, so re-executing the transaction during state synchronization sees the same values.
+#[derive(Arbitrary, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockMetadata {
+    /// Height of the consensus block.
+    height: u64,
+    /// Timestamp the proposer put in the consensus block, in microseconds.
+    timestamp_usecs: u64,
+}
+
+impl BlockMetadata {
+    pub fn new(height: u64, timestamp_usecs: u64) -> Self {
+        Self {
+            height,
+            timestamp_usecs,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn timestamp_usecs(&self) -> u64 {
+        self.timestamp_usecs
+    }
+}
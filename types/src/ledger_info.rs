@@ -14,11 +14,13 @@ use crypto::{
     HashValue, Signature,
 };
 use failure::prelude::*;
+use nextgen_crypto::bls12381::BLS12381Signature;
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     fmt::{Display, Formatter},
 };
 
@@ -189,6 +191,8 @@ pub struct LedgerInfoWithSignatures {
     /// The validator is identified by its account address: in order to verify a signature
     /// one needs to retrieve the public key of the validator for the given epoch.
     signatures: HashMap<AccountAddress, Signature>,
+    /// Set instead of `signatures` when the validators sign with their BLS12-381 keys.
+    aggregated_signature: Option<AggregatedSignature>,
 }
 
 impl Display for LedgerInfoWithSignatures {
@@ -202,6 +206,19 @@ impl LedgerInfoWithSignatures {
         LedgerInfoWithSignatures {
             ledger_info,
             signatures,
+            aggregated_signature: None,
+        }
+    }
+
+    /// A ledger info certified by the aggregated BLS12-381 signature of the signers.
+    pub fn new_aggregated(
+        ledger_info: LedgerInfo,
+        aggregated_signature: AggregatedSignature,
+    ) -> Self {
+        LedgerInfoWithSignatures {
+            ledger_info,
+            signatures: HashMap::new(),
+            aggregated_signature: Some(aggregated_signature),
         }
     }
 
@@ -217,13 +234,22 @@ impl LedgerInfoWithSignatures {
         &self.signatures
     }
 
+    pub fn aggregated_signature(&self) -> Option<&AggregatedSignature> {
+        self.aggregated_signature.as_ref()
+    }
+
     pub fn verify(&self, validator: &ValidatorVerifier) -> ::std::result::Result<(), VerifyError> {
         if self.ledger_info.is_zero() {
             // We're not trying to verify nominal ledger info that does not carry any information.
             return Ok(());
         }
         let ledger_hash = self.ledger_info().hash();
-        validator.verify_aggregated_signature(ledger_hash, self.signatures())
+        match &self.aggregated_signature {
+            Some(aggregated_signature) => {
+                validator.verify_bls_aggregated_signature(ledger_hash, aggregated_signature)
+            }
+            None => validator.verify_aggregated_signature(ledger_hash, self.signatures()),
+        }
     }
 }
 
@@ -247,10 +273,22 @@ impl FromProto for LedgerInfoWithSignatures {
             signatures.len() == num_signatures,
             "Signatures should be from different validators."
         );
+        let aggregated_signature = if proto.has_aggregated_signature() {
+            ensure!(
+                signatures.is_empty(),
+                "Signatures should not be sent along an aggregated signature."
+            );
+            Some(AggregatedSignature::from_proto(
+                proto.take_aggregated_signature(),
+            )?)
+        } else {
+            None
+        };
 
         Ok(LedgerInfoWithSignatures {
             ledger_info,
             signatures,
+            aggregated_signature,
         })
     }
 }
@@ -269,6 +307,68 @@ impl IntoProto for LedgerInfoWithSignatures {
                 validator_signature.set_signature(signature.to_compact().to_vec());
                 proto.mut_signatures().push(validator_signature)
             });
+        if let Some(aggregated_signature) = self.aggregated_signature {
+            proto.set_aggregated_signature(aggregated_signature.into_proto());
+        }
+        proto
+    }
+}
+
+/// The BLS12-381 signatures of several validators on the same message, aggregated into one, along
+/// with a bitmap of the signers. The bits follow the order of the validators' account addresses
+/// (see `ValidatorVerifier::get_ordered_account_addresses`), most significant bit first.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedSignature {
+    signature: BLS12381Signature,
+    signers: Vec<u8>,
+}
+
+impl AggregatedSignature {
+    pub fn new(signature: BLS12381Signature, signers: Vec<u8>) -> Self {
+        AggregatedSignature { signature, signers }
+    }
+
+    pub fn signature(&self) -> &BLS12381Signature {
+        &self.signature
+    }
+
+    pub fn signers(&self) -> &[u8] {
+        &self.signers
+    }
+
+    /// Whether the validator at `index` in the ordered validators is a signer.
+    pub fn is_signer(&self, index: usize) -> bool {
+        self.signers
+            .get(index / 8)
+            .map_or(false, |byte| byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    pub fn num_signers(&self) -> usize {
+        self.signers
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+}
+
+impl FromProto for AggregatedSignature {
+    type ProtoType = crate::proto::ledger_info::AggregatedSignature;
+
+    fn from_proto(mut proto: Self::ProtoType) -> Result<Self> {
+        Ok(AggregatedSignature {
+            signature: BLS12381Signature::try_from(proto.get_signature())?,
+            signers: proto.take_signers(),
+        })
+    }
+}
+
+impl IntoProto for AggregatedSignature {
+    type ProtoType = crate::proto::ledger_info::AggregatedSignature;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_signature(self.signature.to_bytes().to_vec());
+        proto.set_signers(self.signers);
         proto
     }
 }
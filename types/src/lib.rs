@@ -7,6 +7,7 @@ pub mod access_path;
 pub mod account_address;
 pub mod account_config;
 pub mod account_state_blob;
+pub mod block_metadata;
 pub mod byte_array;
 pub mod contract_event;
 pub mod get_with_proof;
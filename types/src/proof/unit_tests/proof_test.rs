@@ -4,6 +4,7 @@
 use crate::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     ledger_info::LedgerInfo,
     proof::{
         verify_account_state, verify_event, verify_signed_transaction,
@@ -15,6 +16,7 @@ use crate::{
     transaction::{
         Program, RawTransaction, SignedTransaction, TransactionInfo, TransactionListWithProof,
     },
+    vm_error::{ExecutionStatus, VMStatus},
 };
 use crypto::{
     hash::{
@@ -279,6 +281,8 @@ fn test_verify_signed_transaction() {
         state_root1_hash,
         event_root1_hash,
         /* gas_used = */ 0,
+        BlockMetadata::default(),
+        VMStatus::Execution(ExecutionStatus::Executed),
     );
     let txn_info1_hash = txn_info1.hash();
 
@@ -377,6 +381,8 @@ fn test_verify_account_state_and_event() {
         state_root_hash,
         event_root_hash,
         /* gas_used = */ 0,
+        BlockMetadata::default(),
+        VMStatus::Execution(ExecutionStatus::Executed),
     );
     let txn_info2_hash = txn_info2.hash();
 
@@ -496,6 +502,8 @@ fn arb_signed_txn_list_and_range(
                         txn_info.state_root_hash(),
                         txn_info.event_root_hash(),
                         txn_info.gas_used(),
+                        txn_info.block_metadata(),
+                        txn_info.vm_status().clone(),
                     ),
                 )
             })
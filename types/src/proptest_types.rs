@@ -5,6 +5,7 @@ use crate::{
     access_path::AccessPath,
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     byte_array::ByteArray,
     contract_event::ContractEvent,
     get_with_proof::{ResponseItem, UpdateToLatestLedgerResponse},
@@ -16,7 +17,7 @@ use crate::{
         TransactionStatus, TransactionToCommit, Version,
     },
     validator_change::ValidatorChangeEventWithProof,
-    vm_error::VMStatus,
+    vm_error::{ExecutionStatus, VMStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use crypto::{
@@ -395,6 +396,8 @@ pub fn arb_txn_to_commit_batch(
                         t.account_states().clone(),
                         events,
                         t.gas_used(),
+                        t.block_metadata(),
+                        t.vm_status().clone(),
                     )
                 })
                 .collect::<Vec<_>>()
@@ -448,17 +451,34 @@ impl TransactionToCommit {
         // gas_used
         let gas_used_strategy = any::<u64>();
 
+        // block_metadata
+        let block_metadata_strategy = any::<BlockMetadata>();
+
+        // vm_status, only execution statuses are kept
+        let vm_status_strategy = any::<ExecutionStatus>().prop_map(VMStatus::Execution);
+
         // Combine the above into result.
         (
             txn_strategy,
             account_states_strategy,
             events_strategy,
             gas_used_strategy,
+            block_metadata_strategy,
+            vm_status_strategy,
         )
-            .prop_map(|(txn, account_states, events, gas_used)| {
-                let signed_txn = txn.into_inner();
-                Self::new(signed_txn, account_states, events, gas_used)
-            })
+            .prop_map(
+                |(txn, account_states, events, gas_used, block_metadata, vm_status)| {
+                    let signed_txn = txn.into_inner();
+                    Self::new(
+                        signed_txn,
+                        account_states,
+                        events,
+                        gas_used,
+                        block_metadata,
+                        vm_status,
+                    )
+                },
+            )
     }
 }
 
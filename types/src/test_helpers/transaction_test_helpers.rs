@@ -8,8 +8,8 @@ use crate::{
         SignedTransactionsBlock,
     },
     transaction::{
-        Program, RawTransaction, RawTransactionBytes, SignatureCheckedTransaction,
-        SignedTransaction,
+        MultiEd25519PublicKey, MultiEd25519Signature, Program, RawTransaction, RawTransactionBytes,
+        SignatureCheckedTransaction, SignedTransaction, TransactionAuthenticator,
     },
     transaction_helpers::get_signed_transactions_digest,
     write_set::WriteSet,
@@ -134,6 +134,44 @@ pub fn get_test_unchecked_txn(
     )
 }
 
+// Test helper for creating transactions of a multi-ed25519 account, signed by the keys at the
+// given indices of the policy. The signatures haven't been checked.
+pub fn get_test_multi_signed_txn(
+    sender: AccountAddress,
+    sequence_number: u64,
+    private_keys: &[(u8, PrivateKey)],
+    public_key: MultiEd25519PublicKey,
+    program: Option<Program>,
+) -> SignedTransaction {
+    let expiration_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 10; // 10 seconds from now.
+    let mut raw_txn = ProtoRawTransaction::new();
+    raw_txn.set_sender_account(sender.as_ref().to_vec());
+    raw_txn.set_sequence_number(sequence_number);
+    raw_txn.set_program(program.unwrap_or_else(placeholder_script).into_proto());
+    raw_txn.set_expiration_time(expiration_time);
+    raw_txn.set_max_gas_amount(MAX_GAS_AMOUNT);
+    raw_txn.set_gas_unit_price(MAX_GAS_PRICE);
+
+    let bytes = raw_txn.write_to_bytes().unwrap();
+    let hash = RawTransactionBytes(&bytes).hash();
+    let signatures = private_keys
+        .iter()
+        .map(|(index, private_key)| (*index, sign_message(hash, private_key).unwrap()))
+        .collect();
+
+    SignedTransaction::craft_authenticated_transaction_for_client(
+        RawTransaction::from_proto(raw_txn).unwrap(),
+        TransactionAuthenticator::multi_ed25519(
+            public_key,
+            MultiEd25519Signature::new(signatures).unwrap(),
+        ),
+    )
+}
+
 pub fn placeholder_script() -> Program {
     Program::new(PLACEHOLDER_SCRIPT.to_vec(), vec![], vec![])
 }
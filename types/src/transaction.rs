@@ -6,6 +6,7 @@
 use crate::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
+    block_metadata::BlockMetadata,
     contract_event::ContractEvent,
     ledger_info::LedgerInfo,
     proof::{
@@ -33,8 +34,12 @@ use proto_conv::{FromProto, IntoProto, IntoProtoBytes};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, convert::TryFrom, fmt, time::Duration};
 
+mod authenticator;
 mod program;
 
+pub use authenticator::{
+    MultiEd25519PublicKey, MultiEd25519Signature, TransactionAuthenticator, MAX_NUM_OF_KEYS,
+};
 pub use program::{Program, TransactionArgument, SCRIPT_HASH_LENGTH};
 use protobuf::well_known_types::UInt64Value;
 use std::ops::Deref;
@@ -120,8 +125,7 @@ impl RawTransaction {
         let signature = signing::sign_message(hash, private_key)?;
         Ok(SignatureCheckedTransaction(SignedTransaction {
             raw_txn: self,
-            public_key,
-            signature,
+            authenticator: TransactionAuthenticator::ed25519(public_key, signature),
             raw_txn_bytes,
         }))
     }
@@ -168,6 +172,11 @@ impl RawTransaction {
     pub fn sender(&self) -> AccountAddress {
         self.sender
     }
+
+    /// Return the sequence number of this transaction.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
 }
 
 pub struct RawTransactionBytes<'a>(pub &'a [u8]);
@@ -243,12 +252,10 @@ pub struct SignedTransaction {
     /// The raw transaction
     raw_txn: RawTransaction,
 
-    /// Sender's public key. When checking the signature, we first need to check whether this key
-    /// is indeed the pre-image of the pubkey hash stored under sender's account.
-    public_key: PublicKey,
-
-    /// Signature of the transaction that correspond to the public key
-    signature: Signature,
+    /// Sender's public key and signature. When checking the signature, we first need to check
+    /// whether the public key is indeed the pre-image of the pubkey hash stored under sender's
+    /// account.
+    authenticator: TransactionAuthenticator,
 
     // The original raw bytes from the protobuf are also stored here so that we use
     // these bytes when generating the canonical serialization of the SignedTransaction struct
@@ -294,11 +301,10 @@ impl fmt::Debug for SignedTransaction {
             f,
             "SignedTransaction {{ \n \
              {{ raw_txn: {:#?}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {:#?}, \n \
              }} \n \
              }}",
-            self.raw_txn, self.public_key, self.signature,
+            self.raw_txn, self.authenticator,
         )
     }
 }
@@ -308,22 +314,33 @@ impl SignedTransaction {
         raw_txn: RawTransaction,
         public_key: PublicKey,
         signature: Signature,
+    ) -> SignedTransaction {
+        Self::craft_authenticated_transaction_for_client(
+            raw_txn,
+            TransactionAuthenticator::ed25519(public_key, signature),
+        )
+    }
+
+    pub fn craft_authenticated_transaction_for_client(
+        raw_txn: RawTransaction,
+        authenticator: TransactionAuthenticator,
     ) -> SignedTransaction {
         SignedTransaction {
             raw_txn: raw_txn.clone(),
-            public_key,
-            signature,
+            authenticator,
             // In real world raw_txn should be derived from raw_txn_bytes, not the opposite.
             raw_txn_bytes: raw_txn.into_proto_bytes().expect("Should convert."),
         }
     }
 
-    pub fn public_key(&self) -> PublicKey {
-        self.public_key
+    pub fn authenticator(&self) -> &TransactionAuthenticator {
+        &self.authenticator
     }
 
-    pub fn signature(&self) -> Signature {
-        self.signature
+    /// The bytes whose sha3 is checked against the authentication key of the sender, the ed25519
+    /// public key or the multi-ed25519 policy of the authenticator.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        self.authenticator.public_key_bytes()
     }
 
     pub fn sender(&self) -> AccountAddress {
@@ -362,7 +379,7 @@ impl SignedTransaction {
     /// the signature is valid.
     pub fn check_signature(self) -> Result<SignatureCheckedTransaction> {
         let hash = RawTransactionBytes(&self.raw_txn_bytes).hash();
-        signing::verify_message(hash, &self.signature, &self.public_key)?;
+        self.authenticator.verify(hash)?;
         Ok(SignatureCheckedTransaction(self))
     }
 
@@ -370,12 +387,10 @@ impl SignedTransaction {
         format!(
             "SignedTransaction {{ \n \
              raw_txn: {}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {:#?}, \n \
              }}",
             self.raw_txn.format_for_client(get_transaction_name),
-            self.public_key,
-            self.signature,
+            self.authenticator,
         )
     }
 }
@@ -411,8 +426,21 @@ impl FromProto for SignedTransaction {
 
         let t = SignedTransaction {
             raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: PublicKey::from_slice(txn.get_sender_public_key())?,
-            signature: Signature::from_compact(txn.get_sender_signature())?,
+            authenticator: if txn.get_sender_multi_public_key().is_empty() {
+                TransactionAuthenticator::ed25519(
+                    PublicKey::from_slice(txn.get_sender_public_key())?,
+                    Signature::from_compact(txn.get_sender_signature())?,
+                )
+            } else {
+                ensure!(
+                    txn.get_sender_public_key().is_empty() && txn.get_sender_signature().is_empty(),
+                    "A multi-ed25519 transaction can not carry a single key signature"
+                );
+                TransactionAuthenticator::multi_ed25519(
+                    MultiEd25519PublicKey::from_bytes(txn.get_sender_multi_public_key())?,
+                    MultiEd25519Signature::from_bytes(txn.get_sender_multi_signature())?,
+                )
+            },
             raw_txn_bytes: txn.raw_txn_bytes,
         };
 
@@ -428,8 +456,22 @@ impl IntoProto for SignedTransaction {
     fn into_proto(self) -> Self::ProtoType {
         let mut transaction = Self::ProtoType::new();
         transaction.set_raw_txn_bytes(self.raw_txn_bytes);
-        transaction.set_sender_public_key(self.public_key.to_slice().to_vec());
-        transaction.set_sender_signature(self.signature.to_compact().to_vec());
+        match self.authenticator {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => {
+                transaction.set_sender_public_key(public_key.to_slice().to_vec());
+                transaction.set_sender_signature(signature.to_compact().to_vec());
+            }
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => {
+                transaction.set_sender_multi_public_key(public_key.to_bytes());
+                transaction.set_sender_multi_signature(signature.to_bytes());
+            }
+        }
         transaction
     }
 }
@@ -550,8 +592,8 @@ impl CanonicalSerialize for SignedTransaction {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
         serializer
             .encode_variable_length_bytes(&self.raw_txn_bytes)?
-            .encode_variable_length_bytes(&self.public_key.to_slice())?
-            .encode_variable_length_bytes(&self.signature.to_compact())?;
+            .encode_variable_length_bytes(&self.authenticator.public_key_bytes())?
+            .encode_variable_length_bytes(&self.authenticator.signature_bytes())?;
         Ok(())
     }
 }
@@ -570,8 +612,10 @@ impl CanonicalDeserialize for SignedTransaction {
 
         Ok(SignedTransaction {
             raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: PublicKey::from_slice(&public_key_bytes)?,
-            signature: Signature::from_compact(&signature_bytes)?,
+            authenticator: TransactionAuthenticator::from_bytes(
+                &public_key_bytes,
+                &signature_bytes,
+            )?,
             raw_txn_bytes,
         })
     }
@@ -669,6 +713,10 @@ impl TransactionOutput {
 
 /// `TransactionInfo` is the object we store in the transaction accumulator. It consists of the
 /// transaction as well as the execution result of this transaction.
+///
+/// The block height and timestamp and the VM status are part of its hash, so adding them changed
+/// the format of the accumulator: a database written without them can't be verified by this
+/// version and the chain has to be restarted from a new genesis.
 #[derive(Arbitrary, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, FromProto, IntoProto)]
 #[ProtoType(crate::proto::transaction_info::TransactionInfo)]
 pub struct TransactionInfo {
@@ -684,22 +732,37 @@ pub struct TransactionInfo {
 
     /// The amount of gas used.
     gas_used: u64,
+
+    /// The height of the consensus block this transaction was executed in.
+    block_height: u64,
+
+    /// The timestamp of the consensus block this transaction was executed in.
+    block_timestamp_usecs: u64,
+
+    /// The status the VM kept this transaction with, i.e. whether it executed successfully.
+    vm_status: VMStatus,
 }
 
 impl TransactionInfo {
-    /// Constructs a new `TransactionInfo` object using signed transaction hash, state root hash
-    /// and event root hash.
+    /// Constructs a new `TransactionInfo` object using signed transaction hash, state root hash,
+    /// event root hash, the metadata of the block the transaction was executed in and the status
+    /// of its execution.
     pub fn new(
         signed_transaction_hash: HashValue,
         state_root_hash: HashValue,
         event_root_hash: HashValue,
         gas_used: u64,
+        block_metadata: BlockMetadata,
+        vm_status: VMStatus,
     ) -> TransactionInfo {
         TransactionInfo {
             signed_transaction_hash,
             state_root_hash,
             event_root_hash,
             gas_used,
+            block_height: block_metadata.height(),
+            block_timestamp_usecs: block_metadata.timestamp_usecs(),
+            vm_status,
         }
     }
 
@@ -724,6 +787,16 @@ impl TransactionInfo {
     pub fn gas_used(&self) -> u64 {
         self.gas_used
     }
+
+    /// Returns the metadata of the block this transaction was executed in.
+    pub fn block_metadata(&self) -> BlockMetadata {
+        BlockMetadata::new(self.block_height, self.block_timestamp_usecs)
+    }
+
+    /// Returns the status of the execution of this transaction.
+    pub fn vm_status(&self) -> &VMStatus {
+        &self.vm_status
+    }
 }
 
 impl CanonicalSerialize for TransactionInfo {
@@ -732,7 +805,10 @@ impl CanonicalSerialize for TransactionInfo {
             .encode_raw_bytes(self.signed_transaction_hash.as_ref())?
             .encode_raw_bytes(self.state_root_hash.as_ref())?
             .encode_raw_bytes(self.event_root_hash.as_ref())?
-            .encode_u64(self.gas_used)?;
+            .encode_u64(self.gas_used)?
+            .encode_u64(self.block_height)?
+            .encode_u64(self.block_timestamp_usecs)?
+            .encode_struct(&self.vm_status)?;
         Ok(())
     }
 }
@@ -755,6 +831,8 @@ pub struct TransactionToCommit {
     account_states: HashMap<AccountAddress, AccountStateBlob>,
     events: Vec<ContractEvent>,
     gas_used: u64,
+    block_metadata: BlockMetadata,
+    vm_status: VMStatus,
 }
 
 impl TransactionToCommit {
@@ -763,12 +841,16 @@ impl TransactionToCommit {
         account_states: HashMap<AccountAddress, AccountStateBlob>,
         events: Vec<ContractEvent>,
         gas_used: u64,
+        block_metadata: BlockMetadata,
+        vm_status: VMStatus,
     ) -> Self {
         TransactionToCommit {
             signed_txn,
             account_states,
             events,
             gas_used,
+            block_metadata,
+            vm_status,
         }
     }
 
@@ -787,6 +869,14 @@ impl TransactionToCommit {
     pub fn gas_used(&self) -> u64 {
         self.gas_used
     }
+
+    pub fn block_metadata(&self) -> BlockMetadata {
+        self.block_metadata
+    }
+
+    pub fn vm_status(&self) -> &VMStatus {
+        &self.vm_status
+    }
 }
 
 impl FromProto for TransactionToCommit {
@@ -815,12 +905,19 @@ impl FromProto for TransactionToCommit {
             .map(ContractEvent::from_proto)
             .collect::<Result<Vec<_>>>()?;
         let gas_used = object.get_gas_used();
+        let block_metadata = BlockMetadata::new(
+            object.get_block_height(),
+            object.get_block_timestamp_usecs(),
+        );
+        let vm_status = VMStatus::from_proto(object.take_vm_status())?;
 
         Ok(TransactionToCommit {
             signed_txn,
             account_states,
             events,
             gas_used,
+            block_metadata,
+            vm_status,
         })
     }
 }
@@ -849,6 +946,9 @@ impl IntoProto for TransactionToCommit {
                 .collect::<Vec<_>>(),
         ));
         proto.set_gas_used(self.gas_used);
+        proto.set_block_height(self.block_metadata.height());
+        proto.set_block_timestamp_usecs(self.block_metadata.timestamp_usecs());
+        proto.set_vm_status(self.vm_status.into_proto());
         proto
     }
 }
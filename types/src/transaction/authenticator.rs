@@ -0,0 +1,297 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Authenticators of a `SignedTransaction`.
+//!
+//! An account is controlled either by a single ed25519 key or by a k-of-n multi-ed25519 policy.
+//! In both cases the authentication key stored under the account is the sha3 of the public key
+//! bytes returned by [`TransactionAuthenticator::public_key_bytes`], which the prologue checks
+//! against the key of the transaction. Switching an account to a policy is a rotation of its
+//! authentication key to the hash of the policy's public key.
+
+use crypto::{signing, HashValue, PublicKey, Signature};
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Maximal number of keys of a multi-ed25519 policy, bounded by the bitmap of the signature.
+pub const MAX_NUM_OF_KEYS: usize = 32;
+
+const BITMAP_LENGTH: usize = 4;
+const SIGNATURE_LENGTH: usize = 64;
+
+/// A k-of-n policy of ed25519 keys.
+///
+/// Serialized as the n public keys followed by the threshold k as a single byte.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MultiEd25519PublicKey {
+    public_keys: Vec<PublicKey>,
+    threshold: u8,
+}
+
+impl MultiEd25519PublicKey {
+    /// Creates a policy requiring `threshold` signatures of `public_keys`.
+    pub fn new(public_keys: Vec<PublicKey>, threshold: u8) -> Result<Self> {
+        ensure!(
+            !public_keys.is_empty() && public_keys.len() <= MAX_NUM_OF_KEYS,
+            "A multi-ed25519 policy takes 1 to {} keys, got {}",
+            MAX_NUM_OF_KEYS,
+            public_keys.len()
+        );
+        ensure!(
+            threshold > 0 && threshold as usize <= public_keys.len(),
+            "Threshold {} is out of range for {} keys",
+            threshold,
+            public_keys.len()
+        );
+        Ok(Self {
+            public_keys,
+            threshold,
+        })
+    }
+
+    pub fn public_keys(&self) -> &[PublicKey] {
+        &self.public_keys
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The position of `public_key` in the policy, i.e. the index its signatures are given at.
+    pub fn index_of(&self, public_key: &PublicKey) -> Option<u8> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.public_keys.len() * PublicKey::LENGTH + 1);
+        for public_key in &self.public_keys {
+            bytes.extend_from_slice(&public_key.to_slice());
+        }
+        bytes.push(self.threshold);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            !bytes.is_empty() && (bytes.len() - 1) % PublicKey::LENGTH == 0,
+            "Invalid multi-ed25519 public key length {}",
+            bytes.len()
+        );
+        let (keys, threshold) = bytes.split_at(bytes.len() - 1);
+        let public_keys = keys
+            .chunks(PublicKey::LENGTH)
+            .map(PublicKey::from_slice)
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(public_keys, threshold[0])
+    }
+}
+
+/// Signatures of a subset of the keys of a `MultiEd25519PublicKey`.
+///
+/// Serialized as a 4 byte bitmap of the signing keys, the most significant bit of the first byte
+/// standing for the first key, followed by the signatures ordered by key index.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MultiEd25519Signature {
+    // Sorted by key index, at most one signature per index.
+    signatures: Vec<(u8, Signature)>,
+}
+
+impl MultiEd25519Signature {
+    pub fn new(signatures: Vec<(u8, Signature)>) -> Result<Self> {
+        let mut multi_signature = Self::default();
+        for (index, signature) in signatures {
+            ensure!(
+                !multi_signature.contains(index),
+                "Duplicate signature for key {}",
+                index
+            );
+            multi_signature.add_signature(index, signature)?;
+        }
+        Ok(multi_signature)
+    }
+
+    /// Adds the signature of the key at `index`, replacing a previous one of the same key.
+    pub fn add_signature(&mut self, index: u8, signature: Signature) -> Result<()> {
+        ensure!(
+            (index as usize) < MAX_NUM_OF_KEYS,
+            "Key index {} is out of range",
+            index
+        );
+        match self.signatures.binary_search_by_key(&index, |(i, _)| *i) {
+            Ok(position) => self.signatures[position] = (index, signature),
+            Err(position) => self.signatures.insert(position, (index, signature)),
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, index: u8) -> bool {
+        self.signatures.iter().any(|(i, _)| *i == index)
+    }
+
+    pub fn signatures(&self) -> &[(u8, Signature)] {
+        &self.signatures
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bitmap = [0u8; BITMAP_LENGTH];
+        let mut bytes = vec![];
+        for (index, signature) in &self.signatures {
+            bitmap[*index as usize / 8] |= 0x80 >> (*index % 8);
+            bytes.extend_from_slice(&signature.to_compact());
+        }
+        let mut result = bitmap.to_vec();
+        result.extend(bytes);
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() >= BITMAP_LENGTH && (bytes.len() - BITMAP_LENGTH) % SIGNATURE_LENGTH == 0,
+            "Invalid multi-ed25519 signature length {}",
+            bytes.len()
+        );
+        let (bitmap, signature_bytes) = bytes.split_at(BITMAP_LENGTH);
+        let indices = (0..MAX_NUM_OF_KEYS as u8)
+            .filter(|index| bitmap[*index as usize / 8] & (0x80 >> (*index % 8)) != 0)
+            .collect::<Vec<_>>();
+        ensure!(
+            indices.len() * SIGNATURE_LENGTH == signature_bytes.len(),
+            "The bitmap announces {} signatures but {} bytes are given",
+            indices.len(),
+            signature_bytes.len()
+        );
+        let signatures = indices
+            .into_iter()
+            .zip(signature_bytes.chunks(SIGNATURE_LENGTH))
+            .map(|(index, bytes)| Ok((index, Signature::from_compact(bytes)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { signatures })
+    }
+
+    /// Checks that exactly `threshold` keys of `public_key` signed `message`. Extra signatures
+    /// are refused, otherwise anyone could relay a transaction signed by more keys than required
+    /// with a different subset of the signatures, changing its hash.
+    pub fn verify(&self, message: HashValue, public_key: &MultiEd25519PublicKey) -> Result<()> {
+        ensure!(
+            self.signatures.len() == public_key.threshold as usize,
+            "{} signatures given, exactly {} required",
+            self.signatures.len(),
+            public_key.threshold
+        );
+        for (index, signature) in &self.signatures {
+            let key = public_key
+                .public_keys
+                .get(*index as usize)
+                .ok_or_else(|| format_err!("No key at index {}", index))?;
+            signing::verify_message(message, signature, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// The key and signatures authenticating the sender of a transaction.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TransactionAuthenticator {
+    /// A single ed25519 signature.
+    Ed25519 {
+        public_key: PublicKey,
+        signature: Signature,
+    },
+    /// Signatures of exactly `threshold` keys of a multi-ed25519 policy.
+    MultiEd25519 {
+        public_key: MultiEd25519PublicKey,
+        signature: MultiEd25519Signature,
+    },
+}
+
+impl TransactionAuthenticator {
+    pub fn ed25519(public_key: PublicKey, signature: Signature) -> Self {
+        TransactionAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        }
+    }
+
+    pub fn multi_ed25519(
+        public_key: MultiEd25519PublicKey,
+        signature: MultiEd25519Signature,
+    ) -> Self {
+        TransactionAuthenticator::MultiEd25519 {
+            public_key,
+            signature,
+        }
+    }
+
+    /// The bytes whose sha3 has to match the authentication key of the sender.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_slice().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { public_key, .. } => public_key.to_bytes(),
+        }
+    }
+
+    pub fn signature_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { signature, .. } => signature.to_compact().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { signature, .. } => signature.to_bytes(),
+        }
+    }
+
+    /// Checks the signatures of `message`.
+    pub fn verify(&self, message: HashValue) -> Result<()> {
+        match self {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => signing::verify_message(message, signature, public_key),
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => signature.verify(message, public_key),
+        }
+    }
+
+    /// Decodes an authenticator from its public key and signature bytes. A single ed25519 key is
+    /// exactly `PublicKey::LENGTH` bytes long while a policy is one byte longer than a multiple of
+    /// it, so the encodings can not be confused.
+    pub fn from_bytes(public_key: &[u8], signature: &[u8]) -> Result<Self> {
+        if public_key.len() == PublicKey::LENGTH {
+            Ok(Self::ed25519(
+                PublicKey::from_slice(public_key)?,
+                Signature::from_compact(signature)?,
+            ))
+        } else {
+            Ok(Self::multi_ed25519(
+                MultiEd25519PublicKey::from_bytes(public_key)?,
+                MultiEd25519Signature::from_bytes(signature)?,
+            ))
+        }
+    }
+}
+
+impl fmt::Debug for TransactionAuthenticator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => write!(
+                f,
+                "Ed25519 {{ public_key: {:?}, signature: {:?} }}",
+                public_key, signature
+            ),
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => write!(
+                f,
+                "MultiEd25519 {{ threshold: {}, public_keys: {:?}, signatures: {:?} }}",
+                public_key.threshold, public_key.public_keys, signature.signatures
+            ),
+        }
+    }
+}
@@ -22,6 +22,7 @@ pub fn get_signed_transactions_digest(signed_txns: &[ProtoSignedTransaction]) ->
     let mut signatures = vec![];
     for transaction in signed_txns {
         signatures.extend_from_slice(&transaction.sender_signature);
+        signatures.extend_from_slice(&transaction.sender_multi_signature);
     }
     signatures.test_only_hash()
 }
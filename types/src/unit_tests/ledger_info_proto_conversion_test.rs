@@ -1,9 +1,15 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
+use crate::ledger_info::{AggregatedSignature, LedgerInfo, LedgerInfoWithSignatures};
+use crypto::hash::CryptoHash;
+use nextgen_crypto::{
+    bls12381::BLS12381PrivateKey,
+    traits::{SigningKey, Uniform},
+};
 use proptest::prelude::*;
 use proto_conv::test_helper::assert_protobuf_encode_decode;
+use rand::{rngs::StdRng, SeedableRng};
 
 proptest! {
     #[test]
@@ -30,3 +36,20 @@ proptest! {
         assert_protobuf_encode_decode(&ledger_info_with_signatures);
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+    #[test]
+    fn test_ledger_info_with_aggregated_signature(
+        ledger_info in any::<LedgerInfo>(),
+        seed in any::<[u8; 32]>(),
+    ) {
+        let private_key = BLS12381PrivateKey::generate_for_testing(&mut StdRng::from_seed(seed));
+        let signature = private_key.sign_message(&ledger_info.hash());
+        let ledger_info_with_signatures = LedgerInfoWithSignatures::new_aggregated(
+            ledger_info,
+            AggregatedSignature::new(signature, vec![0x80]),
+        );
+        assert_protobuf_encode_decode(&ledger_info_with_signatures);
+    }
+}
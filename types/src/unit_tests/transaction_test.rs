@@ -3,8 +3,12 @@
 
 use crate::{
     account_address::AccountAddress,
-    transaction::{Program, RawTransaction, SignedTransaction},
+    test_helpers::transaction_test_helpers::get_test_multi_signed_txn,
+    transaction::{
+        MultiEd25519PublicKey, MultiEd25519Signature, Program, RawTransaction, SignedTransaction,
+    },
 };
+use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
 use crypto::{
     signing::{generate_keypair, Signature},
     utils::keypair_strategy,
@@ -35,6 +39,95 @@ fn test_invalid_signature() {
         .expect_err("signature checking should fail");
 }
 
+#[test]
+fn test_multi_ed25519_signature() {
+    let keypairs: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+    let public_key =
+        MultiEd25519PublicKey::new(keypairs.iter().map(|(_, pk)| *pk).collect(), 2).unwrap();
+    let sender = AccountAddress::random();
+
+    // Two of three keys sign.
+    let txn = get_test_multi_signed_txn(
+        sender,
+        0,
+        &[(0, keypairs[0].0.clone()), (2, keypairs[2].0.clone())],
+        public_key.clone(),
+        None,
+    );
+    assert_eq!(txn.public_key_bytes(), public_key.to_bytes());
+    let txn = SignedTransaction::from_proto(txn.into_proto()).unwrap();
+    let bytes = SimpleSerializer::<Vec<u8>>::serialize(&txn).unwrap();
+    assert_eq!(
+        SimpleDeserializer::deserialize::<SignedTransaction>(&bytes).unwrap(),
+        txn
+    );
+    txn.check_signature().unwrap();
+
+    // Below the threshold.
+    let txn = get_test_multi_signed_txn(
+        sender,
+        0,
+        &[(1, keypairs[1].0.clone())],
+        public_key.clone(),
+        None,
+    );
+    txn.check_signature()
+        .expect_err("a single signature should not meet the threshold");
+
+    // Above the threshold, a relayer could drop either signature and get another valid
+    // transaction.
+    let txn = get_test_multi_signed_txn(
+        sender,
+        0,
+        &[
+            (0, keypairs[0].0.clone()),
+            (1, keypairs[1].0.clone()),
+            (2, keypairs[2].0.clone()),
+        ],
+        public_key.clone(),
+        None,
+    );
+    txn.check_signature()
+        .expect_err("more signatures than the threshold should be refused");
+
+    // A signature given for the wrong key.
+    let txn = get_test_multi_signed_txn(
+        sender,
+        0,
+        &[(0, keypairs[0].0.clone()), (1, keypairs[2].0.clone())],
+        public_key,
+        None,
+    );
+    txn.check_signature()
+        .expect_err("signature checking should fail");
+}
+
+#[test]
+fn test_multi_ed25519_encoding() {
+    let keys: Vec<_> = (0..3).map(|_| generate_keypair().1).collect();
+    assert!(MultiEd25519PublicKey::new(keys.clone(), 0).is_err());
+    assert!(MultiEd25519PublicKey::new(keys.clone(), 4).is_err());
+    let public_key = MultiEd25519PublicKey::new(keys, 2).unwrap();
+    let bytes = public_key.to_bytes();
+    assert_eq!(bytes.len(), 3 * 32 + 1);
+    assert_eq!(
+        MultiEd25519PublicKey::from_bytes(&bytes).unwrap(),
+        public_key
+    );
+
+    let signature = Signature::from_compact(&[0; 64]).unwrap();
+    let multi_signature = MultiEd25519Signature::new(vec![(9, signature), (0, signature)]).unwrap();
+    let bytes = multi_signature.to_bytes();
+    assert_eq!(&bytes[..4], &[0b1000_0000, 0b0100_0000, 0, 0]);
+    assert_eq!(
+        MultiEd25519Signature::from_bytes(&bytes).unwrap(),
+        multi_signature
+    );
+    assert!(MultiEd25519Signature::new(vec![(1, signature), (1, signature)]).is_err());
+    // The bitmap announces more signatures than given.
+    assert!(MultiEd25519Signature::from_bytes(&bytes[..68]).is_err());
+}
+
 proptest! {
     #[test]
     fn test_sig(raw_txn in any::<RawTransaction>(), (sk1, pk1) in keypair_strategy()) {
@@ -0,0 +1,37 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::vm_error::{ExecutionStatus, VMStatus};
+use canonical_serialization::SimpleSerializer;
+use proptest::prelude::*;
+use proptest_helpers::with_stack_size;
+
+fn canonical_bytes(vm_status: &VMStatus) -> Vec<u8> {
+    SimpleSerializer::<Vec<u8>>::serialize(vm_status).unwrap()
+}
+
+#[test]
+fn test_vm_status_canonical_serialization() {
+    // The variant tag, the execution status tag and the runtime status code, as u32s.
+    assert_eq!(
+        canonical_bytes(&VMStatus::Execution(ExecutionStatus::Executed)),
+        vec![3, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0],
+    );
+    assert_eq!(
+        canonical_bytes(&VMStatus::Execution(ExecutionStatus::AssertionFailure(7))),
+        vec![3, 0, 0, 0, 3, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0],
+    );
+}
+
+#[test]
+fn test_vm_status_canonical_serialization_is_injective() {
+    with_stack_size(4 * 1024 * 1024, || {
+        proptest!(|(status1 in any::<VMStatus>(), status2 in any::<VMStatus>())| {
+            prop_assert_eq!(
+                status1 == status2,
+                canonical_bytes(&status1) == canonical_bytes(&status2)
+            );
+        })
+    })
+    .unwrap();
+}
@@ -3,7 +3,13 @@
 
 #![allow(clippy::unit_arg)]
 
-use crate::{contract_event::EventWithProof, ledger_info::LedgerInfoWithSignatures};
+use crate::{
+    access_path::VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH, contract_event::EventWithProof,
+    ledger_info::LedgerInfoWithSignatures, validator_set::ValidatorSet,
+    validator_verifier::ValidatorVerifier,
+};
+use canonical_serialization::SimpleDeserializer;
+use failure::prelude::*;
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
 
@@ -13,3 +19,41 @@ pub struct ValidatorChangeEventWithProof {
     ledger_info_with_sigs: LedgerInfoWithSignatures,
     event_with_proof: EventWithProof,
 }
+
+impl ValidatorChangeEventWithProof {
+    /// Constructor.
+    pub fn new(
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+        event_with_proof: EventWithProof,
+    ) -> Self {
+        ValidatorChangeEventWithProof {
+            ledger_info_with_sigs,
+            event_with_proof,
+        }
+    }
+
+    pub fn ledger_info_with_sigs(&self) -> &LedgerInfoWithSignatures {
+        &self.ledger_info_with_sigs
+    }
+
+    pub fn event_with_proof(&self) -> &EventWithProof {
+        &self.event_with_proof
+    }
+
+    /// Verifies that the ledger info is signed by a quorum of the current validators and that the
+    /// event is part of the ledger it describes and was emitted on the validator set change event
+    /// path, so that no other account can announce validators. Returns the validator set announced
+    /// by the event, which signs the ledger infos from then on.
+    pub fn verify(&self, validator_verifier: &ValidatorVerifier) -> Result<ValidatorSet> {
+        self.ledger_info_with_sigs.verify(validator_verifier)?;
+        let event = &self.event_with_proof;
+        event.verify(
+            self.ledger_info_with_sigs.ledger_info(),
+            &VALIDATOR_SET_CHANGE_EVENT_ACCESS_PATH,
+            event.event.sequence_number(),
+            event.transaction_version,
+            event.event_index,
+        )?;
+        SimpleDeserializer::deserialize(event.event.event_data())
+    }
+}
@@ -11,9 +11,16 @@ use canonical_serialization::{
     CanonicalDeserialize, CanonicalDeserializer, CanonicalSerialize, CanonicalSerializer,
 };
 use crypto::{x25519::X25519PublicKey, PublicKey};
-use failure::Result;
+use failure::prelude::*;
+use nextgen_crypto::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+    traits::Uniform,
+};
+use proptest::prelude::*;
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
+use rand::{rngs::StdRng, SeedableRng};
+use std::convert::TryFrom;
 
 /// After executing a special transaction that sets the validators that should be used for the
 /// next epoch, consensus and networking get the new list of validators.  Consensus will have a
@@ -26,6 +33,11 @@ pub struct ValidatorPublicKeys {
     account_address: AccountAddress,
     // This key can validate messages sent from this validator
     consensus_public_key: PublicKey,
+    // This key can validate the BLS12-381 signatures of this validator, which are aggregated into
+    // the quorum certificates when consensus runs with BLS12-381 signatures. It comes with the
+    // proof of possession of its private key, which is checked before the key is aggregated
+    #[proptest(strategy = "arb_bls_public_key()")]
+    consensus_bls_public_key: Option<(BLS12381PublicKey, BLS12381Signature)>,
     // This key can validate signed messages at the network layer
     network_signing_public_key: PublicKey,
     // This key establishes the corresponding PrivateKey holder's eligibility to join the p2p
@@ -37,12 +49,14 @@ impl ValidatorPublicKeys {
     pub fn new(
         account_address: AccountAddress,
         consensus_public_key: PublicKey,
+        consensus_bls_public_key: Option<(BLS12381PublicKey, BLS12381Signature)>,
         network_signing_public_key: PublicKey,
         network_identity_public_key: X25519PublicKey,
     ) -> Self {
         ValidatorPublicKeys {
             account_address,
             consensus_public_key,
+            consensus_bls_public_key,
             network_signing_public_key,
             network_identity_public_key,
         }
@@ -59,6 +73,12 @@ impl ValidatorPublicKeys {
         &self.consensus_public_key
     }
 
+    /// Returns the key for validating the BLS12-381 signatures from this validator along with its
+    /// proof of possession, if it has one
+    pub fn consensus_bls_public_key(&self) -> Option<&(BLS12381PublicKey, BLS12381Signature)> {
+        self.consensus_bls_public_key.as_ref()
+    }
+
     /// Returns the key for validating signed messages at the network layers
     pub fn network_signing_public_key(&self) -> &PublicKey {
         &self.network_signing_public_key
@@ -76,6 +96,10 @@ impl FromProto for ValidatorPublicKeys {
     fn from_proto(object: Self::ProtoType) -> Result<Self> {
         let account_address = AccountAddress::from_proto(object.get_account_address().to_vec())?;
         let consensus_public_key = PublicKey::from_slice(object.get_consensus_public_key())?;
+        let consensus_bls_public_key = decode_bls_public_key(
+            object.get_consensus_bls_public_key(),
+            object.get_consensus_bls_proof_of_possession(),
+        )?;
         let network_signing_public_key =
             PublicKey::from_slice(object.get_network_signing_public_key())?;
         let network_identity_public_key =
@@ -83,6 +107,7 @@ impl FromProto for ValidatorPublicKeys {
         Ok(Self::new(
             account_address,
             consensus_public_key,
+            consensus_bls_public_key,
             network_signing_public_key,
             network_identity_public_key,
         ))
@@ -96,6 +121,10 @@ impl IntoProto for ValidatorPublicKeys {
         let mut proto = Self::ProtoType::new();
         proto.set_account_address(AccountAddress::into_proto(self.account_address));
         proto.set_consensus_public_key(PublicKey::to_slice(&self.consensus_public_key).to_vec());
+        let (bls_public_key, bls_proof_of_possession) =
+            encode_bls_public_key(&self.consensus_bls_public_key);
+        proto.set_consensus_bls_public_key(bls_public_key);
+        proto.set_consensus_bls_proof_of_possession(bls_proof_of_possession);
         proto.set_network_signing_public_key(
             PublicKey::to_slice(&self.network_signing_public_key).to_vec(),
         );
@@ -108,8 +137,12 @@ impl IntoProto for ValidatorPublicKeys {
 
 impl CanonicalSerialize for ValidatorPublicKeys {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        let (bls_public_key, bls_proof_of_possession) =
+            encode_bls_public_key(&self.consensus_bls_public_key);
         serializer
             .encode_struct(&self.account_address)?
+            .encode_variable_length_bytes(&bls_proof_of_possession)?
+            .encode_variable_length_bytes(&bls_public_key)?
             .encode_variable_length_bytes(&self.consensus_public_key.to_slice())?
             .encode_variable_length_bytes(&self.network_identity_public_key.to_slice())?
             .encode_variable_length_bytes(&self.network_signing_public_key.to_slice())?;
@@ -120,6 +153,11 @@ impl CanonicalSerialize for ValidatorPublicKeys {
 impl CanonicalDeserialize for ValidatorPublicKeys {
     fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
         let account_address = deserializer.decode_struct::<AccountAddress>()?;
+        let bls_proof_of_possession = deserializer.decode_variable_length_bytes()?;
+        let consensus_bls_public_key = decode_bls_public_key(
+            &deserializer.decode_variable_length_bytes()?,
+            &bls_proof_of_possession,
+        )?;
         let concensus_public_key =
             PublicKey::from_slice(&deserializer.decode_variable_length_bytes()?)?;
         let network_identity_public_key =
@@ -129,8 +167,48 @@ impl CanonicalDeserialize for ValidatorPublicKeys {
         Ok(ValidatorPublicKeys::new(
             account_address,
             concensus_public_key,
+            consensus_bls_public_key,
             network_signing_public_key,
             network_identity_public_key,
         ))
     }
 }
+
+/// The BLS12-381 public key and its proof of possession are encoded as empty bytes if the
+/// validator has none.
+fn encode_bls_public_key(
+    public_key: &Option<(BLS12381PublicKey, BLS12381Signature)>,
+) -> (Vec<u8>, Vec<u8>) {
+    public_key
+        .as_ref()
+        .map_or_else(Default::default, |(public_key, proof_of_possession)| {
+            (
+                public_key.to_bytes().to_vec(),
+                proof_of_possession.to_bytes().to_vec(),
+            )
+        })
+}
+
+fn decode_bls_public_key(
+    public_key: &[u8],
+    proof_of_possession: &[u8],
+) -> Result<Option<(BLS12381PublicKey, BLS12381Signature)>> {
+    match (public_key.is_empty(), proof_of_possession.is_empty()) {
+        (true, true) => Ok(None),
+        (false, false) => Ok(Some((
+            BLS12381PublicKey::from_bytes(public_key)?,
+            BLS12381Signature::try_from(proof_of_possession)?,
+        ))),
+        _ => bail!("A BLS12-381 public key has to come with its proof of possession"),
+    }
+}
+
+fn arb_bls_public_key() -> impl Strategy<Value = Option<(BLS12381PublicKey, BLS12381Signature)>> {
+    proptest::option::of(any::<[u8; 32]>().prop_map(|seed| {
+        let private_key = BLS12381PrivateKey::generate_for_testing(&mut StdRng::from_seed(seed));
+        (
+            BLS12381PublicKey::from(&private_key),
+            private_key.proof_of_possession(),
+        )
+    }))
+}
@@ -32,6 +32,16 @@ pub(crate) fn validator_set_path() -> Vec<u8> {
     AccessPath::resource_access_vec(&validator_set_tag(), &Accesses::empty())
 }
 
+/// Return the path of the events announcing a new validator set, under the account holding the
+/// validator set. Light clients only accept validator changes emitted there.
+pub fn validator_set_change_event_path() -> Vec<u8> {
+    let mut path = validator_set_path();
+    path.push(b'/');
+    path.extend_from_slice(b"change_events_count");
+    path.push(b'/');
+    path
+}
+
 #[derive(Arbitrary, Clone, Debug, Eq, PartialEq)]
 pub struct ValidatorSet(Vec<ValidatorPublicKeys>);
 
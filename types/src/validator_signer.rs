@@ -4,17 +4,48 @@
 use crate::account_address::{AccountAddress, ADDRESS_LENGTH};
 use crypto::{signing, HashValue, PrivateKey, PublicKey, Signature};
 use failure::Error;
+use nextgen_crypto::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+    vrf::ecvrf::{Proof, VRFPrivateKey, VRFPublicKey},
+};
 use proptest::{prelude::*, sample, strategy::LazyJust};
-use std::convert::TryFrom;
+use std::{convert::TryFrom, fmt::Debug, sync::Arc};
+
+/// Signs messages with a private key that is held outside of this process, e.g. by the secret
+/// service.
+pub trait RemoteSigner: Debug + Send + Sync {
+    /// Constructs a signature for `message` with the remote private key.
+    fn sign_message(&self, message: HashValue) -> Result<Signature, Error>;
+
+    /// Constructs a BLS12-381 signature for `message` with the remote private key, which fails if
+    /// it is not a BLS12-381 key.
+    fn sign_bls_message(&self, message: HashValue) -> Result<BLS12381Signature, Error>;
+}
+
+#[derive(Debug, Clone)]
+enum SigningKey {
+    Local(PrivateKey),
+    Remote(Arc<dyn RemoteSigner>),
+}
+
+#[derive(Debug, Clone)]
+enum BLSSigningKey {
+    Local(Arc<BLS12381PrivateKey>),
+    Remote(BLS12381PublicKey, Arc<dyn RemoteSigner>),
+}
 
 /// ValidatorSigner associates an author with public and private keys with helpers for signing and
 /// validating. This struct can be used for all signing operations including block and network
-/// signing, respectively.
+/// signing, respectively. The private key is either held by the signer or by a `RemoteSigner`.
+/// A signer may have a BLS12-381 key as well, local or remote, for the signatures that get
+/// aggregated, and an ECVRF private key for the VRF proofs of the proposer election.
 #[derive(Debug, Clone)]
 pub struct ValidatorSigner {
     author: AccountAddress,
     public_key: PublicKey,
-    private_key: PrivateKey,
+    private_key: SigningKey,
+    bls_signing_key: Option<BLSSigningKey>,
+    vrf_private_key: Option<Arc<VRFPrivateKey>>,
 }
 
 impl ValidatorSigner {
@@ -26,10 +57,51 @@ impl ValidatorSigner {
         ValidatorSigner {
             author: account_address,
             public_key,
-            private_key,
+            private_key: SigningKey::Local(private_key),
+            bls_signing_key: None,
+            vrf_private_key: None,
+        }
+    }
+
+    /// A signer whose signatures are produced by `remote_signer`. `public_key` has to be the
+    /// public key of the remote private key.
+    pub fn new_remote(
+        account_address: AccountAddress,
+        public_key: PublicKey,
+        remote_signer: Arc<dyn RemoteSigner>,
+    ) -> Self {
+        ValidatorSigner {
+            author: account_address,
+            public_key,
+            private_key: SigningKey::Remote(remote_signer),
+            bls_signing_key: None,
+            vrf_private_key: None,
         }
     }
 
+    /// Adds a BLS12-381 private key to the signer, see `sign_bls_message`.
+    pub fn with_bls_private_key(mut self, bls_private_key: Arc<BLS12381PrivateKey>) -> Self {
+        self.bls_signing_key = Some(BLSSigningKey::Local(bls_private_key));
+        self
+    }
+
+    /// Adds a BLS12-381 key held by `remote_signer` to the signer, see `sign_bls_message`.
+    /// `bls_public_key` has to be the public key of the remote private key.
+    pub fn with_remote_bls_signer(
+        mut self,
+        bls_public_key: BLS12381PublicKey,
+        remote_signer: Arc<dyn RemoteSigner>,
+    ) -> Self {
+        self.bls_signing_key = Some(BLSSigningKey::Remote(bls_public_key, remote_signer));
+        self
+    }
+
+    /// Adds an ECVRF private key to the signer, see `vrf_prove`.
+    pub fn with_vrf_private_key(mut self, vrf_private_key: Arc<VRFPrivateKey>) -> Self {
+        self.vrf_private_key = Some(vrf_private_key);
+        self
+    }
+
     /// Generate the genesis block signer information.
     pub fn genesis() -> Self {
         let (private_key, public_key) = signing::generate_genesis_keypair();
@@ -39,11 +111,7 @@ impl ValidatorSigner {
     /// Generate a random set of public and private keys and author information.
     pub fn random() -> Self {
         let (private_key, public_key) = signing::generate_keypair();
-        ValidatorSigner {
-            author: AccountAddress::from(public_key),
-            public_key,
-            private_key,
-        }
+        Self::new(AccountAddress::from(public_key), public_key, private_key)
     }
 
     /// For test only - makes signer with nicely looking account address that has specified integer
@@ -52,16 +120,42 @@ impl ValidatorSigner {
         let mut address = [0; ADDRESS_LENGTH];
         address[0] = num;
         let (private_key, public_key) = signing::generate_keypair();
-        ValidatorSigner {
-            author: AccountAddress::try_from(&address[..]).unwrap(),
+        Self::new(
+            AccountAddress::try_from(&address[..]).unwrap(),
             public_key,
             private_key,
-        }
+        )
     }
 
     /// Constructs a signature for `message` using `private_key`.
     pub fn sign_message(&self, message: HashValue) -> Result<Signature, Error> {
-        signing::sign_message(message, &self.private_key)
+        match &self.private_key {
+            SigningKey::Local(private_key) => signing::sign_message(message, private_key),
+            SigningKey::Remote(remote_signer) => remote_signer.sign_message(message),
+        }
+    }
+
+    /// Constructs a BLS12-381 signature for `message` if the signer has a BLS12-381 key.
+    pub fn sign_bls_message(&self, message: HashValue) -> Result<Option<BLS12381Signature>, Error> {
+        match &self.bls_signing_key {
+            Some(BLSSigningKey::Local(bls_private_key)) => {
+                Ok(Some(nextgen_crypto::traits::SigningKey::sign_message(
+                    bls_private_key.as_ref(),
+                    &message,
+                )))
+            }
+            Some(BLSSigningKey::Remote(_, remote_signer)) => {
+                remote_signer.sign_bls_message(message).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Constructs a VRF proof for `alpha` if the signer has an ECVRF private key.
+    pub fn vrf_prove(&self, alpha: &[u8]) -> Option<Proof> {
+        self.vrf_private_key
+            .as_ref()
+            .map(|vrf_private_key| vrf_private_key.prove(alpha))
     }
 
     /// Checks that `signature` is valid for `message` using `public_key`.
@@ -78,6 +172,35 @@ impl ValidatorSigner {
     pub fn public_key(&self) -> PublicKey {
         self.public_key
     }
+
+    /// Returns the BLS12-381 public key of the signer, if it has a BLS12-381 key.
+    pub fn bls_public_key(&self) -> Option<BLS12381PublicKey> {
+        match &self.bls_signing_key {
+            Some(BLSSigningKey::Local(bls_private_key)) => {
+                Some(BLS12381PublicKey::from(bls_private_key.as_ref()))
+            }
+            Some(BLSSigningKey::Remote(bls_public_key, _)) => Some(bls_public_key.clone()),
+            None => None,
+        }
+    }
+
+    /// Returns the proof of possession of the BLS12-381 private key, which has to accompany the
+    /// BLS12-381 public key wherever it is registered. Only a local private key can prove it.
+    pub fn bls_proof_of_possession(&self) -> Option<BLS12381Signature> {
+        match &self.bls_signing_key {
+            Some(BLSSigningKey::Local(bls_private_key)) => {
+                Some(bls_private_key.proof_of_possession())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the ECVRF public key of the signer, if it has an ECVRF private key.
+    pub fn vrf_public_key(&self) -> Option<VRFPublicKey> {
+        self.vrf_private_key
+            .as_ref()
+            .map(|vrf_private_key| VRFPublicKey::from(vrf_private_key.as_ref()))
+    }
 }
 
 #[allow(clippy::redundant_closure)]
@@ -130,10 +253,43 @@ pub fn mostly_in_keypair_pool(
 mod tests {
     use crate::{
         account_address::AccountAddress,
-        validator_signer::{arb_keypair, arb_signer, ValidatorSigner},
+        validator_signer::{arb_keypair, arb_signer, RemoteSigner, ValidatorSigner},
+    };
+    use crypto::{signing, HashValue, PrivateKey, Signature};
+    use failure::{format_err, Error};
+    use nextgen_crypto::{
+        bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+        traits::{SigningKey, Uniform, VerifyingKey},
     };
-    use crypto::HashValue;
     use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct TestRemoteSigner(PrivateKey);
+
+    impl RemoteSigner for TestRemoteSigner {
+        fn sign_message(&self, message: HashValue) -> Result<Signature, Error> {
+            signing::sign_message(message, &self.0)
+        }
+
+        fn sign_bls_message(&self, _message: HashValue) -> Result<BLS12381Signature, Error> {
+            Err(format_err!("Not a BLS12-381 key"))
+        }
+    }
+
+    #[derive(Debug)]
+    struct TestRemoteBLSSigner(BLS12381PrivateKey);
+
+    impl RemoteSigner for TestRemoteBLSSigner {
+        fn sign_message(&self, _message: HashValue) -> Result<Signature, Error> {
+            Err(format_err!("Not an Ed25519 key"))
+        }
+
+        fn sign_bls_message(&self, message: HashValue) -> Result<BLS12381Signature, Error> {
+            Ok(self.0.sign_message(&message))
+        }
+    }
 
     proptest! {
         #[test]
@@ -150,5 +306,49 @@ mod tests {
                          .verify_message(message, &signature)
                          .is_ok());
         }
+
+        #[test]
+        fn test_remote_signer(keypair in arb_keypair(), message in HashValue::arbitrary()) {
+            let (private_key, public_key) = keypair;
+            let signer = ValidatorSigner::new_remote(
+                AccountAddress::from(public_key),
+                public_key,
+                Arc::new(TestRemoteSigner(private_key)),
+            );
+            let signature = signer.sign_message(message).unwrap();
+            prop_assert!(signer.verify_message(message, &signature).is_ok());
+        }
+
+        #[test]
+        fn test_bls_signer(signer in arb_signer(), seed in any::<[u8; 32]>(), message in HashValue::arbitrary()) {
+            prop_assert!(signer.sign_bls_message(message).unwrap().is_none());
+            let bls_private_key = BLS12381PrivateKey::generate_for_testing(&mut StdRng::from_seed(seed));
+            let signer = signer.with_bls_private_key(Arc::new(bls_private_key));
+            let signature = signer.sign_bls_message(message).unwrap().unwrap();
+            prop_assert!(signer
+                         .bls_public_key()
+                         .unwrap()
+                         .verify_signature(&message, &signature)
+                         .is_ok());
+            prop_assert!(signer
+                         .bls_public_key()
+                         .unwrap()
+                         .verify_proof_of_possession(&signer.bls_proof_of_possession().unwrap())
+                         .is_ok());
+        }
+
+        #[test]
+        fn test_remote_bls_signer(signer in arb_signer(), seed in any::<[u8; 32]>(), message in HashValue::arbitrary()) {
+            let bls_private_key = BLS12381PrivateKey::generate_for_testing(&mut StdRng::from_seed(seed));
+            let bls_public_key = BLS12381PublicKey::from(&bls_private_key);
+            let signer = signer.with_remote_bls_signer(
+                bls_public_key.clone(),
+                Arc::new(TestRemoteBLSSigner(bls_private_key)),
+            );
+            prop_assert_eq!(signer.bls_public_key(), Some(bls_public_key.clone()));
+            prop_assert!(signer.bls_proof_of_possession().is_none());
+            let signature = signer.sign_bls_message(message).unwrap().unwrap();
+            prop_assert!(bls_public_key.verify_signature(&message, &signature).is_ok());
+        }
     }
 }
@@ -1,9 +1,16 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::account_address::AccountAddress;
+use crate::{
+    account_address::AccountAddress, ledger_info::AggregatedSignature, validator_set::ValidatorSet,
+};
 use crypto::{signing, HashValue, PublicKey, Signature};
 use failure::prelude::*;
+use nextgen_crypto::{
+    bls12381::{BLS12381PublicKey, BLS12381Signature},
+    traits::VerifyingKey,
+    vrf::ecvrf::{Proof, VRFPublicKey},
+};
 use std::collections::HashMap;
 
 /// Errors possible during signature verification.
@@ -31,6 +38,12 @@ pub enum VerifyError {
     #[fail(display = "Signature is invalid")]
     /// The signature does not match the hash.
     InvalidSignature,
+    #[fail(display = "Signer bitmap is invalid")]
+    /// The signer bitmap of an aggregated signature does not match the authors.
+    InvalidSignerBitmap,
+    #[fail(display = "VRF proof is invalid")]
+    /// The VRF proof does not match the input.
+    InvalidVRFProof,
 }
 
 /// Supports validation of signatures for known authors. This struct can be used for all signature
@@ -38,6 +51,10 @@ pub enum VerifyError {
 #[derive(Clone)]
 pub struct ValidatorVerifier {
     author_to_public_keys: HashMap<AccountAddress, PublicKey>,
+    /// The BLS12-381 public keys of the authors, empty unless they sign with BLS12-381 keys.
+    author_to_bls_public_keys: HashMap<AccountAddress, BLS12381PublicKey>,
+    /// The ECVRF public keys of the authors, empty unless the proposers are elected by VRF.
+    author_to_vrf_public_keys: HashMap<AccountAddress, VRFPublicKey>,
     quorum_size: usize,
 }
 
@@ -52,6 +69,8 @@ impl ValidatorVerifier {
         };
         ValidatorVerifier {
             author_to_public_keys,
+            author_to_bls_public_keys: HashMap::new(),
+            author_to_vrf_public_keys: HashMap::new(),
             quorum_size,
         }
     }
@@ -70,6 +89,8 @@ impl ValidatorVerifier {
         );
         Ok(ValidatorVerifier {
             author_to_public_keys,
+            author_to_bls_public_keys: HashMap::new(),
+            author_to_vrf_public_keys: HashMap::new(),
             quorum_size,
         })
     }
@@ -86,6 +107,66 @@ impl ValidatorVerifier {
         Self::new(HashMap::new())
     }
 
+    /// Sets the BLS12-381 public keys of the authors, which verify the BLS signatures and the
+    /// aggregated signatures. Every author needs a key, along with the proof of possession of its
+    /// private key. Without the proofs, a rogue key chosen from the keys of the other authors could
+    /// forge an aggregated signature of a quorum.
+    pub fn with_bls_public_keys(
+        mut self,
+        author_to_bls_public_keys: HashMap<AccountAddress, (BLS12381PublicKey, BLS12381Signature)>,
+    ) -> Result<Self> {
+        ensure!(
+            author_to_bls_public_keys.len() == self.len()
+                && self
+                    .author_to_public_keys
+                    .keys()
+                    .all(|author| author_to_bls_public_keys.contains_key(author)),
+            "The BLS public keys do not match the authors."
+        );
+        let mut bls_public_keys = HashMap::new();
+        for (author, (public_key, proof_of_possession)) in author_to_bls_public_keys {
+            public_key
+                .verify_proof_of_possession(&proof_of_possession)
+                .with_context(|_| {
+                    format!(
+                        "Invalid proof of possession for the BLS public key of {}",
+                        author
+                    )
+                })?;
+            bls_public_keys.insert(author, public_key);
+        }
+        self.author_to_bls_public_keys = bls_public_keys;
+        Ok(self)
+    }
+
+    /// Whether the authors sign with BLS12-381 keys, see `with_bls_public_keys`.
+    pub fn has_bls_public_keys(&self) -> bool {
+        !self.author_to_bls_public_keys.is_empty()
+    }
+
+    /// Sets the ECVRF public keys of the authors, which verify the VRF proofs. Every author needs
+    /// a key.
+    pub fn with_vrf_public_keys(
+        mut self,
+        author_to_vrf_public_keys: HashMap<AccountAddress, VRFPublicKey>,
+    ) -> Result<Self> {
+        ensure!(
+            author_to_vrf_public_keys.len() == self.len()
+                && self
+                    .author_to_public_keys
+                    .keys()
+                    .all(|author| author_to_vrf_public_keys.contains_key(author)),
+            "The VRF public keys do not match the authors."
+        );
+        self.author_to_vrf_public_keys = author_to_vrf_public_keys;
+        Ok(self)
+    }
+
+    /// Whether the authors have ECVRF keys, see `with_vrf_public_keys`.
+    pub fn has_vrf_public_keys(&self) -> bool {
+        !self.author_to_vrf_public_keys.is_empty()
+    }
+
     /// Verify the correctness of a signature of a hash by a known author.
     pub fn verify_signature(
         &self,
@@ -137,10 +218,106 @@ impl ValidatorVerifier {
         Ok(())
     }
 
+    /// Verify the correctness of a BLS12-381 signature of a hash by a known author.
+    pub fn verify_bls_signature(
+        &self,
+        author: AccountAddress,
+        hash: HashValue,
+        signature: &BLS12381Signature,
+    ) -> std::result::Result<(), VerifyError> {
+        match self.author_to_bls_public_keys.get(&author) {
+            None => Err(VerifyError::UnknownAuthor),
+            Some(public_key) => public_key
+                .verify_signature(&hash, signature)
+                .map_err(|_| VerifyError::InvalidSignature),
+        }
+    }
+
+    /// Aggregates the BLS12-381 signatures of known authors on the same hash. The signatures are
+    /// expected to be verified already, an invalid one makes the aggregated signature invalid.
+    pub fn aggregate_bls_signatures(
+        &self,
+        signatures: &HashMap<AccountAddress, BLS12381Signature>,
+    ) -> std::result::Result<AggregatedSignature, VerifyError> {
+        let authors = self.get_ordered_account_addresses();
+        let mut signers = vec![0u8; (authors.len() + 7) / 8];
+        for author in signatures.keys() {
+            let index = authors
+                .binary_search(author)
+                .map_err(|_| VerifyError::UnknownAuthor)?;
+            signers[index / 8] |= 0x80 >> (index % 8);
+        }
+        let signature = BLS12381Signature::aggregate(signatures.values())
+            .map_err(|_| VerifyError::InvalidSignature)?;
+        Ok(AggregatedSignature::new(signature, signers))
+    }
+
+    /// This function will successfully return when the signers of the aggregated signature are
+    /// known authors, at least quorum_size of them, and the aggregated signature is valid for the
+    /// aggregate of their BLS12-381 public keys.
+    pub fn verify_bls_aggregated_signature(
+        &self,
+        hash: HashValue,
+        aggregated_signature: &AggregatedSignature,
+    ) -> std::result::Result<(), VerifyError> {
+        let authors = self.get_ordered_account_addresses();
+        if aggregated_signature.signers().len() != (authors.len() + 7) / 8 {
+            return Err(VerifyError::InvalidSignerBitmap);
+        }
+        let signers: Vec<_> = authors
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| aggregated_signature.is_signer(*index))
+            .map(|(_, author)| author)
+            .collect();
+        // The padding bits of the last byte must not be set.
+        if signers.len() != aggregated_signature.num_signers() {
+            return Err(VerifyError::InvalidSignerBitmap);
+        }
+        if signers.len() < self.quorum_size {
+            return Err(VerifyError::TooFewSignatures {
+                num_of_signatures: signers.len(),
+                quorum_size: self.quorum_size,
+            });
+        }
+        let public_keys = signers
+            .into_iter()
+            .map(|author| {
+                self.author_to_bls_public_keys
+                    .get(author)
+                    .ok_or(VerifyError::UnknownAuthor)
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        BLS12381PublicKey::aggregate(public_keys)
+            .and_then(|public_key| {
+                public_key.verify_signature(&hash, aggregated_signature.signature())
+            })
+            .map_err(|_| VerifyError::InvalidSignature)
+    }
+
+    /// Verify the correctness of a VRF proof of an input by a known author.
+    pub fn verify_vrf_proof(
+        &self,
+        author: AccountAddress,
+        alpha: &[u8],
+        proof: &Proof,
+    ) -> std::result::Result<(), VerifyError> {
+        match self.author_to_vrf_public_keys.get(&author) {
+            None => Err(VerifyError::UnknownAuthor),
+            Some(public_key) => public_key
+                .verify(proof, alpha)
+                .map_err(|_| VerifyError::InvalidVRFProof),
+        }
+    }
+
     pub fn get_public_key(&self, author: AccountAddress) -> Option<PublicKey> {
         self.author_to_public_keys.get(&author).cloned()
     }
 
+    pub fn get_bls_public_key(&self, author: AccountAddress) -> Option<BLS12381PublicKey> {
+        self.author_to_bls_public_keys.get(&author).cloned()
+    }
+
     /// Returns a ordered list of account addresses from smallest to largest.
     pub fn get_ordered_account_addresses(&self) -> Vec<AccountAddress> {
         let mut account_addresses: Vec<AccountAddress> = self
@@ -169,15 +346,57 @@ impl ValidatorVerifier {
     }
 }
 
+impl From<&ValidatorSet> for ValidatorVerifier {
+    /// The verifier of the ledger infos signed by the consensus keys of a validator set. The
+    /// aggregated signatures are verified as well if every validator published a BLS12-381 key
+    /// with a valid proof of possession.
+    fn from(validator_set: &ValidatorSet) -> Self {
+        let verifier = ValidatorVerifier::new(
+            validator_set
+                .payload()
+                .iter()
+                .map(|keys| (*keys.account_address(), *keys.consensus_public_key()))
+                .collect(),
+        );
+        let bls_public_keys: Option<HashMap<_, _>> = validator_set
+            .payload()
+            .iter()
+            .map(|keys| {
+                keys.consensus_bls_public_key()
+                    .map(|(public_key, proof_of_possession)| {
+                        (
+                            *keys.account_address(),
+                            (public_key.clone(), proof_of_possession.clone()),
+                        )
+                    })
+            })
+            .collect();
+        match bls_public_keys {
+            // With an invalid proof of possession, aggregated signatures are rejected as if the
+            // validators had no BLS keys.
+            Some(bls_public_keys) if !bls_public_keys.is_empty() => verifier
+                .clone()
+                .with_bls_public_keys(bls_public_keys)
+                .unwrap_or(verifier),
+            _ => verifier,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         account_address::AccountAddress,
+        ledger_info::AggregatedSignature,
         validator_signer::ValidatorSigner,
         validator_verifier::{ValidatorVerifier, VerifyError},
     };
     use crypto::{HashValue, PublicKey, Signature};
-    use std::collections::HashMap;
+    use nextgen_crypto::{
+        bls12381::BLS12381PrivateKey, traits::Uniform, vrf::ecvrf::VRFPrivateKey,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{collections::HashMap, sync::Arc};
 
     #[test]
     fn test_validator() {
@@ -295,4 +514,169 @@ mod tests {
             Err(VerifyError::UnknownAuthor)
         );
     }
+
+    #[test]
+    fn test_bls_aggregated_signature() {
+        // Generate 4 random signers with BLS keys, the quorum size is 3.
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let validator_signers: Vec<ValidatorSigner> = (0..4)
+            .map(|_| {
+                ValidatorSigner::random().with_bls_private_key(Arc::new(
+                    BLS12381PrivateKey::generate_for_testing(&mut rng),
+                ))
+            })
+            .collect();
+        let random_hash = HashValue::random();
+        let validator_verifier = ValidatorVerifier::new(
+            validator_signers
+                .iter()
+                .map(|signer| (signer.author(), signer.public_key()))
+                .collect(),
+        );
+        assert!(validator_verifier
+            .clone()
+            .with_bls_public_keys(HashMap::new())
+            .is_err());
+        // A key is refused without the proof of possession of its own private key.
+        assert!(validator_verifier
+            .clone()
+            .with_bls_public_keys(
+                validator_signers
+                    .iter()
+                    .map(|signer| {
+                        (
+                            signer.author(),
+                            (
+                                signer.bls_public_key().unwrap(),
+                                validator_signers[0].bls_proof_of_possession().unwrap(),
+                            ),
+                        )
+                    })
+                    .collect(),
+            )
+            .is_err());
+        let validator_verifier = validator_verifier
+            .with_bls_public_keys(
+                validator_signers
+                    .iter()
+                    .map(|signer| {
+                        (
+                            signer.author(),
+                            (
+                                signer.bls_public_key().unwrap(),
+                                signer.bls_proof_of_possession().unwrap(),
+                            ),
+                        )
+                    })
+                    .collect(),
+            )
+            .unwrap();
+
+        // Aggregate the signatures of 3 signers; this will pass.
+        let mut author_to_signature_map: HashMap<_, _> = validator_signers
+            .iter()
+            .take(3)
+            .map(|signer| {
+                (
+                    signer.author(),
+                    signer.sign_bls_message(random_hash).unwrap().unwrap(),
+                )
+            })
+            .collect();
+        let aggregated_signature = validator_verifier
+            .aggregate_bls_signatures(&author_to_signature_map)
+            .unwrap();
+        assert_eq!(aggregated_signature.num_signers(), 3);
+        assert_eq!(
+            validator_verifier.verify_bls_aggregated_signature(random_hash, &aggregated_signature),
+            Ok(())
+        );
+        assert_eq!(
+            validator_verifier
+                .verify_bls_aggregated_signature(HashValue::random(), &aggregated_signature),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // Claim that all 4 validators signed; this will fail.
+        let all_signers =
+            AggregatedSignature::new(aggregated_signature.signature().clone(), vec![0xf0]);
+        assert_eq!(
+            validator_verifier.verify_bls_aggregated_signature(random_hash, &all_signers),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // Set a bit past the last validator; this will fail.
+        let padding_signer = AggregatedSignature::new(
+            aggregated_signature.signature().clone(),
+            vec![aggregated_signature.signers()[0] | 0x01],
+        );
+        assert_eq!(
+            validator_verifier.verify_bls_aggregated_signature(random_hash, &padding_signer),
+            Err(VerifyError::InvalidSignerBitmap)
+        );
+
+        // Aggregate the signatures of 2 signers only (quorum threshold is NOT met); this will fail.
+        author_to_signature_map.remove(&validator_signers[0].author());
+        let aggregated_signature = validator_verifier
+            .aggregate_bls_signatures(&author_to_signature_map)
+            .unwrap();
+        assert_eq!(
+            validator_verifier.verify_bls_aggregated_signature(random_hash, &aggregated_signature),
+            Err(VerifyError::TooFewSignatures {
+                num_of_signatures: 2,
+                quorum_size: 3
+            })
+        );
+
+        // Add an unknown signer; this will fail.
+        let unknown_validator_signer = ValidatorSigner::random()
+            .with_bls_private_key(Arc::new(BLS12381PrivateKey::generate_for_testing(&mut rng)));
+        author_to_signature_map.insert(
+            unknown_validator_signer.author(),
+            unknown_validator_signer
+                .sign_bls_message(random_hash)
+                .unwrap()
+                .unwrap(),
+        );
+        assert_eq!(
+            validator_verifier.aggregate_bls_signatures(&author_to_signature_map),
+            Err(VerifyError::UnknownAuthor)
+        );
+    }
+
+    #[test]
+    fn test_vrf_proof() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let validator_signer = ValidatorSigner::random()
+            .with_vrf_private_key(Arc::new(VRFPrivateKey::generate_for_testing(&mut rng)));
+        let validator_verifier =
+            ValidatorVerifier::new_single(validator_signer.author(), validator_signer.public_key());
+        assert!(validator_verifier
+            .clone()
+            .with_vrf_public_keys(HashMap::new())
+            .is_err());
+        let mut author_to_vrf_public_keys = HashMap::new();
+        author_to_vrf_public_keys.insert(
+            validator_signer.author(),
+            validator_signer.vrf_public_key().unwrap(),
+        );
+        let validator_verifier = validator_verifier
+            .with_vrf_public_keys(author_to_vrf_public_keys)
+            .unwrap();
+
+        let proof = validator_signer.vrf_prove(b"round 1").unwrap();
+        assert_eq!(
+            validator_verifier.verify_vrf_proof(validator_signer.author(), b"round 1", &proof),
+            Ok(())
+        );
+        assert_eq!(
+            validator_verifier.verify_vrf_proof(validator_signer.author(), b"round 2", &proof),
+            Err(VerifyError::InvalidVRFProof)
+        );
+        assert_eq!(
+            validator_verifier.verify_vrf_proof(AccountAddress::random(), b"round 1", &proof),
+            Err(VerifyError::UnknownAuthor)
+        );
+        assert!(ValidatorSigner::random().vrf_prove(b"round 1").is_none());
+    }
 }
@@ -4,14 +4,17 @@
 #![allow(clippy::unit_arg)]
 
 use crate::language_storage::ModuleId;
+use canonical_serialization::{CanonicalSerialize, CanonicalSerializer};
 use failure::prelude::*;
 use proptest::{collection::vec, prelude::*};
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
+use protobuf::ProtobufEnum;
+use serde::{Deserialize, Serialize};
 
 // We want conversions here so that we don't need to be dealing with the unknown default values
 // that we want in the protobuf.
-#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 #[proptest(no_params)]
 pub enum VMValidationStatus {
     InvalidSignature,
@@ -33,7 +36,7 @@ pub enum VMValidationStatus {
 }
 
 // TODO: Add string parameters to all the other types as well
-#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 #[proptest(no_params)]
 pub enum VMVerificationError {
     IndexOutOfBounds(String),
@@ -105,7 +108,7 @@ pub enum VMVerificationError {
     CreateAccountTypeMismatchError(String),
 }
 
-#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 #[proptest(no_params)]
 pub enum VMVerificationStatus {
     /// Verification error in a transaction script.
@@ -117,7 +120,7 @@ pub enum VMVerificationStatus {
     Dependency(ModuleId, VMVerificationError),
 }
 
-#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum VMInvariantViolationError {
     OutOfBoundsIndex,
     OutOfBoundsRange,
@@ -129,7 +132,7 @@ pub enum VMInvariantViolationError {
     StorageError,
 }
 
-#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum BinaryError {
     Malformed,
     BadMagic,
@@ -143,7 +146,7 @@ pub enum BinaryError {
     DuplicateTable,
 }
 
-#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum DynamicReferenceErrorType {
     MoveOfBorrowedResource,
     GlobalRefAlreadyReleased,
@@ -151,14 +154,14 @@ pub enum DynamicReferenceErrorType {
     GlobalAlreadyBorrowed,
 }
 
-#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ArithmeticErrorType {
     Underflow,
     Overflow,
     DivisionByZero,
 }
 
-#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ExecutionStatus {
     Executed,
     OutOfGas,
@@ -180,7 +183,7 @@ pub enum ExecutionStatus {
     DuplicateModuleName,
 }
 
-#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Arbitrary, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 #[proptest(no_params)]
 pub enum VMStatus {
     Validation(VMValidationStatus),
@@ -1127,3 +1130,98 @@ impl FromProto for VMStatus {
         }
     }
 }
+
+//***********************************
+// Canonical serialization
+//***********************************
+// A status is hashed as part of the `TransactionInfo` of a committed transaction. Its canonical
+// form is a tag for each variant followed by its fields, where the leaf enums are written as their
+// protobuf codes, so the hash doesn't depend on the protobuf encoder.
+
+impl CanonicalSerialize for VMVerificationError {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        let (kind, message) = self.clone().into_proto();
+        serializer
+            .encode_u32(kind.value() as u32)?
+            .encode_variable_length_bytes(message.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for VMVerificationStatus {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            VMVerificationStatus::Script(error) => {
+                serializer.encode_u32(0)?.encode_struct(error)?;
+            }
+            VMVerificationStatus::Module(module_idx, error) => {
+                serializer
+                    .encode_u32(1)?
+                    .encode_u32(u32::from(*module_idx))?
+                    .encode_struct(error)?;
+            }
+            VMVerificationStatus::Dependency(dependency_id, error) => {
+                serializer
+                    .encode_u32(2)?
+                    .encode_struct(dependency_id)?
+                    .encode_struct(error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for ExecutionStatus {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        let status = self.into_proto();
+        if status.has_runtime_status() {
+            serializer
+                .encode_u32(0)?
+                .encode_u32(status.get_runtime_status().value() as u32)?;
+        } else if status.has_arithmetic_error() {
+            serializer
+                .encode_u32(1)?
+                .encode_u32(status.get_arithmetic_error().get_error_code().value() as u32)?;
+        } else if status.has_reference_error() {
+            serializer
+                .encode_u32(2)?
+                .encode_u32(status.get_reference_error().get_error_code().value() as u32)?;
+        } else {
+            serializer
+                .encode_u32(3)?
+                .encode_u64(status.get_assertion_failure().get_assertion_error_code())?;
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for VMStatus {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            VMStatus::Validation(status) => {
+                let status = status.clone().into_proto();
+                serializer
+                    .encode_u32(0)?
+                    .encode_u32(status.get_code().value() as u32)?
+                    .encode_variable_length_bytes(status.get_message().as_bytes())?;
+            }
+            VMStatus::InvariantViolation(error) => {
+                serializer
+                    .encode_u32(1)?
+                    .encode_u32(error.into_proto().value() as u32)?;
+            }
+            VMStatus::Deserialization(error) => {
+                serializer
+                    .encode_u32(2)?
+                    .encode_u32(error.into_proto().value() as u32)?;
+            }
+            VMStatus::Execution(status) => {
+                serializer.encode_u32(3)?.encode_struct(status)?;
+            }
+            VMStatus::Verification(statuses) => {
+                serializer.encode_u32(4)?.encode_vec(statuses)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -18,7 +18,10 @@ use storage_service::start_storage_service;
 use types::{
     account_address, account_config,
     test_helpers::transaction_test_helpers,
-    transaction::{Program, SignedTransaction, TransactionArgument, MAX_TRANSACTION_SIZE_IN_BYTES},
+    transaction::{
+        MultiEd25519PublicKey, Program, SignedTransaction, TransactionArgument,
+        MAX_TRANSACTION_SIZE_IN_BYTES,
+    },
     vm_error::{VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus},
 };
 use vm_genesis::encode_transfer_program;
@@ -358,6 +361,52 @@ fn test_validate_invalid_auth_key() {
     );
 }
 
+#[test]
+fn test_validate_multi_ed25519() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    let (other_private_key, other_public_key) = ::crypto::signing::generate_keypair();
+    let public_key =
+        MultiEd25519PublicKey::new(vec![keypair.public_key(), other_public_key], 2).unwrap();
+
+    let address = account_config::association_address();
+    let program = encode_transfer_program(&address, 100);
+    // Below the threshold of the policy
+    let signed_txn = transaction_test_helpers::get_test_multi_signed_txn(
+        address,
+        0,
+        &[(0, keypair.private_key().clone())],
+        public_key.clone(),
+        Some(program.clone()),
+    );
+    let ret = vm_validator
+        .validate_transaction(signed_txn)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        ret,
+        Some(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+
+    // Valid signatures, but the account is not controlled by this policy
+    let signed_txn = transaction_test_helpers::get_test_multi_signed_txn(
+        address,
+        0,
+        &[(0, keypair.private_key().clone()), (1, other_private_key)],
+        public_key,
+        Some(program),
+    );
+    let ret = vm_validator
+        .validate_transaction(signed_txn)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        ret,
+        Some(VMStatus::Validation(VMValidationStatus::InvalidAuthKey))
+    );
+}
+
 #[test]
 fn test_validate_balance_below_gas_fee() {
     let (config, keypair) = get_test_config();